@@ -0,0 +1,16 @@
+use std::process::Command;
+
+// 把构建时的 git 提交写入 TIMETICKER_GIT_HASH，供“关于”对话框显示；
+// 不在 git 仓库中构建时为 unknown
+fn main() {
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=TIMETICKER_GIT_HASH={hash}");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs");
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_HASH: &str = env!("TIMETICKER_GIT_HASH"); // 由 build.rs 写入
+
+// 诊断信息中的运行状态
+#[derive(Debug, Clone, Copy)]
+pub struct Diagnostics<'a> {
+    pub version: &'a str,
+    pub git_hash: &'a str,
+    pub os: &'a str,
+    pub arch: &'a str,
+    pub config_path: Option<&'a Path>,
+    pub save_path: Option<&'a Path>,
+    pub history_path: Option<&'a Path>,
+    pub log_dir: Option<&'a Path>,
+    pub task_count: usize,
+    pub archived_count: usize,
+    pub pinned_icon_count: usize,
+}
+
+// 关于对话框和“复制诊断信息”使用的文本，提交问题时原样粘贴，
+// 所以不随界面语言变化
+pub fn diagnostics_text(diagnostics: &Diagnostics) -> String {
+    let path = |path: Option<&Path>| path.map_or_else(|| "(none)".to_string(), |path| path.display().to_string());
+    [
+        format!("TimeTicker {} ({})", diagnostics.version, diagnostics.git_hash),
+        format!("Platform: {} {}", diagnostics.os, diagnostics.arch),
+        format!("Config: {}", path(diagnostics.config_path)),
+        format!("Tasks file: {}", path(diagnostics.save_path)),
+        format!("History file: {}", path(diagnostics.history_path)),
+        format!("Logs: {}", path(diagnostics.log_dir)),
+        format!(
+            "Tasks: {} ({} archived)",
+            diagnostics.task_count, diagnostics.archived_count
+        ),
+        format!("Pinned icons: {}", diagnostics.pinned_icon_count),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostics<'a>(config_path: Option<&'a Path>, save_path: Option<&'a Path>) -> Diagnostics<'a> {
+        Diagnostics {
+            version: "1.2.3",
+            git_hash: "abc1234",
+            os: "macos",
+            arch: "aarch64",
+            config_path,
+            save_path,
+            history_path: None,
+            log_dir: Some(Path::new("/home/me/.local/share/TimeTicker/logs")),
+            task_count: 5,
+            archived_count: 2,
+            pinned_icon_count: 1,
+        }
+    }
+
+    #[test]
+    fn diagnostics_list_version_paths_and_counts() {
+        let text = diagnostics_text(&diagnostics(
+            Some(Path::new("/home/me/.config/TimeTicker/config.toml")),
+            Some(Path::new("/home/me/.local/share/TimeTicker/tasks.json")),
+        ));
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                "TimeTicker 1.2.3 (abc1234)",
+                "Platform: macos aarch64",
+                "Config: /home/me/.config/TimeTicker/config.toml",
+                "Tasks file: /home/me/.local/share/TimeTicker/tasks.json",
+                "History file: (none)",
+                "Logs: /home/me/.local/share/TimeTicker/logs",
+                "Tasks: 5 (2 archived)",
+                "Pinned icons: 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_paths_are_marked() {
+        let text = diagnostics_text(&diagnostics(None, None));
+        assert!(text.contains("Config: (none)\nTasks file: (none)\nHistory file: (none)"), "{text}");
+    }
+
+    #[test]
+    fn build_constants_are_set() {
+        assert_eq!(VERSION, env!("CARGO_PKG_VERSION"));
+        assert!(!GIT_HASH.is_empty());
+    }
+}
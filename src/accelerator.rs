@@ -0,0 +1,168 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use tray_icon::menu::accelerator::Accelerator;
+
+use crate::error::{AcceleratorConflictSnafu, AcceleratorParseSnafu, Error, Result};
+
+pub const TOGGLE_TASK_COUNT: usize = 9; // 带快捷键的开始/暂停项数量，对应数字键 1 到 9
+
+// 菜单打开时可用的快捷键，格式如 CmdOrCtrl+N、Alt+Shift+P；
+// CmdOrCtrl 在 macOS 上是 Command，其他平台是 Ctrl。未设置时不使用快捷键
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MenuAccelerators {
+    pub new_task: Option<String>,    // 新建任务
+    pub quit: Option<String>,        // 退出
+    pub pause_all: Option<String>,   // 全部暂停
+    pub toggle_task: Option<String>, // 前九个任务开始/暂停的修饰键，后面加数字 1 到 9
+}
+
+impl Default for MenuAccelerators {
+    fn default() -> Self {
+        Self {
+            new_task: Some("CmdOrCtrl+N".to_string()),
+            quit: Some("CmdOrCtrl+Q".to_string()),
+            pause_all: Some("CmdOrCtrl+P".to_string()),
+            toggle_task: Some("CmdOrCtrl".to_string()),
+        }
+    }
+}
+
+// 解析后的菜单快捷键
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Accelerators {
+    pub new_task: Option<Accelerator>,
+    pub quit: Option<Accelerator>,
+    pub pause_all: Option<Accelerator>,
+    toggle_task: Vec<Option<Accelerator>>, // 按菜单中任务的位置排列
+}
+
+impl Accelerators {
+    // 解析设置中的快捷键；格式错误或与前面的快捷键重复的项不使用，
+    // 错误一并返回，其余快捷键照常生效
+    pub fn from_config(config: &MenuAccelerators) -> (Self, Vec<Error>) {
+        let mut resolver = Resolver::default();
+        let new_task = resolver.resolve(config.new_task.as_deref());
+        let quit = resolver.resolve(config.quit.as_deref());
+        let pause_all = resolver.resolve(config.pause_all.as_deref());
+        let toggle_task = match config.toggle_task.as_deref() {
+            Some(modifiers) => (1..=TOGGLE_TASK_COUNT)
+                .map(|digit| resolver.resolve(Some(&format!("{}+{digit}", modifiers.trim()))))
+                .collect(),
+            None => Vec::new(),
+        };
+        let accelerators = Self {
+            new_task,
+            quit,
+            pause_all,
+            toggle_task,
+        };
+        (accelerators, resolver.errors)
+    }
+
+    // 菜单中第 index 个任务（从 0 开始）的开始/暂停快捷键
+    pub fn toggle_task(&self, index: usize) -> Option<Accelerator> {
+        self.toggle_task.get(index).copied().flatten()
+    }
+}
+
+pub fn parse_accelerator(spec: &str) -> Result<Accelerator> {
+    Accelerator::from_str(spec.trim()).context(AcceleratorParseSnafu { spec })
+}
+
+// 按顺序解析快捷键，记录已使用的组合以发现冲突
+#[derive(Default)]
+struct Resolver {
+    used: Vec<(Accelerator, String)>,
+    errors: Vec<Error>,
+}
+
+impl Resolver {
+    fn resolve(&mut self, spec: Option<&str>) -> Option<Accelerator> {
+        let spec = spec?;
+        let result = parse_accelerator(spec).and_then(|accelerator| {
+            match self.used.iter().find(|(used, _)| *used == accelerator) {
+                Some((_, other)) => AcceleratorConflictSnafu { spec, other }.fail(),
+                None => Ok(accelerator),
+            }
+        });
+        match result {
+            Ok(accelerator) => {
+                self.used.push((accelerator, spec.to_string()));
+                Some(accelerator)
+            }
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tray_icon::menu::accelerator::{Code, Modifiers};
+
+    use super::*;
+
+    fn config(new_task: &str, quit: &str, pause_all: &str, toggle_task: Option<&str>) -> MenuAccelerators {
+        MenuAccelerators {
+            new_task: Some(new_task.to_string()),
+            quit: Some(quit.to_string()),
+            pause_all: Some(pause_all.to_string()),
+            toggle_task: toggle_task.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn default_accelerators_are_all_distinct() {
+        let (accelerators, errors) = Accelerators::from_config(&MenuAccelerators::default());
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(accelerators.new_task, Some(parse_accelerator("CmdOrCtrl+N").unwrap()));
+        assert_eq!(accelerators.toggle_task(0), Some(parse_accelerator("CmdOrCtrl+1").unwrap()));
+        assert_eq!(accelerators.toggle_task(8), Some(parse_accelerator("CmdOrCtrl+9").unwrap()));
+        assert_eq!(accelerators.toggle_task(TOGGLE_TASK_COUNT), None);
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let accelerator = parse_accelerator(" Alt+Shift+P ").unwrap();
+        assert_eq!(accelerator, Accelerator::new(Some(Modifiers::ALT | Modifiers::SHIFT), Code::KeyP));
+        assert!(matches!(parse_accelerator("Ctrl+"), Err(Error::AcceleratorParse { .. })));
+        assert!(matches!(parse_accelerator("Hyper+N"), Err(Error::AcceleratorParse { .. })));
+    }
+
+    #[test]
+    fn invalid_and_conflicting_accelerators_are_dropped() {
+        let (accelerators, errors) = Accelerators::from_config(&config("Ctrl+N", "Ctrl+N", "Nope+P", Some("Ctrl")));
+        assert_eq!(accelerators.new_task, Some(parse_accelerator("Ctrl+N").unwrap()));
+        // 与新建任务重复的退出快捷键和格式错误的全部暂停不使用，其余照常生效
+        assert_eq!((accelerators.quit, accelerators.pause_all), (None, None));
+        assert_eq!(accelerators.toggle_task(2), Some(parse_accelerator("Ctrl+3").unwrap()));
+        assert!(matches!(
+            errors.as_slice(),
+            [Error::AcceleratorConflict { spec, other, .. }, Error::AcceleratorParse { .. }]
+                if spec == "Ctrl+N" && other == "Ctrl+N"
+        ));
+    }
+
+    #[test]
+    fn task_toggles_can_be_disabled() {
+        let (accelerators, errors) = Accelerators::from_config(&config("Ctrl+N", "Ctrl+Q", "Ctrl+P", None));
+        assert!(errors.is_empty());
+        assert_eq!(accelerators.toggle_task(0), None);
+    }
+
+    #[test]
+    fn settings_round_trip_through_toml() {
+        let settings = config("Alt+N", "Alt+Q", "Alt+P", Some("Alt+Shift"));
+        let text = toml::to_string(&settings).unwrap();
+        assert_eq!(toml::from_str::<MenuAccelerators>(&text).unwrap(), settings);
+        // 缺少的项使用默认值
+        let partial: MenuAccelerators = toml::from_str("quit = \"Alt+Q\"").unwrap();
+        assert_eq!(partial.quit.as_deref(), Some("Alt+Q"));
+        assert_eq!(partial.new_task, MenuAccelerators::default().new_task);
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+// 托盘图标创建失败后依次等待的时间，用完后放弃
+const DELAYS: [Duration; 6] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(20),
+    Duration::from_secs(30),
+];
+
+// 重试的退避状态：每次失败取下一个等待时间
+#[derive(Debug, Clone, Default)]
+pub struct Backoff {
+    attempts: usize, // 已经安排过的重试次数
+}
+
+impl Backoff {
+    // 下一次重试前的等待时间，重试次数用完时返回 None，表示应当放弃
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        let delay = DELAYS.get(self.attempts).copied()?;
+        self.attempts += 1;
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_to_the_cap_then_give_up() {
+        let mut backoff = Backoff::default();
+        let delays: Vec<u64> = std::iter::from_fn(|| backoff.next_delay()).map(|d| d.as_secs()).collect();
+        assert_eq!(delays, [1, 2, 5, 10, 20, 30]);
+        // 放弃之后不会重新开始
+        assert_eq!(backoff.next_delay(), None);
+        assert_eq!(backoff.next_delay(), None);
+    }
+
+    #[test]
+    fn each_backoff_counts_its_own_attempts() {
+        let (mut first, mut second) = (Backoff::default(), Backoff::default());
+        first.next_delay();
+        first.next_delay();
+        assert_eq!(first.next_delay(), Some(Duration::from_secs(5)));
+        assert_eq!(second.next_delay(), Some(Duration::from_secs(1)));
+        // 成功后重新创建的退避状态从头开始
+        first = Backoff::default();
+        assert_eq!(first.next_delay(), Some(Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use crate::task::TaskId;
+
+// 主托盘图标当前显示的画面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlinkFrame {
+    #[default]
+    Normal, // 原图标
+    Alert, // 红色提醒图标
+}
+
+// 有未查看的已完成任务时，每次刷新在原图标和提醒图标之间切换；
+// 打开菜单时全部清除，重置、删除或重新开始任务时清除该任务
+#[derive(Debug, Default)]
+pub struct Blinker {
+    pending: HashSet<TaskId>, // 完成后还没有被查看的任务
+    frame: BlinkFrame,
+}
+
+impl Blinker {
+    pub fn finish(&mut self, id: TaskId) {
+        self.pending.insert(id);
+    }
+
+    pub fn clear(&mut self, id: TaskId) {
+        self.pending.remove(&id);
+    }
+
+    pub fn clear_all(&mut self) {
+        self.pending.clear();
+    }
+
+    // 是否还有未查看的完成，标题据此加上 ⏰
+    pub fn is_active(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    // 每次刷新调用，返回需要切换到的画面；停止闪烁时切回原图标一次，之后不再切换
+    pub fn tick(&mut self) -> Option<BlinkFrame> {
+        let next = match (self.is_active(), self.frame) {
+            (true, BlinkFrame::Normal) => BlinkFrame::Alert,
+            (_, BlinkFrame::Alert) => BlinkFrame::Normal,
+            (false, BlinkFrame::Normal) => return None,
+        };
+        self.frame = next;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_alternate_while_tasks_are_unseen() {
+        let mut blinker = Blinker::default();
+        assert_eq!(blinker.tick(), None);
+
+        blinker.finish(TaskId::next());
+        assert!(blinker.is_active());
+        for _ in 0..3 {
+            assert_eq!(blinker.tick(), Some(BlinkFrame::Alert));
+            assert_eq!(blinker.tick(), Some(BlinkFrame::Normal));
+        }
+    }
+
+    #[test]
+    fn stopping_restores_the_normal_frame_once() {
+        let mut blinker = Blinker::default();
+        let id = TaskId::next();
+        blinker.finish(id);
+        assert_eq!(blinker.tick(), Some(BlinkFrame::Alert));
+
+        blinker.clear(id);
+        assert!(!blinker.is_active());
+        assert_eq!(blinker.tick(), Some(BlinkFrame::Normal));
+        assert_eq!(blinker.tick(), None);
+    }
+
+    #[test]
+    fn clear_only_stops_after_every_task_is_seen() {
+        let mut blinker = Blinker::default();
+        let (first, second) = (TaskId::next(), TaskId::next());
+        blinker.finish(first);
+        blinker.finish(second);
+        blinker.clear(first);
+        assert!(blinker.is_active());
+
+        blinker.finish(first);
+        blinker.clear_all();
+        assert!(!blinker.is_active());
+        assert_eq!(blinker.tick(), None);
+    }
+}
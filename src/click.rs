@@ -0,0 +1,98 @@
+use std::time::{Duration, Instant};
+
+pub const DOUBLE_CLICK_THRESHOLD: Duration = Duration::from_millis(400); // 两次单击的最长间隔
+const DOUBLE_CLICK_DISTANCE: f64 = 4.0; // 两次单击位置的最大距离（像素）
+
+// 单击的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    Pending, // 等待可能的第二次单击，超时后作为单击处理
+    Double,  // 与上一次单击组成双击，上一次单击不再处理
+}
+
+// 区分单击和双击：tray-icon 不是在所有平台上都会发出双击事件，
+// 单击先挂起，阈值内出现第二次单击时作为双击，否则超时后再执行单击
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    pending: Option<(Instant, (f64, f64))>, // 挂起的单击时间和位置
+}
+
+impl ClickTracker {
+    pub fn click(&mut self, position: (f64, f64), now: Instant) -> Click {
+        match self.pending.take() {
+            Some((at, last)) if now.duration_since(at) < DOUBLE_CLICK_THRESHOLD && near(last, position) => {
+                Click::Double
+            }
+            _ => {
+                self.pending = Some((now, position));
+                Click::Pending
+            }
+        }
+    }
+
+    // 挂起的单击已超时时清除并返回 true，由调用方执行单击动作；
+    // 在处理新的单击之前也要调用，避免超时通知晚到时丢失上一次单击
+    pub fn expire(&mut self, now: Instant) -> bool {
+        match self.pending {
+            Some((at, _)) if now.duration_since(at) >= DOUBLE_CLICK_THRESHOLD => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // 系统直接发出双击事件时清除挂起的单击
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+fn near(a: (f64, f64), b: (f64, f64)) -> bool {
+    (a.0 - b.0).abs() <= DOUBLE_CLICK_DISTANCE && (a.1 - b.1).abs() <= DOUBLE_CLICK_DISTANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn second_nearby_click_within_the_threshold_is_a_double_click() {
+        let start = Instant::now();
+        let mut tracker = ClickTracker::default();
+        assert_eq!(tracker.click((10.0, 10.0), start), Click::Pending);
+        assert_eq!(tracker.click((13.0, 6.0), start + 399 * MS), Click::Double);
+        // 双击之后没有挂起的单击
+        assert!(!tracker.expire(start + 2 * DOUBLE_CLICK_THRESHOLD));
+    }
+
+    #[test]
+    fn late_or_distant_clicks_start_a_new_click() {
+        let start = Instant::now();
+        let mut tracker = ClickTracker::default();
+        tracker.click((10.0, 10.0), start);
+        assert_eq!(tracker.click((10.0, 10.0), start + DOUBLE_CLICK_THRESHOLD), Click::Pending);
+
+        let start = start + DOUBLE_CLICK_THRESHOLD;
+        assert_eq!(tracker.click((15.0, 10.0), start + MS), Click::Pending);
+        assert_eq!(tracker.click((15.0, 10.0), start + 2 * MS), Click::Double);
+    }
+
+    #[test]
+    fn pending_click_expires_once_after_the_threshold() {
+        let start = Instant::now();
+        let mut tracker = ClickTracker::default();
+        assert!(!tracker.expire(start));
+        tracker.click((0.0, 0.0), start);
+        assert!(!tracker.expire(start + 399 * MS));
+        assert!(tracker.expire(start + DOUBLE_CLICK_THRESHOLD));
+        assert!(!tracker.expire(start + DOUBLE_CLICK_THRESHOLD));
+
+        tracker.click((0.0, 0.0), start);
+        tracker.reset();
+        assert!(!tracker.expire(start + DOUBLE_CLICK_THRESHOLD));
+        assert_eq!(tracker.click((0.0, 0.0), start + MS), Click::Pending);
+    }
+}
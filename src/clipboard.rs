@@ -0,0 +1,96 @@
+use std::cell::RefCell;
+
+use snafu::{OptionExt, ResultExt};
+
+use crate::{
+    error::{ClipboardSnafu, Result, TaskIdNotFoundSnafu},
+    task::{SummaryStyle, TaskId, TaskList},
+};
+
+// 写入剪贴板的方式，便于替换实现
+pub trait ClipboardSink {
+    fn copy_text(&self, text: &str) -> Result<()>;
+}
+
+// 系统剪贴板；在 Linux 上剪贴板内容由持有者提供，因此在程序运行期间保留同一个实例
+#[derive(Default)]
+pub struct SystemClipboard {
+    inner: RefCell<Option<arboard::Clipboard>>,
+}
+
+impl ClipboardSink for SystemClipboard {
+    fn copy_text(&self, text: &str) -> Result<()> {
+        let mut inner = self.inner.borrow_mut();
+        let clipboard = match &mut *inner {
+            Some(clipboard) => clipboard,
+            None => inner.insert(arboard::Clipboard::new().context(ClipboardSnafu)?),
+        };
+        clipboard.set_text(text).context(ClipboardSnafu)
+    }
+}
+
+// 复制任务的剩余时间和名称，返回复制的文本
+pub fn copy_task(clipboard: &dyn ClipboardSink, tasks: &TaskList, id: TaskId) -> Result<String> {
+    let text = tasks
+        .get(id)
+        .context(TaskIdNotFoundSnafu { id })?
+        .summary(SummaryStyle::Tooltip)?;
+    clipboard.copy_text(&text)?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        error::{Error, InvalidCliArgsSnafu},
+        task::{Task, TaskType},
+    };
+
+    // 记录复制内容的剪贴板，broken 时每次写入都失败
+    #[derive(Default)]
+    struct FakeClipboard {
+        copied: RefCell<Vec<String>>,
+        broken: bool,
+    }
+
+    impl ClipboardSink for FakeClipboard {
+        fn copy_text(&self, text: &str) -> Result<()> {
+            if self.broken {
+                return InvalidCliArgsSnafu { msg: "clipboard unavailable" }.fail();
+            }
+            self.copied.borrow_mut().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    fn tasks() -> (TaskList, TaskId) {
+        let mut task = Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        task.group = Some("工作".into());
+        let mut tasks = TaskList::new();
+        let id = tasks.push(task);
+        (tasks, id)
+    }
+
+    #[test]
+    fn copies_the_task_summary() {
+        let (tasks, id) = tasks();
+        let clipboard = FakeClipboard::default();
+        assert_eq!(copy_task(&clipboard, &tasks, id).unwrap(), "00:25:00#写作 /工作");
+        assert_eq!(*clipboard.copied.borrow(), ["00:25:00#写作 /工作"]);
+    }
+
+    #[test]
+    fn missing_tasks_and_clipboard_failures_are_errors() {
+        let (tasks, id) = tasks();
+        let clipboard = FakeClipboard::default();
+        let missing = TaskId::next();
+        assert!(matches!(copy_task(&clipboard, &tasks, missing), Err(Error::TaskIdNotFound { .. })));
+        assert!(clipboard.copied.borrow().is_empty());
+
+        let broken = FakeClipboard { broken: true, ..FakeClipboard::default() };
+        assert!(copy_task(&broken, &tasks, id).is_err());
+    }
+}
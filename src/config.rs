@@ -0,0 +1,478 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{
+    accelerator::MenuAccelerators,
+    error::{ConfigParseSnafu, ConfigSerializeSnafu, Error, InvalidPresetSnafu, IoSnafu, Result},
+    i18n::Locale,
+    icon::{IconStyle, Palette},
+    parser::parse_time_input,
+    task::{ExpiredRetention, MAX_DURATION, TaskId, TaskOrder, TimeFormat, WarningDefaults},
+};
+
+// 修改后需要重启才能生效的设置：全局快捷键在启动时注册，日志格式在启动时确定
+pub const RESTART_REQUIRED: [&str; 4] = ["new_task_hotkey", "toggle_hotkey", "quick_add_hotkey", "json_logs"];
+
+// 配置文件的修改时间，文件不存在或无法读取时为 None
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// 用户设置，保存在平台配置目录下的 config.toml，缺少的字段使用默认值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub hide_dock_on_launch: bool,       // 启动时隐藏 Dock 图标
+    pub notify_on_finish: bool,          // 任务完成时发送通知
+    pub do_not_disturb: bool,            // 勿扰模式，暂停通知、提示音、webhook 和图标闪烁
+    pub sound_enabled: bool,             // 任务完成时播放提示音
+    pub sound_file: Option<PathBuf>,     // 提示音文件，未设置时使用系统声音
+    pub sound_volume: f32,               // 提示音音量，0.0 到 1.0
+    pub nag_interval_secs: u64,          // 持续提醒的间隔秒数
+    pub snooze_minutes: u64,             // 控制命令 snooze 推迟已完成任务的分钟数
+    pub warn_offsets_secs: Vec<u64>,     // 剩余多少秒时提前提醒，任务输入中的 ~5m 会覆盖此设置
+    pub warn_milestones: Vec<f64>,       // 已用比例达到这些值时提醒，0.5 表示一半，任务输入中的 ~50% 会覆盖此设置
+    pub time_format: TimeFormat,         // 菜单、提示和通知中的时间格式：full、compact 或 adaptive
+    pub title_time_format: TimeFormat,   // 托盘和固定图标标题的时间格式
+    pub launch_at_login: bool,           // 开机自启
+    pub max_task_hours: u64,             // 时间段任务的最长时长（小时）
+    pub clear_pinned_finished: bool,     // 清除已完成任务时一并清除固定的任务（先取消固定）
+    pub task_order: TaskOrder,           // 菜单和提示中任务的排列顺序
+    pub progress_bar_width: usize,       // 菜单中进度条的格数，0 表示不显示
+    pub progress_bar_filled: char,       // 进度条已完成部分的字符
+    pub progress_bar_empty: char,        // 进度条未完成部分的字符
+    pub show_tray_title: bool,           // 在主托盘图标旁显示最紧急的剩余时间
+    pub tray_title_task: Option<TaskId>, // 主托盘标题固定显示的任务，未设置时显示最紧急的任务
+    pub pinned_drawn_icon: bool,         // 固定图标绘制剩余时间，而不是显示文本标题（Linux/Windows 不支持标题）
+    pub warning_threshold_secs: u64,     // 剩余时间低于此值时图标数字变为橙色
+    pub critical_threshold_secs: u64,    // 剩余时间低于此值时图标反色显示
+    pub pinned_icon_style: IconStyle,    // 绘制模式下固定图标的样式：digits、ring 或 ring+digits
+    pub icon_scale: u32,                 // 绘制图标的放大倍数，2 适合 Retina 屏幕
+    pub icon_palette: Palette,           // 无法检测系统外观时使用的图标配色
+    pub icon_template: bool,             // 绘制透明背景的模板图标，由系统按菜单栏外观着色
+    pub tray_icon_path: Option<PathBuf>, // 自定义托盘图标，未设置时使用内嵌图标
+    pub dock_icon_path: Option<PathBuf>, // 自定义 Dock 图标，未设置时使用内嵌图标
+    pub tooltip_max_tasks: usize,        // 托盘提示中最多显示的任务数
+    pub menu_max_tasks: usize,           // 主菜单顶层最多显示的任务数，其余放入“更多任务”子菜单，0 表示不限制
+    pub max_pinned: usize,               // 最多同时固定的任务数，过多的托盘图标在部分系统上会被隐藏
+    pub pause_during_sleep: bool,        // 系统休眠期间时间段任务不计时
+    pub single_running_task: bool,       // 同一时间只计时一个时间段任务，开始任务时暂停其他任务
+    pub budget_auto_pause: bool,         // 分组用完当天的预算时暂停该分组计时中的任务
+    // 过期的截止时间任务保留多久后自动归档："forever"、"immediately" 或 { after_secs = 3600 }
+    pub expired_deadline_retention: ExpiredRetention,
+    pub new_task_hotkey: Option<String>, // 新建任务的全局快捷键，例如 ctrl+alt+T，未设置时不注册
+    pub toggle_hotkey: Option<String>,   // 开始/暂停当前任务的全局快捷键，未设置时不注册
+    pub quick_add_hotkey: Option<String>, // 打开快速添加窗口的全局快捷键，未设置时不注册
+    pub left_click_toggle: bool,         // 左键单击托盘图标开始/暂停任务，右键打开菜单
+    pub native_input_dialog: bool,       // 使用内置输入窗口，关闭时使用系统对话框（osascript、zenity、kdialog 或 PowerShell）
+    pub webhook_url: Option<String>,     // 任务完成时 POST 的地址，需要启用 webhook 功能
+    pub webhook_alert_on_failure: bool,  // webhook 发送失败时弹出错误提示，否则只记录日志
+    pub locale: Option<Locale>,          // 界面语言：zh-CN 或 en-US，未设置时跟随系统语言
+    pub json_logs: bool,                 // 日志使用 JSON 格式，便于程序分析；菜单中没有对应选项
+    // 分组每天的时间预算（分钟），写在配置文件最后的 [tag_budgets] 表中，例如 娱乐 = 60
+    pub tag_budgets: BTreeMap<String, u64>,
+    // 菜单快捷键，写在配置文件最后的 [menu_accelerators] 表中
+    pub menu_accelerators: MenuAccelerators,
+    // 快速添加菜单中的预设任务，写在配置文件最后的 [[presets]] 表中
+    pub presets: Vec<Preset>,
+}
+
+// 快速添加的预设任务
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    pub label: String, // 菜单中显示的名称
+    pub input: String, // 与新建任务相同的输入格式，例如 25m#专注
+}
+
+impl Preset {
+    fn new(label: &str, input: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            input: input.to_string(),
+        }
+    }
+
+    // 名称不能为空，输入要能按新建任务的格式解析
+    pub fn validate(&self) -> Result<()> {
+        if self.label.trim().is_empty() {
+            return InvalidPresetSnafu {
+                label: &self.label,
+                msg: "label is empty",
+            }
+            .fail();
+        }
+        parse_time_input(&self.input).map_err(|e| {
+            InvalidPresetSnafu {
+                label: &self.label,
+                msg: e.to_string(),
+            }
+            .build()
+        })?;
+        Ok(())
+    }
+}
+
+// 过滤掉无效的预设，错误一并返回，其余预设照常使用
+pub fn valid_presets(presets: &[Preset]) -> (Vec<Preset>, Vec<Error>) {
+    let mut errors = Vec::new();
+    let valid = presets
+        .iter()
+        .filter(|preset| match preset.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                errors.push(e);
+                false
+            }
+        })
+        .cloned()
+        .collect();
+    (valid, errors)
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            hide_dock_on_launch: false,
+            notify_on_finish: true,
+            do_not_disturb: false,
+            sound_enabled: true,
+            sound_file: None,
+            sound_volume: 1.0,
+            nag_interval_secs: 60,
+            snooze_minutes: 5,
+            warn_offsets_secs: vec![300, 60],
+            warn_milestones: vec![0.5, 0.9],
+            time_format: TimeFormat::Full,
+            title_time_format: TimeFormat::Compact,
+            launch_at_login: false,
+            max_task_hours: MAX_DURATION.as_secs() / 3600,
+            clear_pinned_finished: false,
+            task_order: TaskOrder::default(),
+            progress_bar_width: 8,
+            progress_bar_filled: '▓',
+            progress_bar_empty: '░',
+            show_tray_title: true,
+            tray_title_task: None,
+            pinned_drawn_icon: !cfg!(target_os = "macos"),
+            warning_threshold_secs: 300,
+            critical_threshold_secs: 60,
+            pinned_icon_style: IconStyle::default(),
+            icon_scale: 2,
+            icon_palette: Palette::default(),
+            icon_template: false,
+            tray_icon_path: None,
+            dock_icon_path: None,
+            tooltip_max_tasks: 5,
+            menu_max_tasks: 30,
+            max_pinned: 3,
+            pause_during_sleep: false,
+            single_running_task: false,
+            budget_auto_pause: false,
+            expired_deadline_retention: ExpiredRetention::default(),
+            new_task_hotkey: Some("ctrl+alt+KeyT".to_string()),
+            toggle_hotkey: Some("ctrl+alt+Space".to_string()),
+            quick_add_hotkey: Some("ctrl+alt+KeyN".to_string()),
+            left_click_toggle: true,
+            native_input_dialog: true,
+            webhook_url: None,
+            webhook_alert_on_failure: false,
+            locale: None,
+            json_logs: false,
+            tag_budgets: BTreeMap::new(),
+            menu_accelerators: MenuAccelerators::default(),
+            presets: vec![
+                Preset::new("5分钟", "5m#5分钟"),
+                Preset::new("25分钟专注", "25m#专注"),
+                Preset::new("50分钟", "50m#50分钟"),
+                Preset::new("@下班 18:00", "@18:00#下班"),
+            ],
+        }
+    }
+}
+
+// 解析配置中的路径：相对路径基于配置文件所在目录，而不是当前工作目录
+pub fn resolve_path(path: &Path, config_path: Option<&Path>) -> PathBuf {
+    match config_path.and_then(Path::parent) {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl Config {
+    // 默认配置文件位置：平台配置目录下的 TimeTicker/config.toml
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "TimeTicker").map(|dirs| dirs.config_dir().join("config.toml"))
+    }
+
+    // 时间段任务的最长时长，配置为 0 时使用默认值
+    pub fn max_duration(&self) -> Duration {
+        match self.max_task_hours {
+            0 => MAX_DURATION,
+            hours => Duration::from_secs(hours.saturating_mul(3600)),
+        }
+    }
+
+    // 任务没有单独设置时使用的提前提醒
+    pub fn warning_defaults(&self) -> WarningDefaults {
+        WarningDefaults {
+            offsets: self.warn_offsets_secs.iter().map(|secs| Duration::from_secs(*secs)).collect(),
+            milestones: self.warn_milestones.clone(),
+        }
+    }
+
+    // 读取配置文件，文件不存在时返回默认配置
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(path).context(IoSnafu { path })?;
+        toml::from_str(&text).context(ConfigParseSnafu { path })
+    }
+
+    // 与另一份配置相比值不同的设置，使用配置文件中的键名，按字母顺序排列
+    pub fn changed_keys(&self, other: &Config) -> Vec<String> {
+        let (Ok(toml::Value::Table(old)), Ok(toml::Value::Table(new))) =
+            (toml::Value::try_from(self), toml::Value::try_from(other))
+        else {
+            return Vec::new();
+        };
+        // 值为 None 的设置不会写入，只在其中一份配置中出现
+        let mut keys: Vec<String> = old
+            .keys()
+            .chain(new.keys())
+            .filter(|key| old.get(*key) != new.get(*key))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+        }
+        let text = toml::to_string_pretty(self).context(ConfigSerializeSnafu)?;
+        fs::write(path, text).context(IoSnafu { path })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("timeticker-config-{}-{}", name, std::process::id())).join("config.toml")
+    }
+
+    #[test]
+    fn missing_file_and_fields_use_defaults() {
+        let path = temp_path("missing");
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let config: Config = toml::from_str("time_format = \"adaptive\"").unwrap();
+        assert_eq!(config, Config { time_format: TimeFormat::Adaptive, ..Config::default() });
+        assert!(config.notify_on_finish);
+        assert_eq!(config.max_duration(), MAX_DURATION);
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let text = "launch_at_login = true\nfuture_option = \"x\"\n[future_table]\na = 1";
+        let config: Config = toml::from_str(text).unwrap();
+        assert_eq!(config, Config { launch_at_login: true, ..Config::default() });
+    }
+
+    #[test]
+    fn max_duration_comes_from_hours() {
+        let config = Config { max_task_hours: 12, ..Config::default() };
+        assert_eq!(config.max_duration(), Duration::from_secs(12 * 3600));
+        assert_eq!(Config { max_task_hours: 0, ..Config::default() }.max_duration(), MAX_DURATION);
+    }
+
+    #[test]
+    fn expired_retention_settings() {
+        for (text, retention) in [
+            ("expired_deadline_retention = \"forever\"", ExpiredRetention::Forever),
+            ("expired_deadline_retention = \"immediately\"", ExpiredRetention::Immediately),
+            ("expired_deadline_retention = { after_secs = 600 }", ExpiredRetention::AfterSecs(600)),
+        ] {
+            let config: Config = toml::from_str(text).unwrap();
+            assert_eq!(config.expired_deadline_retention, retention, "{text}");
+        }
+        assert_eq!(Config::default().expired_deadline_retention, ExpiredRetention::Forever);
+    }
+
+    #[test]
+    fn save_round_trips() {
+        let path = temp_path("save");
+        let config = Config {
+            hide_dock_on_launch: true,
+            notify_on_finish: false,
+            time_format: TimeFormat::Adaptive,
+            title_time_format: TimeFormat::Full,
+            launch_at_login: true,
+            max_task_hours: 24,
+            do_not_disturb: true,
+            sound_enabled: false,
+            sound_file: Some(PathBuf::from("/tmp/ding.aiff")),
+            sound_volume: 0.5,
+            nag_interval_secs: 300,
+            snooze_minutes: 15,
+            warn_offsets_secs: vec![600, 120, 30],
+            warn_milestones: vec![0.25, 0.75],
+            clear_pinned_finished: true,
+            task_order: TaskOrder::Remaining,
+            progress_bar_width: 12,
+            progress_bar_filled: '#',
+            progress_bar_empty: '-',
+            show_tray_title: false,
+            tray_title_task: Some(TaskId::next()),
+            pinned_drawn_icon: !Config::default().pinned_drawn_icon,
+            warning_threshold_secs: 600,
+            critical_threshold_secs: 120,
+            pinned_icon_style: IconStyle::RingDigits,
+            icon_scale: 1,
+            icon_palette: Palette::LIGHT,
+            icon_template: true,
+            tray_icon_path: Some(PathBuf::from("icons/tray.png")),
+            dock_icon_path: Some(PathBuf::from("/tmp/dock.png")),
+            tooltip_max_tasks: 3,
+            menu_max_tasks: 12,
+            max_pinned: 5,
+            pause_during_sleep: true,
+            single_running_task: true,
+            budget_auto_pause: true,
+            expired_deadline_retention: ExpiredRetention::AfterSecs(3600),
+            new_task_hotkey: Some("cmd+shift+KeyN".to_string()),
+            toggle_hotkey: Some("ctrl+shift+Space".to_string()),
+            quick_add_hotkey: Some("alt+KeyQ".to_string()),
+            left_click_toggle: false,
+            native_input_dialog: false,
+            webhook_url: Some("https://example.com/hook".to_string()),
+            webhook_alert_on_failure: true,
+            menu_accelerators: MenuAccelerators {
+                new_task: Some("Alt+N".to_string()),
+                quit: Some("CmdOrCtrl+Shift+Q".to_string()),
+                pause_all: Some("Alt+P".to_string()),
+                toggle_task: Some("Alt".to_string()),
+            },
+            locale: Some(Locale::EnUs),
+            json_logs: true,
+            presets: vec![Preset::new("番茄", "25m#番茄"), Preset::new("午饭", "@12:00#午饭")],
+            tag_budgets: BTreeMap::from([("娱乐".to_string(), 60), ("工作".to_string(), 240)]),
+        };
+        config.save(&path).unwrap();
+        assert_eq!(Config::load(&path).unwrap(), config);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn default_presets_are_valid() {
+        let presets = Config::default().presets;
+        assert!(!presets.is_empty());
+        let (valid, errors) = valid_presets(&presets);
+        assert_eq!(valid, presets);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn presets_are_read_from_tables() {
+        let text = "[[presets]]\nlabel = \"番茄\"\ninput = \"25m#番茄\"\nicon = \"x\"";
+        let config: Config = toml::from_str(text).unwrap();
+        assert_eq!(config.presets, [Preset::new("番茄", "25m#番茄")]);
+        // 未写预设时使用默认预设，写成空列表时不显示快速添加菜单
+        assert_eq!(toml::from_str::<Config>("").unwrap().presets, Config::default().presets);
+        assert!(toml::from_str::<Config>("presets = []").unwrap().presets.is_empty());
+    }
+
+    #[test]
+    fn invalid_presets_are_dropped() {
+        let presets = [
+            Preset::new("番茄", "25m#番茄"),
+            Preset::new(" ", "5m#空名称"),
+            Preset::new("坏输入", "abc"),
+            Preset::new("下班", "@18:00#下班"),
+        ];
+        let (valid, errors) = valid_presets(&presets);
+        assert_eq!(valid, [presets[0].clone(), presets[3].clone()]);
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| matches!(e, Error::InvalidPreset { .. })), "{errors:?}");
+        assert!(errors[1].to_string().contains("坏输入"), "{}", errors[1]);
+    }
+
+    #[test]
+    fn invalid_file_is_an_error() {
+        let path = temp_path("invalid");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "notify_on_finish = \"yes\"").unwrap();
+        assert!(Config::load(&path).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_the_config_file() {
+        let config_path = Path::new("/home/user/.config/TimeTicker/config.toml");
+        assert_eq!(
+            resolve_path(Path::new("icons/tray.png"), Some(config_path)),
+            Path::new("/home/user/.config/TimeTicker/icons/tray.png")
+        );
+        assert_eq!(resolve_path(Path::new("/tmp/dock.png"), Some(config_path)), Path::new("/tmp/dock.png"));
+        assert_eq!(resolve_path(Path::new("tray.png"), None), Path::new("tray.png"));
+    }
+
+    #[test]
+    fn changed_keys_lists_differing_settings() {
+        let config = Config::default();
+        assert!(config.changed_keys(&config.clone()).is_empty());
+
+        let changed = Config {
+            sound_enabled: false,
+            time_format: TimeFormat::Adaptive,
+            tray_icon_path: Some(PathBuf::from("tray.png")),
+            new_task_hotkey: None,
+            ..Config::default()
+        };
+        // 只在一份配置中出现的键（值为 None 的设置不会写入）也算变化
+        assert_eq!(
+            config.changed_keys(&changed),
+            ["new_task_hotkey", "sound_enabled", "time_format", "tray_icon_path"]
+        );
+        assert_eq!(changed.changed_keys(&config), config.changed_keys(&changed));
+    }
+
+    #[test]
+    fn restart_required_keys_are_config_keys() {
+        let changed = Config {
+            new_task_hotkey: None,
+            toggle_hotkey: Some("ctrl+KeyT".to_string()),
+            quick_add_hotkey: Some("ctrl+alt+KeyA".to_string()),
+            json_logs: true,
+            ..Config::default()
+        };
+        let mut keys = RESTART_REQUIRED;
+        keys.sort();
+        assert_eq!(Config::default().changed_keys(&changed), keys);
+    }
+
+    #[test]
+    fn saving_changes_the_modified_time_but_no_keys() {
+        let path = temp_path("modified");
+        assert_eq!(modified_time(&path), None);
+
+        let config = Config { max_pinned: 2, ..Config::default() };
+        config.save(&path).unwrap();
+        assert!(modified_time(&path).is_some());
+        // 程序自己保存的设置重新加载后没有变化
+        assert!(config.changed_keys(&Config::load(&path).unwrap()).is_empty());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
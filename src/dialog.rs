@@ -0,0 +1,497 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use tracing::{error, info, warn};
+
+#[cfg(target_os = "macos")]
+use crate::{i18n::tr, notify::escape_applescript};
+
+// 系统对话框的显示方式，便于替换实现；所有方法都会阻塞到对话框关闭
+pub trait Dialog {
+    // 输入框，用户取消或输入为空时返回 None
+    fn input(&self, title: &str, message: &str, default_text: &str) -> Option<String>;
+    // 错误提示
+    fn error(&self, title: &str, message: &str);
+    // 普通信息提示
+    fn info(&self, title: &str, message: &str);
+    // 确认框，用户选择确定时返回 true
+    fn confirm(&self, title: &str, message: &str) -> bool;
+    // 保存文件对话框，返回选择的路径，用户取消时返回 None
+    fn save_path(&self, title: &str, default_name: &str) -> Option<PathBuf>;
+    // 选择图片文件，用户取消时返回 None
+    fn open_image(&self, title: &str) -> Option<PathBuf>;
+}
+
+// 选择当前平台可用的对话框：macOS 使用 osascript，Windows 使用 PowerShell，
+// 其他平台依次尝试 zenity 和 kdialog，都没有时不显示对话框
+pub fn detect() -> Box<dyn Dialog> {
+    #[cfg(target_os = "macos")]
+    {
+        info!("💬 对话框使用 osascript");
+        Box::new(AppleScriptDialog)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        info!("💬 对话框使用 PowerShell");
+        Box::new(PowerShellDialog)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        for backend in [LinuxDialog::Zenity, LinuxDialog::Kdialog] {
+            if backend.available() {
+                info!("💬 对话框使用 {}", backend.program());
+                return Box::new(backend);
+            }
+        }
+        warn!("Neither zenity nor kdialog is installed, dialogs are disabled");
+        Box::new(HeadlessDialog)
+    }
+}
+
+// 通过 osascript 显示的 AppleScript 对话框
+#[cfg(target_os = "macos")]
+#[derive(Debug, Default)]
+pub struct AppleScriptDialog;
+
+#[cfg(target_os = "macos")]
+impl AppleScriptDialog {
+    fn run(script: &str) -> Option<Output> {
+        match Command::new("osascript").arg("-e").arg(script).output() {
+            Ok(output) => Some(output),
+            Err(e) => {
+                error!("Failed to run osascript: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Dialog for AppleScriptDialog {
+    fn input(&self, title: &str, message: &str, default_text: &str) -> Option<String> {
+        let script = format!(
+            concat!(
+                r#"display dialog "{}" with title "{}" default answer "{}" "#,
+                r#"buttons {{"{cancel}", "{ok}"}} default button "{ok}""#
+            ),
+            escape_applescript(message),
+            escape_applescript(title),
+            escape_applescript(default_text),
+            cancel = tr("button.cancel"),
+            ok = tr("button.ok"),
+        );
+        let output = Self::run(&script)?;
+        parse_osascript_input(output.status.success(), &String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn error(&self, title: &str, message: &str) {
+        let script = format!(
+            r#"display dialog "{}" with title "{}" buttons {{"{ok}"}} default button "{ok}" with icon stop"#,
+            escape_applescript(message),
+            escape_applescript(title),
+            ok = tr("button.ok"),
+        );
+        if Self::run(&script).is_some() {
+            info!("Error dialog displayed: {}", title);
+        }
+    }
+
+    fn info(&self, title: &str, message: &str) {
+        let script = format!(
+            r#"display dialog "{}" with title "{}" buttons {{"{ok}"}} default button "{ok}" with icon note"#,
+            escape_applescript(message),
+            escape_applescript(title),
+            ok = tr("button.ok"),
+        );
+        if Self::run(&script).is_some() {
+            info!("Info dialog displayed: {}", title);
+        }
+    }
+
+    fn confirm(&self, title: &str, message: &str) -> bool {
+        let script = format!(
+            r#"display dialog "{}" with title "{}" buttons {{"{cancel}", "{ok}"}} default button "{ok}""#,
+            escape_applescript(message),
+            escape_applescript(title),
+            cancel = tr("button.cancel"),
+            ok = tr("button.ok"),
+        );
+        // 选择取消时 osascript 以错误退出
+        Self::run(&script).is_some_and(|output| output.status.success())
+    }
+
+    fn save_path(&self, title: &str, default_name: &str) -> Option<PathBuf> {
+        let script = format!(
+            r#"POSIX path of (choose file name with prompt "{}" default name "{}")"#,
+            escape_applescript(title),
+            escape_applescript(default_name)
+        );
+        let output = Self::run(&script)?;
+        parse_path_output(output.status.success(), &String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn open_image(&self, title: &str) -> Option<PathBuf> {
+        let script = format!(
+            r#"POSIX path of (choose file with prompt "{}" of type {{"public.image"}})"#,
+            escape_applescript(title)
+        );
+        let output = Self::run(&script)?;
+        parse_path_output(output.status.success(), &String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+// display dialog 的输出形如 button returned:确定, text returned:25m#写作
+pub fn parse_osascript_input(success: bool, stdout: &str) -> Option<String> {
+    if !success {
+        return None;
+    }
+    let (_, text) = stdout.split_once("text returned:")?;
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+// zenity 和 kdialog 命令行对话框
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinuxDialog {
+    Zenity,
+    Kdialog,
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+impl LinuxDialog {
+    fn program(self) -> &'static str {
+        match self {
+            LinuxDialog::Zenity => "zenity",
+            LinuxDialog::Kdialog => "kdialog",
+        }
+    }
+
+    fn available(self) -> bool {
+        Command::new(self.program()).arg("--version").output().is_ok()
+    }
+
+    fn run(self, args: &[&str]) -> Option<Output> {
+        match Command::new(self.program()).args(args).output() {
+            Ok(output) => Some(output),
+            Err(e) => {
+                error!("Failed to run {}: {}", self.program(), e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+impl Dialog for LinuxDialog {
+    fn input(&self, title: &str, message: &str, default_text: &str) -> Option<String> {
+        let output = match self {
+            LinuxDialog::Zenity => {
+                let text = escape_markup(message);
+                self.run(&[
+                    "--entry",
+                    "--title",
+                    title,
+                    "--text",
+                    &text,
+                    "--entry-text",
+                    default_text,
+                ])?
+            }
+            LinuxDialog::Kdialog => self.run(&["--title", title, "--inputbox", message, default_text])?,
+        };
+        parse_entry_output(output.status.code(), &String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn error(&self, title: &str, message: &str) {
+        let output = match self {
+            LinuxDialog::Zenity => self.run(&["--error", "--title", title, "--text", &escape_markup(message)]),
+            LinuxDialog::Kdialog => self.run(&["--title", title, "--error", message]),
+        };
+        if output.is_some() {
+            info!("Error dialog displayed: {}", title);
+        }
+    }
+
+    fn info(&self, title: &str, message: &str) {
+        let output = match self {
+            LinuxDialog::Zenity => self.run(&["--info", "--title", title, "--text", &escape_markup(message)]),
+            LinuxDialog::Kdialog => self.run(&["--title", title, "--msgbox", message]),
+        };
+        if output.is_some() {
+            info!("Info dialog displayed: {}", title);
+        }
+    }
+
+    fn confirm(&self, title: &str, message: &str) -> bool {
+        let output = match self {
+            LinuxDialog::Zenity => self.run(&["--question", "--title", title, "--text", &escape_markup(message)]),
+            LinuxDialog::Kdialog => self.run(&["--title", title, "--yesno", message]),
+        };
+        output.is_some_and(|output| output.status.success())
+    }
+
+    fn save_path(&self, title: &str, default_name: &str) -> Option<PathBuf> {
+        let output = match self {
+            LinuxDialog::Zenity => self.run(&[
+                "--file-selection",
+                "--save",
+                "--confirm-overwrite",
+                "--title",
+                title,
+                "--filename",
+                default_name,
+            ])?,
+            LinuxDialog::Kdialog => self.run(&["--title", title, "--getsavefilename", default_name])?,
+        };
+        parse_entry_output(output.status.code(), &String::from_utf8_lossy(&output.stdout)).map(PathBuf::from)
+    }
+
+    fn open_image(&self, title: &str) -> Option<PathBuf> {
+        let output = match self {
+            LinuxDialog::Zenity => self.run(&[
+                "--file-selection",
+                "--title",
+                title,
+                "--file-filter",
+                "Images | *.png *.jpg *.jpeg *.gif *.bmp *.ico",
+            ])?,
+            LinuxDialog::Kdialog => self.run(&["--title", title, "--getopenfilename", ".", "image/*"])?,
+        };
+        parse_entry_output(output.status.code(), &String::from_utf8_lossy(&output.stdout)).map(PathBuf::from)
+    }
+}
+
+// zenity --entry 和 kdialog --inputbox 确定时退出码为 0 并输出一行文本，
+// 取消时退出码为 1，出错时为其他值
+pub fn parse_entry_output(code: Option<i32>, stdout: &str) -> Option<String> {
+    match code {
+        Some(0) => Some(stdout.trim().to_string()).filter(|text| !text.is_empty()),
+        Some(1) => None,
+        code => {
+            warn!("Input dialog exited abnormally: {:?}", code);
+            None
+        }
+    }
+}
+
+// 选择文件的对话框确定时输出一行路径，取消时以错误退出或不输出
+pub fn parse_path_output(success: bool, stdout: &str) -> Option<PathBuf> {
+    if !success {
+        return None;
+    }
+    Some(stdout.trim()).filter(|path| !path.is_empty()).map(PathBuf::from)
+}
+
+// zenity 的 --text 按 Pango 标记解析，任务输入示例中的 && 需要转义
+pub fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// 通过 PowerShell 调用 VisualBasic 的 InputBox 和 WinForms 的 MessageBox
+#[cfg(target_os = "windows")]
+#[derive(Debug, Default)]
+pub struct PowerShellDialog;
+
+#[cfg(target_os = "windows")]
+impl PowerShellDialog {
+    fn run(script: &str) -> Option<Output> {
+        use std::os::windows::process::CommandExt;
+
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000; // 不弹出控制台窗口
+        // 输出使用 UTF-8，否则中文输入会按系统代码页编码
+        let script = format!("[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; {script}");
+        let result = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+        match result {
+            Ok(output) => Some(output),
+            Err(e) => {
+                error!("Failed to run powershell: {}", e);
+                None
+            }
+        }
+    }
+
+    fn message_box(title: &str, message: &str, buttons: &str, icon: &str) -> Option<Output> {
+        Self::run(&format!(
+            "Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.MessageBox]::Show({}, {}, \
+             '{buttons}', '{icon}')",
+            quote_powershell(message),
+            quote_powershell(title)
+        ))
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Dialog for PowerShellDialog {
+    fn input(&self, title: &str, message: &str, default_text: &str) -> Option<String> {
+        let output = Self::run(&format!(
+            "Add-Type -AssemblyName Microsoft.VisualBasic; [Microsoft.VisualBasic.Interaction]::InputBox({}, {}, {})",
+            quote_powershell(message),
+            quote_powershell(title),
+            quote_powershell(default_text)
+        ))?;
+        parse_powershell_input(output.status.success(), &String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn error(&self, title: &str, message: &str) {
+        if Self::message_box(title, message, "OK", "Error").is_some() {
+            info!("Error dialog displayed: {}", title);
+        }
+    }
+
+    fn info(&self, title: &str, message: &str) {
+        if Self::message_box(title, message, "OK", "Information").is_some() {
+            info!("Info dialog displayed: {}", title);
+        }
+    }
+
+    fn confirm(&self, title: &str, message: &str) -> bool {
+        Self::message_box(title, message, "OKCancel", "Question")
+            .is_some_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "OK")
+    }
+
+    fn save_path(&self, title: &str, default_name: &str) -> Option<PathBuf> {
+        let output = Self::run(&format!(
+            "Add-Type -AssemblyName System.Windows.Forms; $dialog = New-Object System.Windows.Forms.SaveFileDialog; \
+             $dialog.Title = {}; $dialog.FileName = {}; if ($dialog.ShowDialog() -eq 'OK') {{ $dialog.FileName }}",
+            quote_powershell(title),
+            quote_powershell(default_name)
+        ))?;
+        parse_path_output(output.status.success(), &String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn open_image(&self, title: &str) -> Option<PathBuf> {
+        let output = Self::run(&format!(
+            "Add-Type -AssemblyName System.Windows.Forms; $dialog = New-Object System.Windows.Forms.OpenFileDialog; \
+             $dialog.Title = {}; $dialog.Filter = 'Images|*.png;*.jpg;*.jpeg;*.gif;*.bmp;*.ico'; if \
+             ($dialog.ShowDialog() -eq 'OK') {{ $dialog.FileName }}",
+            quote_powershell(title)
+        ))?;
+        parse_path_output(output.status.success(), &String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+// PowerShell 单引号字符串：单引号和弯引号 ‘ ’ 写两次，
+// PowerShell 把弯引号也当作单引号；换行等字符按原样保留
+pub fn quote_powershell(text: &str) -> String {
+    let mut quoted = String::from("'");
+    for ch in text.chars() {
+        if matches!(ch, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(ch);
+        }
+        quoted.push(ch);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+// InputBox 把输入的文本输出到标准输出，取消时输出空行，无法与空输入区分
+pub fn parse_powershell_input(success: bool, stdout: &str) -> Option<String> {
+    if !success {
+        return None;
+    }
+    Some(stdout.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+// 没有可用对话框时只记录日志，输入视为取消
+#[derive(Debug, Default)]
+pub struct HeadlessDialog;
+
+impl Dialog for HeadlessDialog {
+    fn input(&self, title: &str, _message: &str, _default_text: &str) -> Option<String> {
+        warn!("No input dialog available, treating as cancelled: {}", title);
+        None
+    }
+
+    fn error(&self, title: &str, message: &str) {
+        warn!("No error dialog available: {}: {}", title, message);
+    }
+
+    fn info(&self, title: &str, message: &str) {
+        warn!("No info dialog available: {}: {}", title, message);
+    }
+
+    fn confirm(&self, title: &str, _message: &str) -> bool {
+        warn!("No confirm dialog available, treating as cancelled: {}", title);
+        false
+    }
+
+    fn save_path(&self, title: &str, _default_name: &str) -> Option<PathBuf> {
+        warn!("No file dialog available, treating as cancelled: {}", title);
+        None
+    }
+
+    fn open_image(&self, title: &str) -> Option<PathBuf> {
+        warn!("No file dialog available, treating as cancelled: {}", title);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_osascript_input() {
+        let stdout = "button returned:确定, text returned: 25m#写作 /工作\n";
+        assert_eq!(parse_osascript_input(true, stdout).as_deref(), Some("25m#写作 /工作"));
+        assert_eq!(parse_osascript_input(true, "button returned:确定, text returned:\n"), None);
+        assert_eq!(parse_osascript_input(true, "button returned:确定\n"), None);
+        // 选择取消时 osascript 以错误退出
+        assert_eq!(parse_osascript_input(false, stdout), None);
+    }
+
+    #[test]
+    fn parses_entry_output_by_exit_code() {
+        assert_eq!(parse_entry_output(Some(0), "25m#写作\n").as_deref(), Some("25m#写作"));
+        assert_eq!(parse_entry_output(Some(0), "  \n"), None);
+        assert_eq!(parse_entry_output(Some(1), "25m#写作\n"), None);
+        assert_eq!(parse_entry_output(Some(5), "25m#写作\n"), None);
+        assert_eq!(parse_entry_output(None, "25m#写作\n"), None);
+    }
+
+    #[test]
+    fn escapes_pango_markup() {
+        assert_eq!(escape_markup("10m#构建 && say <done>"), "10m#构建 &amp;&amp; say &lt;done&gt;");
+        assert_eq!(escape_markup("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn parses_selected_path() {
+        assert_eq!(parse_path_output(true, "/tmp/a.json\n"), Some(PathBuf::from("/tmp/a.json")));
+        assert_eq!(parse_path_output(true, "\n"), None);
+        assert_eq!(parse_path_output(false, "/tmp/a.json\n"), None);
+    }
+
+    #[test]
+    fn quotes_powershell_literals() {
+        assert_eq!(quote_powershell("写作"), "'写作'");
+        assert_eq!(quote_powershell("it's"), "'it''s'");
+        assert_eq!(quote_powershell("\u{2018}x\u{2019}"), "'\u{2018}\u{2018}x\u{2019}\u{2019}'");
+        // $ 和反引号在单引号字符串中没有特殊含义
+        assert_eq!(quote_powershell("$env:PATH `n"), "'$env:PATH `n'");
+        assert_eq!(quote_powershell("a\nb"), "'a\nb'");
+    }
+
+    #[test]
+    fn parses_powershell_input() {
+        assert_eq!(parse_powershell_input(true, "25m#写作\r\n").as_deref(), Some("25m#写作"));
+        assert_eq!(parse_powershell_input(true, "\r\n"), None);
+        assert_eq!(parse_powershell_input(false, "25m#写作\r\n"), None);
+    }
+
+    #[test]
+    fn headless_dialog_cancels_everything() {
+        let dialog = HeadlessDialog;
+        assert_eq!(dialog.input("新建任务", "请输入", "1h#新任务"), None);
+        assert!(!dialog.confirm("删除任务", "确定删除？"));
+        assert_eq!(dialog.save_path("导出任务", "TimeTicker.json"), None);
+        assert_eq!(dialog.open_image("选择图标"), None);
+        dialog.error("错误", "不会显示");
+        dialog.info("关于", "不会显示");
+    }
+}
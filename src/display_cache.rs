@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::task::TaskId;
+
+// 每秒刷新时设置的一个显示位置：托盘提示和标题、菜单项文本和可用状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Tooltip,
+    Title,
+    MenuTitle(TaskId),     // 主菜单中的任务子菜单标题
+    MenuControl(TaskId),   // 主菜单中的开始/暂停按钮
+    MenuSnooze(TaskId),    // 主菜单中推迟按钮的可用状态
+    PinnedTitle(TaskId),   // 固定图标的文本标题
+    PinnedTooltip(TaskId), // 固定图标的提示
+    PinnedLabel(TaskId),   // 固定菜单中的时间显示项
+    PinnedControl(TaskId), // 固定菜单中的开始/暂停按钮
+    PinnedSnooze(TaskId),  // 固定菜单中推迟按钮的可用状态
+}
+
+impl Slot {
+    // 属于主菜单的位置，主菜单重建或原地更新后失效
+    fn in_main_menu(self) -> bool {
+        matches!(self, Slot::MenuTitle(_) | Slot::MenuControl(_) | Slot::MenuSnooze(_))
+    }
+
+    fn pinned_task(self) -> Option<TaskId> {
+        match self {
+            Slot::PinnedTitle(id)
+            | Slot::PinnedTooltip(id)
+            | Slot::PinnedLabel(id)
+            | Slot::PinnedControl(id)
+            | Slot::PinnedSnooze(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+// 记录上次设置到各显示位置的值，值没有变化时跳过 setter，
+// 避免所有任务都暂停时每秒重复调用菜单系统
+#[derive(Debug, Default)]
+pub struct DisplayCache {
+    texts: HashMap<Slot, String>,
+    flags: HashMap<Slot, bool>,
+    applied: u64, // 本轮调用 setter 的次数
+    skipped: u64, // 本轮因值未变化而跳过的次数
+}
+
+impl DisplayCache {
+    // 文本与上次不同时调用 set
+    pub fn text(&mut self, slot: Slot, value: &str, set: impl FnOnce(&str)) {
+        if self.texts.get(&slot).is_some_and(|last| last == value) {
+            self.skipped += 1;
+            return;
+        }
+        set(value);
+        self.texts.insert(slot, value.to_string());
+        self.applied += 1;
+    }
+
+    // 开关状态与上次不同时调用 set
+    pub fn flag(&mut self, slot: Slot, value: bool, set: impl FnOnce(bool)) {
+        if self.flags.get(&slot) == Some(&value) {
+            self.skipped += 1;
+            return;
+        }
+        set(value);
+        self.flags.insert(slot, value);
+        self.applied += 1;
+    }
+
+    pub fn forget(&mut self, slot: Slot) {
+        self.texts.remove(&slot);
+        self.flags.remove(&slot);
+    }
+
+    // 主菜单的菜单项已被替换或由其他路径更新，下次刷新重新设置
+    pub fn forget_main_menu(&mut self) {
+        self.texts.retain(|slot, _| !slot.in_main_menu());
+        self.flags.retain(|slot, _| !slot.in_main_menu());
+    }
+
+    // 固定图标重建或移除后，下次刷新重新设置
+    pub fn forget_pinned(&mut self, id: TaskId) {
+        self.texts.retain(|slot, _| slot.pinned_task() != Some(id));
+        self.flags.retain(|slot, _| slot.pinned_task() != Some(id));
+    }
+
+    // 主托盘图标重建后，提示和标题需要重新设置
+    pub fn forget_tray(&mut self) {
+        self.forget(Slot::Tooltip);
+        self.forget(Slot::Title);
+    }
+
+    // 取出本轮的调用和跳过次数并清零
+    pub fn take_counts(&mut self) -> (u64, u64) {
+        let counts = (self.applied, self.skipped);
+        self.applied = 0;
+        self.skipped = 0;
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 设置文本，返回 setter 是否被调用
+    fn set_text(cache: &mut DisplayCache, slot: Slot, value: &str) -> bool {
+        let mut called = false;
+        cache.text(slot, value, |_| called = true);
+        called
+    }
+
+    fn set_flag(cache: &mut DisplayCache, slot: Slot, value: bool) -> bool {
+        let mut called = false;
+        cache.flag(slot, value, |_| called = true);
+        called
+    }
+
+    #[test]
+    fn unchanged_values_skip_the_setter() {
+        let mut cache = DisplayCache::default();
+        let id = TaskId::next();
+        let mut written = Vec::new();
+        cache.text(Slot::MenuTitle(id), "写作 25:00", |text| written.push(text.to_string()));
+        cache.text(Slot::MenuTitle(id), "写作 25:00", |text| written.push(text.to_string()));
+        cache.text(Slot::MenuTitle(id), "写作 24:59", |text| written.push(text.to_string()));
+        assert_eq!(written, ["写作 25:00", "写作 24:59"]);
+
+        assert!(set_flag(&mut cache, Slot::MenuSnooze(id), false));
+        assert!(!set_flag(&mut cache, Slot::MenuSnooze(id), false));
+        assert!(set_flag(&mut cache, Slot::MenuSnooze(id), true));
+        assert_eq!(cache.take_counts(), (4, 2));
+        assert_eq!(cache.take_counts(), (0, 0));
+    }
+
+    #[test]
+    fn slots_are_independent() {
+        let mut cache = DisplayCache::default();
+        let (first, second) = (TaskId::next(), TaskId::next());
+        assert!(set_text(&mut cache, Slot::Tooltip, "00:10"));
+        assert!(set_text(&mut cache, Slot::Title, "00:10"));
+        assert!(set_text(&mut cache, Slot::MenuControl(first), "暂停"));
+        assert!(set_text(&mut cache, Slot::MenuControl(second), "暂停"));
+        assert!(set_text(&mut cache, Slot::PinnedControl(first), "暂停"));
+        // 同一位置的文本和开关分别记录
+        assert!(set_flag(&mut cache, Slot::MenuControl(first), true));
+    }
+
+    #[test]
+    fn forgetting_the_main_menu_keeps_other_slots() {
+        let mut cache = DisplayCache::default();
+        let id = TaskId::next();
+        set_text(&mut cache, Slot::Tooltip, "提示");
+        set_text(&mut cache, Slot::MenuTitle(id), "标题");
+        set_text(&mut cache, Slot::MenuControl(id), "开始");
+        set_flag(&mut cache, Slot::MenuSnooze(id), true);
+        set_text(&mut cache, Slot::PinnedLabel(id), "25:00");
+
+        cache.forget_main_menu();
+        assert!(set_text(&mut cache, Slot::MenuTitle(id), "标题"));
+        assert!(set_text(&mut cache, Slot::MenuControl(id), "开始"));
+        assert!(set_flag(&mut cache, Slot::MenuSnooze(id), true));
+        assert!(!set_text(&mut cache, Slot::Tooltip, "提示"));
+        assert!(!set_text(&mut cache, Slot::PinnedLabel(id), "25:00"));
+    }
+
+    #[test]
+    fn forgetting_a_pinned_icon_keeps_other_tasks() {
+        let mut cache = DisplayCache::default();
+        let (removed, kept) = (TaskId::next(), TaskId::next());
+        for id in [removed, kept] {
+            set_text(&mut cache, Slot::PinnedTitle(id), "25:00");
+            set_text(&mut cache, Slot::PinnedTooltip(id), "写作");
+            set_flag(&mut cache, Slot::PinnedSnooze(id), false);
+            set_text(&mut cache, Slot::MenuTitle(id), "写作");
+        }
+
+        cache.forget_pinned(removed);
+        assert!(set_text(&mut cache, Slot::PinnedTitle(removed), "25:00"));
+        assert!(set_text(&mut cache, Slot::PinnedTooltip(removed), "写作"));
+        assert!(set_flag(&mut cache, Slot::PinnedSnooze(removed), false));
+        assert!(!set_text(&mut cache, Slot::MenuTitle(removed), "写作"));
+        assert!(!set_text(&mut cache, Slot::PinnedTitle(kept), "25:00"));
+        assert!(!set_flag(&mut cache, Slot::PinnedSnooze(kept), false));
+    }
+
+    #[test]
+    fn forgetting_the_tray_resets_tooltip_and_title() {
+        let mut cache = DisplayCache::default();
+        let id = TaskId::next();
+        set_text(&mut cache, Slot::Tooltip, "提示");
+        set_text(&mut cache, Slot::Title, "25:00");
+        set_text(&mut cache, Slot::PinnedTitle(id), "25:00");
+
+        cache.forget_tray();
+        assert!(set_text(&mut cache, Slot::Tooltip, "提示"));
+        assert!(set_text(&mut cache, Slot::Title, "25:00"));
+        assert!(!set_text(&mut cache, Slot::PinnedTitle(id), "25:00"));
+    }
+}
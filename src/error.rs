@@ -20,10 +20,45 @@ pub enum Error {
     Io { path: PathBuf, source: std::io::Error, backtrace: Backtrace },
 
     // Task related errors (from task.rs or main.rs involving tasks)
-    #[snafu(display("Task not found at index: {}", index))]
-    TaskNotFound { index: usize, backtrace: Backtrace },
-    #[snafu(display("Failed to acquire lock on tasks"))]
+    #[snafu(display("Task '{}' (#{}) no longer exists", name, id))]
+    TaskNotFound { id: crate::task::TaskId, name: String, backtrace: Backtrace },
+    #[snafu(display("Task not found with id: {}", id))]
+    TaskIdNotFound { id: crate::task::TaskId, backtrace: Backtrace },
+    #[snafu(display("Task {} has not finished and cannot be snoozed", id))]
+    TaskNotSnoozable { id: crate::task::TaskId, backtrace: Backtrace },
+    // 包装任务操作中的错误，带上是哪个任务
+    #[snafu(display("Task '{}' (#{}) failed to {}: {}", name, id, operation, source))]
+    TaskOperation {
+        id: crate::task::TaskId,
+        name: String,
+        operation: &'static str,
+        #[snafu(source(from(Error, Box::new)))]
+        source: Box<Error>,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Timed out waiting for the task lock"))]
     TaskLock { backtrace: Backtrace },
+    #[snafu(display("Linking task '{}' to '{}' would create a cycle", from, to))]
+    ChainCycle { from: String, to: String, backtrace: Backtrace },
+    #[snafu(display("Invalid save file: {}", source))]
+    SaveFormat { source: serde_json::Error, backtrace: Backtrace },
+    #[snafu(display("Invalid history record: {}", source))]
+    HistoryFormat { source: serde_json::Error, backtrace: Backtrace },
+    #[snafu(display("Invalid recent inputs file: {}", source))]
+    RecentFormat { source: serde_json::Error, backtrace: Backtrace },
+    #[snafu(display("Invalid save file: {}", reason))]
+    InvalidSaveFile { reason: String, backtrace: Backtrace },
+    #[snafu(display("Save file version {} is newer than this build supports (up to {})", version, supported))]
+    UnsupportedSaveVersion { version: u64, supported: u64, backtrace: Backtrace },
+    #[cfg(not(target_os = "macos"))]
+    #[snafu(display("Failed to show notification: {}", source))]
+    Notification { source: notify_rust::error::Error, backtrace: Backtrace },
+    #[snafu(display("Failed to load font: {}", source))]
+    FontLoad { source: ab_glyph::InvalidFont, backtrace: Backtrace },
+    #[snafu(display("Failed to parse config file '{}': {}", path.display(), source))]
+    ConfigParse { path: PathBuf, source: toml::de::Error, backtrace: Backtrace },
+    #[snafu(display("Failed to serialize config: {}", source))]
+    ConfigSerialize { source: toml::ser::Error, backtrace: Backtrace },
     #[snafu(display("SystemTime error: {}", source))]
     SystemTimeError { source: std::time::SystemTimeError, backtrace: Backtrace }, // Added source
 
@@ -38,10 +73,6 @@ pub enum Error {
     MenuAppend { source: MudaError, item_name: String, backtrace: Backtrace }, // Corrected source to MudaError
     #[snafu(display("Failed to update tray icon (operation: {}): {}", operation, source))]
     TrayIconUpdate { operation: String, source: TrayIconError, backtrace: Backtrace },
-    #[snafu(display("Invalid action string format: '{}', expected prefix: '{}'", action_string, expected_prefix))]
-    InvalidActionFormat { action_string: String, expected_prefix: String, backtrace: Backtrace },
-    #[snafu(display("Failed to parse index from action string '{}': {}", action_string, source))]
-    ParseActionIndex { source: ParseIntError, action_string: String, backtrace: Backtrace },
 
     // Event Loop and Windowing Errors (from main.rs)
     #[snafu(display("Failed to create event loop: {}", source))]
@@ -50,6 +81,8 @@ pub enum Error {
     EventLoopSend { source: WinitEventLoopClosedError<crate::UserEvent>, backtrace: Backtrace }, // Corrected source type
     #[snafu(display("Failed to create window: {}", source))]
     WindowCreation { source: WinitOsError, backtrace: Backtrace },
+    #[snafu(display("Failed to draw window: {}", source))]
+    SoftBuffer { source: softbuffer::SoftBufferError, backtrace: Backtrace },
 
     // macOS Specific Errors (from main.rs)
     #[snafu(display("Failed to get main thread marker for macOS operation"))]
@@ -85,9 +118,130 @@ pub enum Error {
     InvalidDurationUnit { unit: String, backtrace: Backtrace },
     #[snafu(display("Duration cannot be zero"))]
     ZeroDuration { backtrace: Backtrace },
+    #[snafu(display("Duration cannot exceed {} hours", max_hours))]
+    DurationTooLong { max_hours: u64, backtrace: Backtrace },
+    #[snafu(display("Deadline has already passed"))]
+    DeadlinePassed { backtrace: Backtrace },
+
+    // 控制套接字和命令行
+    #[snafu(display("{}", msg))]
+    InvalidCliArgs { msg: String, backtrace: Backtrace },
+    #[snafu(display("Invalid control message: {}", source))]
+    IpcMessage { source: serde_json::Error, backtrace: Backtrace },
+    #[snafu(display("Control socket is not supported on this platform"))]
+    IpcUnsupported { backtrace: Backtrace },
+
+    // timeticker:// 链接
+    #[snafu(display("Invalid URL '{}': {}", url, msg))]
+    InvalidUrl { url: String, msg: String, backtrace: Backtrace },
+    #[snafu(display("Completion commands are not accepted from {}", origin))]
+    UntrustedCommand { origin: String, backtrace: Backtrace },
+
+    #[snafu(display("Clipboard error: {}", source))]
+    Clipboard { source: arboard::Error, backtrace: Backtrace },
+
+    // 全局快捷键
+    #[snafu(display("Invalid hotkey '{}': {}", hotkey, source))]
+    HotKeyParse { hotkey: String, source: global_hotkey::hotkey::HotKeyParseError, backtrace: Backtrace },
+    #[snafu(display("Failed to register hotkey '{}': {}", hotkey, source))]
+    HotKeyRegister { hotkey: String, source: global_hotkey::Error, backtrace: Backtrace },
+    #[snafu(display("Failed to create hotkey manager: {}", source))]
+    HotKeyManager { source: global_hotkey::Error, backtrace: Backtrace },
+
+    // 菜单快捷键
+    #[snafu(display("Invalid menu accelerator '{}': {}", spec, source))]
+    AcceleratorParse { spec: String, source: muda::accelerator::AcceleratorParseError, backtrace: Backtrace },
+    #[snafu(display("Menu accelerator '{}' is already used by '{}'", spec, other))]
+    AcceleratorConflict { spec: String, other: String, backtrace: Backtrace },
+
+    // 任务完成 webhook
+    #[snafu(display("Failed to encode webhook payload: {}", source))]
+    WebhookPayload { source: serde_json::Error, backtrace: Backtrace },
+    #[snafu(display("Webhook request to '{}' failed: {}", url, msg))]
+    Webhook { url: String, msg: String, backtrace: Backtrace },
+
+    // 快速添加预设
+    #[snafu(display("Invalid preset '{}': {}", label, msg))]
+    InvalidPreset { label: String, msg: String, backtrace: Backtrace },
     // ParserErrorWrapper is removed as parser.rs now uses variants from this Error enum directly.
 }
 
+// 错误的处理方式，见 Application::report_error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    UserInput,   // 用户输入或配置有误，弹窗提示
+    Recoverable, // 内部错误，记录日志后继续运行
+    Fatal,       // 无法继续运行，保存任务后退出
+}
+
+impl Error {
+    // 逐个列出所有变体，新增变体时必须决定处理方式
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            // 包装的错误按原来的错误处理
+            Error::TaskOperation { source, .. } => source.kind(),
+
+            Error::ChainCycle { .. }
+            | Error::ConfigParse { .. }
+            | Error::InvalidInputFormat { .. }
+            | Error::MissingTimeInput { .. }
+            | Error::ChronoParse { .. }
+            | Error::TimezoneConversion { .. }
+            | Error::ParseNumber { .. }
+            | Error::InvalidDurationUnit { .. }
+            | Error::ZeroDuration { .. }
+            | Error::DurationTooLong { .. }
+            | Error::DeadlinePassed { .. }
+            | Error::InvalidCliArgs { .. }
+            | Error::HotKeyParse { .. }
+            | Error::AcceleratorParse { .. }
+            | Error::AcceleratorConflict { .. }
+            | Error::InvalidPreset { .. }
+            | Error::InvalidUrl { .. }
+            | Error::UntrustedCommand { .. } => ErrorKind::UserInput,
+
+            // 等锁超时说明出现了死锁，事件循环关闭或无法创建时程序已经无法响应
+            Error::TaskLock { .. } | Error::EventLoopCreation { .. } | Error::EventLoopSend { .. } => ErrorKind::Fatal,
+
+            Error::Io { .. }
+            | Error::TaskNotFound { .. }
+            | Error::TaskIdNotFound { .. }
+            | Error::TaskNotSnoozable { .. }
+            | Error::SaveFormat { .. }
+            | Error::HistoryFormat { .. }
+            | Error::RecentFormat { .. }
+            | Error::InvalidSaveFile { .. }
+            | Error::UnsupportedSaveVersion { .. }
+            | Error::FontLoad { .. }
+            | Error::ConfigSerialize { .. }
+            | Error::SystemTimeError { .. }
+            | Error::TrayIconBuild { .. }
+            | Error::Image { .. }
+            | Error::IconConversion { .. }
+            | Error::MenuAppend { .. }
+            | Error::TrayIconUpdate { .. }
+            | Error::WindowCreation { .. }
+            | Error::SoftBuffer { .. }
+            | Error::MainThreadMarker { .. }
+            | Error::CanonicalizePath { .. }
+            | Error::MacOsMainRunLoopUnavailable { .. }
+            | Error::AppleScriptExecution { .. }
+            | Error::AppleScriptOutput { .. }
+            | Error::AppleScriptParse { .. }
+            | Error::RegexCompile { .. }
+            | Error::IpcMessage { .. }
+            | Error::IpcUnsupported { .. }
+            | Error::Clipboard { .. }
+            | Error::HotKeyRegister { .. }
+            | Error::HotKeyManager { .. }
+            | Error::WebhookPayload { .. }
+            | Error::Webhook { .. } => ErrorKind::Recoverable,
+            #[cfg(not(target_os = "macos"))]
+            Error::Notification { .. } => ErrorKind::Recoverable,
+        }
+    }
+}
+
 // The SystemTimeSnafu struct is removed. Snafu will auto-generate SystemTimeErrorSnafu.
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -95,4 +249,41 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 // Helper for unwraps related to SystemTime
 pub fn system_time_to_duration(system_time: std::time::SystemTime) -> Result<std::time::Duration> {
     system_time.duration_since(std::time::UNIX_EPOCH).context(SystemTimeSnafu)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use snafu::IntoError;
+
+    use super::*;
+    use crate::task::TaskId;
+
+    // 包装一层任务操作，处理方式应与原错误相同
+    fn in_task(source: Error) -> Error {
+        TaskOperationSnafu { id: TaskId::next(), name: "写作", operation: "pause" }.into_error(source)
+    }
+
+    #[test]
+    fn errors_are_classified() {
+        let io = || std::io::Error::other("disk full");
+        let cases = [
+            (ZeroDurationSnafu.build(), ErrorKind::UserInput),
+            (DeadlinePassedSnafu.build(), ErrorKind::UserInput),
+            (InvalidUrlSnafu { url: "timeticker://x", msg: "unknown action" }.build(), ErrorKind::UserInput),
+            (UntrustedCommandSnafu { origin: "links" }.build(), ErrorKind::UserInput),
+            (ChainCycleSnafu { from: "a", to: "b" }.build(), ErrorKind::UserInput),
+            (TaskLockSnafu.build(), ErrorKind::Fatal),
+            (TaskIdNotFoundSnafu { id: TaskId::next() }.build(), ErrorKind::Recoverable),
+            (InvalidSaveFileSnafu { reason: "missing version" }.build(), ErrorKind::Recoverable),
+            (UnsupportedSaveVersionSnafu { version: 99u64, supported: 6u64 }.build(), ErrorKind::Recoverable),
+            (Err::<(), _>(io()).context(IoSnafu { path: "tasks.json" }).unwrap_err(), ErrorKind::Recoverable),
+            (WebhookSnafu { url: "https://example.com/hook", msg: "timed out" }.build(), ErrorKind::Recoverable),
+            (in_task(TaskLockSnafu.build()), ErrorKind::Fatal),
+            (in_task(ZeroDurationSnafu.build()), ErrorKind::UserInput),
+            (in_task(in_task(TaskIdNotFoundSnafu { id: TaskId::next() }.build())), ErrorKind::Recoverable),
+        ];
+        for (error, kind) in cases {
+            assert_eq!(error.kind(), kind, "{error}");
+        }
+    }
+}
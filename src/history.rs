@@ -0,0 +1,269 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::JoinHandle,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use tracing::{debug, error, warn};
+
+use crate::{
+    error::{HistoryFormatSnafu, IoSnafu, Result},
+    task::{Task, TaskStats, TaskType},
+};
+
+const MAX_HISTORY_BYTES: u64 = 5 * 1024 * 1024; // 超过此大小时轮换为 history.1.jsonl
+
+// 默认历史记录位置：平台数据目录下的 TimeTicker/history.jsonl
+pub fn default_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "TimeTicker").map(|dirs| dirs.data_dir().join("history.jsonl"))
+}
+
+// 一条完成记录，每行一个 JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub task_type: String, // duration 或 deadline
+    pub planned_secs: u64,
+    pub actual_secs: u64,
+    pub paused_secs: u64,
+    pub finished_at: String, // 本地时间，RFC 3339 格式
+    #[serde(default)]
+    pub tags: Vec<String>, // 任务的分组
+}
+
+impl HistoryRecord {
+    pub fn new(task: &Task, stats: &TaskStats) -> Self {
+        let task_type = match task.task_type {
+            TaskType::Duration(_) => "duration",
+            TaskType::Deadline(_) => "deadline",
+        };
+        let finished_at = DateTime::<Local>::from(task.finished_at.unwrap_or_else(SystemTime::now));
+        Self {
+            name: task.name.clone(),
+            task_type: task_type.to_string(),
+            planned_secs: stats.planned.as_secs(),
+            actual_secs: stats.actual.as_secs(),
+            paused_secs: stats.paused.as_secs(),
+            finished_at: finished_at.to_rfc3339(),
+            tags: task.group.iter().cloned().collect(),
+        }
+    }
+
+    pub fn to_line(&self) -> Result<String> {
+        serde_json::to_string(self).context(HistoryFormatSnafu)
+    }
+}
+
+// 历史记录写入器：记录发送到后台线程，追加写入后在队列空闲时刷新，
+// 不阻塞事件循环；同时在内存中保留当前文件中的记录，供统计使用
+pub struct HistoryLog {
+    path: PathBuf,
+    records: Vec<HistoryRecord>,
+    sender: Sender<HistoryRecord>,
+    writer: JoinHandle<()>,
+}
+
+impl HistoryLog {
+    pub fn open(path: PathBuf) -> Self {
+        let records = read_recent(&path, usize::MAX).unwrap_or_else(|e| {
+            warn!("Failed to read history from {}: {}", path.display(), e);
+            Vec::new()
+        });
+        let (sender, receiver) = mpsc::channel();
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || write_records(&writer_path, receiver));
+        Self {
+            path,
+            records,
+            sender,
+            writer,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn records(&self) -> &[HistoryRecord] {
+        &self.records
+    }
+
+    pub fn append(&mut self, record: HistoryRecord) {
+        self.records.push(record.clone());
+        if let Err(e) = self.sender.send(record) {
+            error!("Failed to queue history record: {}", e);
+        }
+    }
+
+    // 等待队列中的记录全部写入，退出前调用
+    pub fn close(self) {
+        drop(self.sender);
+        if self.writer.join().is_err() {
+            error!("History writer thread panicked");
+        }
+    }
+}
+
+fn write_records(path: &Path, receiver: Receiver<HistoryRecord>) {
+    while let Ok(record) = receiver.recv() {
+        let result = open_for_append(path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            // 一次写入所有积压的记录
+            for record in std::iter::once(record).chain(receiver.try_iter()) {
+                writeln!(writer, "{}", record.to_line()?).context(IoSnafu { path })?;
+            }
+            writer.flush().context(IoSnafu { path })
+        });
+        match result {
+            Ok(()) => debug!("History written to {}", path.display()),
+            Err(e) => error!("Failed to write history: {}", e),
+        }
+    }
+}
+
+// 打开历史文件用于追加，超过大小上限时先轮换
+fn open_for_append(path: &Path) -> Result<File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+    }
+    if fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_HISTORY_BYTES) {
+        let rotated = path.with_extension("1.jsonl");
+        fs::rename(path, &rotated).context(IoSnafu { path: &rotated })?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(IoSnafu { path })
+}
+
+// 读取最近的 limit 条记录，按完成先后排列；无法解析的行跳过
+pub fn read_recent(path: &Path, limit: usize) -> Result<Vec<HistoryRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path).context(IoSnafu { path })?;
+    let mut records: Vec<HistoryRecord> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!("Skipping malformed history line: {}", e);
+                None
+            }
+        })
+        .collect();
+    let skip = records.len().saturating_sub(limit);
+    records.drain(..skip);
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timeticker-history-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("history.jsonl")
+    }
+
+    fn record(name: &str) -> HistoryRecord {
+        HistoryRecord {
+            name: name.to_string(),
+            task_type: "duration".to_string(),
+            planned_secs: 1500,
+            actual_secs: 1620,
+            paused_secs: 60,
+            finished_at: "2026-06-03T12:00:00+08:00".to_string(),
+            tags: vec!["工作".to_string()],
+        }
+    }
+
+    #[test]
+    fn record_describes_the_finished_task() {
+        let mut task = Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        task.group = Some("工作".into());
+        let finished_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_780_000_000);
+        task.finished_at = Some(finished_at);
+        let stats = TaskStats {
+            planned: Duration::from_secs(1500),
+            actual: Duration::from_millis(1_620_900),
+            paused: Duration::from_secs(60),
+            overrun: Duration::from_secs(120),
+        };
+
+        let record = HistoryRecord::new(&task, &stats);
+        assert_eq!((record.name.as_str(), record.task_type.as_str()), ("写作", "duration"));
+        assert_eq!((record.planned_secs, record.actual_secs, record.paused_secs), (1500, 1620, 60));
+        assert_eq!(record.tags, ["工作"]);
+        let parsed = DateTime::parse_from_rfc3339(&record.finished_at).unwrap();
+        assert_eq!(SystemTime::from(parsed), finished_at);
+
+        let line = record.to_line().unwrap();
+        assert!(line.contains(r#""type":"duration""#) && !line.contains('\n'));
+        assert_eq!(serde_json::from_str::<HistoryRecord>(&line).unwrap(), record);
+    }
+
+    #[test]
+    fn appended_records_are_read_back_in_order() {
+        let path = temp_path("append");
+        let mut log = HistoryLog::open(path.clone());
+        for name in ["写作", "阅读", "评审"] {
+            log.append(record(name));
+        }
+        log.close();
+
+        let names = |records: Vec<HistoryRecord>| records.into_iter().map(|r| r.name).collect::<Vec<_>>();
+        assert_eq!(names(read_recent(&path, 20).unwrap()), ["写作", "阅读", "评审"]);
+        assert_eq!(names(read_recent(&path, 2).unwrap()), ["阅读", "评审"]);
+        assert!(read_recent(&path, 0).unwrap().is_empty());
+
+        // 再次打开时读入已有的记录，新记录追加到原文件末尾
+        let mut log = HistoryLog::open(path.clone());
+        assert_eq!(names(log.records().to_vec()), ["写作", "阅读", "评审"]);
+        log.append(record("下班"));
+        assert_eq!(log.records().len(), 4);
+        log.close();
+        assert_eq!(names(read_recent(&path, 2).unwrap()), ["评审", "下班"]);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn missing_files_are_empty_and_malformed_lines_are_skipped() {
+        let path = temp_path("malformed");
+        assert!(read_recent(&path, 20).unwrap().is_empty());
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let lines = [record("写作").to_line().unwrap(), "not json".into(), String::new()];
+        fs::write(&path, lines.join("\n")).unwrap();
+        assert_eq!(read_recent(&path, 20).unwrap(), [record("写作")]);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn large_files_are_rotated_before_appending() {
+        let path = temp_path("rotate");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(&path).unwrap().set_len(MAX_HISTORY_BYTES + 1).unwrap();
+
+        let mut log = HistoryLog::open(path.clone());
+        log.append(record("写作"));
+        log.close();
+        assert_eq!(read_recent(&path, 20).unwrap(), [record("写作")]);
+        let rotated = path.with_extension("1.jsonl");
+        assert_eq!(fs::metadata(&rotated).unwrap().len(), MAX_HISTORY_BYTES + 1);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
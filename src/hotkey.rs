@@ -0,0 +1,92 @@
+use std::{collections::HashMap, str::FromStr};
+
+use global_hotkey::{GlobalHotKeyManager, hotkey::HotKey};
+use snafu::ResultExt;
+use tracing::{info, warn};
+
+use crate::error::{HotKeyManagerSnafu, HotKeyParseSnafu, HotKeyRegisterSnafu, Result};
+
+// 全局快捷键触发的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    NewTask,      // 打开新建任务对话框
+    ToggleActive, // 开始/暂停当前任务
+    QuickAdd,     // 打开快速添加窗口
+}
+
+// 解析快捷键字符串，例如 ctrl+alt+T、cmd+shift+KeyN；
+// 修饰键可写作 ctrl/control、alt/option、shift、cmd/command/super
+pub fn parse_hotkey(spec: &str) -> Result<HotKey> {
+    HotKey::from_str(spec.trim()).context(HotKeyParseSnafu { hotkey: spec })
+}
+
+// 已注册的全局快捷键，释放时全部注销
+pub struct Hotkeys {
+    manager: GlobalHotKeyManager,
+    actions: HashMap<u32, (HotKey, HotkeyAction)>, // 快捷键 id 到动作的映射
+}
+
+impl Hotkeys {
+    // 必须在主线程中创建
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            manager: GlobalHotKeyManager::new().context(HotKeyManagerSnafu)?,
+            actions: HashMap::new(),
+        })
+    }
+
+    // 注册快捷键；与其他程序冲突时返回错误
+    pub fn register(&mut self, spec: &str, action: HotkeyAction) -> Result<()> {
+        let hotkey = parse_hotkey(spec)?;
+        self.manager
+            .register(hotkey)
+            .context(HotKeyRegisterSnafu { hotkey: spec })?;
+        info!("⌨️ 已注册快捷键 {} -> {:?}", spec, action);
+        self.actions.insert(hotkey.id(), (hotkey, action));
+        Ok(())
+    }
+
+    // 快捷键 id 到动作的映射，供事件回调在其他线程中使用
+    pub fn actions(&self) -> HashMap<u32, HotkeyAction> {
+        self.actions.iter().map(|(id, (_, action))| (*id, *action)).collect()
+    }
+
+    pub fn unregister_all(&mut self) {
+        for (_, (hotkey, action)) in self.actions.drain() {
+            if let Err(e) = self.manager.unregister(hotkey) {
+                warn!("Failed to unregister hotkey for {:?}: {}", action, e);
+            }
+        }
+    }
+}
+
+impl Drop for Hotkeys {
+    fn drop(&mut self) {
+        self.unregister_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use global_hotkey::hotkey::{Code, Modifiers};
+
+    use super::*;
+
+    #[test]
+    fn parses_modifier_aliases_and_key_codes() {
+        let hotkey = parse_hotkey("ctrl+alt+KeyT").unwrap();
+        assert_eq!(hotkey, HotKey::new(Some(Modifiers::CONTROL | Modifiers::ALT), Code::KeyT));
+        assert_eq!(parse_hotkey(" control+option+T ").unwrap(), hotkey);
+        assert_eq!(
+            parse_hotkey("cmd+shift+KeyN").unwrap(),
+            HotKey::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyN)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_hotkeys() {
+        for spec in ["", "ctrl+", "ctrl+alt+NoSuchKey", "hyper+T"] {
+            assert!(matches!(parse_hotkey(spec), Err(crate::error::Error::HotKeyParse { .. })), "{spec}");
+        }
+    }
+}
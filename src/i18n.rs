@@ -0,0 +1,451 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+static CURRENT: AtomicU8 = AtomicU8::new(Locale::ZhCn as u8); // 当前界面语言，由设置控制
+
+// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    // 系统语言为中文时使用中文，其他语言都使用英文
+    pub fn system() -> Self {
+        match sys_locale::get_locale() {
+            Some(tag) if tag.to_ascii_lowercase().starts_with("zh") => Locale::ZhCn,
+            Some(_) => Locale::EnUs,
+            None => Locale::ZhCn,
+        }
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::ZhCn => ZH_CN,
+            Locale::EnUs => EN_US,
+        }
+    }
+}
+
+pub fn set_locale(locale: Locale) {
+    CURRENT.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        x if x == Locale::EnUs as u8 => Locale::EnUs,
+        _ => Locale::ZhCn,
+    }
+}
+
+// 按当前语言查找文本，缺少的键原样返回并记录警告
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(locale(), key)
+}
+
+// 查找带占位符的文本，{name} 形式的占位符替换为 args 中对应的值
+pub fn tr_args(key: &'static str, args: &[(&str, &str)]) -> String {
+    let mut text = tr(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+    text
+}
+
+pub fn lookup(locale: Locale, key: &'static str) -> &'static str {
+    match locale.catalog().iter().find(|(k, _)| *k == key) {
+        Some((_, text)) => text,
+        None => {
+            warn!("Missing {:?} translation for '{}'", locale, key);
+            key
+        }
+    }
+}
+
+const ZH_CN: &[(&str, &str)] = &[
+    // 任务分段标题
+    ("section.running", "▶ 进行中"),
+    ("section.paused", "⏸ 已暂停"),
+    ("section.idle", "○ 未开始"),
+    ("section.finished", "✔ 已完成"),
+    // 任务子菜单
+    ("menu.ack", "✅ 知道了"),
+    ("menu.start", "开始"),
+    ("menu.pause", "暂停"),
+    ("menu.reset", "重置"),
+    ("menu.snooze", "推迟 {minutes} 分钟"),
+    ("menu.chain", "完成后开始…"),
+    ("menu.no_chain", "不自动开始"),
+    ("menu.new", "新增"),
+    ("menu.edit", "编辑"),
+    ("menu.move_up", "上移"),
+    ("menu.move_down", "下移"),
+    ("menu.mute", "静音"),
+    ("menu.nag", "持续提醒"),
+    ("menu.copy_remaining", "复制剩余时间"),
+    ("menu.duplicate", "复制"),
+    ("menu.archive", "归档"),
+    ("menu.delete", "删除"),
+    ("menu.pin", "固定"),
+    ("menu.set_icon", "设置图标…"),
+    ("menu.clear_icon", "移除图标"),
+    ("menu.archived", "🗄️ 已归档 ({count})"),
+    ("menu.more_tasks", "更多任务 ({count})"),
+    ("menu.restore", "恢复"),
+    ("menu.delete_forever", "彻底删除"),
+    ("menu.trash", "🗑️ 回收站 ({count})"),
+    ("menu.empty_trash", "清空回收站"),
+    // 主菜单
+    ("menu.new_task", "新建任务"),
+    ("menu.presets", "快速添加"),
+    ("menu.recent", "最近使用"),
+    ("menu.undo", "↩️ 撤销上次操作"),
+    ("menu.pause_all", "⏸️ 全部暂停"),
+    ("menu.resume_all", "▶️ 全部继续"),
+    ("menu.do_not_disturb", "🔕 勿扰模式"),
+    ("menu.copy_all", "📋 复制全部"),
+    ("menu.clear_finished", "🧹 清除已完成 ({count})"),
+    ("menu.about", "ℹ️ 关于 TimeTicker"),
+    ("menu.copy_diagnostics", "复制诊断信息"),
+    ("menu.quit", "退出"),
+    // 统计
+    ("stats.title", "📊 统计"),
+    ("stats.today", "今天：{time}"),
+    ("stats.week", "本周：{time}"),
+    ("stats.empty", "本周暂无记录"),
+    ("stats.budgets", "预算"),
+    // 设置
+    ("settings.title", "⚙️ 设置"),
+    ("settings.hide_dock_on_launch", "启动时隐藏 Dock"),
+    ("settings.notify_on_finish", "完成时通知"),
+    ("settings.sound_enabled", "完成时播放声音"),
+    ("settings.launch_at_login", "开机自启"),
+    ("settings.pinned_drawn_icon", "固定图标绘制时间"),
+    ("settings.single_running_task", "同时只计时一个任务"),
+    ("settings.max_pinned", "📌 最多固定"),
+    ("settings.order", "↕️ 排序方式"),
+    ("settings.order_manual", "按添加顺序"),
+    ("settings.order_created", "按创建时间"),
+    ("settings.order_remaining", "按剩余时间"),
+    ("settings.time_format", "🕐 菜单时间格式"),
+    ("settings.title_time_format", "🕐 标题时间格式"),
+    ("settings.time_format_full", "完整 01:05:00"),
+    ("settings.time_format_compact", "紧凑 05:00"),
+    ("settings.time_format_adaptive", "自适应 1h05m"),
+    ("settings.export", "导出任务…"),
+    ("settings.open_log_dir", "打开日志文件夹"),
+    ("dock.title", "🖥️ Dock 设置"),
+    ("dock.show", "显示在 Dock 中"),
+    ("dock.hide", "隐藏 Dock 图标"),
+    ("dock.reload_icon", "🔄 重新设置 dock.png"),
+    // 时间
+    ("time.deadline", "截止 {time}"),
+    ("time.expected_end", "预计结束 {time}"),
+    ("time.scheduled_start", "将于 {time} 开始"),
+    ("time.duration", "时长 {time}"),
+    ("time.yesterday", "{clock} (昨天)"),
+    ("time.today", "{clock} (今天)"),
+    ("time.tomorrow", "{clock} (明天)"),
+    ("time.date", "{month}月{day}日 {clock}"),
+    ("time.date_year", "{year}年{month}月{day}日 {clock}"),
+    ("tooltip.more", "…以及另外 {count} 个任务"),
+    ("tooltip.empty", "Time Ticker"),
+    // 输入
+    ("input.new_title", "新建任务"),
+    ("input.quick_add_title", "快速添加"),
+    ("input.quick_add_hint", "例如 25m#专注、@18:00#下班，回车添加，Esc 关闭"),
+    (
+        "input.new_message",
+        "请输入任务信息：\n\n格式示例：\n• 时间段：1h30m#学习\n• 截止时间：@19:00#工作\n• 定时开始：@14:00>25m#专注\n• \
+         分组：25m#评审 /工作\n• 提前提醒：@18:00#下班 ~5m、2h#写作 ~50%\n• 完成命令：10m#构建 && say done\n\n其中 # 后面是任务名称（可选），\
+         / 后面是分组（可选），~ 后面是提前提醒的时长（可选），&& 后面是完成时执行的命令（可选）",
+    ),
+    ("input.new_default", "1h#新任务"),
+    ("input.edit_title", "编辑任务"),
+    (
+        "input.edit_message",
+        "请修改任务信息：\n\n格式示例：\n• 时间段：1h30m#学习\n• 截止时间：@19:00#工作\n\n省略 # \
+         后面的名称将保留原任务名称",
+    ),
+    ("input.on_complete", "完成后执行命令"),
+    ("button.ok", "确定"),
+    ("button.cancel", "取消"),
+    // 提示和错误
+    ("notify.finished_title", "⏰ 任务完成"),
+    ("notify.finished_body", "{name} 已完成（计划 {planned}）"),
+    ("notify.already_running", "Time Ticker 已在运行，请在菜单栏中查看任务"),
+    ("notify.started", "▶️ 已开始 '{name}'"),
+    ("notify.paused", "⏸️ 已暂停 '{name}'"),
+    ("notify.exclusive_paused", "⏸️ 开始 '{name}'，已暂停 {paused}"),
+    ("notify.budget_exhausted", "⛔ 预算已用完：'{tag}' 今天已用 {used}，预算 {budget}"),
+    ("notify.warning", "⏳ '{name}' 还剩 {time}"),
+    ("notify.milestone", "⏳ '{name}' 已用 {percent}%，还剩 {time}"),
+    ("notify.scheduled_start", "⏰ 已按计划开始 '{name}'"),
+    ("notify.nothing_to_toggle", "没有可以开始或暂停的任务"),
+    ("notify.quiet_summary_title", "🔕 勿扰期间的通知"),
+    ("notify.quiet_summary_body", "{count} 个任务已完成：{names}"),
+    ("hotkey.new_task", "新建任务"),
+    ("hotkey.toggle", "开始/暂停"),
+    ("hotkey.quick_add", "快速添加"),
+    ("hotkey.failed_title", "快捷键注册失败"),
+    ("hotkey.failed", "无法注册{label}快捷键 {spec}：{error}"),
+    ("about.title", "关于 TimeTicker"),
+    ("export.title", "导出任务"),
+    ("icon.choose_title", "选择图标图片"),
+    ("error.export", "无法导出任务"),
+    ("error.open_log_dir", "无法打开日志文件夹"),
+    ("error.fatal", "TimeTicker 遇到严重错误，已保存任务并退出"),
+    ("error.pin_limit", "无法固定更多任务"),
+    ("error.tray_icon", "无法显示托盘图标"),
+    ("error.pinned_tray_icon", "无法显示固定图标，已取消固定"),
+    (
+        "error.pin_limit_detail",
+        "最多同时固定 {max} 个任务，请先取消固定其中一个：\n{tasks}",
+    ),
+    ("error.copy", "无法复制"),
+    ("error.config_reload", "配置文件有误，继续使用之前的设置"),
+    ("error.webhook", "Webhook 发送失败"),
+    ("error.webhook_task", "任务 '{name}'：{error}"),
+    ("error.input_title", "输入错误"),
+    (
+        "error.parse",
+        "解析任务输入失败：\n\n{error}\n\n请检查输入格式：\n• 时间段：1h30m#任务名\n• 截止时间：@19:00#任务名",
+    ),
+];
+
+const EN_US: &[(&str, &str)] = &[
+    // 任务分段标题
+    ("section.running", "▶ Running"),
+    ("section.paused", "⏸ Paused"),
+    ("section.idle", "○ Not Started"),
+    ("section.finished", "✔ Finished"),
+    // 任务子菜单
+    ("menu.ack", "✅ Got It"),
+    ("menu.start", "Start"),
+    ("menu.pause", "Pause"),
+    ("menu.reset", "Reset"),
+    ("menu.snooze", "Snooze {minutes} Min"),
+    ("menu.chain", "Start When Done…"),
+    ("menu.no_chain", "Nothing"),
+    ("menu.new", "New"),
+    ("menu.edit", "Edit"),
+    ("menu.move_up", "Move Up"),
+    ("menu.move_down", "Move Down"),
+    ("menu.mute", "Mute"),
+    ("menu.nag", "Keep Reminding"),
+    ("menu.copy_remaining", "Copy Remaining Time"),
+    ("menu.duplicate", "Duplicate"),
+    ("menu.archive", "Archive"),
+    ("menu.delete", "Delete"),
+    ("menu.pin", "Pin"),
+    ("menu.set_icon", "Set Icon…"),
+    ("menu.clear_icon", "Remove Icon"),
+    ("menu.archived", "🗄️ Archived ({count})"),
+    ("menu.more_tasks", "More Tasks ({count})"),
+    ("menu.restore", "Restore"),
+    ("menu.delete_forever", "Delete Permanently"),
+    ("menu.trash", "🗑️ Trash ({count})"),
+    ("menu.empty_trash", "Empty Trash"),
+    // 主菜单
+    ("menu.new_task", "New Task"),
+    ("menu.presets", "Quick Add"),
+    ("menu.recent", "Recent"),
+    ("menu.undo", "↩️ Undo Last Action"),
+    ("menu.pause_all", "⏸️ Pause All"),
+    ("menu.resume_all", "▶️ Resume All"),
+    ("menu.do_not_disturb", "🔕 Do Not Disturb"),
+    ("menu.copy_all", "📋 Copy All"),
+    ("menu.clear_finished", "🧹 Clear Finished ({count})"),
+    ("menu.about", "ℹ️ About TimeTicker"),
+    ("menu.copy_diagnostics", "Copy Diagnostics"),
+    ("menu.quit", "Quit"),
+    // 统计
+    ("stats.title", "📊 Statistics"),
+    ("stats.today", "Today: {time}"),
+    ("stats.week", "This week: {time}"),
+    ("stats.empty", "No records this week"),
+    ("stats.budgets", "Budgets"),
+    // 设置
+    ("settings.title", "⚙️ Settings"),
+    ("settings.hide_dock_on_launch", "Hide Dock Icon on Launch"),
+    ("settings.notify_on_finish", "Notify When Finished"),
+    ("settings.sound_enabled", "Play Sound When Finished"),
+    ("settings.launch_at_login", "Launch at Login"),
+    ("settings.pinned_drawn_icon", "Draw Time on Pinned Icons"),
+    ("settings.single_running_task", "One Running Task at a Time"),
+    ("settings.max_pinned", "📌 Pin Limit"),
+    ("settings.order", "↕️ Sort By"),
+    ("settings.order_manual", "Order Added"),
+    ("settings.order_created", "Creation Time"),
+    ("settings.order_remaining", "Time Remaining"),
+    ("settings.time_format", "🕐 Menu Time Format"),
+    ("settings.title_time_format", "🕐 Title Time Format"),
+    ("settings.time_format_full", "Full 01:05:00"),
+    ("settings.time_format_compact", "Compact 05:00"),
+    ("settings.time_format_adaptive", "Adaptive 1h05m"),
+    ("settings.export", "Export Tasks…"),
+    ("settings.open_log_dir", "Open Log Folder"),
+    ("dock.title", "🖥️ Dock"),
+    ("dock.show", "Show in Dock"),
+    ("dock.hide", "Hide Dock Icon"),
+    ("dock.reload_icon", "🔄 Reload dock.png"),
+    // 时间
+    ("time.deadline", "Due {time}"),
+    ("time.expected_end", "Ends {time}"),
+    ("time.scheduled_start", "Starts {time}"),
+    ("time.duration", "Duration {time}"),
+    ("time.yesterday", "{clock} (yesterday)"),
+    ("time.today", "{clock} (today)"),
+    ("time.tomorrow", "{clock} (tomorrow)"),
+    ("time.date", "{month}/{day} {clock}"),
+    ("time.date_year", "{year}/{month}/{day} {clock}"),
+    ("tooltip.more", "…and {count} more"),
+    ("tooltip.empty", "Time Ticker"),
+    // 输入
+    ("input.new_title", "New Task"),
+    ("input.quick_add_title", "Quick Add"),
+    ("input.quick_add_hint", "e.g. 25m#Focus or @18:00#Leave, Enter to add, Esc to close"),
+    (
+        "input.new_message",
+        "Enter the task:\n\nExamples:\n• Duration: 1h30m#Study\n• Deadline: @19:00#Work\n• Scheduled: \
+         @14:00>25m#Focus\n• Group: 25m#Review /Work\n• Warning: @18:00#Leave ~5m, 2h#Write ~50%\n• Command: \
+         10m#Build && say done\n\nThe \
+         name follows # (optional), the group follows / (optional), a warning before the end follows ~ (optional) and the \
+         command to run when done follows && (optional)",
+    ),
+    ("input.new_default", "1h#New task"),
+    ("input.edit_title", "Edit Task"),
+    (
+        "input.edit_message",
+        "Edit the task:\n\nExamples:\n• Duration: 1h30m#Study\n• Deadline: @19:00#Work\n\nLeave out the name after # \
+         to keep the current name",
+    ),
+    ("input.on_complete", "runs a command when done"),
+    ("button.ok", "OK"),
+    ("button.cancel", "Cancel"),
+    // 提示和错误
+    ("notify.finished_title", "⏰ Task Finished"),
+    ("notify.finished_body", "{name} finished (planned {planned})"),
+    (
+        "notify.already_running",
+        "Time Ticker is already running, see the menu bar for your tasks",
+    ),
+    ("notify.started", "▶️ Started '{name}'"),
+    ("notify.paused", "⏸️ Paused '{name}'"),
+    ("notify.exclusive_paused", "⏸️ Started '{name}', paused {paused}"),
+    ("notify.budget_exhausted", "⛔ Budget used up: '{tag}' used {used} today of {budget}"),
+    ("notify.warning", "⏳ '{name}' has {time} left"),
+    ("notify.milestone", "⏳ '{name}' is {percent}% done, {time} left"),
+    ("notify.scheduled_start", "⏰ Started '{name}' as scheduled"),
+    ("notify.nothing_to_toggle", "No task to start or pause"),
+    ("notify.quiet_summary_title", "🔕 During Do Not Disturb"),
+    ("notify.quiet_summary_body", "{count} tasks finished: {names}"),
+    ("hotkey.new_task", "new task"),
+    ("hotkey.toggle", "start/pause"),
+    ("hotkey.quick_add", "quick add"),
+    ("hotkey.failed_title", "Hotkey Registration Failed"),
+    ("hotkey.failed", "Could not register the {label} hotkey {spec}: {error}"),
+    ("about.title", "About TimeTicker"),
+    ("export.title", "Export Tasks"),
+    ("icon.choose_title", "Choose Icon Image"),
+    ("error.export", "Could Not Export Tasks"),
+    ("error.open_log_dir", "Could Not Open Log Folder"),
+    (
+        "error.fatal",
+        "TimeTicker Hit a Fatal Error and Saved Your Tasks Before Quitting",
+    ),
+    ("error.pin_limit", "Too Many Pinned Tasks"),
+    ("error.tray_icon", "Could Not Show Tray Icon"),
+    ("error.pinned_tray_icon", "Could Not Show Pinned Icon, Task Unpinned"),
+    (
+        "error.pin_limit_detail",
+        "At most {max} tasks can be pinned. Unpin one of these first:\n{tasks}",
+    ),
+    ("error.copy", "Could Not Copy"),
+    ("error.config_reload", "Config File Invalid, Keeping Previous Settings"),
+    ("error.webhook", "Webhook Delivery Failed"),
+    ("error.webhook_task", "Task '{name}': {error}"),
+    ("error.input_title", "Invalid Input"),
+    (
+        "error.parse",
+        "Could not parse the task:\n\n{error}\n\nPlease check the format:\n• Duration: 1h30m#Name\n• Deadline: \
+         @19:00#Name",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use regex::Regex;
+
+    use super::*;
+
+    // 调用 tr 和 tr_args 的源文件
+    const SOURCES: [&str; 6] = [
+        include_str!("main.rs"),
+        include_str!("menu_model.rs"),
+        include_str!("dialog.rs"),
+        include_str!("input_window.rs"),
+        include_str!("notify.rs"),
+        include_str!("task.rs"),
+    ];
+
+    fn keys(catalog: &[(&'static str, &str)]) -> BTreeSet<&'static str> {
+        catalog.iter().map(|(key, _)| *key).collect()
+    }
+
+    fn placeholders(text: &str) -> BTreeSet<String> {
+        let re = Regex::new(r"\{(\w+)\}").unwrap();
+        re.captures_iter(text).map(|cap| cap[1].to_string()).collect()
+    }
+
+    #[test]
+    fn catalogs_have_the_same_keys() {
+        for catalog in [ZH_CN, EN_US] {
+            assert_eq!(keys(catalog).len(), catalog.len(), "duplicate keys");
+        }
+        let zh = keys(ZH_CN);
+        let en = keys(EN_US);
+        assert_eq!(zh.difference(&en).collect::<Vec<_>>(), Vec::<&&str>::new(), "missing in en-US");
+        assert_eq!(en.difference(&zh).collect::<Vec<_>>(), Vec::<&&str>::new(), "missing in zh-CN");
+    }
+
+    #[test]
+    fn translations_use_the_same_placeholders() {
+        for (key, zh) in ZH_CN {
+            let en = lookup(Locale::EnUs, key);
+            assert_eq!(placeholders(zh), placeholders(en), "{key}");
+        }
+    }
+
+    #[test]
+    fn every_used_key_is_translated() {
+        let re = Regex::new(r#"\btr(?:_args)?\("([^"]+)""#).unwrap();
+        let used: BTreeSet<&str> = SOURCES
+            .iter()
+            .flat_map(|source| re.captures_iter(source).map(|cap| cap.get(1).unwrap().as_str()))
+            .collect();
+        assert!(!used.is_empty());
+        let known = keys(ZH_CN);
+        let missing: Vec<_> = used.iter().filter(|key| !known.contains(*key)).collect();
+        assert!(missing.is_empty(), "untranslated keys: {missing:?}");
+    }
+
+    #[test]
+    fn lookup_and_placeholders() {
+        assert_eq!(lookup(Locale::ZhCn, "menu.start"), "开始");
+        assert_ne!(lookup(Locale::EnUs, "menu.start"), "开始");
+        // 缺少的键原样返回
+        assert_eq!(lookup(Locale::EnUs, "no.such.key"), "no.such.key");
+
+        let text = tr_args("error.webhook_task", &[("name", "写作"), ("error", "timed out")]);
+        assert!(text.contains("写作") && text.contains("timed out"), "{text}");
+        assert!(placeholders(&text).is_empty(), "{text}");
+    }
+}
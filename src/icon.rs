@@ -0,0 +1,1209 @@
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::{path::Path, time::Duration};
+
+use image::{
+    ImageBuffer, ImageFormat, Rgba, RgbaImage,
+    imageops::{self, FilterType},
+};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+use tray_icon::Icon;
+
+#[cfg(feature = "font-icons")]
+use crate::error::FontLoadSnafu;
+use crate::error::{IconConversionSnafu, ImageSnafu, Result};
+
+pub const ICON_SIZE: u32 = 32; // 托盘图标的逻辑边长（像素），实际尺寸为 ICON_SIZE * scale
+pub const DOCK_ICON_SIZE: u32 = ICON_SIZE * 4; // Dock 图标上绘制倒计时的尺寸
+const DOCK_BACKDROP_ALPHA: u8 = 170; // Dock 图标上数字底色的不透明度，背景图仍隐约可见
+const TASK_IMAGE_OPACITY: f32 = 0.35; // 任务自定义图片画在数字后面时的不透明度
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const ORANGE: Rgba<u8> = Rgba([255, 140, 0, 255]);
+const RED: Rgba<u8> = Rgba([220, 40, 40, 255]);
+
+// 系统外观（菜单栏的深色/浅色模式）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+// 获取系统外观的方式，便于替换实现
+pub trait AppearanceSource {
+    // 无法检测时返回 None，此时使用配置中的配色
+    fn appearance(&self) -> Option<Appearance>;
+}
+
+// macOS 读取 AppleInterfaceStyle（深色模式下为
+// Dark，浅色模式下不存在），其他平台无法检测
+#[derive(Debug, Default)]
+pub struct SystemAppearance;
+
+impl AppearanceSource for SystemAppearance {
+    #[cfg(target_os = "macos")]
+    fn appearance(&self) -> Option<Appearance> {
+        let output = Command::new("defaults")
+            .args(["read", "-g", "AppleInterfaceStyle"])
+            .output()
+            .ok()?;
+        let dark = output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "Dark";
+        Some(if dark { Appearance::Dark } else { Appearance::Light })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn appearance(&self) -> Option<Appearance> {
+        None
+    }
+}
+
+// 图标配色：数字、背景和进度环未完成部分的颜色（RGBA）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Palette {
+    pub foreground: [u8; 4],
+    pub background: [u8; 4],
+    pub track: [u8; 4],
+}
+
+impl Palette {
+    // 深色菜单栏：深灰底白字
+    pub const DARK: Palette = Palette {
+        foreground: [255, 255, 255, 255],
+        background: [45, 45, 45, 255],
+        track: [90, 90, 90, 255],
+    };
+    // 浅色菜单栏：浅灰底黑字
+    pub const LIGHT: Palette = Palette {
+        foreground: [20, 20, 20, 255],
+        background: [235, 235, 235, 255],
+        track: [180, 180, 180, 255],
+    };
+    // 模板图标：透明背景，系统只使用透明度并按菜单栏外观着色
+    pub const TEMPLATE: Palette = Palette {
+        foreground: [0, 0, 0, 255],
+        background: [0, 0, 0, 0],
+        track: [0, 0, 0, 80],
+    };
+
+    // 按检测到的系统外观选择配色，无法检测时使用配置中的配色
+    pub fn for_appearance(appearance: Option<Appearance>, configured: Palette) -> Palette {
+        match appearance {
+            Some(Appearance::Dark) => Palette::DARK,
+            Some(Appearance::Light) => Palette::LIGHT,
+            None => configured,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::DARK
+    }
+}
+
+// 绘制时间图标的参数，与时间文本一起决定是否需要重新绘制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconParams {
+    pub style: IconStyle,
+    pub urgency: Urgency,
+    pub palette: Palette,
+    pub template: bool, // 模板图标只有透明度有效，不显示紧急程度的颜色
+    pub scale: u32,
+}
+
+impl IconParams {
+    // 数字、背景和进度环未完成部分的颜色，紧急时数字变橙色或反色显示
+    pub fn colors(&self) -> (Rgba<u8>, Rgba<u8>, Rgba<u8>) {
+        let palette = if self.template { Palette::TEMPLATE } else { self.palette };
+        let (foreground, background, track) = (Rgba(palette.foreground), Rgba(palette.background), Rgba(palette.track));
+        if self.template {
+            return (foreground, background, track);
+        }
+        match self.urgency {
+            Urgency::Normal => (foreground, background, track),
+            Urgency::Warning => (ORANGE, background, track),
+            Urgency::Critical => (WHITE, RED, track),
+        }
+    }
+}
+
+// 绘制图标的样式：数字、进度环，或进度环中间显示数字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IconStyle {
+    #[default]
+    #[serde(rename = "digits")]
+    Digits,
+    #[serde(rename = "ring")]
+    Ring,
+    #[serde(rename = "ring+digits")]
+    RingDigits,
+}
+
+impl IconStyle {
+    // 判断是否需要重新绘制的依据：数字样式看时间文本，进度环只看取整后的百分比
+    pub fn render_key(self, time_str: &str, progress: f64) -> String {
+        let percent = (progress.clamp(0.0, 1.0) * 100.0).round() as u8;
+        match self {
+            IconStyle::Digits => time_str.to_string(),
+            IconStyle::Ring => format!("{percent}%"),
+            IconStyle::RingDigits => format!("{percent}% {time_str}"),
+        }
+    }
+}
+
+// 运行平台，决定托盘图标支持的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    MacOs,
+    Windows,
+    Linux,
+    Other,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else if cfg!(target_os = "linux") {
+            Platform::Linux
+        } else {
+            Platform::Other
+        }
+    }
+
+    // 托盘图标旁能否显示文本标题：Windows 不支持，
+    // Linux 上大多数面板不显示 AppIndicator 的标签
+    pub fn supports_tray_title(self) -> bool {
+        self == Platform::MacOs
+    }
+}
+
+// 固定图标显示剩余时间的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    Title, // 在图标旁显示文本标题
+    Drawn, // 把剩余时间绘制在图标上
+}
+
+// 配置为绘制模式，或者平台不支持标题时使用绘制模式
+pub fn display_mode(platform: Platform, drawn_icon: bool) -> DisplayMode {
+    if drawn_icon || !platform.supports_tray_title() {
+        DisplayMode::Drawn
+    } else {
+        DisplayMode::Title
+    }
+}
+
+// 剩余时间的紧急程度，决定图标颜色和标题前缀
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Normal,   // 时间充足
+    Warning,  // 低于提醒阈值
+    Critical, // 低于紧急阈值或已到时
+}
+
+impl Urgency {
+    pub fn from_remaining(remaining: Duration, warning: Duration, critical: Duration) -> Self {
+        if remaining < critical {
+            Urgency::Critical
+        } else if remaining < warning {
+            Urgency::Warning
+        } else {
+            Urgency::Normal
+        }
+    }
+
+    // 文本标题无法设置颜色，用 emoji 前缀提示
+    pub fn title_prefix(self) -> &'static str {
+        match self {
+            Urgency::Normal => "",
+            Urgency::Warning | Urgency::Critical => "🔴 ",
+        }
+    }
+}
+
+// 像素字体：Small 为 3x5，Large 为 5x7
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Small,
+    Large,
+}
+
+impl Font {
+    // 字形的宽和高（未缩放）
+    fn glyph_size(self, ch: char) -> (u32, u32) {
+        match (self, ch) {
+            (_, ':') => (1, self.height()),
+            (Font::Small, _) => (3, 5),
+            (Font::Large, _) => (5, 7),
+        }
+    }
+
+    fn height(self) -> u32 {
+        match self {
+            Font::Small => 5,
+            Font::Large => 7,
+        }
+    }
+
+    // 字形之间的间距（未缩放），冒号两侧更紧凑
+    fn spacing(self, ch: char) -> u32 {
+        match (self, ch) {
+            (Font::Large, ':') => 2,
+            _ => 1,
+        }
+    }
+}
+
+// 一个字形在图标中的位置，scale 为每个字体像素占用的像素边长
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub ch: char,
+    pub x: u32,
+    pub y: u32,
+    pub font: Font,
+    pub scale: u32,
+}
+
+// 解析 [-][H:]MM:SS 或 2d 3h、1h05m、45s 格式的时间文本，返回总秒数
+pub fn parse_time(text: &str) -> Option<u64> {
+    let text = text.trim_start_matches('-');
+    if !text.contains(':') {
+        return parse_unit_time(text);
+    }
+    let parts: Vec<u64> = text.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [minutes, seconds] => Some(minutes * 60 + seconds),
+        [hours, minutes, seconds] => Some(hours * 3600 + minutes * 60 + seconds),
+        _ => None,
+    }
+}
+
+// 带单位的时间文本，例如 2d 3h、1h05m、45s
+fn parse_unit_time(text: &str) -> Option<u64> {
+    let (mut total, mut number) = (0, String::new());
+    for ch in text.chars().filter(|ch| !ch.is_whitespace()) {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            'd' => 86400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        total += number.parse::<u64>().ok()? * unit;
+        number.clear();
+    }
+    (number.is_empty() && !text.is_empty()).then_some(total)
+}
+
+// 图标上显示的时间文本：一小时以上为 H:MM，一小时以内为 MM:SS，一分钟以内为 SS
+pub enum TimeText {
+    Hours(String),
+    Minutes(String),
+    Seconds(String),
+}
+
+impl TimeText {
+    pub fn new(total_seconds: u64) -> Self {
+        let hours = (total_seconds / 3600).min(99);
+        let minutes = (total_seconds % 3600) / 60;
+        let seconds = total_seconds % 60;
+        if hours > 0 {
+            TimeText::Hours(format!("{hours}:{minutes:02}"))
+        } else if minutes > 0 {
+            TimeText::Minutes(format!("{minutes:02}:{seconds:02}"))
+        } else {
+            TimeText::Seconds(format!("{seconds:02}"))
+        }
+    }
+}
+
+// 像素字体的布局：H:MM 上方标记小号 h，SS 用两倍大小显示
+pub fn layout(total_seconds: u64) -> Vec<Glyph> {
+    match TimeText::new(total_seconds) {
+        TimeText::Hours(text) => {
+            let mut glyphs = centered(&text, Font::Large, 1, 14);
+            glyphs.extend(centered("h", Font::Small, 1, 5));
+            glyphs
+        }
+        TimeText::Minutes(text) => centered(&text, Font::Large, 1, (ICON_SIZE - 7) / 2),
+        TimeText::Seconds(text) => centered(&text, Font::Large, 2, (ICON_SIZE - 14) / 2),
+    }
+}
+
+// 把剩余时间画到图标上的方式，像素字体和矢量字体两种实现
+pub trait IconRenderer {
+    fn draw_time(&self, img: &mut RgbaImage, total_seconds: u64, color: Rgba<u8>);
+}
+
+// 内置的 5x7 像素字体
+#[derive(Debug, Default)]
+pub struct PixelRenderer;
+
+impl IconRenderer for PixelRenderer {
+    fn draw_time(&self, img: &mut RgbaImage, total_seconds: u64, color: Rgba<u8>) {
+        draw_glyphs(img, &layout(total_seconds), color);
+    }
+}
+
+// 开启 font-icons 特性时使用内嵌字体，否则使用像素字体
+pub fn default_renderer() -> Result<Box<dyn IconRenderer>> {
+    #[cfg(feature = "font-icons")]
+    return Ok(Box::new(FontRenderer::new()?));
+    #[cfg(not(feature = "font-icons"))]
+    Ok(Box::new(PixelRenderer))
+}
+
+// 文本绘制后的宽度（像素）
+pub fn text_width(text: &str, font: Font, scale: u32) -> u32 {
+    let chars: Vec<char> = text.chars().collect();
+    let width: u32 = chars
+        .iter()
+        .enumerate()
+        .map(|(i, &ch)| {
+            let (w, _) = font.glyph_size(ch);
+            let gap = if i + 1 < chars.len() { font.spacing(ch) } else { 0 };
+            w + gap
+        })
+        .sum();
+    width * scale
+}
+
+// 水平居中排列文本，y 为顶部位置
+fn centered(text: &str, font: Font, scale: u32, y: u32) -> Vec<Glyph> {
+    let mut x = ICON_SIZE.saturating_sub(text_width(text, font, scale)) / 2;
+    text.chars()
+        .map(|ch| {
+            let glyph = Glyph { ch, x, y, font, scale };
+            x += (font.glyph_size(ch).0 + font.spacing(ch)) * scale;
+            glyph
+        })
+        .collect()
+}
+
+// 绘制显示剩余时间的图标，颜色由紧急程度决定，无法解析时间文本时显示时钟图标；
+// 有任务图片时以较低的不透明度画在数字后面
+pub fn time_icon(
+    renderer: &dyn IconRenderer,
+    time_str: &str,
+    progress: f64,
+    params: &IconParams,
+    task_image: Option<&RgbaImage>,
+) -> Result<Icon> {
+    image_icon(render_time_icon(renderer, time_str, progress, params, task_image))
+}
+
+pub fn render_time_icon(
+    renderer: &dyn IconRenderer,
+    time_str: &str,
+    progress: f64,
+    params: &IconParams,
+    task_image: Option<&RgbaImage>,
+) -> RgbaImage {
+    let (color, background, track) = params.colors();
+    let size = ICON_SIZE * params.scale.max(1);
+    let mut img: RgbaImage = ImageBuffer::from_pixel(size, size, background);
+    if let Some(task_image) = task_image {
+        draw_faded_image(&mut img, task_image, TASK_IMAGE_OPACITY);
+    }
+    if params.style != IconStyle::Digits {
+        draw_progress_ring(&mut img, progress, color, track);
+    }
+    if params.style != IconStyle::Ring {
+        match parse_time(time_str) {
+            Some(total_seconds) => renderer.draw_time(&mut img, total_seconds, color),
+            None => draw_clock_icon(&mut img, color),
+        }
+    }
+    img
+}
+
+// Dock 倒计时图标：背景图缩放到 DOCK_ICON_SIZE，叠加半透明底色和剩余时间
+pub fn render_dock_icon(
+    renderer: &dyn IconRenderer,
+    background: &RgbaImage,
+    total_seconds: u64,
+    params: &IconParams,
+) -> RgbaImage {
+    let mut img = imageops::resize(background, DOCK_ICON_SIZE, DOCK_ICON_SIZE, FilterType::Triangle);
+    let (color, backdrop, _) = params.colors();
+    let backdrop = Rgba([backdrop[0], backdrop[1], backdrop[2], DOCK_BACKDROP_ALPHA]);
+    let mut layer: RgbaImage = ImageBuffer::from_pixel(DOCK_ICON_SIZE, DOCK_ICON_SIZE, backdrop);
+    renderer.draw_time(&mut layer, total_seconds, color);
+    imageops::overlay(&mut img, &layer, 0, 0);
+    img
+}
+
+// 把图片缩放到画布大小，按 opacity 降低不透明度后叠加
+pub fn draw_faded_image(img: &mut RgbaImage, image: &RgbaImage, opacity: f32) {
+    let mut layer = imageops::resize(image, img.width(), img.height(), FilterType::Triangle);
+    for pixel in layer.pixels_mut() {
+        pixel[3] = (f32::from(pixel[3]) * opacity.clamp(0.0, 1.0)).round() as u8;
+    }
+    imageops::overlay(img, &layer, 0, 0);
+}
+
+// 读取任务自定义图标使用的图片
+pub fn load_image(path: &Path) -> Result<RgbaImage> {
+    Ok(image::open(path).context(ImageSnafu)?.into_rgba8())
+}
+
+pub fn image_icon(img: RgbaImage) -> Result<Icon> {
+    let (width, height) = img.dimensions();
+    Icon::from_rgba(img.into_raw(), width, height).context(IconConversionSnafu)
+}
+
+// 编码为 PNG，供只接受图片数据的系统接口使用
+pub fn encode_png(img: &RgbaImage) -> Result<Vec<u8>> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut bytes, ImageFormat::Png).context(ImageSnafu)?;
+    Ok(bytes.into_inner())
+}
+
+// 画布相对逻辑尺寸的放大倍数，逻辑上的一个像素画成 scale x scale 的方块
+fn canvas_scale(img: &RgbaImage) -> u32 {
+    (img.width() / ICON_SIZE).max(1)
+}
+
+// 在逻辑坐标 (x, y) 处填充一个放大后的像素
+fn put_logical_pixel(img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
+    let scale = canvas_scale(img);
+    for dy in 0..scale {
+        for dx in 0..scale {
+            let (px, py) = (x * scale + dx, y * scale + dy);
+            if px < img.width() && py < img.height() {
+                img.put_pixel(px, py, color);
+            }
+        }
+    }
+}
+
+// 图片无法加载时使用的图标：深色背景上的时钟
+pub fn fallback_icon() -> Result<Icon> {
+    let img = clock_image(Rgba(Palette::DARK.foreground), Rgba(Palette::DARK.background));
+    Icon::from_rgba(img.into_raw(), ICON_SIZE, ICON_SIZE).context(IconConversionSnafu)
+}
+
+fn clock_image(color: Rgba<u8>, background: Rgba<u8>) -> RgbaImage {
+    let mut img: RgbaImage = ImageBuffer::from_pixel(ICON_SIZE, ICON_SIZE, background);
+    draw_clock_icon(&mut img, color);
+    img
+}
+
+// 有未查看的已完成任务时与原图标交替显示：红色背景上的白色时钟
+pub fn alert_icon() -> Result<Icon> {
+    let img = clock_image(WHITE, RED);
+    Icon::from_rgba(img.into_raw(), ICON_SIZE, ICON_SIZE).context(IconConversionSnafu)
+}
+
+// 按逻辑坐标绘制字形，画布放大时字形随之放大
+pub fn draw_glyphs(img: &mut RgbaImage, glyphs: &[Glyph], color: Rgba<u8>) {
+    let s = canvas_scale(img);
+    for glyph in glyphs {
+        let (x, y, scale) = (glyph.x * s, glyph.y * s, glyph.scale * s);
+        match glyph.font {
+            Font::Small => draw_pattern(img, x, y, &small_pattern(glyph.ch), scale, color),
+            Font::Large => draw_pattern(img, x, y, &large_pattern(glyph.ch), scale, color),
+        }
+    }
+}
+
+// 按 scale 放大绘制像素图案，超出图标的部分被裁掉
+fn draw_pattern<const W: usize, const H: usize>(
+    img: &mut RgbaImage,
+    x: u32,
+    y: u32,
+    pattern: &[[u8; W]; H],
+    scale: u32,
+    color: Rgba<u8>,
+) {
+    for (row, line) in pattern.iter().enumerate() {
+        for (col, &pixel) in line.iter().enumerate() {
+            if pixel == 0 {
+                continue;
+            }
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let px = x + col as u32 * scale + dx;
+                    let py = y + row as u32 * scale + dy;
+                    if px < img.width() && py < img.height() {
+                        img.put_pixel(px, py, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 沿图标边缘绘制进度环，从正上方开始顺时针填充
+pub fn draw_progress_ring(img: &mut RgbaImage, progress: f64, color: Rgba<u8>, track: Rgba<u8>) {
+    let center = ICON_SIZE as f64 / 2.0;
+    let (inner, outer) = (center - 2.5, center);
+    let progress = progress.clamp(0.0, 1.0);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            // 以像素中心计算，避免环偏向左上角
+            let dx = x as f64 + 0.5 - center;
+            let dy = y as f64 + 0.5 - center;
+            let distance = (dx * dx + dy * dy).sqrt();
+            if !(inner..=outer).contains(&distance) {
+                continue;
+            }
+            // 角度从正上方起顺时针计，范围 0.0 到 1.0
+            let angle = dx.atan2(-dy).rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU;
+            put_logical_pixel(img, x, y, if angle < progress { color } else { track });
+        }
+    }
+}
+
+fn draw_clock_icon(img: &mut RgbaImage, color: Rgba<u8>) {
+    // 绘制圆形边框
+    for y in 8..24 {
+        for x in 8..24 {
+            let dx = (x as i32 - 16).abs();
+            let dy = (y as i32 - 16).abs();
+            let distance = ((dx * dx + dy * dy) as f32).sqrt();
+
+            if (6.0..=8.0).contains(&distance) {
+                put_logical_pixel(img, x, y, color);
+            }
+        }
+    }
+
+    // 绘制时钟指针
+    // 短针（小时）
+    for i in 0..4 {
+        put_logical_pixel(img, 16, 16 - i, color);
+    }
+    // 长针（分钟）
+    for i in 0..6 {
+        put_logical_pixel(img, 16 + i, 16, color);
+    }
+}
+
+// 简单的3x5像素字体
+fn small_pattern(ch: char) -> [[u8; 3]; 5] {
+    match ch {
+        '0' => [[1, 1, 1], [1, 0, 1], [1, 0, 1], [1, 0, 1], [1, 1, 1]],
+        '1' => [[0, 1, 0], [1, 1, 0], [0, 1, 0], [0, 1, 0], [1, 1, 1]],
+        '2' => [[1, 1, 1], [0, 0, 1], [1, 1, 1], [1, 0, 0], [1, 1, 1]],
+        '3' => [[1, 1, 1], [0, 0, 1], [1, 1, 1], [0, 0, 1], [1, 1, 1]],
+        '4' => [[1, 0, 1], [1, 0, 1], [1, 1, 1], [0, 0, 1], [0, 0, 1]],
+        '5' => [[1, 1, 1], [1, 0, 0], [1, 1, 1], [0, 0, 1], [1, 1, 1]],
+        '6' => [[1, 1, 1], [1, 0, 0], [1, 1, 1], [1, 0, 1], [1, 1, 1]],
+        '7' => [[1, 1, 1], [0, 0, 1], [0, 0, 1], [0, 0, 1], [0, 0, 1]],
+        '8' => [[1, 1, 1], [1, 0, 1], [1, 1, 1], [1, 0, 1], [1, 1, 1]],
+        '9' => [[1, 1, 1], [1, 0, 1], [1, 1, 1], [0, 0, 1], [1, 1, 1]],
+        ':' => [[0, 0, 0], [1, 0, 0], [0, 0, 0], [1, 0, 0], [0, 0, 0]],
+        'h' => [[1, 0, 0], [1, 0, 0], [1, 1, 1], [1, 0, 1], [1, 0, 1]],
+        _ => [[0; 3]; 5],
+    }
+}
+
+// 大字体 (5x7 像素)
+fn large_pattern(ch: char) -> [[u8; 5]; 7] {
+    match ch {
+        '0' => [
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+        ],
+        '1' => [
+            [0, 0, 1, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [1, 1, 1, 1, 1],
+        ],
+        '2' => [
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+            [1, 1, 1, 1, 1],
+        ],
+        '3' => [
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+        ],
+        '4' => [
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+        ],
+        '5' => [
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+        ],
+        '6' => [
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+        ],
+        '7' => [
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+        ],
+        '8' => [
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+        ],
+        '9' => [
+            [1, 1, 1, 1, 1],
+            [1, 0, 0, 0, 1],
+            [1, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 1],
+            [1, 1, 1, 1, 1],
+        ],
+        ':' => [
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ],
+        _ => [[0; 5]; 7],
+    }
+}
+
+// 内嵌的等宽字体，输入窗口找不到中文字体时也使用它
+pub const FONT_DATA: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono-Bold.ttf");
+
+// 用内嵌的 DejaVu Sans Mono
+// 字体绘制，字号随画布大小调整，支持亚像素定位和抗锯齿
+#[cfg(feature = "font-icons")]
+pub struct FontRenderer {
+    font: ab_glyph::FontRef<'static>,
+}
+
+#[cfg(feature = "font-icons")]
+impl FontRenderer {
+    pub fn new() -> Result<Self> {
+        let font = ab_glyph::FontRef::try_from_slice(FONT_DATA).context(FontLoadSnafu)?;
+        Ok(Self { font })
+    }
+
+    // 文本在指定字号下的宽度
+    fn text_width(&self, text: &str, px: f32) -> f32 {
+        use ab_glyph::{Font as _, ScaleFont as _};
+        let scaled = self.font.as_scaled(px);
+        text.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum()
+    }
+
+    // 在 max_px 以内选择能放进 max_width 的字号
+    fn fit(&self, text: &str, max_px: f32, max_width: f32) -> f32 {
+        let width = self.text_width(text, max_px);
+        if width > max_width {
+            max_px * max_width / width
+        } else {
+            max_px
+        }
+    }
+
+    // 以 center_y 为垂直中心水平居中绘制一行文本，按覆盖率与背景混合
+    fn draw_line(&self, img: &mut RgbaImage, text: &str, px: f32, center_y: f32, color: Rgba<u8>) {
+        use ab_glyph::{Font as _, ScaleFont as _};
+        let scaled = self.font.as_scaled(px);
+        let baseline = center_y + (scaled.ascent() + scaled.descent()) / 2.0;
+        let mut x = (img.width() as f32 - self.text_width(text, px)) / 2.0;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            let glyph = id.with_scale_and_position(px, ab_glyph::point(x, baseline));
+            x += scaled.h_advance(id);
+            let Some(outlined) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let ix = bounds.min.x as i32 + gx as i32;
+                let iy = bounds.min.y as i32 + gy as i32;
+                if ix < 0 || iy < 0 || ix as u32 >= img.width() || iy as u32 >= img.height() {
+                    return;
+                }
+                let pixel = img.get_pixel_mut(ix as u32, iy as u32);
+                // 透明度也参与混合，模板图标的透明背景上才能显示文字
+                for i in 0..4 {
+                    let blended = pixel.0[i] as f32 + (color.0[i] as f32 - pixel.0[i] as f32) * coverage;
+                    pixel.0[i] = blended.round() as u8;
+                }
+            });
+        }
+    }
+}
+
+#[cfg(feature = "font-icons")]
+impl IconRenderer for FontRenderer {
+    fn draw_time(&self, img: &mut RgbaImage, total_seconds: u64, color: Rgba<u8>) {
+        let size = img.width() as f32;
+        let max_width = size - 2.0;
+        match TimeText::new(total_seconds) {
+            TimeText::Hours(text) => {
+                let px = self.fit(&text, size * 0.5, max_width);
+                self.draw_line(img, &text, px, size * 0.62, color);
+                self.draw_line(img, "h", size * 0.3, size * 0.18, color);
+            }
+            TimeText::Minutes(text) => {
+                let px = self.fit(&text, size * 0.5, max_width);
+                self.draw_line(img, &text, px, size / 2.0, color);
+            }
+            TimeText::Seconds(text) => {
+                let px = self.fit(&text, size * 0.8, max_width);
+                self.draw_line(img, &text, px, size / 2.0, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    const BACKGROUND: Rgba<u8> = Rgba(Palette::DARK.background);
+    const RING_TRACK: Rgba<u8> = Rgba(Palette::DARK.track);
+
+    fn params(style: IconStyle, urgency: Urgency, scale: u32) -> IconParams {
+        IconParams { style, urgency, palette: Palette::DARK, template: false, scale }
+    }
+
+    fn render(time: &str, progress: f64, params: &IconParams) -> RgbaImage {
+        render_time_icon(&PixelRenderer, time, progress, params, None)
+    }
+
+    // 与背景（左上角）颜色不同的像素
+    fn is_lit(img: &RgbaImage, x: u32, y: u32) -> bool {
+        img.get_pixel(x, y) != img.get_pixel(0, 0)
+    }
+
+    // 图标中亮起像素的外框 (x0, y0, x1, y1)，包含两端
+    fn bounds(img: &RgbaImage) -> (u32, u32, u32, u32) {
+        let lit: Vec<(u32, u32)> =
+            img.enumerate_pixels().map(|(x, y, _)| (x, y)).filter(|&(x, y)| is_lit(img, x, y)).collect();
+        let xs = lit.iter().map(|&(x, _)| x);
+        let ys = lit.iter().map(|&(_, y)| y);
+        (xs.clone().min().unwrap(), ys.clone().min().unwrap(), xs.max().unwrap(), ys.max().unwrap())
+    }
+
+    // 外框内的像素画，# 为亮起的像素
+    fn ascii(img: &RgbaImage) -> Vec<String> {
+        let (x0, y0, x1, y1) = bounds(img);
+        (y0..=y1)
+            .map(|y| (x0..=x1).map(|x| if is_lit(img, x, y) { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    fn text(glyphs: &[Glyph]) -> String {
+        glyphs.iter().map(|g| g.ch).collect()
+    }
+
+    #[test]
+    fn pinned_icons_are_drawn_where_titles_are_unsupported() {
+        assert_eq!(display_mode(Platform::MacOs, false), DisplayMode::Title);
+        assert_eq!(display_mode(Platform::MacOs, true), DisplayMode::Drawn);
+        for platform in [Platform::Windows, Platform::Linux, Platform::Other] {
+            assert_eq!(display_mode(platform, false), DisplayMode::Drawn, "{platform:?}");
+        }
+    }
+
+    #[test]
+    fn parses_signed_and_hour_times() {
+        assert_eq!(parse_time("25:00"), Some(1500));
+        assert_eq!(parse_time("1:02:03"), Some(3723));
+        assert_eq!(parse_time("-00:42"), Some(42));
+        assert_eq!(parse_time("-1:00:00"), Some(3600));
+        for text in ["", "42", "1:2:3:4", "ab:cd", "12:-3"] {
+            assert_eq!(parse_time(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn parses_adaptive_times() {
+        assert_eq!(parse_time("2d 3h"), Some(2 * 86400 + 3 * 3600));
+        assert_eq!(parse_time("1h05m"), Some(3900));
+        assert_eq!(parse_time("45s"), Some(45));
+        assert_eq!(parse_time("-0s"), Some(0));
+        for text in ["-", "h", "1x", "5m3", "1h 5"] {
+            assert_eq!(parse_time(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn time_text_depends_on_remaining_time() {
+        assert!(matches!(TimeText::new(0), TimeText::Seconds(text) if text == "00"));
+        assert!(matches!(TimeText::new(59), TimeText::Seconds(text) if text == "59"));
+        assert!(matches!(TimeText::new(60), TimeText::Minutes(text) if text == "01:00"));
+        assert!(matches!(TimeText::new(3599), TimeText::Minutes(text) if text == "59:59"));
+        assert!(matches!(TimeText::new(3600), TimeText::Hours(text) if text == "1:00"));
+        assert!(matches!(TimeText::new(1000 * 3600), TimeText::Hours(text) if text == "99:00"));
+    }
+
+    #[test]
+    fn layout_depends_on_remaining_time() {
+        let seconds = layout(59);
+        assert_eq!(text(&seconds), "59");
+        assert!(seconds.iter().all(|g| g.scale == 2 && g.font == Font::Large));
+
+        assert_eq!(text(&layout(60)), "01:00");
+        assert_eq!(text(&layout(3599)), "59:59");
+        assert!(layout(3599).iter().all(|g| g.scale == 1));
+
+        let hours = layout(3600 + 5 * 60);
+        assert_eq!(text(&hours), "1:05h");
+        assert_eq!(hours.last().unwrap().font, Font::Small);
+        assert_eq!(text(&layout(120 * 3600)), "99:00h");
+    }
+
+    #[test]
+    fn glyphs_are_centered_and_fit_the_icon() {
+        for total_seconds in [0, 9, 59, 60, 754, 3599, 3600, 36_000, 359_999] {
+            let glyphs = layout(total_seconds);
+            let time = format!("{}:{:02}", total_seconds / 60, total_seconds % 60);
+            let img = render(&time, 0.0, &params(IconStyle::Digits, Urgency::Normal, 1));
+            let (x0, _, x1, y1) = bounds(&img);
+            assert!(x1 < ICON_SIZE - 1 && y1 < ICON_SIZE - 1, "{total_seconds}s");
+            // 左右留白最多相差一个像素
+            assert!((ICON_SIZE - 1 - x1).abs_diff(x0) <= 1, "{total_seconds}s: {x0}..{x1}");
+            assert!(!glyphs.is_empty());
+        }
+    }
+
+    #[test]
+    fn renders_minutes_and_seconds() {
+        let img = render("01:05", 0.0, &params(IconStyle::Digits, Urgency::Normal, 1));
+        assert_eq!(bounds(&img), (3, 12, 28, 18));
+        assert_eq!(
+            ascii(&img),
+            [
+                "#####...#......#####.#####",
+                "#...#..##......#...#.#....",
+                "#...#...#...#..#...#.#....",
+                "#...#...#......#...#.#####",
+                "#...#...#...#..#...#.....#",
+                "#...#...#......#...#.....#",
+                "#####.#####....#####.#####",
+            ]
+        );
+    }
+
+    #[test]
+    fn last_minute_is_drawn_twice_as_large() {
+        let img = render("00:07", 0.0, &params(IconStyle::Digits, Urgency::Normal, 1));
+        let (x0, y0, x1, y1) = bounds(&img);
+        assert_eq!((x1 - x0 + 1, y1 - y0 + 1), (text_width("07", Font::Large, 2), 14));
+    }
+
+    #[test]
+    fn dock_icon_scales_the_tray_digits() {
+        let params = params(IconStyle::Digits, Urgency::Normal, 1);
+        let background = ImageBuffer::from_pixel(64, 64, BACKGROUND);
+        let dock = render_dock_icon(&PixelRenderer, &background, 65, &params);
+        assert_eq!(dock.dimensions(), (DOCK_ICON_SIZE, DOCK_ICON_SIZE));
+        let tray = render("01:05", 0.0, &params);
+        for (x, y, _) in tray.enumerate_pixels() {
+            assert_eq!(is_lit(&dock, x * 4, y * 4), is_lit(&tray, x, y), "({x}, {y})");
+        }
+        assert_eq!(*dock.get_pixel(12, 48), WHITE);
+    }
+
+    #[test]
+    fn dock_background_shows_through_the_backdrop() {
+        let blue = Rgba([0, 0, 255, 255]);
+        let dock = render_dock_icon(
+            &PixelRenderer,
+            &ImageBuffer::from_pixel(16, 16, blue),
+            65,
+            &params(IconStyle::Digits, Urgency::Normal, 1),
+        );
+        let Rgba([r, g, b, a]) = *dock.get_pixel(0, 0);
+        assert_eq!(a, 255);
+        assert!(r == g && r > 0 && r < BACKGROUND[0], "{r}");
+        assert!(b > BACKGROUND[2] && b < 255, "{b}");
+    }
+
+    #[test]
+    fn encoded_png_decodes_to_the_same_image() {
+        let img = render("01:05", 0.5, &params(IconStyle::Ring, Urgency::Warning, 2));
+        let png = encode_png(&img).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(image::load_from_memory(&png).unwrap().to_rgba8(), img);
+    }
+
+    #[test]
+    fn unparsable_time_draws_a_clock() {
+        let img = render("--:--", 0.0, &params(IconStyle::Digits, Urgency::Normal, 1));
+        assert_eq!(bounds(&img), (8, 8, 23, 23));
+        assert_eq!(*img.get_pixel(16, 13), WHITE);
+        assert_eq!(*img.get_pixel(21, 16), WHITE);
+        assert_eq!(*img.get_pixel(0, 0), BACKGROUND);
+    }
+
+    #[test]
+    fn urgency_follows_the_thresholds() {
+        let (warning, critical) = (Duration::from_secs(300), Duration::from_secs(60));
+        let urgency = |secs| Urgency::from_remaining(Duration::from_secs(secs), warning, critical);
+        assert_eq!(urgency(301), Urgency::Normal);
+        assert_eq!(urgency(300), Urgency::Normal);
+        assert_eq!(urgency(299), Urgency::Warning);
+        assert_eq!(urgency(60), Urgency::Warning);
+        assert_eq!(urgency(59), Urgency::Critical);
+        assert_eq!(urgency(0), Urgency::Critical);
+        assert_eq!(Urgency::Normal.title_prefix(), "");
+        assert_eq!(Urgency::Critical.title_prefix(), "🔴 ");
+    }
+
+    #[test]
+    fn urgency_colors_the_digits_and_background() {
+        let cases = [
+            (Urgency::Normal, WHITE, BACKGROUND),
+            (Urgency::Warning, ORANGE, BACKGROUND),
+            (Urgency::Critical, WHITE, RED),
+        ];
+        for (urgency, digits, background) in cases {
+            let img = render("01:05", 0.0, &params(IconStyle::Digits, urgency, 1));
+            assert_eq!(*img.get_pixel(0, 0), background, "{urgency:?}");
+            // "0" 的左上角
+            assert_eq!(*img.get_pixel(3, 12), digits, "{urgency:?}");
+            assert!(img.pixels().all(|p| *p == digits || *p == background), "{urgency:?}");
+        }
+    }
+
+    #[test]
+    fn render_key_tracks_what_the_style_shows() {
+        assert_eq!(IconStyle::Digits.render_key("12:34", 0.5), "12:34");
+        assert_eq!(IconStyle::Ring.render_key("12:34", 0.504), "50%");
+        assert_eq!(IconStyle::Ring.render_key("12:33", 0.496), "50%");
+        assert_eq!(IconStyle::Ring.render_key("12:34", 1.5), "100%");
+        assert_eq!(IconStyle::RingDigits.render_key("12:34", 0.25), "25% 12:34");
+    }
+
+    // 进度环上亮起的像素占比
+    fn ring_share(img: &RgbaImage, color: Rgba<u8>) -> f64 {
+        let ring: Vec<_> = img.pixels().filter(|p| **p == color || **p == RING_TRACK).collect();
+        ring.iter().filter(|p| ***p == color).count() as f64 / ring.len() as f64
+    }
+
+    #[test]
+    fn ring_fills_clockwise_from_the_top() {
+        let empty = render("10:00", 0.0, &params(IconStyle::Ring, Urgency::Normal, 1));
+        assert_eq!(ring_share(&empty, WHITE), 0.0);
+        // 只有进度环，没有数字
+        assert_eq!(*empty.get_pixel(16, 16), BACKGROUND);
+        assert_eq!(*empty.get_pixel(16, 0), RING_TRACK);
+
+        let quarter = render("10:00", 0.25, &params(IconStyle::Ring, Urgency::Normal, 1));
+        assert!((ring_share(&quarter, WHITE) - 0.25).abs() < 0.02);
+        // 右上角已填充，左上角尚未填充
+        assert_eq!(*quarter.get_pixel(26, 5), WHITE);
+        assert_eq!(*quarter.get_pixel(5, 5), RING_TRACK);
+
+        let full = render("10:00", 1.0, &params(IconStyle::Ring, Urgency::Normal, 1));
+        assert_eq!(ring_share(&full, WHITE), 1.0);
+    }
+
+    #[test]
+    fn ring_and_digits_are_drawn_together() {
+        let digits = render("01:05", 0.5, &params(IconStyle::Digits, Urgency::Normal, 1));
+        let both = render("01:05", 0.5, &params(IconStyle::RingDigits, Urgency::Normal, 1));
+        assert!(!digits.pixels().any(|p| *p == RING_TRACK));
+        assert!(both.pixels().any(|p| *p == RING_TRACK));
+        // 数字部分与只画数字时相同
+        for (x, y) in [(3, 12), (8, 12), (15, 14), (28, 18)] {
+            assert_eq!(both.get_pixel(x, y), digits.get_pixel(x, y), "({x}, {y})");
+        }
+    }
+
+    #[cfg(feature = "font-icons")]
+    #[test]
+    fn font_renderer_draws_centered_text_inside_the_icon() {
+        let renderer = FontRenderer::new().unwrap();
+        for time in ["00:07", "12:34", "1:05:00"] {
+            let img = render_time_icon(&renderer, time, 0.0, &params(IconStyle::Digits, Urgency::Normal, 1), None);
+            let (x0, y0, x1, y1) = bounds(&img);
+            assert!(x0 > 0 && y0 > 0 && x1 < ICON_SIZE - 1 && y1 < ICON_SIZE - 1, "{time}");
+            assert!((ICON_SIZE - 1 - x1).abs_diff(x0) <= 2, "{time}: {x0}..{x1}");
+        }
+    }
+
+    #[test]
+    fn scaled_icons_enlarge_every_logical_pixel() {
+        for style in [IconStyle::Digits, IconStyle::RingDigits] {
+            let small = render("12:34", 0.4, &params(style, Urgency::Warning, 1));
+            let large = render("12:34", 0.4, &params(style, Urgency::Warning, 2));
+            assert_eq!(large.dimensions(), (2 * ICON_SIZE, 2 * ICON_SIZE));
+            for (x, y, pixel) in large.enumerate_pixels() {
+                assert_eq!(pixel, small.get_pixel(x / 2, y / 2), "{style:?} ({x}, {y})");
+            }
+        }
+        // 放大倍数为 0 时按 1 处理
+        let zero = render("--:--", 0.0, &params(IconStyle::Digits, Urgency::Normal, 0));
+        assert_eq!(zero.dimensions(), (ICON_SIZE, ICON_SIZE));
+    }
+
+    #[test]
+    fn palette_follows_the_detected_appearance() {
+        let configured = Palette::LIGHT;
+        assert_eq!(Palette::for_appearance(Some(Appearance::Dark), configured), Palette::DARK);
+        assert_eq!(Palette::for_appearance(Some(Appearance::Light), Palette::DARK), Palette::LIGHT);
+        assert_eq!(Palette::for_appearance(None, configured), configured);
+    }
+
+    #[test]
+    fn light_palette_draws_dark_digits() {
+        let params = IconParams { palette: Palette::LIGHT, ..params(IconStyle::Digits, Urgency::Normal, 1) };
+        let img = render("01:05", 0.0, &params);
+        assert_eq!(*img.get_pixel(0, 0), Rgba(Palette::LIGHT.background));
+        assert_eq!(*img.get_pixel(3, 12), Rgba(Palette::LIGHT.foreground));
+        // 紧急程度的颜色不受配色影响
+        let warning = IconParams { urgency: Urgency::Warning, ..params };
+        assert_eq!(warning.colors().0, ORANGE);
+    }
+
+    #[test]
+    fn template_icons_are_transparent_and_ignore_urgency() {
+        for urgency in [Urgency::Normal, Urgency::Critical] {
+            let params = IconParams { template: true, ..params(IconStyle::RingDigits, urgency, 1) };
+            let colors = params.colors();
+            let expected = (Rgba(Palette::TEMPLATE.foreground), Rgba([0, 0, 0, 0]), Rgba(Palette::TEMPLATE.track));
+            assert_eq!(colors, expected, "{urgency:?}");
+
+            let img = render("01:05", 0.5, &params);
+            assert_eq!(img.get_pixel(16, 16).0[3], 0, "{urgency:?}");
+            assert_eq!(img.get_pixel(3, 12).0[3], 255, "{urgency:?}");
+        }
+    }
+
+    #[test]
+    fn fallback_icon_is_a_clock() {
+        let img = clock_image(Rgba(Palette::DARK.foreground), BACKGROUND);
+        assert_eq!(
+            ascii(&img),
+            [
+                "........#.......",
+                ".....#######....",
+                "...###########..",
+                "..###.......###.",
+                "..##.........##.",
+                ".##.....#.....##",
+                ".##.....#.....##",
+                ".##.....#.....##",
+                "###.....########",
+                ".##...........##",
+                ".##...........##",
+                ".##...........##",
+                "..##.........##.",
+                "..###.......###.",
+                "...###########..",
+                ".....#######....",
+            ]
+        );
+        // 与无法解析时间时绘制的图标相同
+        let unparsable = render("?", 0.0, &params(IconStyle::Digits, Urgency::Normal, 1));
+        assert_eq!(img, unparsable);
+    }
+
+    #[test]
+    fn faded_image_is_scaled_to_the_canvas() {
+        let red = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+        draw_faded_image(&mut img, &red, 0.5);
+        for pixel in img.pixels() {
+            let [r, g, b, a] = pixel.0;
+            assert!((126..=129).contains(&r), "{pixel:?}");
+            assert_eq!((g, b), (0, 0));
+            assert!(a >= 254, "{pixel:?}");
+        }
+
+        // 不透明度超出范围时按 0 和 1 处理
+        let mut img = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+        draw_faded_image(&mut img, &red, -1.0);
+        assert!(img.pixels().all(|p| p.0 == [0, 0, 0, 255]));
+        draw_faded_image(&mut img, &red, 2.0);
+        assert!(img.pixels().all(|p| p.0 == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn task_image_is_drawn_behind_the_digits() {
+        let params = params(IconStyle::RingDigits, Urgency::Normal, 1);
+        let plain = render("01:05", 0.5, &params);
+        let blue = RgbaImage::from_pixel(8, 8, Rgba([0, 0, 255, 255]));
+        let img = render_time_icon(&PixelRenderer, "01:05", 0.5, &params, Some(&blue));
+        assert_eq!(img.dimensions(), plain.dimensions());
+
+        let mut tinted = 0;
+        for (x, y, pixel) in plain.enumerate_pixels() {
+            if *pixel == BACKGROUND {
+                // 背景透出淡淡的图片颜色
+                let [r, _, b, _] = img.get_pixel(x, y).0;
+                assert!(b > BACKGROUND[2] && r < BACKGROUND[0], "({x}, {y})");
+                tinted += 1;
+            } else {
+                // 数字和进度环画在图片上面，不受影响
+                assert_eq!(img.get_pixel(x, y), pixel, "({x}, {y})");
+            }
+        }
+        assert!(tinted > 0);
+    }
+
+    #[test]
+    fn task_images_are_loaded_from_disk() {
+        let dir = std::env::temp_dir().join(format!("timeticker-task-image-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("icon.png");
+        let img = RgbaImage::from_pixel(3, 2, Rgba([10, 20, 30, 40]));
+        std::fs::write(&path, encode_png(&img).unwrap()).unwrap();
+
+        assert_eq!(load_image(&path).unwrap(), img);
+        assert!(matches!(load_image(&dir.join("missing.png")), Err(Error::Image { .. })));
+        std::fs::write(&path, b"not a png").unwrap();
+        assert!(matches!(load_image(&path), Err(Error::Image { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
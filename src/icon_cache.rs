@@ -0,0 +1,179 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+use tracing::trace;
+use tray_icon::Icon;
+
+use crate::{error::Result, icon::IconParams};
+
+pub const ICON_CACHE_CAPACITY: usize = 64; // 时间图标缓存的最大条目数
+
+// 决定时间图标内容的全部输入：显示的文本（或进度百分比）、绘制参数和任务图片
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconKey {
+    pub content: String,
+    pub params: IconParams,
+    pub image: Option<PathBuf>,
+}
+
+// 按绘制内容缓存时间图标，几个固定任务显示相同时间、或同一时间反复出现时不再重新绘制；
+// 条目数超过上限时淘汰最久未使用的
+pub struct IconCache {
+    entries: VecDeque<(IconKey, Icon)>, // 最近使用的在最后
+    capacity: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl Default for IconCache {
+    fn default() -> Self {
+        Self::new(ICON_CACHE_CAPACITY)
+    }
+}
+
+impl IconCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    // 缓存中有相同内容的图标时直接返回，否则调用 render 绘制并加入缓存
+    pub fn get_or_render(&mut self, key: IconKey, render: impl FnOnce() -> Result<Icon>) -> Result<Icon> {
+        if let Some(position) = self.entries.iter().position(|(cached, _)| *cached == key)
+            && let Some(entry) = self.entries.remove(position)
+        {
+            self.hits += 1;
+            trace!(hits = self.hits, misses = self.misses, "时间图标缓存命中: {}", key.content);
+            let icon = entry.1.clone();
+            self.entries.push_back(entry);
+            return Ok(icon);
+        }
+        self.misses += 1;
+        trace!(hits = self.hits, misses = self.misses, "时间图标缓存未命中: {}", key.content);
+        let icon = render()?;
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, icon.clone()));
+        Ok(icon)
+    }
+
+    // 任务图片重新读取后，用旧图片绘制的图标不再可用
+    pub fn forget_image(&mut self, path: &Path) {
+        self.entries.retain(|(key, _)| key.image.as_deref() != Some(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, io};
+
+    use snafu::ResultExt;
+
+    use super::*;
+    use crate::{
+        error::IoSnafu,
+        icon::{self, IconStyle, Palette, Urgency},
+    };
+
+    fn key(content: &str) -> IconKey {
+        IconKey {
+            content: content.to_string(),
+            params: IconParams {
+                style: IconStyle::Digits,
+                urgency: Urgency::Normal,
+                palette: Palette::DARK,
+                template: false,
+                scale: 1,
+            },
+            image: None,
+        }
+    }
+
+    // 取图标并返回是否重新绘制
+    fn rendered(cache: &mut IconCache, key: IconKey) -> bool {
+        let rendered = Cell::new(false);
+        cache
+            .get_or_render(key, || {
+                rendered.set(true);
+                icon::fallback_icon()
+            })
+            .unwrap();
+        rendered.get()
+    }
+
+    #[test]
+    fn same_content_is_drawn_once() {
+        let mut cache = IconCache::default();
+        assert!(rendered(&mut cache, key("25:00")));
+        assert!(!rendered(&mut cache, key("25:00")));
+        assert!(rendered(&mut cache, key("24:59")));
+        assert_eq!((cache.hits, cache.misses), (1, 2));
+
+        // 绘制参数或图片不同也是不同的图标
+        let warning = IconKey {
+            params: IconParams { urgency: Urgency::Warning, ..key("25:00").params },
+            ..key("25:00")
+        };
+        assert!(rendered(&mut cache, warning));
+        let with_image = IconKey { image: Some(PathBuf::from("/tmp/a.png")), ..key("25:00") };
+        assert!(rendered(&mut cache, with_image));
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let mut cache = IconCache::new(2);
+        rendered(&mut cache, key("a"));
+        rendered(&mut cache, key("b"));
+        // 使用过的 a 变为最近使用，加入 c 时淘汰 b
+        assert!(!rendered(&mut cache, key("a")));
+        assert!(rendered(&mut cache, key("c")));
+        assert_eq!(cache.entries.len(), 2);
+        assert!(!rendered(&mut cache, key("a")));
+        assert!(!rendered(&mut cache, key("c")));
+        assert!(rendered(&mut cache, key("b")));
+    }
+
+    #[test]
+    fn zero_capacity_still_keeps_the_last_icon() {
+        let mut cache = IconCache::new(0);
+        assert!(rendered(&mut cache, key("a")));
+        assert!(!rendered(&mut cache, key("a")));
+        assert!(rendered(&mut cache, key("b")));
+        assert!(rendered(&mut cache, key("a")));
+    }
+
+    #[test]
+    fn failed_renders_are_not_cached() {
+        let mut cache = IconCache::default();
+        let path = Path::new("/tmp/missing.png");
+        let failed = cache.get_or_render(key("a"), || {
+            Err(io::Error::from(io::ErrorKind::NotFound)).context(IoSnafu { path })
+        });
+        assert!(failed.is_err());
+        assert!(cache.entries.is_empty());
+        assert!(rendered(&mut cache, key("a")));
+    }
+
+    #[test]
+    fn forgetting_an_image_drops_only_its_icons() {
+        let mut cache = IconCache::default();
+        let old = PathBuf::from("/tmp/old.png");
+        let with_old = IconKey { image: Some(old.clone()), ..key("a") };
+        let with_other = IconKey { image: Some(PathBuf::from("/tmp/other.png")), ..key("a") };
+        for key in [with_old.clone(), with_other.clone(), key("a")] {
+            rendered(&mut cache, key);
+        }
+
+        cache.forget_image(&old);
+        assert!(rendered(&mut cache, with_old));
+        assert!(!rendered(&mut cache, with_other));
+        assert!(!rendered(&mut cache, key("a")));
+    }
+}
@@ -0,0 +1,734 @@
+use std::{fs, num::NonZeroU32, rc::Rc, sync::OnceLock, time::SystemTime};
+
+use ab_glyph::{Font as _, FontVec, ScaleFont as _};
+use snafu::ResultExt;
+use tracing::{debug, error, warn};
+use winit::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    event::{ElementState, Ime, KeyEvent, MouseButton, WindowEvent},
+    event_loop::ActiveEventLoop,
+    keyboard::{Key, ModifiersState, NamedKey},
+    window::{Window, WindowId, WindowLevel},
+};
+
+use crate::{
+    error::{FontLoadSnafu, Result, SoftBufferSnafu, WindowCreationSnafu},
+    i18n::{tr, tr_args},
+    icon::FONT_DATA,
+    parser::{TaskInput, parse_time_input},
+    task::{TaskId, TaskType, format_clock_time, format_remaining_time, time_format},
+};
+
+// 布局尺寸，均为逻辑像素
+const WIDTH: f32 = 440.0;
+const PADDING: f32 = 16.0;
+const GAP: f32 = 10.0;
+const TEXT_SIZE: f32 = 14.0;
+const LINE_HEIGHT: f32 = 20.0;
+const FIELD_HEIGHT: f32 = 30.0;
+const BUTTON_WIDTH: f32 = 80.0;
+const BUTTON_HEIGHT: f32 = 28.0;
+
+// 颜色，格式为 softbuffer 使用的 0RGB
+const BACKGROUND: u32 = 0xF2F2F2;
+const TEXT: u32 = 0x202020;
+const FIELD: u32 = 0xFFFFFF;
+const BORDER: u32 = 0x2F6FEB;
+const PREVIEW_OK: u32 = 0x2E7D32;
+const PREVIEW_ERROR: u32 = 0xC0392B;
+const BUTTON: u32 = 0xDDDDDD;
+const PRIMARY: u32 = 0x2F6FEB;
+const PRIMARY_DISABLED: u32 = 0xA9C0EE;
+const PRIMARY_TEXT: u32 = 0xFFFFFF;
+const PREEDIT: u32 = 0x707070;
+
+// 支持中文的系统字体，都找不到时使用内嵌字体（不含中文字形）
+const SYSTEM_FONTS: &[&str] = &[
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Medium.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+];
+
+// 输入结果的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputPurpose {
+    NewTask,
+    EditTask(TaskId),
+    QuickAdd, // 快速添加窗口，回车直接创建任务
+}
+
+// 一次输入请求：标题、提示和预填的文本
+#[derive(Debug, Clone)]
+pub struct InputRequest {
+    pub purpose: InputPurpose,
+    pub title: &'static str,
+    pub message: &'static str,
+    pub default_text: String,
+}
+
+// 输入窗口关闭的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputOutcome {
+    Submit(InputPurpose, String),
+    Cancel(InputPurpose),
+}
+
+// 输入的实时解析结果：成功时为解析出的任务描述，失败时为错误原因
+pub fn preview(input: &str, now: SystemTime) -> std::result::Result<String, String> {
+    let task_input = parse_time_input(input).map_err(|e| e.to_string())?;
+    Ok(describe(&task_input, input.contains('#'), now))
+}
+
+fn describe(input: &TaskInput, named: bool, now: SystemTime) -> String {
+    let mut parts = vec![match input.task_type {
+        TaskType::Duration(duration) => tr_args("time.duration", &[(
+            "time",
+            &format_remaining_time(duration, time_format()),
+        )]),
+        TaskType::Deadline(deadline) => tr_args("time.deadline", &[("time", &format_clock_time(deadline, now))]),
+    }];
+    // 没有 # 时新建任务使用默认名称，编辑任务保留原名称，都不显示
+    if named {
+        parts.push(input.name.clone());
+    }
+    if let Some(group) = &input.group {
+        parts.push(format!("/{group}"));
+    }
+    if input.on_complete.is_some() {
+        parts.push(tr("input.on_complete").to_string());
+    }
+    parts.join(" · ")
+}
+
+// 内置的任务输入窗口：文本框、实时解析预览以及确定/取消按钮，
+// 在事件循环中绘制和处理输入，打开期间计时照常刷新；
+// 快速添加窗口是紧凑样式：无边框，只有文本框和预览，失去焦点时关闭，关闭后隐藏以便复用
+pub struct InputWindow {
+    window: Rc<Window>,
+    surface: softbuffer::Surface<Rc<Window>, Rc<Window>>,
+    font: &'static FontVec,
+    purpose: InputPurpose,
+    compact: bool,                                // 快速添加的紧凑样式
+    focused: bool,                                // 本次显示后是否已经获得过焦点
+    message_lines: Vec<String>,                   // 按窗口宽度折行后的提示
+    text: String,                                 // 文本框内容
+    cursor: usize,                                // 光标位置（字符数）
+    preedit: String,                              // 输入法正在组合的文本
+    preview: std::result::Result<String, String>, // 当前内容的解析结果
+    modifiers: ModifiersState,                    // 当前按下的修饰键
+    pointer: Option<PhysicalPosition<f64>>,       // 鼠标在窗口中的位置
+}
+
+impl InputWindow {
+    pub fn open(event_loop: &ActiveEventLoop, request: &InputRequest) -> Result<Self> {
+        let font = font()?;
+        let message_lines = wrap_text(font, request.message, TEXT_SIZE, WIDTH - PADDING * 2.0);
+        let height = PADDING * 2.0
+            + message_lines.len() as f32 * LINE_HEIGHT
+            + GAP
+            + FIELD_HEIGHT
+            + LINE_HEIGHT
+            + GAP
+            + BUTTON_HEIGHT;
+        let attributes = Window::default_attributes()
+            .with_title(request.title)
+            .with_inner_size(LogicalSize::new(WIDTH, height))
+            .with_resizable(false)
+            .with_window_level(WindowLevel::AlwaysOnTop);
+        let window = Rc::new(event_loop.create_window(attributes).context(WindowCreationSnafu)?);
+        let context = softbuffer::Context::new(window.clone()).context(SoftBufferSnafu)?;
+        let surface = softbuffer::Surface::new(&context, window.clone()).context(SoftBufferSnafu)?;
+        window.set_ime_allowed(true);
+        window.focus_window();
+
+        let text = request.default_text.clone();
+        Ok(Self {
+            window,
+            surface,
+            font,
+            purpose: request.purpose,
+            compact: false,
+            focused: false,
+            message_lines,
+            cursor: text.chars().count(),
+            preview: preview(&text, SystemTime::now()),
+            text,
+            preedit: String::new(),
+            modifiers: ModifiersState::empty(),
+            pointer: None,
+        })
+    }
+
+    // 创建快速添加窗口，创建后保持隐藏，由 show 显示
+    pub fn open_quick_add(event_loop: &ActiveEventLoop) -> Result<Self> {
+        let font = font()?;
+        let height = PADDING * 2.0 + FIELD_HEIGHT + LINE_HEIGHT;
+        let attributes = Window::default_attributes()
+            .with_title(tr("input.quick_add_title"))
+            .with_inner_size(LogicalSize::new(WIDTH, height))
+            .with_resizable(false)
+            .with_decorations(false)
+            .with_visible(false)
+            .with_window_level(WindowLevel::AlwaysOnTop);
+        let window = Rc::new(event_loop.create_window(attributes).context(WindowCreationSnafu)?);
+        let context = softbuffer::Context::new(window.clone()).context(SoftBufferSnafu)?;
+        let surface = softbuffer::Surface::new(&context, window.clone()).context(SoftBufferSnafu)?;
+        window.set_ime_allowed(true);
+        Ok(Self {
+            window,
+            surface,
+            font,
+            purpose: InputPurpose::QuickAdd,
+            compact: true,
+            focused: false,
+            message_lines: Vec::new(),
+            cursor: 0,
+            preview: preview("", SystemTime::now()),
+            text: String::new(),
+            preedit: String::new(),
+            modifiers: ModifiersState::empty(),
+            pointer: None,
+        })
+    }
+
+    // 清空内容后显示在当前显示器水平居中、略高于中线的位置，并获得焦点
+    pub fn show(&mut self) {
+        self.text.clear();
+        self.preedit.clear();
+        self.cursor = 0;
+        self.focused = false;
+        self.preview = preview("", SystemTime::now());
+        let monitor = self.window.current_monitor().or_else(|| self.window.primary_monitor());
+        if let Some(monitor) = monitor {
+            let position = popup_position(monitor.position(), monitor.size(), self.window.outer_size());
+            self.window.set_outer_position(position);
+        }
+        self.window.set_visible(true);
+        self.window.focus_window();
+        self.window.request_redraw();
+    }
+
+    // 隐藏窗口，保留给下次快速添加使用
+    pub fn hide(&mut self) {
+        self.focused = false;
+        self.window.set_visible(false);
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    // 再次请求输入时把已打开的窗口移到前台
+    pub fn focus(&self) {
+        self.window.focus_window();
+    }
+
+    // 处理窗口事件，窗口需要关闭时返回结果
+    pub fn handle_event(&mut self, event: &WindowEvent) -> Option<InputOutcome> {
+        match event {
+            WindowEvent::CloseRequested => return Some(InputOutcome::Cancel(self.purpose)),
+            // 快速添加窗口像 Spotlight 一样，获得焦点后点击其他位置即关闭；
+            // 显示前的失焦事件不算，避免刚显示就被关闭
+            WindowEvent::Focused(true) => {
+                self.focused = true;
+                return None;
+            }
+            WindowEvent::Focused(false) if self.compact && self.focused => {
+                return Some(InputOutcome::Cancel(self.purpose));
+            }
+            WindowEvent::RedrawRequested => {
+                if let Err(e) = self.redraw() {
+                    error!("Failed to draw input window: {}", e);
+                }
+                return None;
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+                return None;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer = Some(*position);
+                return None;
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if !self.compact => {
+                let pointer = self.pointer?;
+                let layout = self.layout();
+                if layout.confirm.contains(pointer) {
+                    return self.submit();
+                }
+                if layout.cancel.contains(pointer) {
+                    return Some(InputOutcome::Cancel(self.purpose));
+                }
+                return None;
+            }
+            WindowEvent::Ime(Ime::Preedit(text, _)) => self.preedit = text.clone(),
+            WindowEvent::Ime(Ime::Commit(text)) => {
+                self.preedit.clear();
+                self.insert(text);
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                return self.handle_key(event);
+            }
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {}
+            _ => return None,
+        }
+        self.window.request_redraw();
+        None
+    }
+
+    fn handle_key(&mut self, event: &KeyEvent) -> Option<InputOutcome> {
+        // 输入法组合中的按键由输入法处理
+        if !self.preedit.is_empty() {
+            return None;
+        }
+        let shortcut = if cfg!(target_os = "macos") {
+            self.modifiers.super_key()
+        } else {
+            self.modifiers.control_key()
+        };
+        let len = self.text.chars().count();
+        match &event.logical_key {
+            Key::Named(NamedKey::Enter) => return self.submit(),
+            Key::Named(NamedKey::Escape) => return Some(InputOutcome::Cancel(self.purpose)),
+            Key::Named(NamedKey::Backspace) if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(byte_index(&self.text, self.cursor));
+            }
+            Key::Named(NamedKey::Delete) if self.cursor < len => {
+                self.text.remove(byte_index(&self.text, self.cursor));
+            }
+            Key::Named(NamedKey::ArrowLeft) => self.cursor = self.cursor.saturating_sub(1),
+            Key::Named(NamedKey::ArrowRight) => self.cursor = (self.cursor + 1).min(len),
+            Key::Named(NamedKey::Home) | Key::Named(NamedKey::ArrowUp) => self.cursor = 0,
+            Key::Named(NamedKey::End) | Key::Named(NamedKey::ArrowDown) => self.cursor = len,
+            Key::Character(c) if shortcut && c.eq_ignore_ascii_case("v") => self.paste(),
+            _ if shortcut => return None,
+            _ => match &event.text {
+                Some(text) => self.insert(text),
+                None => return None,
+            },
+        }
+        self.preview = preview(&self.text, SystemTime::now());
+        self.window.request_redraw();
+        None
+    }
+
+    // 在光标处插入文本，忽略换行等控制字符
+    fn insert(&mut self, text: &str) {
+        for ch in text.chars().filter(|ch| !ch.is_control()) {
+            self.text.insert(byte_index(&self.text, self.cursor), ch);
+            self.cursor += 1;
+        }
+        self.preview = preview(&self.text, SystemTime::now());
+    }
+
+    fn paste(&mut self) {
+        match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(text) => self.insert(&text),
+            Err(e) => warn!("Failed to paste into input window: {}", e),
+        }
+    }
+
+    // 无法解析时保持窗口打开，预览中已经显示了原因
+    fn submit(&self) -> Option<InputOutcome> {
+        if self.preview.is_err() {
+            debug!("Ignoring submit of unparsable input: {}", self.text);
+            return None;
+        }
+        Some(InputOutcome::Submit(self.purpose, self.text.trim().to_string()))
+    }
+
+    // 当前缩放比例下各部件的位置（物理像素）
+    fn layout(&self) -> Layout {
+        let scale = self.window.scale_factor() as f32;
+        let width = self.window.inner_size().width as f32;
+        let message_height = match self.message_lines.len() {
+            0 => 0.0,
+            lines => lines as f32 * LINE_HEIGHT + GAP,
+        };
+        let field_y = (PADDING + message_height) * scale;
+        let field = Rect::new(
+            PADDING * scale,
+            field_y,
+            width - PADDING * 2.0 * scale,
+            FIELD_HEIGHT * scale,
+        );
+        let preview_y = field.y + field.h;
+        // 紧凑样式没有按钮，按钮位置不会被绘制或点击
+        let button_y = preview_y + (LINE_HEIGHT + GAP) * scale;
+        let confirm_x = width - (PADDING + BUTTON_WIDTH) * scale;
+        let cancel_x = confirm_x - (GAP + BUTTON_WIDTH) * scale;
+        Layout {
+            scale,
+            field,
+            preview_y,
+            confirm: Rect::new(confirm_x, button_y, BUTTON_WIDTH * scale, BUTTON_HEIGHT * scale),
+            cancel: Rect::new(cancel_x, button_y, BUTTON_WIDTH * scale, BUTTON_HEIGHT * scale),
+        }
+    }
+
+    fn redraw(&mut self) -> Result<()> {
+        let size = self.window.inner_size();
+        let (Some(width), Some(height)) = (NonZeroU32::new(size.width), NonZeroU32::new(size.height)) else {
+            return Ok(());
+        };
+        let layout = self.layout();
+        // 先画到临时缓冲区，绘制时需要借用 self，而 surface 的缓冲区会一直可变借用 self.surface
+        let mut pixels = vec![0; size.width as usize * size.height as usize];
+        let mut canvas = Canvas {
+            pixels: &mut pixels,
+            width: size.width,
+            height: size.height,
+        };
+        self.paint(&mut canvas, &layout);
+        self.surface.resize(width, height).context(SoftBufferSnafu)?;
+        let mut buffer = self.surface.buffer_mut().context(SoftBufferSnafu)?;
+        buffer.copy_from_slice(&pixels);
+        buffer.present().context(SoftBufferSnafu)
+    }
+
+    fn paint(&self, canvas: &mut Canvas, layout: &Layout) {
+        let scale = layout.scale;
+        let px = TEXT_SIZE * scale;
+        let window = Rect::new(0.0, 0.0, canvas.width as f32, canvas.height as f32);
+        canvas.fill(window, BACKGROUND);
+
+        let mut y = PADDING * scale;
+        for line in &self.message_lines {
+            self.draw_text(
+                canvas,
+                line,
+                px,
+                PADDING * scale,
+                y + LINE_HEIGHT * scale / 2.0,
+                TEXT,
+                window,
+            );
+            y += LINE_HEIGHT * scale;
+        }
+
+        // 文本框：内容超出宽度时向左滚动，保持光标可见
+        let field = layout.field;
+        canvas.fill(field, BORDER);
+        canvas.fill(field.inset(scale.max(1.0)), FIELD);
+        let inner = field.inset(8.0 * scale);
+        let before: String = self.text.chars().take(self.cursor).collect();
+        let after: String = self.text.chars().skip(self.cursor).collect();
+        let caret_offset = text_width(self.font, &before, px) + text_width(self.font, &self.preedit, px);
+        let text_x = inner.x - (caret_offset - inner.w).max(0.0);
+        let center_y = field.y + field.h / 2.0;
+        let mut x = text_x;
+        x += self.draw_text(canvas, &before, px, x, center_y, TEXT, inner);
+        x += self.draw_text(canvas, &self.preedit, px, x, center_y, PREEDIT, inner);
+        canvas.fill(Rect::new(x, center_y - px * 0.6, scale.max(1.0), px * 1.2), TEXT);
+        self.draw_text(canvas, &after, px, x, center_y, TEXT, inner);
+
+        let (preview, color) = preview_line(&self.preview, &self.text, self.compact);
+        let preview_center = layout.preview_y + LINE_HEIGHT * scale / 2.0;
+        self.draw_text(canvas, preview, px * 0.9, field.x, preview_center, color, window);
+
+        if self.compact {
+            return;
+        }
+        let confirm_color = if self.preview.is_ok() {
+            PRIMARY
+        } else {
+            PRIMARY_DISABLED
+        };
+        self.draw_button(canvas, layout.cancel, tr("button.cancel"), px, BUTTON, TEXT);
+        self.draw_button(canvas, layout.confirm, tr("button.ok"), px, confirm_color, PRIMARY_TEXT);
+    }
+
+    fn draw_button(&self, canvas: &mut Canvas, rect: Rect, label: &str, px: f32, background: u32, color: u32) {
+        canvas.fill(rect, background);
+        let x = rect.x + (rect.w - text_width(self.font, label, px)) / 2.0;
+        self.draw_text(canvas, label, px, x, rect.y + rect.h / 2.0, color, rect);
+    }
+
+    // 以 center_y 为垂直中心从 x 开始绘制一行文本，只绘制 clip
+    // 以内的部分；返回文本宽度
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text(
+        &self,
+        canvas: &mut Canvas,
+        text: &str,
+        px: f32,
+        x: f32,
+        center_y: f32,
+        color: u32,
+        clip: Rect,
+    ) -> f32 {
+        let scaled = self.font.as_scaled(px);
+        let baseline = center_y + (scaled.ascent() + scaled.descent()) / 2.0;
+        let mut pen = x;
+        for ch in text.chars() {
+            let id = scaled.glyph_id(ch);
+            let glyph = id.with_scale_and_position(px, ab_glyph::point(pen, baseline));
+            pen += scaled.h_advance(id);
+            let Some(outlined) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let (pixel_x, pixel_y) = (bounds.min.x + gx as f32, bounds.min.y + gy as f32);
+                if clip.contains_point(pixel_x, pixel_y) {
+                    canvas.blend(pixel_x as i32, pixel_y as i32, color, coverage);
+                }
+            });
+        }
+        pen - x
+    }
+}
+
+// 窗口各部件的位置
+struct Layout {
+    scale: f32,
+    field: Rect,
+    preview_y: f32,
+    confirm: Rect,
+    cancel: Rect,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+impl Rect {
+    fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    fn inset(self, d: f32) -> Self {
+        Self::new(
+            self.x + d,
+            self.y + d,
+            (self.w - d * 2.0).max(0.0),
+            (self.h - d * 2.0).max(0.0),
+        )
+    }
+
+    fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.w && y >= self.y && y < self.y + self.h
+    }
+
+    fn contains(&self, position: PhysicalPosition<f64>) -> bool {
+        self.contains_point(position.x as f32, position.y as f32)
+    }
+}
+
+// softbuffer 的像素缓冲区
+struct Canvas<'a> {
+    pixels: &'a mut [u32],
+    width: u32,
+    height: u32,
+}
+
+impl Canvas<'_> {
+    fn fill(&mut self, rect: Rect, color: u32) {
+        let x0 = rect.x.max(0.0) as u32;
+        let y0 = rect.y.max(0.0) as u32;
+        let x1 = ((rect.x + rect.w).max(0.0) as u32).min(self.width);
+        let y1 = ((rect.y + rect.h).max(0.0) as u32).min(self.height);
+        for y in y0..y1 {
+            let row = (y * self.width) as usize;
+            self.pixels[row + x0 as usize..row + x1.max(x0) as usize].fill(color);
+        }
+    }
+
+    // 按覆盖率把颜色混合到背景上
+    fn blend(&mut self, x: i32, y: i32, color: u32, coverage: f32) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return;
+        }
+        let index = (y as u32 * self.width + x as u32) as usize;
+        let background = self.pixels[index];
+        let mix = |shift: u32| {
+            let b = ((background >> shift) & 0xFF) as f32;
+            let c = ((color >> shift) & 0xFF) as f32;
+            ((b + (c - b) * coverage.clamp(0.0, 1.0)).round() as u32) << shift
+        };
+        self.pixels[index] = mix(16) | mix(8) | mix(0);
+    }
+}
+
+// 读取一次字体后缓存，之后打开窗口不再读取
+// 预览行的文字和颜色；快速添加窗口刚打开时提示输入格式，而不是显示缺少时间的错误
+fn preview_line<'a>(
+    preview: &'a std::result::Result<String, String>,
+    text: &str,
+    compact: bool,
+) -> (&'a str, u32) {
+    match preview {
+        Err(_) if compact && text.is_empty() => (tr("input.quick_add_hint"), PREEDIT),
+        Ok(description) => (description.as_str(), PREVIEW_OK),
+        Err(reason) => (reason.as_str(), PREVIEW_ERROR),
+    }
+}
+
+// 快速添加窗口在显示器上的位置：水平居中，顶部位于三分之一高度处
+fn popup_position(
+    origin: PhysicalPosition<i32>,
+    area: PhysicalSize<u32>,
+    size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    let x = origin.x + (area.width.saturating_sub(size.width) / 2) as i32;
+    let y = origin.y + (area.height.saturating_sub(size.height) / 3) as i32;
+    PhysicalPosition::new(x, y)
+}
+
+fn font() -> Result<&'static FontVec> {
+    static FONT: OnceLock<FontVec> = OnceLock::new();
+    if let Some(font) = FONT.get() {
+        return Ok(font);
+    }
+    let font = load_font()?;
+    Ok(FONT.get_or_init(|| font))
+}
+
+fn load_font() -> Result<FontVec> {
+    for path in SYSTEM_FONTS {
+        let Ok(data) = fs::read(path) else {
+            continue;
+        };
+        match FontVec::try_from_vec_and_index(data, 0) {
+            Ok(font) => {
+                debug!("Input window font: {}", path);
+                return Ok(font);
+            }
+            Err(e) => warn!("Failed to load font {}: {}", path, e),
+        }
+    }
+    warn!("No CJK font found, Chinese text in the input window may not render");
+    FontVec::try_from_vec(FONT_DATA.to_vec()).context(FontLoadSnafu)
+}
+
+fn text_width(font: &FontVec, text: &str, px: f32) -> f32 {
+    let scaled = font.as_scaled(px);
+    text.chars().map(|ch| scaled.h_advance(scaled.glyph_id(ch))).sum()
+}
+
+// 按宽度折行，保留原有的换行和空行
+fn wrap_text(font: &FontVec, text: &str, px: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for ch in paragraph.chars() {
+            line.push(ch);
+            if text_width(font, &line, px) > max_width && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::take(&mut line));
+                line.push(ch);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn byte_index(text: &str, chars: usize) -> usize {
+    text.char_indices().nth(chars).map_or(text.len(), |(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded_font() -> FontVec {
+        FontVec::try_from_vec(FONT_DATA.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn preview_describes_the_parsed_input() {
+        let now = SystemTime::now();
+        assert_eq!(preview("1h", now).unwrap(), "时长 01:00:00");
+        assert_eq!(
+            preview("25m#写作 /工作 && say done", now).unwrap(),
+            "时长 00:25:00 · 写作 · /工作 · 完成后执行命令"
+        );
+        // 错误原因与解析错误的说明一致
+        let error = parse_time_input("abc").unwrap_err().to_string();
+        assert_eq!(preview("abc", now), Err(error));
+    }
+
+    #[test]
+    fn quick_add_hints_until_something_is_typed() {
+        let empty = preview("", SystemTime::now());
+        assert_eq!(preview_line(&empty, "", true), (tr("input.quick_add_hint"), PREEDIT));
+        // 普通输入窗口仍然显示错误原因
+        assert_eq!(preview_line(&empty, "", false).1, PREVIEW_ERROR);
+        let invalid = preview("abc", SystemTime::now());
+        assert_eq!(preview_line(&invalid, "abc", true).1, PREVIEW_ERROR);
+        let valid = preview("1h", SystemTime::now());
+        assert_eq!(preview_line(&valid, "1h", true), ("时长 01:00:00", PREVIEW_OK));
+    }
+
+    #[test]
+    fn popup_is_centred_a_third_of_the_way_down() {
+        let position = popup_position(
+            PhysicalPosition::new(1920, -100),
+            PhysicalSize::new(1920, 1080),
+            PhysicalSize::new(440, 66),
+        );
+        assert_eq!(position, PhysicalPosition::new(1920 + 740, -100 + 338));
+        // 窗口比显示器大时贴住左上角
+        let position =
+            popup_position(PhysicalPosition::new(0, 0), PhysicalSize::new(300, 50), PhysicalSize::new(440, 66));
+        assert_eq!(position, PhysicalPosition::new(0, 0));
+    }
+
+    #[test]
+    fn wraps_text_to_the_width_and_keeps_line_breaks() {
+        let font = embedded_font();
+        assert_eq!(wrap_text(&font, "ab\n\ncd", TEXT_SIZE, 1000.0), ["ab", "", "cd"]);
+        let width = text_width(&font, "abc", TEXT_SIZE);
+        assert_eq!(wrap_text(&font, "abcdef", TEXT_SIZE, width), ["abc", "def"]);
+        // 比宽度还宽的单个字符自成一行
+        assert_eq!(wrap_text(&font, "ab", TEXT_SIZE, 1.0), ["a", "b"]);
+    }
+
+    #[test]
+    fn byte_index_counts_characters() {
+        assert_eq!(byte_index("写作a", 0), 0);
+        assert_eq!(byte_index("写作a", 1), 3);
+        assert_eq!(byte_index("写作a", 2), 6);
+        assert_eq!(byte_index("写作a", 3), 7);
+        assert_eq!(byte_index("写作a", 10), 7);
+    }
+
+    #[test]
+    fn rects_inset_and_contain_points() {
+        let rect = Rect::new(10.0, 20.0, 30.0, 40.0);
+        assert!(rect.contains_point(10.0, 20.0));
+        assert!(!rect.contains_point(40.0, 20.0));
+        assert!(rect.contains(PhysicalPosition::new(39.5, 59.5)));
+        let inner = rect.inset(5.0);
+        assert_eq!((inner.x, inner.y, inner.w, inner.h), (15.0, 25.0, 20.0, 30.0));
+        assert_eq!(rect.inset(100.0).w, 0.0);
+    }
+
+    #[test]
+    fn canvas_fills_and_blends_inside_its_bounds() {
+        let mut pixels = vec![0; 8];
+        let mut canvas = Canvas { pixels: &mut pixels, width: 4, height: 2 };
+        canvas.fill(Rect::new(2.0, -1.0, 5.0, 2.0), 0xFFFFFF);
+        canvas.blend(1, 1, 0xFF8000, 0.5);
+        canvas.blend(4, 0, 0xFFFFFF, 1.0);
+        canvas.blend(-1, 0, 0xFFFFFF, 1.0);
+        assert_eq!(pixels, [0, 0, 0xFFFFFF, 0xFFFFFF, 0, 0x804000, 0, 0]);
+    }
+}
@@ -0,0 +1,354 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{self, Stdio},
+};
+
+use directories::ProjectDirs;
+use snafu::ResultExt;
+use tracing::{debug, warn};
+
+use crate::{
+    error::{IoSnafu, IpcMessageSnafu, IpcUnsupportedSnafu, Result},
+    ipc::{self, Command, Reply},
+};
+
+const LOCK_FILE: &str = "timeticker.lock"; // 内容为持有锁的进程 PID
+#[cfg(unix)]
+const SOCKET_FILE: &str = "timeticker.sock"; // 已运行的实例在此监听其他实例的消息
+
+// 单实例锁所在目录：平台数据目录，与任务存档放在一起
+pub fn default_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "TimeTicker").map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+// 尝试获取单实例锁的结果
+#[derive(Debug)]
+pub enum Acquire {
+    Acquired(InstanceLock),
+    Running(Option<u32>), // 已有实例在运行，附带其 PID（无法读取时为 None）
+}
+
+// 单实例锁，释放时删除锁文件和监听的套接字
+#[derive(Debug)]
+pub struct InstanceLock {
+    dir: PathBuf,
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if let Err(e) = remove_if_exists(&self.dir.join(LOCK_FILE)) {
+            warn!("Failed to remove instance lock: {}", e);
+        }
+        #[cfg(unix)]
+        if let Err(e) = remove_if_exists(&self.dir.join(SOCKET_FILE)) {
+            warn!("Failed to remove instance socket: {}", e);
+        }
+    }
+}
+
+// 获取单实例锁：锁文件已存在且其中的进程仍在运行时返回 Running；
+// 进程已经退出（崩溃后残留的锁）时删除旧锁并重新获取
+pub fn acquire(dir: &Path) -> Result<Acquire> {
+    fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+    let path = dir.join(LOCK_FILE);
+
+    // 先写入带 PID 的临时文件再硬链接到锁文件，其他实例不会读到空的锁文件
+    let tmp = dir.join(format!("{LOCK_FILE}.{}", process::id()));
+    fs::write(&tmp, process::id().to_string()).context(IoSnafu { path: &tmp })?;
+    let result = link_lock(&tmp, &path);
+    remove_if_exists(&tmp)?;
+    if result? {
+        Ok(Acquire::Acquired(InstanceLock { dir: dir.to_path_buf() }))
+    } else {
+        Ok(Acquire::Running(read_pid(&path)))
+    }
+}
+
+// 创建锁文件，遇到过期的锁时删除后重试一次；返回是否获取成功
+fn link_lock(tmp: &Path, path: &Path) -> Result<bool> {
+    for _ in 0..2 {
+        match fs::hard_link(tmp, path) {
+            Ok(()) => return Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => match read_pid(path) {
+                Some(pid) if pid != process::id() && process_alive(pid) => return Ok(false),
+                pid => {
+                    warn!("Removing stale instance lock held by {:?}", pid);
+                    remove_if_exists(path)?;
+                }
+            },
+            Err(e) => return Err(e).context(IoSnafu { path }),
+        }
+    }
+    Ok(false)
+}
+
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e).context(IoSnafu { path }),
+        _ => Ok(()),
+    }
+}
+
+// 检查进程是否仍在运行
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+// 在数据目录下监听控制命令（其他实例的通知和命令行），每行一个 JSON 命令，
+// 每条命令回复一行 JSON；on_command 在监听线程中调用
+#[cfg(unix)]
+pub fn listen(dir: &Path, on_command: impl Fn(Command) -> Reply + Send + 'static) -> Result<()> {
+    use std::os::unix::net::UnixListener;
+
+    // 持有锁时残留的套接字一定来自已退出的实例
+    let path = dir.join(SOCKET_FILE);
+    remove_if_exists(&path)?;
+    let listener = UnixListener::bind(&path).context(IoSnafu { path: &path })?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Failed to accept control connection: {}", e);
+                    continue;
+                }
+            };
+            let mut writer = &stream;
+            for line in BufReader::new(&stream).lines() {
+                let line = match line {
+                    Ok(line) if line.trim().is_empty() => continue,
+                    Ok(line) => line,
+                    Err(e) => {
+                        warn!("Failed to read control message: {}", e);
+                        break;
+                    }
+                };
+                let reply = ipc::handle_line(&line, &on_command);
+                if let Err(e) = writeln!(writer, "{reply}") {
+                    debug!("Failed to reply to control message: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn listen(_dir: &Path, _on_command: impl Fn(Command) -> Reply + Send + 'static) -> Result<()> {
+    debug!("Control socket is only supported on unix platforms");
+    Ok(())
+}
+
+// 向已在运行的实例发送命令并等待回复
+#[cfg(unix)]
+pub fn send(dir: &Path, command: &Command) -> Result<Reply> {
+    use std::os::unix::net::UnixStream;
+
+    let path = dir.join(SOCKET_FILE);
+    let mut stream = UnixStream::connect(&path).context(IoSnafu { path: &path })?;
+    let request = serde_json::to_string(command).context(IpcMessageSnafu)?;
+    writeln!(stream, "{request}").context(IoSnafu { path: &path })?;
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .context(IoSnafu { path: &path })?;
+    serde_json::from_str(&reply).context(IpcMessageSnafu)
+}
+
+#[cfg(not(unix))]
+pub fn send(_dir: &Path, _command: &Command) -> Result<Reply> {
+    IpcUnsupportedSnafu.fail()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, SystemTime},
+    };
+
+    use parking_lot::Mutex;
+    use snafu::OptionExt;
+
+    use super::*;
+    use crate::{
+        error::TaskIdNotFoundSnafu,
+        ipc::TaskInfo,
+        task::{Task, TaskId, TaskList, TaskType},
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timeticker-instance-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn lock_is_acquired_and_released() {
+        let dir = temp_dir("release");
+        let Acquire::Acquired(lock) = acquire(&dir).unwrap() else {
+            panic!("lock should be free");
+        };
+        assert_eq!(read_pid(&dir.join(LOCK_FILE)), Some(process::id()));
+        drop(lock);
+        assert!(!dir.join(LOCK_FILE).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn live_lock_refuses_and_stale_lock_is_replaced() {
+        let dir = temp_dir("stale");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOCK_FILE);
+
+        let mut other = process::Command::new("sleep").arg("30").spawn().unwrap();
+        fs::write(&path, other.id().to_string()).unwrap();
+        assert!(matches!(acquire(&dir).unwrap(), Acquire::Running(Some(pid)) if pid == other.id()));
+
+        // 持有锁的进程退出后，锁被当作崩溃后的残留
+        other.kill().unwrap();
+        other.wait().unwrap();
+        assert!(matches!(acquire(&dir).unwrap(), Acquire::Acquired(_)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_reach_the_listening_instance() {
+        let dir = temp_dir("ping");
+        let Acquire::Acquired(lock) = acquire(&dir).unwrap() else {
+            panic!("lock should be free");
+        };
+        let (sender, receiver) = std::sync::mpsc::channel();
+        listen(&dir, move |command| {
+            sender.send(command).unwrap();
+            Reply::ok()
+        })
+        .unwrap();
+
+        assert!(send(&dir, &Command::Ping).unwrap().ok);
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), Command::Ping);
+        assert!(send(&dir, &Command::List).unwrap().ok);
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), Command::List);
+
+        drop(lock);
+        assert!(!dir.join(SOCKET_FILE).exists());
+        assert!(send(&dir, &Command::Ping).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 代替应用处理控制命令，任务保存在内存中
+    #[derive(Default)]
+    struct MockApp {
+        tasks: TaskList,
+    }
+
+    impl MockApp {
+        fn handle(&mut self, command: Command) -> Result<Reply> {
+            let mut reply = Reply::ok();
+            match command {
+                Command::Ping => {}
+                Command::Add { input } => {
+                    let task = Task::new(input, TaskType::Duration(Duration::from_secs(1500)))?;
+                    reply.id = Some(self.tasks.push(task));
+                }
+                Command::List => {
+                    let infos = self.tasks.iter().map(TaskInfo::new).collect::<Result<Vec<_>>>()?;
+                    reply.tasks = Some(infos);
+                }
+                Command::Start { id } => self.tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?.start(),
+                Command::Pause { id } => self.tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?.pause()?,
+                Command::Reset { id } => self.tasks.reset(id)?,
+                Command::Delete { id } => {
+                    self.tasks.delete(id).context(TaskIdNotFoundSnafu { id })?;
+                }
+                Command::Snooze { id } => {
+                    self.tasks.snooze(id, Duration::from_secs(300), SystemTime::now())?;
+                }
+                Command::History { .. } => reply.history = Some(Vec::new()),
+            }
+            Ok(reply)
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn control_commands_drive_the_application() {
+        let dir = temp_dir("commands");
+        let Acquire::Acquired(_lock) = acquire(&dir).unwrap() else {
+            panic!("lock should be free");
+        };
+        let app = Arc::new(Mutex::new(MockApp::default()));
+        let handler = Arc::clone(&app);
+        listen(&dir, move |command| Reply::from_result(handler.lock().handle(command))).unwrap();
+
+        let state = |id: TaskId| {
+            let tasks = send(&dir, &Command::List).unwrap().tasks.unwrap();
+            tasks.into_iter().find(|info| info.id == id).map(|info| info.state)
+        };
+
+        let id = send(&dir, &Command::Add { input: "写作".into() }).unwrap().id.unwrap();
+        assert_eq!(state(id).as_deref(), Some("idle"));
+        assert!(send(&dir, &Command::Start { id }).unwrap().ok);
+        assert_eq!(state(id).as_deref(), Some("running"));
+        assert!(send(&dir, &Command::Pause { id }).unwrap().ok);
+        assert_eq!(state(id).as_deref(), Some("paused"));
+        assert!(send(&dir, &Command::Reset { id }).unwrap().ok);
+        assert_eq!(state(id).as_deref(), Some("idle"));
+
+        // 只有已完成的任务可以推迟
+        assert!(!send(&dir, &Command::Snooze { id: Some(id) }).unwrap().ok);
+        assert!(send(&dir, &Command::Snooze { id: None }).unwrap().ok);
+        assert_eq!(state(id).as_deref(), Some("idle"));
+        assert!(send(&dir, &Command::Start { id }).unwrap().ok);
+        {
+            // 把开始时间提前到计时长度之前，下一次检查即到时
+            let mut app = app.lock();
+            let started = SystemTime::now() - Duration::from_secs(1500);
+            app.tasks.get_mut(id).unwrap().start_time = Some(started);
+            app.tasks.tick(SystemTime::now(), false).unwrap();
+        }
+        assert_eq!(state(id).as_deref(), Some("finished"));
+        assert!(send(&dir, &Command::Snooze { id: Some(id) }).unwrap().ok);
+        assert_eq!(state(id).as_deref(), Some("running"));
+        assert!(send(&dir, &Command::Reset { id }).unwrap().ok);
+        assert_eq!(send(&dir, &Command::History { limit: None }).unwrap().history, Some(Vec::new()));
+
+        assert!(send(&dir, &Command::Delete { id }).unwrap().ok);
+        assert_eq!(state(id), None);
+
+        // 失败的命令回复错误，连接和应用都不受影响
+        let reply = send(&dir, &Command::Pause { id }).unwrap();
+        assert!(!reply.ok);
+        assert!(reply.error.is_some());
+        assert!(!send(&dir, &Command::Reset { id }).unwrap().ok);
+        assert_eq!(send(&dir, &Command::List).unwrap().tasks, Some(Vec::new()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+
+use crate::{
+    error::{InvalidCliArgsSnafu, ParseNumberSnafu, Result},
+    history::HistoryRecord,
+    task::{SummaryStyle, Task, TaskId, TaskState},
+};
+
+// 控制套接字上的命令，每行一个 JSON，例如 {"cmd":"add","input":"25m#写作"}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    Ping, // 再次启动的实例通知已运行的实例
+    Add { input: String },
+    List,
+    Start { id: TaskId },
+    Pause { id: TaskId },
+    Reset { id: TaskId },
+    Delete { id: TaskId },
+    Snooze { id: Option<TaskId> },    // 推迟已完成的任务，未指定任务时推迟所有待确认的任务
+    History { limit: Option<usize> }, // 最近的完成记录，默认 20 条
+}
+
+// 对每条命令的回复，同样是一行 JSON
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<TaskId>, // add 创建的任务
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<Vec<TaskInfo>>, // list 的结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<HistoryRecord>>, // history 的结果
+}
+
+impl Reply {
+    pub fn ok() -> Self {
+        Self {
+            ok: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn error(message: impl ToString) -> Self {
+        Self {
+            error: Some(message.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn from_result(result: Result<Self>) -> Self {
+        result.unwrap_or_else(Self::error)
+    }
+}
+
+// list 返回的任务信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub name: String,
+    pub state: String, // running、paused、idle 或 finished
+    pub remaining: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+impl TaskInfo {
+    pub fn new(task: &Task) -> Result<Self> {
+        let state = match task.state() {
+            TaskState::Running => "running",
+            TaskState::Paused => "paused",
+            TaskState::Idle => "idle",
+            TaskState::Finished => "finished",
+        };
+        Ok(Self {
+            id: task.id,
+            name: task.name.clone(),
+            state: state.to_string(),
+            remaining: task.summary(SummaryStyle::PinnedTitle)?,
+            group: task.group.clone(),
+        })
+    }
+}
+
+// 命令行用法
+pub const USAGE: &str = "用法：timeticker [add <输入> | list | start <id> | pause <id> | reset <id> | delete <id> | \
+                         snooze [id] | history [n]]";
+
+// 解析命令行参数，例如 timeticker add "25m#写作"
+pub fn parse_args(args: &[String]) -> Result<Command> {
+    let (name, rest) = args.split_first().context(InvalidCliArgsSnafu { msg: USAGE })?;
+    let id = || -> Result<TaskId> {
+        let id = rest.first().context(InvalidCliArgsSnafu {
+            msg: format!("{name} 需要任务 id"),
+        })?;
+        id.parse().context(ParseNumberSnafu)
+    };
+    Ok(match name.as_str() {
+        "add" if !rest.is_empty() => Command::Add { input: rest.join(" ") },
+        "add" => {
+            return InvalidCliArgsSnafu {
+                msg: "add 需要任务输入",
+            }
+            .fail();
+        }
+        "list" => Command::List,
+        "start" => Command::Start { id: id()? },
+        "pause" => Command::Pause { id: id()? },
+        "reset" => Command::Reset { id: id()? },
+        "delete" => Command::Delete { id: id()? },
+        "snooze" if rest.is_empty() => Command::Snooze { id: None },
+        "snooze" => Command::Snooze { id: Some(id()?) },
+        "history" => Command::History {
+            limit: rest.first().map(|n| n.parse()).transpose().context(ParseNumberSnafu)?,
+        },
+        _ => return InvalidCliArgsSnafu { msg: USAGE }.fail(),
+    })
+}
+
+// 处理一行请求：解析失败时回复错误，不中断连接
+pub fn handle_line(line: &str, handler: impl FnOnce(Command) -> Reply) -> String {
+    let reply = match serde_json::from_str(line) {
+        Ok(command) => handler(command),
+        Err(e) => Reply::error(format!("invalid command: {e}")),
+    };
+    serde_json::to_string(&reply).unwrap_or_else(|e| format!(r#"{{"ok":false,"error":"{e}"}}"#))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::task::TaskType;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn id(n: &str) -> TaskId {
+        n.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_command_line() {
+        let cases = [
+            ("add 25m#写作 /工作", Command::Add { input: "25m#写作 /工作".into() }),
+            ("list", Command::List),
+            ("start 3", Command::Start { id: id("3") }),
+            ("pause 3", Command::Pause { id: id("3") }),
+            ("reset 3", Command::Reset { id: id("3") }),
+            ("delete 3", Command::Delete { id: id("3") }),
+            ("snooze", Command::Snooze { id: None }),
+            ("snooze 3", Command::Snooze { id: Some(id("3")) }),
+            ("history", Command::History { limit: None }),
+            ("history 5", Command::History { limit: Some(5) }),
+        ];
+        for (line, command) in cases {
+            assert_eq!(parse_args(&args(line)).unwrap(), command, "{line}");
+        }
+        for invalid in ["", "add", "start", "start x", "history x", "stop 3"] {
+            assert!(parse_args(&args(invalid)).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn commands_on_the_wire() {
+        let cases = [
+            (r#"{"cmd":"ping"}"#, Command::Ping),
+            (r#"{"cmd":"add","input":"25m#写作"}"#, Command::Add { input: "25m#写作".into() }),
+            (r#"{"cmd":"start","id":3}"#, Command::Start { id: id("3") }),
+            (r#"{"cmd":"snooze","id":null}"#, Command::Snooze { id: None }),
+            (r#"{"cmd":"history","limit":5}"#, Command::History { limit: Some(5) }),
+        ];
+        for (json, command) in cases {
+            assert_eq!(serde_json::from_str::<Command>(json).unwrap(), command, "{json}");
+            assert_eq!(serde_json::to_string(&command).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn handle_line_replies_with_one_json_line() {
+        let reply = handle_line(r#"{"cmd":"list"}"#, |command| {
+            assert_eq!(command, Command::List);
+            Reply { tasks: Some(Vec::new()), ..Reply::ok() }
+        });
+        assert_eq!(reply, r#"{"ok":true,"tasks":[]}"#);
+
+        // 无法解析的请求不会交给处理函数
+        let reply = handle_line(r#"{"cmd":"stop"}"#, |_| unreachable!());
+        let reply: Reply = serde_json::from_str(&reply).unwrap();
+        assert!(!reply.ok);
+        assert!(reply.error.unwrap().starts_with("invalid command"));
+
+        let reply = handle_line(r#"{"cmd":"start","id":9}"#, |_| {
+            Reply::from_result(InvalidCliArgsSnafu { msg: "no such task" }.fail())
+        });
+        assert_eq!(reply, r#"{"ok":false,"error":"no such task"}"#);
+        assert!(!reply.contains('\n'));
+    }
+
+    #[test]
+    fn task_info_for_list() {
+        let mut task = Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        task.group = Some("工作".into());
+        let info = TaskInfo::new(&task).unwrap();
+        assert_eq!((info.id, info.name.as_str(), info.state.as_str()), (task.id, "写作", "idle"));
+        assert_eq!(info.group.as_deref(), Some("工作"));
+
+        task.start();
+        assert_eq!(TaskInfo::new(&task).unwrap().state, "running");
+        task.pause().unwrap();
+        assert_eq!(TaskInfo::new(&task).unwrap().state, "paused");
+    }
+}
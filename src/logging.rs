@@ -0,0 +1,312 @@
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use directories::{BaseDirs, ProjectDirs};
+use snafu::ResultExt;
+use tracing::{Span, debug_span, field, info};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry,
+    fmt::{MakeWriter, format::FmtSpan},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+use crate::error::{IoSnafu, Result};
+
+const DEFAULT_FILTER: &str = "time_ticker=debug,info"; // 未设置 RUST_LOG 时的日志级别
+const LOG_FILE_PREFIX: &str = "timeticker"; // 日志文件名为 timeticker.2024-06-03.log
+const MAX_LOG_FILES: usize = 7; // 按天轮换，最多保留的日志文件数
+const SLOW_HANDLER: Duration = Duration::from_millis(50); // 处理耗时超过此值时输出一条 info 日志
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+// 默认日志目录：macOS 为 ~/Library/Logs/TimeTicker，其他平台为数据目录下的 logs
+pub fn default_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        BaseDirs::new().map(|dirs| dirs.home_dir().join("Library/Logs/TimeTicker"))
+    } else {
+        ProjectDirs::from("", "", "TimeTicker").map(|dirs| dirs.data_dir().join("logs"))
+    }
+}
+
+// 初始化日志：写入按天轮换的日志文件，连接终端时同时输出到 stderr；
+// 日志文件无法创建时只输出到 stderr，不影响启动。json 为 true 时每行输出一个
+// JSON 对象
+pub fn init(dir: Option<&Path>, json: bool) {
+    let file_error = dir.map(file_appender).transpose();
+    let (file, file_error) = match file_error {
+        Ok(file) => (file, None),
+        Err(e) => (None, Some(e)),
+    };
+    let mut layers: Vec<BoxedLayer> = Vec::new();
+    // 没有日志文件时总是输出到 stderr，避免日志完全丢失
+    if std::io::stderr().is_terminal() || file.is_none() {
+        layers.push(fmt_layer(std::io::stderr, true, json));
+    }
+    if let Some(appender) = file {
+        layers.push(fmt_layer(appender, false, json));
+    }
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_FILTER.into()))
+        .init();
+    if let Some(e) = file_error {
+        tracing::warn!("Failed to open log file, logging to stderr only: {}", e);
+    }
+    // 轮换时才会清理旧文件，长时间未运行后启动时也清理一次
+    if let Some(dir) = dir
+        && let Err(e) = prune(dir, MAX_LOG_FILES)
+    {
+        tracing::warn!("Failed to prune old log files: {}", e);
+    }
+}
+
+// span 结束时输出一行，带上处理过程中记录的字段（动作、任务 id、耗时）
+fn fmt_layer<W>(writer: W, ansi: bool, json: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        .with_target(false)
+        .with_file(false)
+        .with_line_number(false)
+        .with_span_events(FmtSpan::CLOSE);
+    if json { layer.json().boxed() } else { layer.boxed() }
+}
+
+// 菜单事件的 span，动作和任务 id 在找到菜单项对应的动作后记录
+pub fn menu_event_span() -> Span {
+    debug_span!("menu_event", action = field::Empty, task_id = field::Empty, duration_ms = field::Empty)
+}
+
+// 记录一次处理的耗时：结束时写入所在 span 的 duration_ms 字段，超过
+// SLOW_HANDLER 时输出一条 info 日志。 需要在进入 span 之后创建，保证先于 span
+// 的进入守卫释放
+pub struct HandlerTimer {
+    name: &'static str,
+    span: Span,
+    started: Instant,
+}
+
+impl HandlerTimer {
+    pub fn start(name: &'static str, span: &Span) -> Self {
+        Self {
+            name,
+            span: span.clone(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Drop for HandlerTimer {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        let duration_ms = elapsed.as_millis() as u64;
+        self.span.record("duration_ms", duration_ms);
+        if elapsed > SLOW_HANDLER {
+            info!(duration_ms, "🐢 {} 耗时 {} ms", self.name, duration_ms);
+        }
+    }
+}
+
+fn file_appender(dir: &Path) -> std::result::Result<RollingFileAppender, String> {
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))
+}
+
+// 按文件名中的日期保留最新的 keep 个日志文件，其他文件不受影响；返回删除的文件数
+fn prune(dir: &Path, keep: usize) -> Result<usize> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).context(IoSnafu { path: dir }),
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&format!("{LOG_FILE_PREFIX}.")) && name.ends_with(".log"))
+        })
+        .collect();
+    // 日期格式为 YYYY-MM-DD，按文件名排序即按时间排序，最新的在最后
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for path in &logs[..excess] {
+        std::fs::remove_file(path).context(IoSnafu { path })?;
+    }
+    Ok(excess)
+}
+
+// 在系统文件管理器中打开目录，不存在时先创建
+pub fn open_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program).arg(dir).spawn().context(IoSnafu { path: dir })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs, io,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+
+    // 收集日志输出，代替 stderr 和日志文件
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Captured {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'w> MakeWriter<'w> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'w self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    // 模拟一次菜单事件：进入 span 后解析动作，再记录动作和任务 id
+    fn capture_menu_event(json: bool, work: Duration) -> String {
+        let captured = Captured::default();
+        let subscriber = Registry::default().with(fmt_layer(captured.clone(), false, json));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = menu_event_span();
+            let _entered = span.enter();
+            let _timer = HandlerTimer::start("menu_event", &span);
+            span.record("action", "toggle_42");
+            span.record("task_id", field::display(42));
+            std::thread::sleep(work);
+        });
+        captured.text()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timeticker-logging-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> =
+            fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn default_dir_is_platform_specific() {
+        let dir = default_dir().unwrap();
+        if cfg!(target_os = "macos") {
+            assert!(dir.ends_with("Library/Logs/TimeTicker"), "{}", dir.display());
+        } else {
+            assert!(dir.ends_with("logs"), "{}", dir.display());
+        }
+    }
+
+    #[test]
+    fn prune_keeps_the_newest_logs() {
+        let dir = temp_dir("prune");
+        for day in 1..=9 {
+            fs::write(dir.join(format!("timeticker.2024-06-0{day}.log")), "").unwrap();
+        }
+        for other in ["notes.txt", "timeticker.log.bak", "other.2024-06-01.log"] {
+            fs::write(dir.join(other), "").unwrap();
+        }
+
+        assert_eq!(prune(&dir, MAX_LOG_FILES).unwrap(), 2);
+        let names = file_names(&dir);
+        assert_eq!(names.iter().filter(|name| name.starts_with("timeticker.2024")).count(), MAX_LOG_FILES);
+        assert!(!names.contains(&"timeticker.2024-06-02.log".to_string()));
+        assert!(names.contains(&"timeticker.2024-06-03.log".to_string()));
+        for other in ["notes.txt", "timeticker.log.bak", "other.2024-06-01.log"] {
+            assert!(names.contains(&other.to_string()), "{other}");
+        }
+        assert_eq!(prune(&dir, MAX_LOG_FILES).unwrap(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_dir_has_nothing_to_prune() {
+        let dir = temp_dir("missing").join("logs");
+        assert_eq!(prune(&dir, MAX_LOG_FILES).unwrap(), 0);
+    }
+
+    #[test]
+    fn appender_creates_the_log_dir_and_a_dated_file() {
+        use std::io::Write;
+
+        let dir = temp_dir("appender").join("logs");
+        let mut appender = file_appender(&dir).unwrap();
+        appender.write_all(b"hello\n").unwrap();
+        appender.flush().unwrap();
+        let names = file_names(&dir);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].starts_with("timeticker.") && names[0].ends_with(".log"), "{names:?}");
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn menu_event_span_reports_its_fields_on_close() {
+        let text = capture_menu_event(false, Duration::ZERO);
+        let line = text.lines().find(|line| line.contains("menu_event")).expect(&text);
+        for field in ["action=\"toggle_42\"", "task_id=42", "duration_ms=", "close"] {
+            assert!(line.contains(field), "missing {field} in {line}");
+        }
+        // 处理很快时不输出耗时提示
+        assert!(!text.contains("🐢"), "{text}");
+    }
+
+    #[test]
+    fn json_logs_carry_the_span_fields() {
+        let text = capture_menu_event(true, Duration::ZERO);
+        let line = text.lines().find(|line| line.contains("menu_event")).expect(&text);
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["span"]["action"], "toggle_42");
+        assert_eq!(value["span"]["task_id"], "42");
+        assert!(value["span"]["duration_ms"].is_u64(), "{line}");
+    }
+
+    #[test]
+    fn slow_handlers_log_a_summary() {
+        let text = capture_menu_event(false, SLOW_HANDLER + Duration::from_millis(10));
+        let summary = text.lines().find(|line| line.contains("🐢")).expect(&text);
+        assert!(summary.contains("INFO") && summary.contains("menu_event"), "{summary}");
+    }
+}
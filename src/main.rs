@@ -1,17 +1,77 @@
 #![allow(unused)]
-
+// 托盘程序在 Windows 上不需要控制台窗口
+#![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
+
+mod about;
+mod accelerator;
+mod backoff;
+mod blink;
+mod click;
+mod clipboard;
+mod config;
+mod dialog;
+mod display_cache;
 mod error;
+mod history;
+mod hotkey;
+mod i18n;
+mod icon;
+mod icon_cache;
+mod input_window;
+mod instance;
+mod ipc;
+mod logging;
+mod menu_action;
+mod menu_model;
+mod notify;
 mod parser;
+mod persistence;
+mod recent;
+mod stats;
+mod store;
 mod task;
+mod url_scheme;
+mod watchdog;
+mod webhook;
 
 use std::{
-    collections::HashMap,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
     process::Command,
-    sync::{Arc, Mutex},
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
     time::{Duration, Instant, SystemTime},
 };
 
-use image::{ImageBuffer, Rgba, RgbaImage};
+use accelerator::Accelerators;
+use backoff::Backoff;
+use blink::{BlinkFrame, Blinker};
+use chrono::{DateTime, Local};
+use click::{Click, ClickTracker};
+use clipboard::{ClipboardSink, SystemClipboard};
+use config::{Config, Preset};
+use dialog::Dialog;
+use display_cache::{DisplayCache, Slot};
+use global_hotkey::{GlobalHotKeyEvent, HotKeyState};
+use history::{HistoryLog, HistoryRecord};
+use hotkey::{HotkeyAction, Hotkeys};
+use i18n::{Locale, tr, tr_args};
+use icon::{
+    Appearance, AppearanceSource, DisplayMode, IconParams, IconRenderer, IconStyle, Palette, PixelRenderer, Platform,
+    SystemAppearance, Urgency,
+};
+use icon_cache::{IconCache, IconKey};
+use image::RgbaImage;
+use input_window::{InputOutcome, InputPurpose, InputRequest, InputWindow};
+use instance::{Acquire, InstanceLock};
+use menu_action::{FormatTarget, MenuAction, Setting};
+use menu_model::{MenuChange, MenuEntry, MenuInput, SNOOZE_MINUTES, start_pause_label};
+use notify::{NagScheduler, NotificationSink, QuietQueue, SoundPlayer, SystemNotifier};
 #[cfg(target_os = "macos")]
 use objc2::{ClassType, msg_send_id};
 // macOS 特定导入，用于 Dock 控制
@@ -19,404 +79,1110 @@ use objc2::{ClassType, msg_send_id};
 use objc2_app_kit::{NSApp, NSApplication, NSApplicationActivationPolicy, NSImage};
 #[cfg(target_os = "macos")]
 use objc2_foundation::{MainThreadMarker, NSData, NSString};
-use parser::parse_time_input;
+use parser::{TaskInput, format_task_input, parse_time_input};
+use recent::RecentInputs;
 use snafu::{Backtrace, ResultExt, prelude::*};
-use task::{Task, TaskType};
-use tracing::{debug, error, info, trace, warn};
+use stats::{BudgetTracker, BudgetUsage};
+use store::TaskStore;
+use task::{
+    MoveDirection, SummaryStyle, Task, TaskEvent, TaskId, TaskList, TaskOrder, TaskSnapshot, TaskState, TaskStats,
+    TaskType, TimeFormat, Warning, format_remaining_time,
+};
+use tracing::{debug, debug_span, error, field, info, trace, trace_span, warn};
 use tray_icon::{
-    Icon, TrayIcon, TrayIconBuilder, TrayIconEvent, TrayIconEventReceiver,
-    menu::{Menu, MenuEvent as TrayMenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
+    Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent, TrayIconEventReceiver, TrayIconId,
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent as TrayMenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu},
 };
+use watchdog::TickWatchdog;
 use winit::{
     application::ApplicationHandler,
     event::Event,
-    event_loop::{ControlFlow, EventLoop, EventLoopBuilder},
+    event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy},
     window::Window,
 };
 
 use crate::error::{
-    CanonicalizePathSnafu, Error, EventLoopCreationSnafu, EventLoopSendSnafu, IconConversionSnafu, ImageSnafu,
-    InvalidActionFormatSnafu, IoSnafu, MacOsMainRunLoopUnavailableSnafu, MainThreadMarkerSnafu, MenuAppendSnafu,
-    ParseActionIndexSnafu, /* ParserErrorWrapperSnafu was correctly removed. SystemTimeSnafu was correctly changed
-                            * to SystemTimeErrorSnafu. */
-    Result, TaskLockSnafu, TrayIconBuildSnafu, TrayIconUpdateSnafu, WindowCreationSnafu,
+    CanonicalizePathSnafu, Error, ErrorKind, EventLoopCreationSnafu, EventLoopSendSnafu, IconConversionSnafu,
+    ImageSnafu, InvalidCliArgsSnafu, IoSnafu, IpcMessageSnafu, MacOsMainRunLoopUnavailableSnafu,
+    MainThreadMarkerSnafu, MenuAppendSnafu, Result, TaskIdNotFoundSnafu, TaskOperationSnafu, TrayIconBuildSnafu,
+    TrayIconUpdateSnafu, WindowCreationSnafu,
 };
 
+const TICK_INTERVAL: Duration = Duration::from_secs(1); // 正常刷新间隔
+const FAST_TICK_INTERVAL: Duration = Duration::from_millis(250); // 最后几秒的刷新间隔
+const FAST_TICK_THRESHOLD: Duration = Duration::from_secs(10); // 剩余时间低于此值时切换到快速刷新
+
+// 距离下一个整秒的时间，让每次刷新都落在整秒上，所有显示同时跳动；
+// 正好在整秒上时等待完整的一秒
+fn until_next_second(now: SystemTime) -> Duration {
+    let nanos = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    TICK_INTERVAL - Duration::from_nanos(u64::from(nanos))
+}
+
+const COMMAND_REPLY_TIMEOUT: Duration = Duration::from_secs(5); // 等待事件循环处理控制命令的最长时间
+const DEFAULT_HISTORY_LIMIT: usize = 20; // history 命令默认返回的记录数
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2); // 检查配置文件是否被修改的间隔
+const WAKE_GAP_THRESHOLD: Duration = Duration::from_secs(2); // 两次刷新间隔超过此值视为系统曾经休眠
+
+// 两次刷新之间超出正常间隔的时长，超过阈值时认为系统休眠过；时钟回拨时不处理
+fn suspended_gap(last_tick: Option<SystemTime>, now: SystemTime) -> Option<Duration> {
+    let elapsed = now.duration_since(last_tick?).ok()?;
+    (elapsed > WAKE_GAP_THRESHOLD).then(|| elapsed.saturating_sub(TICK_INTERVAL))
+}
+
+const MAIN_TRAY_ID: &str = "main"; // 主托盘图标的 id，固定图标的 id 为 pinned_{任务 id}
+
+// 固定托盘图标的 id，单击时据此找到对应的任务
+fn pinned_tray_id(id: TaskId) -> TrayIconId {
+    TrayIconId::new(format!("pinned_{id}"))
+}
+
+fn pinned_task_id(tray_id: &TrayIconId) -> Option<TaskId> {
+    tray_id.as_ref().strip_prefix("pinned_")?.parse().ok()
+}
+
+// 没有需要刷新的内容时让计时线程停下等待，有任务开始时立即唤醒
+#[derive(Debug, Default)]
+struct TickGate {
+    idle: parking_lot::Mutex<bool>,
+    wake: parking_lot::Condvar,
+}
+
+impl TickGate {
+    // 设置是否空闲，返回状态是否发生变化
+    fn set_idle(&self, idle: bool) -> bool {
+        let mut current = self.idle.lock();
+        let changed = *current != idle;
+        *current = idle;
+        if !idle {
+            self.wake.notify_all();
+        }
+        changed
+    }
+
+    fn is_idle(&self) -> bool {
+        *self.idle.lock()
+    }
+
+    // 计时线程在空闲期间阻塞在这里
+    fn wait_while_idle(&self) {
+        let mut idle = self.idle.lock();
+        while *idle {
+            self.wake.wait(&mut idle);
+        }
+    }
+}
+
 #[derive(Debug)]
 enum UserEvent {
     TrayIconEvent(tray_icon::TrayIconEvent),
     MenuEvent(TrayMenuEvent),
     UpdateTimer,
-    StartTask(usize),
-    PauseTask(usize),
-    ResetTask(usize),
-    DeleteTask(usize),
+    SystemWillSleep,                           // macOS 系统即将休眠
+    SystemDidWake,                             // macOS 系统从休眠中唤醒
+    Command(ipc::Command, Sender<ipc::Reply>), // 来自控制套接字的命令，处理后通过 Sender 回复
+    NewTaskHotkey,                             // 按下新建任务的全局快捷键
+    ToggleActiveHotkey,                        // 按下开始/暂停当前任务的全局快捷键
+    QuickAddHotkey,                            // 按下快速添加的全局快捷键
+    ClickTimeout,                              // 主图标单击后没有出现第二次单击
+    StartTask(TaskId),
+    PauseTask(TaskId),
+    ResetTask(TaskId),
+    DeleteTask(TaskId),
+    OpenUrl(String),             // 通过 timeticker:// 链接打开
+    WebhookFailed(String),       // webhook 最终发送失败，内容为错误说明
+    ArchiveExpired,              // 过期的截止时间任务到达保留时长
+    RetryTrayIcon,               // 重试创建主托盘图标
+    RetryPinnedTrayIcon(TaskId), // 重试创建固定托盘图标
+    TimerStalled,                // 需要刷新时长时间没有收到计时刷新
+    ConfigReloaded,              // 配置文件的修改时间发生变化
+    ScheduledStart,              // 有任务到达定时开始的时刻
 }
 
 struct Application {
     tray_icon: Option<TrayIcon>,
-    tasks: Arc<Mutex<Vec<Task>>>,
-    menu_ids: HashMap<MenuId, String>,              // 菜单ID到动作的映射
-    menu_items: HashMap<usize, Submenu>,            // 任务索引到子菜单的映射，用于更新文本
-    control_items: HashMap<usize, MenuItem>,        // 任务索引到控制按钮的映射
-    pinned_tray_icons: HashMap<usize, TrayIcon>,    // 固定任务的独立托盘图标
-    pinned_menu_items: HashMap<usize, MenuItem>,    // 固定托盘菜单中的时间显示项
-    pinned_control_items: HashMap<usize, MenuItem>, // 固定托盘菜单中的控制按钮
+    tasks: TaskStore,
+    menu_ids: HashMap<MenuId, MenuAction>,                   // 菜单ID到动作的映射
+    menu_items: HashMap<TaskId, Submenu>,                    // 任务 id 到子菜单的映射，用于更新文本
+    control_items: HashMap<TaskId, MenuItem>,                // 任务 id 到控制按钮的映射
+    snooze_items: HashMap<TaskId, Vec<MenuItem>>,            // 任务子菜单中的推迟按钮
+    check_items: HashMap<TaskId, TaskCheckItems>,            // 任务子菜单中的静音、持续提醒和固定勾选项
+    rendered_menu: Vec<MenuEntry>,                           // 当前主菜单的描述，刷新时与新描述比较
+    menu_handles: Vec<MenuHandle>,                           // 主菜单各项的句柄，按先序遍历的顺序排列
+    pinned_tray_icons: HashMap<TaskId, TrayIcon>,            // 固定任务的独立托盘图标
+    pinned_menu_items: HashMap<TaskId, MenuItem>,            // 固定托盘菜单中的时间显示项
+    pinned_control_items: HashMap<TaskId, MenuItem>,         // 固定托盘菜单中的控制按钮
+    pinned_snooze_items: HashMap<TaskId, Vec<MenuItem>>,     // 固定托盘菜单中的推迟按钮
+    pinned_menu_ids: HashMap<TaskId, Vec<(MenuId, MenuAction)>>, // 固定托盘菜单中的菜单ID及其动作
+    task_event_sender: Sender<TaskEvent>,                    // 各修改路径发出任务事件
+    task_event_receiver: Receiver<TaskEvent>,                // 在 user_event 中统一分发
+    fast_tick: Arc<AtomicBool>,                              // 计时线程是否使用快速刷新间隔
+    tick_gate: Arc<TickGate>,                                // 没有需要刷新的内容时暂停计时线程
+    tick_watchdog: Arc<TickWatchdog>,                        // 发现计时线程意外停止
+    last_tick: Option<SystemTime>,                           // 上一次计时刷新的时刻，用于发现系统休眠
+    instance_lock: Option<InstanceLock>,                     // 单实例锁，退出时释放
+    hotkeys: Option<Hotkeys>,                                // 已注册的全局快捷键，退出时注销
+    accelerators: Accelerators,                              // 菜单打开时可用的快捷键
+    presets: Vec<Preset>,                                    // 快速添加菜单中的有效预设
+    click_tracker: ClickTracker,                             // 区分主图标的单击和双击
+    dock_visible: bool,                                      // Dock 图标是否显示
+    dock_badge: Option<String>,                              // 当前 Dock 角标文本
+    dock_countdown: Option<u64>,                             // Dock 图标上绘制的剩余分钟数，None 表示原图标
+    event_proxy: Option<EventLoopProxy<UserEvent>>,          // 用于从其他线程发送延迟事件
+    tray_retry: Backoff,                                     // 主托盘图标创建失败后的重试状态
+    pinned_retries: HashMap<TaskId, Backoff>,                // 固定托盘图标创建失败后的重试状态
+    input_window: Option<InputWindow>,                       // 打开中的任务输入窗口
+    input_request: Option<InputRequest>,                     // 等待在事件循环中打开的输入窗口
+    quick_add_window: Option<InputWindow>,                   // 快速添加窗口，第一次使用时创建，关闭后隐藏以便复用
+    quick_add_requested: bool,                               // 等待在事件循环中显示快速添加窗口
+    save_path: Option<PathBuf>,                              // 任务存档路径，无法确定数据目录时不保存
+    history: Option<HistoryLog>,                             // 完成记录，无法确定数据目录时不记录
+    config: Config,                                          // 用户设置
+    config_path: Option<PathBuf>,                            // 配置文件路径
+    recent_inputs: RecentInputs,                             // 最近成功创建任务的输入
+    recent_path: Option<PathBuf>,                            // 最近使用的输入的保存位置，与配置文件相邻
+    notifier: Box<dyn NotificationSink>,                     // 任务完成通知
+    clipboard: Box<dyn ClipboardSink>,                       // 复制剩余时间用的剪贴板
+    dialog: Box<dyn Dialog>,                                 // 系统输入框和错误提示
+    sound: SoundPlayer,                                      // 任务完成提示音
+    nag: NagScheduler,                                       // 持续提醒的上次提醒时间
+    blinker: Blinker,                                        // 有未查看的完成时闪烁主托盘图标
+    quiet_queue: QuietQueue,                                 // 勿扰模式期间完成的任务和提前提醒，关闭时汇总通知
+    budget_tracker: RefCell<BudgetTracker>,                  // 各分组当天的预算用量和提醒状态
+    pinned_icon_cache: RefCell<HashMap<TaskId, (String, IconParams)>>, // 固定图标上次绘制的内容和参数
+    display_cache: RefCell<DisplayCache>,                    // 每秒刷新时上次设置的文本和状态
+    display_stale: bool,                                     // 显示可能已过时，下一次计时刷新完整更新
+    task_images: RefCell<HashMap<PathBuf, Option<Rc<RgbaImage>>>>, // 任务自定义图片，加载失败时为 None
+    icon_cache: RefCell<IconCache>,                          // 按显示内容缓存绘制好的时间图标
+    icon_renderer: Box<dyn IconRenderer>,                    // 固定图标上时间文本的绘制方式
+    appearance_source: Box<dyn AppearanceSource>,            // 检测系统深色/浅色外观
+    appearance: Option<Appearance>,                          // 最近一次检测到的系统外观
 }
 
 impl Application {
     fn new() -> Self {
-        // 创建一些测试任务
-        let test_tasks_results: Vec<Result<Task>> = vec![];
+        // 从数据目录加载上次保存的任务
+        let save_path = persistence::default_path();
+        let tasks = match &save_path {
+            Some(path) => load_tasks(path),
+            None => {
+                warn!("Could not determine data directory, tasks will not be saved");
+                TaskList::new()
+            }
+        };
 
-        let test_tasks: Vec<Task> = test_tasks_results
-            .into_iter()
-            .filter_map(|task_result| match task_result {
-                Ok(task) => Some(task),
-                Err(e) => {
-                    error!("Failed to create initial task: {}", e);
-                    None
-                }
-            })
-            .collect();
+        let config_path = Config::default_path();
+        let config = match &config_path {
+            Some(path) => Config::load(path).unwrap_or_else(|e| {
+                warn!("Failed to load config, using defaults: {}", e);
+                Config::default()
+            }),
+            None => Config::default(),
+        };
+        task::set_time_formats(config.time_format, config.title_time_format);
+        task::set_max_duration(config.max_duration());
+        i18n::set_locale(config.locale.unwrap_or_else(Locale::system));
+        let (accelerators, errors) = Accelerators::from_config(&config.menu_accelerators);
+        for e in errors {
+            error!("Ignoring menu accelerator: {}", e);
+        }
+        let (presets, errors) = config::valid_presets(&config.presets);
+        for e in errors {
+            warn!("Ignoring preset: {}", e);
+        }
+        let recent_path = config_path.as_deref().map(recent::path_for);
+        let recent_inputs = match &recent_path {
+            Some(path) => RecentInputs::load(path).unwrap_or_else(|e| {
+                warn!("Failed to load recent inputs: {}", e);
+                RecentInputs::default()
+            }),
+            None => RecentInputs::default(),
+        };
+
+        let (task_event_sender, task_event_receiver) = mpsc::channel();
+        let dock_visible = !config.hide_dock_on_launch;
 
-        Self {
+        let mut app = Self {
             tray_icon: None,
-            tasks: Arc::new(Mutex::new(test_tasks)),
+            tasks: TaskStore::new(tasks),
+            save_path,
+            history: history::default_path().map(HistoryLog::open),
+            config,
+            config_path,
+            recent_inputs,
+            recent_path,
+            notifier: Box::new(SystemNotifier),
+            clipboard: Box::new(SystemClipboard::default()),
+            dialog: dialog::detect(),
+            sound: SoundPlayer::default(),
+            nag: NagScheduler::default(),
+            blinker: Blinker::default(),
+            quiet_queue: QuietQueue::default(),
             menu_ids: HashMap::new(),
             menu_items: HashMap::new(),
             control_items: HashMap::new(),
+            snooze_items: HashMap::new(),
+            check_items: HashMap::new(),
+            rendered_menu: Vec::new(),
+            menu_handles: Vec::new(),
             pinned_tray_icons: HashMap::new(),
             pinned_menu_items: HashMap::new(),
             pinned_control_items: HashMap::new(),
+            pinned_snooze_items: HashMap::new(),
+            pinned_menu_ids: HashMap::new(),
+            budget_tracker: RefCell::new(BudgetTracker::default()),
+            pinned_icon_cache: RefCell::new(HashMap::new()),
+            display_cache: RefCell::new(DisplayCache::default()),
+            display_stale: true,
+            task_images: RefCell::new(HashMap::new()),
+            icon_cache: RefCell::new(IconCache::default()),
+            icon_renderer: icon::default_renderer().unwrap_or_else(|e| {
+                error!("Failed to create icon renderer, falling back to pixel font: {}", e);
+                Box::new(PixelRenderer)
+            }),
+            appearance_source: Box::new(SystemAppearance),
+            appearance: None,
+            task_event_sender,
+            task_event_receiver,
+            fast_tick: Arc::new(AtomicBool::new(false)),
+            tick_gate: Arc::new(TickGate::default()),
+            tick_watchdog: Arc::new(TickWatchdog::default()),
+            last_tick: None,
+            instance_lock: None,
+            hotkeys: None,
+            accelerators,
+            presets,
+            click_tracker: ClickTracker::default(),
+            dock_visible,
+            dock_badge: None,
+            dock_countdown: None,
+            event_proxy: None,
+            tray_retry: Backoff::default(),
+            pinned_retries: HashMap::new(),
+            input_window: None,
+            input_request: None,
+            quick_add_window: None,
+            quick_add_requested: false,
+        };
+
+        // 计时中的任务按保存的开始时刻继续计算，
+        // 程序关闭期间已到时的任务在这里补发完成事件
+        if let Err(e) = app.catch_up_finished_tasks() {
+            error!("Failed to check tasks finished while closed: {}", e);
         }
+        app.refresh_appearance();
+        app
+    }
+
+    /// 检查程序关闭期间到时的任务（包括已过期的截止时间任务）
+    fn catch_up_finished_tasks(&mut self) -> Result<()> {
+        let events = self.tasks.write()?.tick(SystemTime::now(), self.config.single_running_task)?;
+        let tasks = self.tasks.read()?;
+        for event in events {
+            if let TaskEvent::Finished(id) = event
+                && let Some(task) = tasks.get(id)
+            {
+                info!("⏰ 任务 '{}' 在程序关闭期间已到时", task.name);
+            }
+            self.emit_task_event(event);
+        }
+        Ok(())
     }
 
     fn new_tray_icon(&mut self) -> Result<TrayIcon> {
-        let path = std::path::Path::new("./assets/logo.png");
-        let icon = load_icon(path)?;
+        let icon = self.logo_icon()?;
 
         let menu = self.build_menu()?;
 
         TrayIconBuilder::new()
+            .with_id(MAIN_TRAY_ID)
             .with_menu(Box::new(menu))
-            .with_tooltip("Time Ticker")
+            .with_menu_on_left_click(!self.config.left_click_toggle)
+            .with_tooltip(tr("tooltip.empty"))
             .with_icon(icon)
             .build()
             .context(TrayIconBuildSnafu)
     }
 
-    fn build_menu(&mut self) -> Result<Menu> {
-        let menu = Menu::new();
+    /// 按当前状态生成主菜单的描述
+    fn main_menu_spec(&self) -> Result<Vec<MenuEntry>> {
+        let now = SystemTime::now();
+        let tasks = self.tasks.read()?;
+        let stats = self.stats_summary(&tasks, now)?;
+        menu_model::main_menu(&MenuInput {
+            tasks: &tasks,
+            config: &self.config,
+            accelerators: &self.accelerators,
+            presets: &self.presets,
+            recent_inputs: &self.recent_inputs,
+            stats: &stats,
+            platform: Platform::current(),
+            now,
+        })
+    }
 
-        // 保存固定托盘菜单的ID，避免被清除
-        let pinned_menu_ids: Vec<(MenuId, String)> = self
-            .menu_ids
-            .iter()
-            .filter(|(_, action)| action.starts_with("pinned_") || action.starts_with("unpin_"))
-            .map(|(id, action)| (id.clone(), action.clone()))
-            .collect();
+    /// 重建主菜单：先按当前状态生成菜单描述，再转换为 muda 菜单
+    fn build_menu(&mut self) -> Result<Menu> {
+        let span = debug_span!("build_menu", duration_ms = field::Empty);
+        let _entered = span.enter();
+        let _timer = logging::HandlerTimer::start("build_menu", &span);
+        let entries = self.main_menu_spec()?;
+        self.render_menu(entries)
+    }
 
+    /// 把菜单描述转换为 muda 菜单，并保存描述供下次刷新时比较
+    fn render_menu(&mut self, entries: Vec<MenuEntry>) -> Result<Menu> {
         self.menu_ids.clear(); // 清除旧的菜单ID映射
         self.menu_items.clear(); // 清除旧的菜单项映射
         self.control_items.clear(); // 清除旧的控制项映射
+        self.snooze_items.clear();
+        self.check_items.clear();
+        self.menu_handles.clear();
+        self.display_cache.borrow_mut().forget_main_menu();
+
+        // 固定托盘菜单不随主菜单重建，重新登记仍然存在的固定菜单ID
+        for (menu_id, action) in self.pinned_menu_ids.values().flatten() {
+            self.menu_ids.insert(menu_id.clone(), *action);
+        }
 
-        // 恢复固定托盘菜单的ID
-        for (id, action) in pinned_menu_ids {
-            self.menu_ids.insert(id, action);
+        let menu = Menu::new();
+        for entry in &entries {
+            let item = self.render_menu_entry(entry)?;
+            menu.append(item.as_ref()).context(MenuAppendSnafu {
+                item_name: entry.label(),
+            })?;
         }
+        self.rendered_menu = entries;
+        Ok(menu)
+    }
 
-        // 添加任务菜单项
-        {
-            let tasks = self.tasks.lock().map_err(|_| error::TaskLockSnafu.build())?;
-            for (i, task) in tasks.iter().enumerate() {
-                // 显示剩余时间的子菜单
-                let remaining_time = task.get_remaining_time()?;
-                let time_str = format_remaining_time(remaining_time);
-                let task_submenu = Submenu::new(format!("{}#{}", time_str, task.name), true);
-                self.menu_items.insert(i, task_submenu.clone()); // 存储子菜单引用
-
-                // 根据任务类型添加不同的控制选项
-                match task.task_type {
-                    TaskType::Duration(_) => {
-                        // 开始/暂停
-                        let start_pause = MenuItem::new(if task.is_running { "暂停" } else { "开始" }, true, None);
-                        let start_pause_id = start_pause.id().clone();
-                        self.menu_ids.insert(start_pause_id, format!("toggle_{i}"));
-                        self.control_items.insert(i, start_pause.clone()); // 存储控制项引用
-                        task_submenu.append(&start_pause).context(MenuAppendSnafu {
-                            item_name: format!("start_pause_task_{}", i),
-                        })?;
-
-                        // 重置
-                        let reset = MenuItem::new("重置", true, None);
-                        let reset_id = reset.id().clone();
-                        self.menu_ids.insert(reset_id, format!("reset_{i}"));
-                        task_submenu.append(&reset).context(MenuAppendSnafu {
-                            item_name: format!("reset_task_{}", i),
-                        })?;
-                    }
-                    TaskType::Deadline(_) => {
-                        // 截止时间类型任务不需要开始/暂停/重置
+    /// 把一项菜单描述转换为 muda 菜单项，登记菜单ID对应的动作，
+    /// 并保存刷新时需要更新文本和状态的任务菜单项
+    fn render_menu_entry(&mut self, entry: &MenuEntry) -> Result<Box<dyn IsMenuItem>> {
+        let item: Box<dyn IsMenuItem> = match entry {
+            MenuEntry::Item {
+                label,
+                enabled,
+                action,
+                accelerator,
+            } => {
+                let item = MenuItem::new(label, *enabled, *accelerator);
+                self.menu_handles.push(MenuHandle::Item(item.clone()));
+                if let Some(action) = *action {
+                    self.menu_ids.insert(item.id().clone(), action);
+                    match action {
+                        MenuAction::Toggle(id) => {
+                            self.control_items.insert(id, item.clone());
+                        }
+                        MenuAction::Snooze(id, _) => self.snooze_items.entry(id).or_default().push(item.clone()),
+                        _ => {}
                     }
                 }
-
-                // 添加分隔线
-                task_submenu
-                    .append(&PredefinedMenuItem::separator())
-                    .context(MenuAppendSnafu {
-                        item_name: format!("separator_after_controls_task_{}", i),
+                Box::new(item)
+            }
+            MenuEntry::Check {
+                label,
+                enabled,
+                checked,
+                action,
+            } => {
+                let item = CheckMenuItem::new(label, *enabled, *checked, None);
+                self.menu_handles.push(MenuHandle::Check(item.clone()));
+                self.menu_ids.insert(item.id().clone(), *action);
+                if let Some(id) = action.task_id() {
+                    self.check_items.entry(id).or_default().push(*action, item.clone());
+                }
+                Box::new(item)
+            }
+            MenuEntry::Submenu { label, task, children } => {
+                let submenu = Submenu::new(label, true);
+                self.menu_handles.push(MenuHandle::Submenu(submenu.clone())); // 先于子项保存，与先序遍历一致
+                for child in children {
+                    let item = self.render_menu_entry(child)?;
+                    submenu.append(item.as_ref()).context(MenuAppendSnafu {
+                        item_name: child.label(),
                     })?;
+                }
+                if let Some(id) = *task {
+                    self.menu_items.insert(id, submenu.clone()); // 存储子菜单引用
+                }
+                Box::new(submenu)
+            }
+            MenuEntry::Separator => {
+                self.menu_handles.push(MenuHandle::Separator);
+                Box::new(PredefinedMenuItem::separator())
+            }
+        };
+        Ok(item)
+    }
 
-                // 新增任务
-                let new_task_item = MenuItem::new("新增", true, None);
-                let new_task_id = new_task_item.id().clone();
-                self.menu_ids.insert(new_task_id, "new_task".to_string());
-                task_submenu.append(&new_task_item).context(MenuAppendSnafu {
-                    item_name: format!("new_sub_task_{}", i),
-                })?;
+    /// 统计子菜单的数据：今天和本周的用时，以及本周用时最多的任务；
+    /// 计时中的任务按已用时间计入，只在重建菜单时更新
+    fn stats_summary(&self, tasks: &TaskList, now: SystemTime) -> Result<stats::Summary> {
+        let now_local = DateTime::<Local>::from(now);
+        let running = tasks
+            .iter()
+            .filter(|task| task.is_running)
+            .map(|task| {
+                Ok(stats::Entry {
+                    name: task.name.clone(),
+                    tag: task.group.clone(),
+                    time: task.stats()?.actual,
+                    at: now_local,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let recorded = self
+            .history
+            .iter()
+            .flat_map(|history| history.records())
+            .filter_map(stats::Entry::from_record);
+        let mut summary = stats::summarize(recorded.chain(running), now_local);
+        summary.budgets = self.budget_usage(tasks, now)?;
+        Ok(summary)
+    }
 
-                // 编辑
-                let edit = MenuItem::new("编辑", true, None);
-                let edit_id = edit.id().clone();
-                self.menu_ids.insert(edit_id, format!("edit_{i}"));
-                task_submenu.append(&edit).context(MenuAppendSnafu {
-                    item_name: format!("edit_task_{}", i),
-                })?;
+    /// 剩余时间降到提前量以内或已用比例达到里程碑时提醒一次，静音任务只记录日志，
+    /// 勿扰模式下记入队列，关闭时汇总通知；返回是否有新的提醒，已提醒的记录由调用方统一保存
+    fn check_warnings(&mut self, now: SystemTime) -> Result<bool> {
+        let mut tasks = self.tasks.write()?;
+        let due = tasks.due_warnings(&self.config.warning_defaults(), now)?;
+        for &(id, warning) in &due {
+            let Some(task) = tasks.get(id) else {
+                continue;
+            };
+            let message = match warning {
+                Warning::Remaining(offset) => {
+                    let time = format_remaining_time(offset, TimeFormat::Full);
+                    tr_args("notify.warning", &[("name", &task.name), ("time", &time)])
+                }
+                Warning::Progress(milestone) => {
+                    let percent = format!("{:.0}", milestone * 100.0);
+                    let time = format_remaining_time(task.remaining_time_at(now)?, TimeFormat::Full);
+                    tr_args(
+                        "notify.milestone",
+                        &[("name", &task.name), ("percent", &percent), ("time", &time)],
+                    )
+                }
+            };
+            info!("{}", message);
+            if self.config.do_not_disturb {
+                self.quiet_queue.push_warning(task, message);
+            } else if !task.muted
+                && let Err(e) = self.notifier.notify("Time Ticker", &message)
+            {
+                error!("Failed to send warning notification for '{}': {}", task.name, e);
+            }
+        }
+        Ok(!due.is_empty())
+    }
 
-                // 删除
-                let delete = MenuItem::new("删除", true, None);
-                let delete_id = delete.id().clone();
-                self.menu_ids.insert(delete_id, format!("delete_{i}"));
-                task_submenu.append(&delete).context(MenuAppendSnafu {
-                    item_name: format!("delete_task_{}", i),
-                })?;
+    /// 设置了预算的分组今天的用时：已完成的任务来自历史记录，未完成的时间段任务计入已计时的部分
+    fn budget_usage(&self, tasks: &TaskList, now: SystemTime) -> Result<Vec<BudgetUsage>> {
+        if self.config.tag_budgets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let now_local = DateTime::<Local>::from(now);
+        let live = tasks
+            .iter()
+            .filter(|task| {
+                task.group.is_some()
+                    && matches!(task.task_type, TaskType::Duration(_))
+                    && matches!(task.state(), TaskState::Running | TaskState::Paused)
+            })
+            .map(|task| {
+                Ok(stats::Entry {
+                    name: task.name.clone(),
+                    tag: task.group.clone(),
+                    time: task.stats()?.actual,
+                    at: now_local,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let records = self.history.as_ref().map_or(&[][..], |history| history.records());
+        Ok(self
+            .budget_tracker
+            .borrow_mut()
+            .usage(records, &live, &self.config.tag_budgets, now_local))
+    }
 
-                // 固定/取消固定
-                let pin = MenuItem::new(if task.pinned { "取消固定" } else { "固定" }, true, None);
-                let pin_id = pin.id().clone();
-                self.menu_ids.insert(pin_id, format!("pin_{i}"));
-                task_submenu.append(&pin).context(MenuAppendSnafu {
-                    item_name: format!("pin_task_{}", i),
-                })?;
+    /// 分组今天的用时达到预算时提醒一次，并按配置暂停该分组计时中的任务；
+    /// 只检查有计时中任务的分组，提醒后再次开始的任务当天不会被再次暂停
+    fn check_budgets(&mut self, now: SystemTime) -> Result<()> {
+        if self.config.tag_budgets.is_empty() {
+            return Ok(());
+        }
+        let exhausted = {
+            let tasks = self.tasks.read()?;
+            let running_tags: HashSet<&str> = tasks.running().filter_map(|task| task.group.as_deref()).collect();
+            let usage: Vec<BudgetUsage> = self
+                .budget_usage(&tasks, now)?
+                .into_iter()
+                .filter(|budget| running_tags.contains(budget.tag.as_str()))
+                .collect();
+            self.budget_tracker.borrow_mut().newly_exhausted(&usage)
+        };
+        if exhausted.is_empty() {
+            return Ok(());
+        }
 
-                // 将子菜单添加到主菜单
-                menu.append(&task_submenu).context(MenuAppendSnafu {
-                    item_name: format!("task_submenu_{}", i),
-                })?;
+        let mut paused_any = false;
+        for budget in exhausted {
+            let used = format_remaining_time(budget.used, TimeFormat::Full);
+            let limit = format_remaining_time(budget.budget, TimeFormat::Full);
+            info!("⛔ 分组 '{}' 今天的预算已用完: {} / {}", budget.tag, used, limit);
+            if self.config.budget_auto_pause {
+                let mut tasks = self.tasks.write()?;
+                for task in tasks.iter_mut() {
+                    if task.group.as_deref() == Some(budget.tag.as_str())
+                        && matches!(task.task_type, TaskType::Duration(_))
+                        && task.state() == TaskState::Running
+                    {
+                        task.pause()?;
+                        info!("⏸️ 任务 '{}' 已因预算用完暂停", task.name);
+                        self.emit_task_event(TaskEvent::Paused(task.id));
+                        paused_any = true;
+                    }
+                }
+            }
+            if self.config.do_not_disturb {
+                continue;
+            }
+            let message = tr_args(
+                "notify.budget_exhausted",
+                &[("tag", &budget.tag), ("used", &used), ("budget", &limit)],
+            );
+            if let Err(e) = self.notifier.notify("Time Ticker", &message) {
+                error!("Failed to send budget notification for '{}': {}", budget.tag, e);
             }
         }
+        if paused_any {
+            self.save_tasks();
+            self.refresh_menu()?;
+        }
+        Ok(())
+    }
 
-        // 添加分隔线
-        menu.append(&PredefinedMenuItem::separator()).context(MenuAppendSnafu {
-            item_name: "separator_after_tasks".to_string(),
-        })?;
+    /// 按同一时刻刷新主托盘和所有固定图标，保证显示的时间同时变化
+    fn update_tray_icon(&self, now: SystemTime) -> Result<()> {
+        if let Some(tray_icon) = &self.tray_icon {
+            let tasks = self.tasks.read()?;
+            let mut cache = self.display_cache.borrow_mut();
+            let mut snapshots = Vec::new();
+
+            // 更新菜单项文本并收集提示信息（跳过已归档的任务）
+            for (_, section_tasks) in tasks.sections(self.config.task_order) {
+                for task in section_tasks {
+                    snapshots.push(TaskSnapshot::new(task, now)?);
+
+                    // 更新菜单项文本（不会关闭菜单）
+                    if let Some(menu_item) = self.menu_items.get(&task.id) {
+                        let title = menu_model::task_title(task, &self.config, now)?;
+                        cache.text(Slot::MenuTitle(task.id), &title, |text| menu_item.set_text(text));
+                    }
 
-        // 添加新建任务选项
-        let new_task_main = MenuItem::new("新建任务", true, None);
-        let new_task_main_id = new_task_main.id().clone();
-        self.menu_ids.insert(new_task_main_id, "new_task".to_string());
-        menu.append(&new_task_main).context(MenuAppendSnafu {
-            item_name: "new_task_main".to_string(),
-        })?;
+                    // 更新控制按钮文本
+                    if let Some(control_item) = self.control_items.get(&task.id)
+                        && let TaskType::Duration(_) = task.task_type
+                    {
+                        cache.text(Slot::MenuControl(task.id), start_pause_label(task.is_running), |text| {
+                            control_item.set_text(text)
+                        });
+                    }
+                    if let Some(items) = self.snooze_items.get(&task.id) {
+                        cache.flag(Slot::MenuSnooze(task.id), task.can_snooze(now), |enabled| {
+                            items.iter().for_each(|item| item.set_enabled(enabled))
+                        });
+                    }
+                    // 勾选项点击时由系统切换，这里按任务状态校正
+                    if let Some(check_items) = self.check_items.get(&task.id) {
+                        check_items.sync(task);
+                    }
+                }
+            }
 
-        // 添加设置选项
-        let settings_submenu = Submenu::new("⚙️ 设置", true);
+            let tooltip = task::build_tooltip(&snapshots, self.config.tooltip_max_tasks);
+            let mut tooltip_result = Ok(());
+            cache.text(Slot::Tooltip, &tooltip, |text| {
+                tooltip_result = tray_icon.set_tooltip(Some(text)).context(TrayIconUpdateSnafu {
+                    operation: "set_tooltip".to_string(),
+                });
+            });
+            if tooltip_result.is_err() {
+                cache.forget_tray(); // 设置失败时下次重试
+            }
+            tooltip_result?;
+
+            // 主托盘标题显示最紧急（或设置中指定）任务的剩余时间，没有计时中的任务时清空
+            let title = match self.title_task(&tasks, now) {
+                Some(task) if self.config.show_tray_title => Some(format!(
+                    "{}{}",
+                    self.task_urgency(task, now)?.title_prefix(),
+                    task.summary_at(SummaryStyle::PinnedTitle, now)?
+                )),
+                _ => None,
+            };
+            // 有未查看的完成时标题前加 ⏰
+            let title = match title {
+                Some(title) if self.blinker.is_active() => Some(format!("⏰{title}")),
+                None if self.blinker.is_active() => Some("⏰".to_string()),
+                title => title,
+            };
+            // 没有标题时记为空文本，清空标题也只调用一次
+            cache.text(Slot::Title, title.as_deref().unwrap_or_default(), |text| {
+                tray_icon.set_title((!text.is_empty()).then_some(text))
+            });
+            drop(cache);
+            drop(tasks);
+        }
 
-        // Dock 设置
-        let dock_submenu = Submenu::new("🖥️ Dock 设置", true);
+        // 更新所有固定的托盘图标
+        let pinned_ids: Vec<TaskId> = self.pinned_tray_icons.keys().cloned().collect();
+        for id in pinned_ids {
+            if let Err(e) = self.update_pinned_tray_icon(id, now) {
+                error!("Failed to update pinned tray icon for task {}: {}", id, e);
+            }
+        }
+        let (applied, skipped) = self.display_cache.borrow_mut().take_counts();
+        trace!("显示刷新：更新 {} 项，未变化跳过 {} 项", applied, skipped);
+        Ok(())
+    }
 
-        let show_dock = MenuItem::new("显示在 Dock 中", true, None);
-        let show_dock_id = show_dock.id().clone();
-        self.menu_ids.insert(show_dock_id, "dock_show".to_string());
-        dock_submenu.append(&show_dock).context(MenuAppendSnafu {
-            item_name: "dock_show".to_string(),
-        })?;
+    /// 托盘提示文本，“复制全部”复制的也是这段文本
+    fn tooltip_text(&self, tasks: &TaskList, now: SystemTime) -> Result<String> {
+        let snapshots = tasks
+            .sections(self.config.task_order)
+            .into_iter()
+            .flat_map(|(_, section_tasks)| section_tasks)
+            .map(|task| TaskSnapshot::new(task, now))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(task::build_tooltip(&snapshots, self.config.tooltip_max_tasks))
+    }
 
-        let hide_dock = MenuItem::new("隐藏 Dock 图标", true, None);
-        let hide_dock_id = hide_dock.id().clone();
-        self.menu_ids.insert(hide_dock_id, "dock_hide".to_string());
-        dock_submenu.append(&hide_dock).context(MenuAppendSnafu {
-            item_name: "dock_hide".to_string(),
-        })?;
+    /// 主托盘标题和 Dock 角标显示的任务：
+    /// 设置中指定的计时中任务，未指定时为剩余时间最短的任务
+    fn title_task<'a>(&self, tasks: &'a TaskList, now: SystemTime) -> Option<&'a Task> {
+        match self.config.tray_title_task {
+            Some(id) => tasks.get(id).filter(|t| t.state() == TaskState::Running),
+            None => tasks.soonest(now),
+        }
+    }
 
-        // 添加分隔线
-        dock_submenu
-            .append(&PredefinedMenuItem::separator())
-            .context(MenuAppendSnafu {
-                item_name: "dock_separator".to_string(),
-            })?;
+    /// Dock 图标显示时在角标上显示最紧急任务的剩余时间，只在文本变化时更新
+    fn update_dock_badge(&mut self, now: SystemTime) -> Result<()> {
+        let label = if self.dock_visible {
+            let tasks = self.tasks.read()?;
+            self.title_task(&tasks, now)
+                .map(|task| task.remaining_time_at(now))
+                .transpose()?
+                .map(task::format_badge)
+        } else {
+            None
+        };
+        if label != self.dock_badge {
+            set_dock_badge(label.as_deref())?;
+            self.dock_badge = label;
+        }
+        Ok(())
+    }
 
-        // 添加测试图标设置
-        let test_icon = MenuItem::new("🔄 重新设置 dock.png", true, None);
-        let test_icon_id = test_icon.id().clone();
-        self.menu_ids.insert(test_icon_id, "dock_test_icon".to_string());
-        dock_submenu.append(&test_icon).context(MenuAppendSnafu {
-            item_name: "dock_test_icon".to_string(),
-        })?;
+    /// 在 Dock 图标上绘制最紧急任务的剩余分钟数，每分钟最多重绘一次；
+    /// 没有计时中的任务或 Dock 图标隐藏时恢复原图标
+    fn update_dock_countdown(&mut self, now: SystemTime) -> Result<()> {
+        let task_state = if self.dock_visible {
+            let tasks = self.tasks.read()?;
+            match self.title_task(&tasks, now) {
+                Some(task) => {
+                    let minutes = task.remaining_time_at(now)?.as_secs().div_ceil(60);
+                    Some((minutes, self.task_urgency(task, now)?))
+                }
+                None => None,
+            }
+        } else {
+            None
+        };
+        let minutes = task_state.map(|(minutes, _)| minutes);
+        if minutes == self.dock_countdown {
+            return Ok(());
+        }
 
-        settings_submenu.append(&dock_submenu).context(MenuAppendSnafu {
-            item_name: "dock_submenu".to_string(),
-        })?;
-        menu.append(&settings_submenu).context(MenuAppendSnafu {
-            item_name: "settings_submenu".to_string(),
-        })?;
+        #[cfg(target_os = "macos")]
+        {
+            let dock_icon = self.custom_icon_path(self.config.dock_icon_path.as_deref());
+            match task_state {
+                Some((minutes, urgency)) => {
+                    let background = match &dock_icon {
+                        Some(path) => image::open(path).context(ImageSnafu)?,
+                        None => image::load_from_memory(DOCK_PNG).context(ImageSnafu)?,
+                    };
+                    let params = IconParams {
+                        style: IconStyle::Digits,
+                        ..self.icon_params(urgency)
+                    };
+                    let img = icon::render_dock_icon(
+                        self.icon_renderer.as_ref(),
+                        &background.into_rgba8(),
+                        minutes * 60,
+                        &params,
+                    );
+                    set_dock_image(&icon::encode_png(&img)?)?;
+                }
+                None if self.dock_visible => set_dock_icon(dock_icon.as_deref())?,
+                None => {}
+            }
+        }
+        self.dock_countdown = minutes;
+        Ok(())
+    }
 
-        // 添加分隔线
-        menu.append(&PredefinedMenuItem::separator()).context(MenuAppendSnafu {
-            item_name: "separator_before_quit".to_string(),
-        })?;
+    /// 绘制固定图标的参数：配色按最近检测到的系统外观选择，
+    /// 无法检测时使用配置中的配色
+    fn icon_params(&self, urgency: Urgency) -> IconParams {
+        IconParams {
+            style: self.config.pinned_icon_style,
+            urgency,
+            palette: Palette::for_appearance(self.appearance, self.config.icon_palette),
+            template: self.config.icon_template,
+            scale: self.config.icon_scale,
+        }
+    }
 
-        // 添加退出选项
-        let quit = MenuItem::new("退出", true, None);
-        let quit_id = quit.id().clone();
-        self.menu_ids.insert(quit_id, "quit".to_string());
-        menu.append(&quit).context(MenuAppendSnafu {
-            item_name: "quit".to_string(),
-        })?;
+    /// 重新检测系统外观，切换深色/浅色模式后下一次刷新即使用新的配色；返回外观是否变化
+    fn refresh_appearance(&mut self) -> bool {
+        let appearance = self.appearance_source.appearance();
+        if appearance == self.appearance {
+            return false;
+        }
+        debug!("系统外观变化: {:?}", appearance);
+        self.appearance = appearance;
+        true
+    }
 
-        Ok(menu)
+    /// 按设置的阈值判断计时中任务的紧急程度，已完成的任务视为紧急，
+    /// 未在计时的任务不提示
+    fn task_urgency(&self, task: &Task, now: SystemTime) -> Result<Urgency> {
+        match task.state() {
+            TaskState::Finished => return Ok(Urgency::Critical),
+            TaskState::Idle | TaskState::Paused => return Ok(Urgency::Normal),
+            TaskState::Running => {}
+        }
+        Ok(Urgency::from_remaining(
+            task.remaining_time_at(now)?,
+            Duration::from_secs(self.config.warning_threshold_secs),
+            Duration::from_secs(self.config.critical_threshold_secs),
+        ))
     }
 
-    fn update_tray_icon(&self) -> Result<()> {
+    /// 刷新主菜单：结构不变时原地更新文本、可用和勾选状态，不会关闭打开中的子菜单；
+    /// 任务增删或顺序变化时才整体重建
+    fn refresh_menu(&mut self) -> Result<()> {
+        let span = debug_span!("refresh_menu", updates = field::Empty, duration_ms = field::Empty);
+        let _entered = span.enter();
+        let _timer = logging::HandlerTimer::start("refresh_menu", &span);
+        self.display_stale = true; // 菜单刷新说明任务或设置有变化，下一次计时刷新完整更新
+        let entries = self.main_menu_spec()?;
+        if self.tray_icon.is_some()
+            && let Some(updates) = menu_model::diff(&self.rendered_menu, &entries)
+        {
+            span.record("updates", updates.len());
+            self.display_cache.borrow_mut().forget_main_menu();
+            for update in &updates {
+                if let Some(handle) = self.menu_handles.get(update.index) {
+                    handle.apply(&update.change);
+                }
+            }
+            self.rendered_menu = entries;
+            return Ok(());
+        }
+        let new_menu = self.render_menu(entries)?;
         if let Some(tray_icon) = &self.tray_icon {
-            let tasks = self.tasks.lock().map_err(|_| TaskLockSnafu.build())?; // Use TaskLockSnafu directly
-            let mut tooltip = String::new();
-
-            // 更新tooltip和菜单项文本
-            for (i, task) in tasks.iter().enumerate() {
-                let remaining = task.get_remaining_time()?;
-                let time_str = format_remaining_time(remaining);
-                tooltip.push_str(&format!("{}#{}\n", time_str, task.name));
-
-                // 更新菜单项文本（不会关闭菜单）
-                if let Some(menu_item) = self.menu_items.get(&i) {
-                    menu_item.set_text(format!("{}#{}", time_str, task.name));
+            tray_icon.set_menu(Some(Box::new(new_menu))); // Use TrayIconUpdateSnafu directly
+        }
+        Ok(())
+    }
+
+    /// 托盘图标：配置了自定义图标时从文件加载，否则使用内嵌的 logo.png；
+    /// 加载失败时使用绘制的时钟图标，保证托盘图标总能显示
+    fn logo_icon(&self) -> Result<Icon> {
+        let loaded = match self.custom_icon_path(self.config.tray_icon_path.as_deref()) {
+            Some(path) => load_icon(&path),
+            None => decode_icon(LOGO_PNG),
+        };
+        loaded.or_else(|e| {
+            error!("Failed to load tray icon, using generated fallback: {}", e);
+            icon::fallback_icon()
+        })
+    }
+
+    /// 绘制模式下固定图标的时间图标，显示内容相同的图标只绘制一次
+    fn pinned_time_icon(
+        &self,
+        content: &str,
+        time_title: &str,
+        progress: f64,
+        params: &IconParams,
+        icon_path: Option<&Path>,
+    ) -> Result<Icon> {
+        let key = IconKey {
+            content: content.to_string(),
+            params: *params,
+            image: icon_path.map(Path::to_path_buf),
+        };
+        self.icon_cache.borrow_mut().get_or_render(key, || {
+            let task_image = icon_path.and_then(|path| self.task_image(path));
+            icon::time_icon(
+                self.icon_renderer.as_ref(),
+                time_title,
+                progress,
+                params,
+                task_image.as_deref(),
+            )
+        })
+    }
+
+    /// 标题模式下固定图标使用的图标：任务图片无法加载时使用生成的时钟图标
+    fn pinned_title_icon(&self, icon_path: Option<&Path>) -> Result<Icon> {
+        let Some(path) = icon_path else {
+            return self.logo_icon();
+        };
+        match self.task_image(path) {
+            Some(image) => icon::image_icon((*image).clone()),
+            None => icon::fallback_icon(),
+        }
+    }
+
+    /// 任务的自定义图片按路径缓存，加载失败时只记录一次错误
+    fn task_image(&self, path: &Path) -> Option<Rc<RgbaImage>> {
+        self.task_images
+            .borrow_mut()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| match icon::load_image(path) {
+                Ok(image) => Some(Rc::new(image)),
+                Err(e) => {
+                    error!("Failed to load task icon {}, using fallback: {}", path.display(), e);
+                    None
                 }
+            })
+            .clone()
+    }
 
-                // 更新控制按钮文本
-                if let Some(control_item) = self.control_items.get(&i)
-                    && let TaskType::Duration(_) = task.task_type
-                {
-                    control_item.set_text(if task.is_running { "暂停" } else { "开始" });
+    /// 配置中的自定义图标路径，相对路径基于配置文件所在目录
+    fn custom_icon_path(&self, path: Option<&Path>) -> Option<PathBuf> {
+        path.map(|path| config::resolve_path(path, self.config_path.as_deref()))
+    }
+
+    /// 固定图标显示剩余时间的方式，不支持标题的平台上始终绘制
+    fn pinned_display_mode(&self) -> DisplayMode {
+        icon::display_mode(Platform::current(), self.config.pinned_drawn_icon)
+    }
+
+    /// 为存档中已固定的任务重新创建固定托盘图标；多次重试仍失败的任务取消固定，
+    /// 使固定标记与实际显示的图标一致
+    fn restore_pinned_tray_icons(&mut self) -> Result<()> {
+        let pinned = self.tasks.read()?.pinned();
+        for id in pinned {
+            self.create_pinned_tray_icon_with_retry(id);
+        }
+        Ok(())
+    }
+
+    /// 创建主托盘图标，失败时按退避时间重试；全部重试失败后提示并退出，
+    /// 避免程序在没有任何界面的情况下继续运行
+    fn init_tray_icon(&mut self) {
+        let error = match self.new_tray_icon() {
+            Ok(tray_icon) => {
+                self.tray_icon = Some(tray_icon);
+                self.display_cache.borrow_mut().forget_tray();
+                // 固定图标在主图标之后创建，保证菜单栏中的顺序稳定
+                if let Err(e) = self.restore_pinned_tray_icons() {
+                    error!("Failed to restore pinned tray icons: {}", e);
                 }
+                return;
             }
-
-            tray_icon.set_tooltip(Some(&tooltip)).context(TrayIconUpdateSnafu {
-                operation: "set_tooltip".to_string(),
-            })?;
-            drop(tasks);
+            Err(e) => e,
+        };
+        if let Some(delay) = self.tray_retry.next_delay() {
+            warn!("Failed to create tray icon, retrying in {:?}: {}", delay, error);
+            self.send_event_after(delay, UserEvent::RetryTrayIcon);
+            return;
         }
+        error!("Giving up creating tray icon: {}", error);
+        self.dialog.error(tr("error.tray_icon"), &error.to_string());
+        self.quit(1);
+    }
 
-        // 更新所有固定的托盘图标
-        let pinned_indices: Vec<usize> = self.pinned_tray_icons.keys().cloned().collect();
-        for index in pinned_indices {
-            if let Err(e) = self.update_pinned_tray_icon(index) {
-                error!("Failed to update pinned tray icon for task {}: {}", index, e);
+    /// 创建固定托盘图标，失败时按退避时间重试；全部重试失败后取消固定并提示
+    fn create_pinned_tray_icon_with_retry(&mut self, id: TaskId) {
+        let error = match self.create_pinned_tray_icon(id) {
+            Ok(()) => {
+                self.pinned_retries.remove(&id);
+                return;
             }
+            Err(e) => e,
+        };
+        if let Some(delay) = self.pinned_retries.entry(id).or_default().next_delay() {
+            warn!(
+                "Failed to create pinned tray icon for task {}, retrying in {:?}: {}",
+                id, delay, error
+            );
+            self.send_event_after(delay, UserEvent::RetryPinnedTrayIcon(id));
+            return;
         }
-        Ok(())
+        self.pinned_retries.remove(&id);
+        error!(
+            "Giving up creating pinned tray icon for task {}, unpinning: {}",
+            id, error
+        );
+        if let Err(e) = self.tasks.write().map(|mut tasks| tasks.unpin(&[id])) {
+            error!("Failed to unpin task {}: {}", id, e);
+        }
+        self.remove_pinned_tray_icon(id);
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after unpinning: {}", e);
+        }
+        self.save_tasks();
+        self.dialog.error(tr("error.pinned_tray_icon"), &error.to_string());
     }
 
-    fn refresh_menu(&mut self) -> Result<()> {
-        let new_menu = self.build_menu()?;
-        if let Some(tray_icon) = &self.tray_icon {
-            tray_icon.set_menu(Some(Box::new(new_menu))); // Use TrayIconUpdateSnafu directly
+    /// 到点重试创建固定托盘图标；等待期间取消固定或已经创建成功时不再重试
+    fn retry_pinned_tray_icon(&mut self, id: TaskId) {
+        let pinned = match self.tasks.read() {
+            Ok(tasks) => tasks.get(id).is_some_and(|task| task.pinned),
+            Err(e) => {
+                error!("Failed to read tasks before retrying pinned tray icon: {}", e);
+                false
+            }
+        };
+        if pinned && !self.pinned_tray_icons.contains_key(&id) {
+            self.create_pinned_tray_icon_with_retry(id);
+        } else {
+            self.pinned_retries.remove(&id);
         }
-        Ok(())
     }
 
-    fn create_pinned_tray_icon(&mut self, task_index: usize) -> Result<()> {
-        let path = std::path::Path::new("./assets/logo.png");
-        let icon_res = load_icon(path); // Keep as Result for now
+    fn create_pinned_tray_icon(&mut self, id: TaskId) -> Result<()> {
+        let now = SystemTime::now();
 
         // 先获取任务信息，然后释放锁
-        let (task_name, task_type, is_running, remaining_time_res) = {
-            let tasks = self.tasks.lock().map_err(|_| error::TaskLockSnafu.build())?;
-            if let Some(task) = tasks.get(task_index) {
+        let (task_type, is_running, can_snooze, label, tooltip, time_title, urgency, progress, icon_path) = {
+            let tasks = self.tasks.read()?;
+            if let Some(task) = tasks.get(id) {
                 (
-                    task.name.clone(),
                     task.task_type.clone(),
                     task.is_running,
-                    task.get_remaining_time(),
+                    task.can_snooze(now),
+                    task.summary_at(SummaryStyle::Menu, now)?,
+                    task.summary_at(SummaryStyle::Tooltip, now)?,
+                    task.summary_at(SummaryStyle::PinnedTitle, now)?,
+                    self.task_urgency(task, now)?,
+                    task.progress_at(now)?,
+                    task.icon_path.clone(),
                 )
             } else {
                 // This case should ideally be an error, but to match original logic, we return
-                // Ok. Consider changing to `Err(Error::TaskNotFound { index:
-                // task_index, ... })`
+                // Ok. Consider changing to `Err(Error::TaskIdNotFound { id, ... })`
                 return Ok(());
             }
         };
-        let remaining_time = remaining_time_res?; // Handle Result for remaining_time
 
         // 现在可以安全地调用 build_pinned_task_menu
-        let menu = self.build_pinned_task_menu(task_index, &task_name, &task_type, is_running, remaining_time)?;
-
-        // 使用时间文本作为标题，格式：MM:SS
-        let time_str = format_remaining_time(remaining_time); // remaining_time is already Duration here
-        let parts: Vec<&str> = time_str.split(':').collect();
-        let time_title = if parts.len() >= 3 {
-            format!("{}:{}", parts[1], parts[2]) // 显示 MM:SS
+        let menu = self.build_pinned_task_menu(id, &label, &task_type, is_running, can_snooze)?;
+
+        // 绘制模式下图标本身显示剩余时间，不再使用文本标题
+        let builder = if self.pinned_display_mode() == DisplayMode::Drawn {
+            let params = self.icon_params(urgency);
+            let key = params.style.render_key(&time_title, progress);
+            let icon = self.pinned_time_icon(&key, &time_title, progress, &params, icon_path.as_deref())?;
+            self.pinned_icon_cache.borrow_mut().insert(id, (key, params));
+            TrayIconBuilder::new()
+                .with_icon(icon)
+                .with_icon_as_template(params.template)
         } else {
-            "00:00".to_string()
+            TrayIconBuilder::new()
+                .with_icon(self.pinned_title_icon(icon_path.as_deref())?)
+                .with_title(format!("{}{}", urgency.title_prefix(), time_title))
         };
 
-        let final_icon = icon_res?; // Handle icon Result here
-
-        let tray_icon = TrayIconBuilder::new()
+        let tray_icon = builder
+            .with_id(pinned_tray_id(id))
             .with_menu(Box::new(menu))
-            .with_tooltip(format!("{}#{}", format_remaining_time(remaining_time), task_name)) // remaining_time is Duration
-            .with_icon(final_icon)
-            .with_title(&time_title)
+            .with_menu_on_left_click(!self.config.left_click_toggle)
+            .with_tooltip(tooltip)
             .build()
             .context(TrayIconBuildSnafu)?; // Use TrayIconBuildSnafu directly
 
-        self.pinned_tray_icons.insert(task_index, tray_icon);
+        self.pinned_tray_icons.insert(id, tray_icon);
+        self.display_cache.borrow_mut().forget_pinned(id);
         Ok(())
     }
 
     fn build_pinned_task_menu(
         &mut self,
-        task_index: usize,
-        task_name: &str,
+        id: TaskId,
+        label: &str,
         task_type: &TaskType,
         is_running: bool,
-        remaining_time: Duration,
+        can_snooze: bool,
     ) -> Result<Menu> {
         let menu = Menu::new();
+        self.unregister_pinned_menu_ids(id); // 重新创建时先清除旧的菜单ID
 
         // 显示任务时间（正确显示当前剩余时间）
-        let time_str = format_remaining_time(remaining_time);
-        let time_item = MenuItem::new(format!("{time_str}#{task_name}"), false, None);
-        self.pinned_menu_items.insert(task_index, time_item.clone()); // 保存引用以便更新
+        let time_item = MenuItem::new(label, false, None);
+        self.pinned_menu_items.insert(id, time_item.clone()); // 保存引用以便更新
         menu.append(&time_item).context(MenuAppendSnafu {
-            item_name: format!("pinned_time_item_task_{}", task_index),
+            item_name: format!("pinned_time_item_task_{}", id),
         })?;
 
+        // 截止时间任务显示截止时刻
+        if let TaskType::Deadline(deadline) = task_type {
+            let end_label = tr_args("time.deadline", &[(
+                "time",
+                &task::format_clock_time(*deadline, SystemTime::now()),
+            )]);
+            let end_item = MenuItem::new(end_label, false, None);
+            menu.append(&end_item).context(MenuAppendSnafu {
+                item_name: format!("pinned_end_time_task_{}", id),
+            })?;
+        }
+
         // 添加分隔线
         menu.append(&PredefinedMenuItem::separator()).context(MenuAppendSnafu {
-            item_name: format!("pinned_separator1_task_{}", task_index),
+            item_name: format!("pinned_separator1_task_{}", id),
         })?;
 
+        // 推迟，计时恢复后在 update_pinned_tray_icon 中更新可用状态
+        let snooze_items = snooze_menu_items(can_snooze);
+        for (item, extra) in &snooze_items {
+            self.register_pinned_menu_id(id, item.id().clone(), MenuAction::Snooze(id, *extra));
+            menu.append(item).context(MenuAppendSnafu {
+                item_name: format!("pinned_snooze_task_{}_{}", id, extra.as_secs()),
+            })?;
+        }
+        self.pinned_snooze_items
+            .insert(id, snooze_items.into_iter().map(|(item, _)| item).collect());
+
         // 根据任务类型添加控制选项
         match task_type {
             TaskType::Duration(_) => {
                 // 开始/暂停
-                let start_pause = MenuItem::new(if is_running { "暂停" } else { "开始" }, true, None);
+                let start_pause = MenuItem::new(start_pause_label(is_running), true, None);
                 let start_pause_id = start_pause.id().clone();
-                self.menu_ids
-                    .insert(start_pause_id, format!("pinned_toggle_{task_index}"));
-                self.pinned_control_items.insert(task_index, start_pause.clone()); // 保存引用以便更新
+                self.register_pinned_menu_id(id, start_pause_id, MenuAction::Toggle(id));
+                self.pinned_control_items.insert(id, start_pause.clone()); // 保存引用以便更新
                 menu.append(&start_pause).context(MenuAppendSnafu {
-                    item_name: format!("pinned_toggle_task_{}", task_index),
+                    item_name: format!("pinned_toggle_task_{}", id),
                 })?;
 
                 // 重置
-                let reset = MenuItem::new("重置", true, None);
+                let reset = MenuItem::new(tr("menu.reset"), true, None);
                 let reset_id = reset.id().clone();
-                self.menu_ids.insert(reset_id, format!("pinned_reset_{task_index}"));
+                self.register_pinned_menu_id(id, reset_id, MenuAction::Reset(id));
                 menu.append(&reset).context(MenuAppendSnafu {
-                    item_name: format!("pinned_reset_task_{}", task_index),
+                    item_name: format!("pinned_reset_task_{}", id),
                 })?;
             }
             TaskType::Deadline(_) => {
@@ -426,823 +1192,1980 @@ impl Application {
 
         // 添加分隔线
         menu.append(&PredefinedMenuItem::separator()).context(MenuAppendSnafu {
-            item_name: format!("pinned_separator2_task_{}", task_index),
+            item_name: format!("pinned_separator2_task_{}", id),
         })?;
 
-        // 取消固定
-        let unpin = MenuItem::new("取消固定", true, None);
+        // 取消固定：固定菜单中的任务总是已固定，点击后取消勾选并移除图标
+        let unpin = CheckMenuItem::new(tr("menu.pin"), true, true, None);
         let unpin_id = unpin.id().clone();
-        self.menu_ids.insert(unpin_id, format!("unpin_{task_index}"));
+        self.register_pinned_menu_id(id, unpin_id, MenuAction::Unpin(id));
         menu.append(&unpin).context(MenuAppendSnafu {
-            item_name: format!("unpin_task_{}", task_index),
+            item_name: format!("unpin_task_{}", id),
         })?;
 
         Ok(menu)
     }
 
-    fn remove_pinned_tray_icon(&mut self, task_index: usize) {
-        self.pinned_tray_icons.remove(&task_index);
-        self.pinned_menu_items.remove(&task_index);
-        self.pinned_control_items.remove(&task_index);
+    fn register_pinned_menu_id(&mut self, id: TaskId, menu_id: MenuId, action: MenuAction) {
+        self.menu_ids.insert(menu_id.clone(), action);
+        self.pinned_menu_ids.entry(id).or_default().push((menu_id, action));
+    }
+
+    fn unregister_pinned_menu_ids(&mut self, id: TaskId) {
+        for (menu_id, _) in self.pinned_menu_ids.remove(&id).unwrap_or_default() {
+            self.menu_ids.remove(&menu_id);
+        }
+    }
+
+    fn remove_pinned_tray_icon(&mut self, id: TaskId) {
+        self.pinned_tray_icons.remove(&id);
+        self.pinned_menu_items.remove(&id);
+        self.pinned_control_items.remove(&id);
+        self.pinned_snooze_items.remove(&id);
+        self.pinned_icon_cache.borrow_mut().remove(&id);
+        self.display_cache.borrow_mut().forget_pinned(id);
+        self.unregister_pinned_menu_ids(id);
     }
 
-    fn update_pinned_tray_icon(&self, task_index: usize) -> Result<()> {
+    fn update_pinned_tray_icon(&self, id: TaskId, now: SystemTime) -> Result<()> {
         // 先获取任务信息
-        let (task_name, task_type, is_running, remaining_time) = {
-            let tasks = self.tasks.lock().map_err(|_| error::TaskLockSnafu.build())?;
-            if let Some(task) = tasks.get(task_index) {
+        let (name, task_type, is_running, can_snooze, label, tooltip, time_title, urgency, progress, icon_path) = {
+            let tasks = self.tasks.read()?;
+            if let Some(task) = tasks.get(id) {
                 (
                     task.name.clone(),
                     task.task_type.clone(),
                     task.is_running,
-                    task.get_remaining_time(),
+                    task.can_snooze(now),
+                    task.summary_at(SummaryStyle::Menu, now)?,
+                    task.summary_at(SummaryStyle::Tooltip, now)?,
+                    task.summary_at(SummaryStyle::PinnedTitle, now)?,
+                    self.task_urgency(task, now)?,
+                    task.progress_at(now)?,
+                    task.icon_path.clone(),
                 )
             } else {
                 // Consider returning an error here if task not found
                 return Ok(()); // Matching original behavior
             }
         };
-        let remaining_time = remaining_time?; // Handle Result from get_remaining_time
-
-        // 更新托盘图标
-        if let Some(tray_icon) = self.pinned_tray_icons.get(&task_index) {
-            let time_str = format_remaining_time(remaining_time); // Handle Result from get_remaining_time
-            let tooltip = format!("{time_str}#{task_name}");
 
-            // 使用文本标题显示时间，格式：MM:SS
-            let parts: Vec<&str> = time_str.split(':').collect();
-            let time_title = if parts.len() >= 3 {
-                format!("{}:{}", parts[1], parts[2]) // 显示 MM:SS
-            } else {
-                "00:00".to_string()
+        // 更新托盘图标，按设置绘制时间图标或使用文本标题显示时间
+        let update_icon = || -> Result<()> {
+            let Some(tray_icon) = self.pinned_tray_icons.get(&id) else {
+                return Ok(());
             };
-
-            tray_icon.set_title(Some(&time_title));
-            tray_icon.set_tooltip(Some(&tooltip)).context(TrayIconUpdateSnafu {
-                operation: format!("set_tooltip_pinned_task_{}", task_index),
-            })?;
-        }
+            let mut cache = self.pinned_icon_cache.borrow_mut();
+            let mut display = self.display_cache.borrow_mut();
+            if self.pinned_display_mode() == DisplayMode::Drawn {
+                // 与上次绘制的内容和参数相同时不重新绘制（进度环只在百分比变化时重绘）
+                let params = self.icon_params(urgency);
+                let rendered = (params.style.render_key(&time_title, progress), params);
+                if cache.get(&id) != Some(&rendered) {
+                    let icon =
+                        self.pinned_time_icon(&rendered.0, &time_title, progress, &params, icon_path.as_deref())?;
+                    tray_icon.set_icon(Some(icon)).context(TrayIconUpdateSnafu {
+                        operation: "set_icon_pinned".to_string(),
+                    })?;
+                    tray_icon.set_icon_as_template(params.template);
+                    tray_icon.set_title(None::<&str>);
+                    display.forget(Slot::PinnedTitle(id)); // 切换回标题模式时重新设置标题
+                    cache.insert(id, rendered);
+                }
+            } else {
+                // 从绘制模式切换回来时恢复原来的图标
+                if cache.remove(&id).is_some() {
+                    let icon = self.pinned_title_icon(icon_path.as_deref())?;
+                    tray_icon.set_icon(Some(icon)).context(TrayIconUpdateSnafu {
+                        operation: "set_icon_pinned".to_string(),
+                    })?;
+                    tray_icon.set_icon_as_template(false);
+                }
+                let title = format!("{}{}", urgency.title_prefix(), time_title);
+                display.text(Slot::PinnedTitle(id), &title, |text| tray_icon.set_title(Some(text)));
+            }
+            let mut result = Ok(());
+            display.text(Slot::PinnedTooltip(id), &tooltip, |text| {
+                result = tray_icon.set_tooltip(Some(text)).context(TrayIconUpdateSnafu {
+                    operation: "set_tooltip_pinned".to_string(),
+                });
+            });
+            if result.is_err() {
+                display.forget_pinned(id); // 设置失败时下次重试
+            }
+            result
+        };
+        // 图标更新失败时带上任务名称，便于在日志中找到对应的任务
+        update_icon().with_context(|_| TaskOperationSnafu {
+            id,
+            name,
+            operation: "update pinned tray icon",
+        })?;
 
         // 更新固定菜单中的时间显示项（不重新构建菜单，避免菜单消失）
-        if let Some(menu_item) = self.pinned_menu_items.get(&task_index) {
-            let time_str = format_remaining_time(remaining_time); // Handle Result from get_remaining_time
-            menu_item.set_text(format!("{time_str}#{task_name}"));
+        let mut display = self.display_cache.borrow_mut();
+        if let Some(menu_item) = self.pinned_menu_items.get(&id) {
+            display.text(Slot::PinnedLabel(id), &label, |text| menu_item.set_text(text));
         }
 
         // 更新固定菜单中的控制按钮文本
-        if let Some(control_item) = self.pinned_control_items.get(&task_index)
+        if let Some(control_item) = self.pinned_control_items.get(&id)
             && let TaskType::Duration(_) = task_type
         {
-            control_item.set_text(if is_running { "暂停" } else { "开始" });
+            display.text(Slot::PinnedControl(id), start_pause_label(is_running), |text| {
+                control_item.set_text(text)
+            });
+        }
+        if let Some(items) = self.pinned_snooze_items.get(&id) {
+            display.flag(Slot::PinnedSnooze(id), can_snooze, |enabled| {
+                items.iter().for_each(|item| item.set_enabled(enabled))
+            });
         }
         Ok(())
     }
 
-    fn create_time_icon(&self, time_str: &str) -> Result<Icon> {
-        // 直接使用简化版本，绘制数字时间
-        self.create_digital_time_icon(time_str)
-    }
+    fn handle_menu_event(&mut self, event: TrayMenuEvent) {
+        let menu_id = event.id;
+        let span = logging::menu_event_span();
+        let _entered = span.enter();
+        let _timer = logging::HandlerTimer::start("menu_event", &span);
 
-    fn create_digital_time_icon(&self, time_str: &str) -> Result<Icon> {
-        // 创建一个32x32的图像
-        let width = 32u32;
-        let height = 32u32;
-        let mut img: RgbaImage = ImageBuffer::new(width, height);
+        trace!("菜单事件触发，ID: {:?}", menu_id);
 
-        // 填充背景色（深色背景）
-        for pixel in img.pixels_mut() {
-            *pixel = Rgba([45, 45, 45, 255]); // 深灰色背景
+        let Some(action) = self.menu_ids.get(&menu_id).copied() else {
+            warn!("❌ 未找到菜单ID对应的动作: {:?}", menu_id);
+            trace!("当前注册的所有菜单ID:");
+            for (id, action) in &self.menu_ids {
+                trace!("  {:?} -> {:?}", id, action);
+            }
+            return;
+        };
+        span.record("action", field::debug(action));
+        if let Some(id) = action.task_id() {
+            span.record("task_id", field::display(id));
         }
-
-        // 解析时间字符串 (HH:MM:SS)
-        let parts: Vec<&str> = time_str.split(':').collect();
-        if parts.len() >= 3 {
-            let minutes = parts[1];
-            let seconds = parts[2];
-
-            // 绘制时间数字（更大的字体，更好的间距）
-            let display_time = format!("{minutes}:{seconds}");
-            self.draw_large_text(&mut img, &display_time, 1, 10);
+        if action.changes_tasks() {
+            // 操作过任务说明已经看到了完成的任务
+            self.stop_blinking(None);
+            self.dispatch_menu_action(action);
+            self.save_tasks();
         } else {
-            // 如果解析失败，显示时钟图标
-            self.draw_clock_icon(&mut img);
-        }
-
-        // 转换为Icon
-        let rgba_data = img.into_raw();
-        Icon::from_rgba(rgba_data, width, height).context(IconConversionSnafu) // Use IconConversionSnafu directly
-    }
-
-    fn draw_large_text(&self, img: &mut RgbaImage, text: &str, x: u32, y: u32) {
-        // 更大的像素字体绘制，适合托盘图标
-        let white = Rgba([255, 255, 255, 255]);
-
-        let mut current_x = x;
-        for ch in text.chars() {
-            match ch {
-                '0' => self.draw_large_digit_0(img, current_x, y, white),
-                '1' => self.draw_large_digit_1(img, current_x, y, white),
-                '2' => self.draw_large_digit_2(img, current_x, y, white),
-                '3' => self.draw_large_digit_3(img, current_x, y, white),
-                '4' => self.draw_large_digit_4(img, current_x, y, white),
-                '5' => self.draw_large_digit_5(img, current_x, y, white),
-                '6' => self.draw_large_digit_6(img, current_x, y, white),
-                '7' => self.draw_large_digit_7(img, current_x, y, white),
-                '8' => self.draw_large_digit_8(img, current_x, y, white),
-                '9' => self.draw_large_digit_9(img, current_x, y, white),
-                ':' => self.draw_large_colon(img, current_x, y, white),
-                _ => {}
+            self.dispatch_menu_action(action);
+        }
+    }
+
+    /// 执行菜单动作
+    fn dispatch_menu_action(&mut self, action: MenuAction) {
+        match action {
+            MenuAction::Quit => self.quit(0),
+            MenuAction::Undo => self.handle_undo(),
+            MenuAction::PauseAll => self.handle_pause_all(),
+            MenuAction::ResumeAll => self.handle_resume_all(),
+            MenuAction::ClearFinished => self.handle_clear_finished(),
+            MenuAction::CopyAll => self.handle_copy_all(),
+            MenuAction::ExportTasks => self.handle_export_tasks(),
+            MenuAction::OpenLogDir => self.handle_open_log_dir(),
+            MenuAction::About => self.handle_about(),
+            MenuAction::CopyDiagnostics => self.handle_copy_diagnostics(),
+            MenuAction::NewTask => self.handle_new_task(),
+            MenuAction::DockShow => self.handle_dock_show(),
+            MenuAction::DockHide => self.handle_dock_hide(),
+            MenuAction::DockReloadIcon => self.handle_dock_reload_icon(),
+            MenuAction::Preset(index) => self.handle_preset(index),
+            MenuAction::Recent(index) => self.handle_recent_input(index),
+            MenuAction::ToggleSetting(setting) => self.handle_toggle_setting(setting),
+            MenuAction::SetOrder(order) => self.handle_set_task_order(order),
+            MenuAction::SetMaxPinned(max) => self.handle_set_max_pinned(max),
+            MenuAction::SetTimeFormat(target, format) => self.handle_set_time_format(target, format),
+            MenuAction::Toggle(id) => self.handle_toggle_task(id),
+            MenuAction::Reset(id) => self.handle_reset_task(id),
+            MenuAction::Chain(id, target) => self.handle_chain_task(id, Some(target)),
+            MenuAction::Unchain(id) => self.handle_chain_task(id, None),
+            MenuAction::Snooze(id, extra) => self.handle_snooze_task(id, extra),
+            MenuAction::Edit(id) => self.handle_edit_task(id),
+            MenuAction::Move(id, direction) => self.handle_move_task(id, direction),
+            MenuAction::Mute(id) => self.handle_mute_task(id),
+            MenuAction::Nag(id) => self.handle_nag_task(id),
+            MenuAction::Ack(id) => self.handle_ack_task(id),
+            MenuAction::CopyTask(id) => self.handle_copy_task(id),
+            MenuAction::Duplicate(id) => self.handle_duplicate_task(id),
+            MenuAction::Archive(id) => self.handle_archive_task(id),
+            MenuAction::Restore(id) => self.handle_restore_task(id),
+            MenuAction::Delete(id) => self.handle_delete_task(id),
+            MenuAction::RestoreTrash(id) => self.handle_restore_from_trash(id),
+            MenuAction::DeleteTrash(id) => self.handle_delete_from_trash(id),
+            MenuAction::EmptyTrash => self.handle_empty_trash(),
+            MenuAction::Pin(id) => self.handle_pin_task(id),
+            MenuAction::Unpin(id) => self.handle_unpin_task(id),
+            MenuAction::SetIcon(id) => self.handle_set_task_icon(id),
+            MenuAction::ClearIcon(id) => self.handle_clear_task_icon(id),
+        }
+    }
+
+    fn handle_dock_show(&mut self) {
+        info!("🖥️ 显示 Dock 图标");
+        let dock_icon = self.custom_icon_path(self.config.dock_icon_path.as_deref());
+        if let Err(e) = set_dock_visibility(true, dock_icon.as_deref()) {
+            error!("Failed to show dock: {}", e);
+        }
+        self.dock_visible = true;
+    }
+
+    fn handle_dock_hide(&mut self) {
+        info!("🖥️ 隐藏 Dock 图标");
+        if let Err(e) = set_dock_visibility(false, None) {
+            error!("Failed to hide dock: {}", e);
+        }
+        // 隐藏 Dock 图标时清除角标
+        self.dock_visible = false;
+        if let Err(e) = self.update_dock_badge(SystemTime::now()) {
+            error!("Failed to clear dock badge: {}", e);
+        }
+    }
+
+    fn handle_dock_reload_icon(&mut self) {
+        info!("🔄 手动重新设置 Dock 图标");
+        if let Err(e) = set_dock_icon(self.custom_icon_path(self.config.dock_icon_path.as_deref()).as_deref()) {
+            error!("Failed to set dock icon: {}", e);
+        }
+        // 恢复原图标后，下一次刷新时按需要重新绘制倒计时
+        self.dock_countdown = None;
+    }
+
+    /// 开始或暂停任务，主菜单和固定菜单共用
+    fn handle_toggle_task(&mut self, id: TaskId) {
+        match self.toggle_task(id) {
+            Ok((name, true)) => info!("▶️ 任务 '{}' 已开始", name),
+            Ok((name, false)) => info!("⏸️ 任务 '{}' 已暂停", name),
+            Err(e) => self.report_error("Failed to toggle task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after toggle", &e);
+        }
+    }
+
+    /// 重置任务，主菜单和固定菜单共用
+    fn handle_reset_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            tasks.reset(id)?;
+            tasks.get(id).map(|task| task.name.clone()).context(TaskIdNotFoundSnafu { id })
+        });
+        match result {
+            Ok(name) => {
+                info!("🔄 任务 '{}' 已重置", name);
+                self.emit_task_event(TaskEvent::Reset(id));
             }
-            current_x += if ch == ':' { 3 } else { 6 }; // 更大的间距
+            Err(e) => self.report_error("Failed to reset task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after reset", &e);
         }
     }
 
-    fn draw_simple_text(&self, img: &mut RgbaImage, text: &str, x: u32, y: u32) {
-        // 简单的像素字体绘制
-        let white = Rgba([255, 255, 255, 255]);
+    /// 归档已完成的任务，归档时自动取消固定
+    fn handle_archive_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            let was_pinned = task.pinned;
+            task.archive();
+            info!("🗄️ 任务 '{}' 已归档", task.name);
+            Ok(was_pinned)
+        });
+        match result {
+            Ok(true) => self.remove_pinned_tray_icon(id),
+            Ok(false) => {}
+            Err(e) => self.report_error("Failed to archive task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after archive", &e);
+        }
+    }
 
-        let mut current_x = x;
-        for ch in text.chars() {
-            match ch {
-                '0' => self.draw_digit_0(img, current_x, y, white),
-                '1' => self.draw_digit_1(img, current_x, y, white),
-                '2' => self.draw_digit_2(img, current_x, y, white),
-                '3' => self.draw_digit_3(img, current_x, y, white),
-                '4' => self.draw_digit_4(img, current_x, y, white),
-                '5' => self.draw_digit_5(img, current_x, y, white),
-                '6' => self.draw_digit_6(img, current_x, y, white),
-                '7' => self.draw_digit_7(img, current_x, y, white),
-                '8' => self.draw_digit_8(img, current_x, y, white),
-                '9' => self.draw_digit_9(img, current_x, y, white),
-                ':' => self.draw_colon(img, current_x, y, white),
-                _ => {}
+    fn handle_restore_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            task.restore();
+            info!("📤 任务 '{}' 已从归档中恢复", task.name);
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("Failed to restore task", &e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after restore", &e);
+        }
+    }
+
+    fn handle_mute_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            task.muted = !task.muted;
+            if task.muted {
+                info!("🔕 任务 '{}' 已静音", task.name);
+            } else {
+                info!("🔔 任务 '{}' 已取消静音", task.name);
             }
-            current_x += if ch == ':' { 2 } else { 4 };
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("Failed to mute task", &e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after mute", &e);
         }
     }
 
-    fn draw_clock_icon(&self, img: &mut RgbaImage) {
-        let white = Rgba([255, 255, 255, 255]);
+    fn handle_nag_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            task.nag = !task.nag;
+            if task.nag {
+                info!("🔔 任务 '{}' 已开启持续提醒", task.name);
+            } else {
+                info!("🔕 任务 '{}' 已关闭持续提醒", task.name);
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("Failed to toggle nag", &e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after nag", &e);
+        }
+    }
 
-        // 绘制圆形边框
-        for y in 8..24 {
-            for x in 8..24 {
-                let dx = (x as i32 - 16).abs();
-                let dy = (y as i32 - 16).abs();
-                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+    /// 确认持续提醒，不再重复提醒
+    fn handle_ack_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            task.acknowledged = true;
+            info!("✅ 任务 '{}' 的提醒已确认", task.name);
+            Ok(())
+        });
+        match result {
+            Ok(()) => self.nag.clear(id),
+            Err(e) => self.report_error("Failed to acknowledge task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after ack", &e);
+        }
+    }
+
+    /// 复制任务，副本插入到原任务之后
+    fn handle_duplicate_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let mut copy = tasks.get(id).context(TaskIdNotFoundSnafu { id })?.duplicate();
+            copy.name = tasks.unique_copy_name(&copy.name);
+            info!("📄 已复制任务 '{}'", copy.name);
+            tasks.insert_after(id, copy);
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("Failed to duplicate task", &e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after duplicate", &e);
+        }
+    }
 
-                if (6.0..=8.0).contains(&distance) {
-                    img.put_pixel(x, y, white);
+    /// 删除任务（可撤销），已固定的任务同时移除固定图标
+    fn handle_delete_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let removed = tasks.delete(id).context(TaskIdNotFoundSnafu { id })?;
+            warn!("🗑️ 任务 '{}' 已删除（可撤销）", removed.name);
+            Ok(removed.pinned)
+        });
+        match result {
+            Ok(was_pinned) => {
+                self.emit_task_event(TaskEvent::Deleted(id));
+                if was_pinned {
+                    self.remove_pinned_tray_icon(id);
                 }
             }
+            Err(e) => self.report_error("Failed to delete task", &e),
         }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after delete", &e);
+        }
+    }
 
-        // 绘制时钟指针
-        // 短针（小时）
-        for i in 0..4 {
-            img.put_pixel(16, 16 - i, white);
+    /// 从回收站恢复任务，恢复的任务使用新的 id 并添加到列表末尾
+    fn handle_restore_from_trash(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let new_id = tasks.restore_from_trash(id).context(TaskIdNotFoundSnafu { id })?;
+            if let Some(task) = tasks.get(new_id) {
+                info!("♻️ 任务 '{}' 已从回收站恢复", task.name);
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("Failed to restore task from trash", &e);
         }
-        // 长针（分钟）
-        for i in 0..6 {
-            img.put_pixel(16 + i, 16, white);
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after trash restore", &e);
         }
     }
 
-    // 简单的3x5像素字体
-    fn draw_digit_0(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [1, 0, 1], [1, 0, 1], [1, 0, 1], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    fn handle_delete_from_trash(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.delete_from_trash(id).context(TaskIdNotFoundSnafu { id })?;
+            warn!("🗑️ 任务 '{}' 已从回收站彻底删除", task.name);
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.report_error("Failed to delete task from trash", &e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after trash delete", &e);
+        }
     }
 
-    fn draw_digit_1(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[0, 1, 0], [1, 1, 0], [0, 1, 0], [0, 1, 0], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    fn handle_empty_trash(&mut self) {
+        match self.tasks.write().map(|mut tasks| tasks.empty_trash()) {
+            Ok(count) => warn!("🗑️ 已清空回收站，彻底删除 {} 个任务", count),
+            Err(e) => self.report_error("Failed to empty trash", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after empty trash", &e);
+        }
     }
 
-    fn draw_digit_2(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [0, 0, 1], [1, 1, 1], [1, 0, 0], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    /// 切换固定状态，达到固定上限时提示而不固定
+    fn handle_pin_task(&mut self, id: TaskId) {
+        let max_pinned = self.config.max_pinned;
+        // 成功时返回任务名称和新的固定状态，达到上限时返回提示信息
+        let result = self.tasks.write().and_then(|mut tasks| {
+            // 固定前检查上限，达到上限时不修改任务
+            let task = tasks.get(id).context(TaskIdNotFoundSnafu { id })?;
+            if !task.pinned && tasks.pin_limit_reached(max_pinned) {
+                return Ok(Err(tasks.pin_limit_message(max_pinned)));
+            }
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            task.pinned = !task.pinned;
+            Ok(Ok((task.name.clone(), task.pinned)))
+        });
+        match result {
+            Ok(Ok((task_name, true))) => {
+                self.create_pinned_tray_icon_with_retry(id);
+                info!("📌 任务 '{}' 已固定", task_name);
+            }
+            Ok(Ok((task_name, false))) => {
+                self.remove_pinned_tray_icon(id);
+                info!("📌 任务 '{}' 已取消固定", task_name);
+            }
+            Ok(Err(message)) => {
+                warn!("Refusing to pin task {}: pin limit {} reached", id, max_pinned);
+                self.dialog.error(tr("error.pin_limit"), &message);
+            }
+            Err(e) => self.report_error("Failed to pin task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after pin/unpin", &e);
+        }
     }
 
-    fn draw_digit_3(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [0, 0, 1], [1, 1, 1], [0, 0, 1], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    /// 固定菜单中的取消固定，总是取消
+    fn handle_unpin_task(&mut self, id: TaskId) {
+        let result = self.tasks.write().and_then(|mut tasks| {
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            task.pinned = false;
+            Ok(task.name.clone())
+        });
+        match result {
+            Ok(task_name) => {
+                self.remove_pinned_tray_icon(id);
+                info!("📌 任务 '{}' 已取消固定", task_name);
+            }
+            Err(e) => self.report_error("Failed to unpin task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after unpin", &e);
+        }
     }
 
-    fn draw_digit_4(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 0, 1], [1, 0, 1], [1, 1, 1], [0, 0, 1], [0, 0, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
-    }
+    /// 注册配置中的全局快捷键，按下时把对应事件转发到事件循环；
+    /// 注册失败（格式错误或与其他程序冲突）时发送通知
+    fn register_hotkeys(&mut self, proxy: winit::event_loop::EventLoopProxy<UserEvent>) {
+        let mut hotkeys = match Hotkeys::new() {
+            Ok(hotkeys) => hotkeys,
+            Err(e) => {
+                error!("Failed to create hotkey manager: {}", e);
+                return;
+            }
+        };
+        let configured = [
+            (
+                &self.config.new_task_hotkey,
+                HotkeyAction::NewTask,
+                tr("hotkey.new_task"),
+            ),
+            (
+                &self.config.toggle_hotkey,
+                HotkeyAction::ToggleActive,
+                tr("hotkey.toggle"),
+            ),
+            (
+                &self.config.quick_add_hotkey,
+                HotkeyAction::QuickAdd,
+                tr("hotkey.quick_add"),
+            ),
+        ];
+        for (spec, action, label) in configured {
+            let Some(spec) = spec else {
+                continue;
+            };
+            if let Err(e) = hotkeys.register(spec, action) {
+                error!("Failed to register {:?} hotkey: {}", action, e);
+                if let Err(e) = self.notifier.notify(
+                    tr("hotkey.failed_title"),
+                    &tr_args("hotkey.failed", &[
+                        ("label", label),
+                        ("spec", spec),
+                        ("error", &e.to_string()),
+                    ]),
+                ) {
+                    error!("Failed to show hotkey notification: {}", e);
+                }
+            }
+        }
 
-    fn draw_digit_5(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [1, 0, 0], [1, 1, 1], [0, 0, 1], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+        let actions = hotkeys.actions();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.state != HotKeyState::Pressed {
+                return;
+            }
+            let Some(action) = actions.get(&event.id) else {
+                return;
+            };
+            let user_event = match action {
+                HotkeyAction::NewTask => UserEvent::NewTaskHotkey,
+                HotkeyAction::ToggleActive => UserEvent::ToggleActiveHotkey,
+                HotkeyAction::QuickAdd => UserEvent::QuickAddHotkey,
+            };
+            if let Err(e) = proxy.send_event(user_event).context(EventLoopSendSnafu) {
+                error!("Failed to send hotkey event to event loop: {}", e);
+            }
+        }));
+        self.hotkeys = Some(hotkeys);
     }
 
-    fn draw_digit_6(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [1, 0, 0], [1, 1, 1], [1, 0, 1], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    /// 左键单击主图标切换当前任务，双击主图标新建任务，
+    /// 单击固定图标切换对应的任务；
+    /// 不单独发送单击事件的平台（如 Linux）不会进入这里
+    fn handle_tray_event(&mut self, event: TrayIconEvent) -> Result<()> {
+        // 打开主菜单时停止闪烁：左键切换计时时由右键打开菜单
+        if let TrayIconEvent::Click {
+            id,
+            button,
+            button_state: MouseButtonState::Up,
+            ..
+        } = &event
+            && pinned_task_id(id).is_none()
+            && (matches!(button, MouseButton::Right) || !self.config.left_click_toggle)
+        {
+            self.stop_blinking(None);
+        }
+        if !self.config.left_click_toggle {
+            return Ok(());
+        }
+        let (id, position) = match event {
+            TrayIconEvent::Click {
+                id,
+                position,
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } => (id, position),
+            // 系统直接发出的双击事件（Windows）
+            TrayIconEvent::DoubleClick {
+                id,
+                button: MouseButton::Left,
+                ..
+            } if pinned_task_id(&id).is_none() => {
+                self.click_tracker.reset();
+                self.handle_new_task();
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        match pinned_task_id(&id) {
+            Some(task_id) => {
+                let (name, running) = self.toggle_task(task_id)?;
+                info!(
+                    "🖱️ 单击固定图标，任务 '{}' {}",
+                    name,
+                    if running { "已开始" } else { "已暂停" }
+                );
+                self.refresh_menu()?;
+                self.save_tasks();
+                Ok(())
+            }
+            None => {
+                // 超时通知晚到时先执行上一次挂起的单击
+                let now = Instant::now();
+                if self.click_tracker.expire(now) {
+                    self.toggle_active_task()?;
+                }
+                match self.click_tracker.click((position.x, position.y), now) {
+                    Click::Double => {
+                        info!("🖱️ 双击主图标，新建任务");
+                        self.handle_new_task();
+                    }
+                    Click::Pending => self.schedule_click_timeout(),
+                }
+                Ok(())
+            }
+        }
     }
 
-    fn draw_digit_7(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [0, 0, 1], [0, 0, 1], [0, 0, 1], [0, 0, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    /// 双击阈值过后通知事件循环处理挂起的单击
+    fn schedule_click_timeout(&self) {
+        self.send_event_after(click::DOUBLE_CLICK_THRESHOLD, UserEvent::ClickTimeout);
     }
 
-    fn draw_digit_8(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [1, 0, 1], [1, 1, 1], [1, 0, 1], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    /// 等待一段时间后向事件循环发送事件
+    fn send_event_after(&self, delay: Duration, event: UserEvent) {
+        let Some(proxy) = self.event_proxy.clone() else {
+            return;
+        };
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            if let Err(e) = proxy.send_event(event).context(EventLoopSendSnafu) {
+                error!("Failed to send delayed event to event loop: {}", e);
+            }
+        });
     }
 
-    fn draw_digit_9(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [[1, 1, 1], [1, 0, 1], [1, 1, 1], [0, 0, 1], [1, 1, 1]];
-        self.draw_pattern(img, x, y, &pattern, color);
+    /// 退出程序；process::exit
+    /// 不会执行析构，先注销快捷键、写完历史记录并释放单实例锁
+    fn quit(&mut self, code: i32) -> ! {
+        self.hotkeys.take();
+        if let Some(history) = self.history.take() {
+            history.close();
+        }
+        self.instance_lock.take();
+        std::process::exit(code);
     }
 
-    fn draw_colon(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        if x + 1 < img.width() && y + 4 < img.height() {
-            img.put_pixel(x, y + 1, color);
-            img.put_pixel(x, y + 3, color);
+    /// 按错误类型处理：输入有误时弹窗提示，内部错误只记录日志，
+    /// 致命错误保存任务后退出
+    fn report_error(&mut self, context: &str, e: &Error) {
+        match e.kind() {
+            ErrorKind::UserInput => {
+                warn!("{}: {}", context, e);
+                show_input_error_dialog(self.dialog.as_ref(), e);
+            }
+            ErrorKind::Recoverable => error!("{}: {}", context, e),
+            ErrorKind::Fatal => {
+                error!("{}: {}. Saving tasks and exiting.", context, e);
+                self.save_tasks();
+                self.dialog.error(tr("error.fatal"), &e.to_string());
+                self.quit(1);
+            }
         }
     }
 
-    fn draw_pattern(&self, img: &mut RgbaImage, x: u32, y: u32, pattern: &[[u8; 3]; 5], color: Rgba<u8>) {
-        for (row, line) in pattern.iter().enumerate() {
-            for (col, &pixel) in line.iter().enumerate() {
-                if pixel == 1 {
-                    let px = x + col as u32;
-                    let py = y + row as u32;
-                    if px < img.width() && py < img.height() {
-                        img.put_pixel(px, py, color);
-                    }
+    /// 配置文件被修改：重新加载并立即应用可以热更新的设置；
+    /// 文件有误时保留当前设置，每次修改只提示一次
+    fn handle_config_reloaded(&mut self) {
+        let Some(path) = self.config_path.clone() else {
+            return;
+        };
+        let config = match Config::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to reload config, keeping previous settings: {}", e);
+                if let Err(e) = self.notifier.notify(tr("error.config_reload"), &e.to_string()) {
+                    error!("Failed to send config reload notification: {}", e);
                 }
+                return;
             }
+        };
+        // 程序自己保存设置时也会改变修改时间，此时内容与当前设置相同
+        let changed = self.config.changed_keys(&config);
+        if changed.is_empty() {
+            debug!("配置文件已修改，设置没有变化");
+            return;
         }
+        info!("⚙️ 配置文件已修改，重新加载: {}", changed.join(", "));
+        let restart: Vec<&str> = changed
+            .iter()
+            .map(String::as_str)
+            .filter(|key| config::RESTART_REQUIRED.contains(key))
+            .collect();
+        if !restart.is_empty() {
+            warn!("Config changes take effect after restart: {}", restart.join(", "));
+        }
+        self.apply_config(config);
     }
 
-    // 大字体绘制方法 (5x7 像素)
-    fn draw_large_pattern(&self, img: &mut RgbaImage, x: u32, y: u32, pattern: &[[u8; 5]; 7], color: Rgba<u8>) {
-        for (row, line) in pattern.iter().enumerate() {
-            for (col, &pixel) in line.iter().enumerate() {
-                if pixel == 1 {
-                    let px = x + col as u32;
-                    let py = y + row as u32;
-                    if px < img.width() && py < img.height() {
-                        img.put_pixel(px, py, color);
-                    }
+    /// 应用重新加载的设置：时间格式、语言、菜单快捷键、预设、托盘图标和菜单按新设置刷新
+    fn apply_config(&mut self, config: Config) {
+        task::set_time_formats(config.time_format, config.title_time_format);
+        task::set_max_duration(config.max_duration());
+        if config.locale != self.config.locale {
+            i18n::set_locale(config.locale.unwrap_or_else(Locale::system));
+        }
+        let (accelerators, errors) = Accelerators::from_config(&config.menu_accelerators);
+        for e in errors {
+            error!("Ignoring menu accelerator: {}", e);
+        }
+        let (presets, errors) = config::valid_presets(&config.presets);
+        for e in errors {
+            warn!("Ignoring preset: {}", e);
+        }
+        self.accelerators = accelerators;
+        self.presets = presets;
+        let tray_icon_changed = config.tray_icon_path != self.config.tray_icon_path;
+        self.config = config;
+
+        let show_menu_on_left_click = !self.config.left_click_toggle;
+        for tray_icon in self.tray_icon.iter().chain(self.pinned_tray_icons.values()) {
+            tray_icon.set_show_menu_on_left_click(show_menu_on_left_click);
+        }
+        if tray_icon_changed
+            && !self.blinker.is_active()
+            && let Some(tray_icon) = &self.tray_icon
+        {
+            let result = self.logo_icon().and_then(|icon| {
+                tray_icon.set_icon(Some(icon)).context(TrayIconUpdateSnafu {
+                    operation: "set_icon_reload".to_string(),
+                })
+            });
+            if let Err(e) = result {
+                self.report_error("Failed to apply reloaded tray icon", &e);
+            }
+        }
+        if let Err(e) = self.refresh_menu() {
+            self.report_error("Failed to refresh menu after config reload", &e);
+        }
+        if let Err(e) = self.update_tray_icon(SystemTime::now()) {
+            self.report_error("Failed to update tray icon after config reload", &e);
+        }
+    }
+
+    /// 开始或暂停任务，返回任务名称和切换后是否在计时
+    fn toggle_task(&mut self, id: TaskId) -> Result<(String, bool)> {
+        let mut tasks = self.tasks.write()?;
+        let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+        if task.is_running {
+            task.pause()?;
+            self.emit_task_event(TaskEvent::Paused(id));
+        } else {
+            self.start_task(&mut tasks, id)?;
+        }
+        let task = tasks.get(id).context(TaskIdNotFoundSnafu { id })?;
+        Ok((task.name.clone(), task.is_running))
+    }
+
+    /// 开始任务；开启单任务计时时一并暂停其他计时中的时间段任务，并说明暂停了哪些任务
+    fn start_task(&self, tasks: &mut TaskList, id: TaskId) -> Result<()> {
+        if !self.config.single_running_task {
+            tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?.start();
+            self.emit_task_event(TaskEvent::Started(id));
+            return Ok(());
+        }
+        let paused = tasks.start_exclusive(id, SystemTime::now())?;
+        self.emit_task_event(TaskEvent::Started(id));
+        if paused.is_empty() {
+            return Ok(());
+        }
+        for &paused_id in &paused {
+            self.emit_task_event(TaskEvent::Paused(paused_id));
+        }
+        let names = paused
+            .iter()
+            .filter_map(|&paused_id| tasks.get(paused_id))
+            .map(|task| format!("'{}'", task.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let name = tasks.get(id).map(|task| task.name.as_str()).unwrap_or_default();
+        info!("⏸️ 开始 '{}'，单任务计时已暂停 {}", name, names);
+        if !self.config.do_not_disturb {
+            let message = tr_args("notify.exclusive_paused", &[("name", name), ("paused", &names)]);
+            if let Err(e) = self.notifier.notify("Time Ticker", &message) {
+                error!("Failed to send exclusive start notification for '{}': {}", name, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 切换当前任务（最近开始的任务）的开始/暂停，并用通知说明执行了什么
+    fn toggle_active_task(&mut self) -> Result<()> {
+        let active = self.tasks.read()?.active_task();
+        let message = match active {
+            Some(id) => {
+                let (name, running) = self.toggle_task(id)?;
+                self.refresh_menu()?;
+                self.save_tasks();
+                let key = if running { "notify.started" } else { "notify.paused" };
+                tr_args(key, &[("name", &name)])
+            }
+            None => tr("notify.nothing_to_toggle").to_string(),
+        };
+        info!("⌨️ {}", message);
+        if self.config.do_not_disturb {
+            return Ok(());
+        }
+        self.notifier.notify("Time Ticker", &message)
+    }
+
+    /// 按解析后的输入创建任务并添加到列表
+    fn add_task(&mut self, task_input: TaskInput) -> Result<TaskId> {
+        let task_name = task_input.name.clone();
+        let scheduled = task_input.scheduled_start.is_some();
+        let task = task_input.into_task()?;
+        let id = self.tasks.write()?.push(task);
+        info!("✅ 成功创建任务: {}", task_name);
+        if scheduled {
+            self.schedule_next_start();
+        }
+        Ok(id)
+    }
+
+    /// 处理来自控制套接字的命令，修改任务后刷新菜单并保存
+    fn handle_command(&mut self, command: ipc::Command) -> Result<ipc::Reply> {
+        debug!("收到控制命令: {:?}", command);
+        let mut reply = ipc::Reply::ok();
+        match command {
+            ipc::Command::Ping => {
+                info!("👋 收到再次启动的实例的通知");
+                if let Err(e) = self.notifier.notify("Time Ticker", tr("notify.already_running")) {
+                    error!("Failed to show already-running notification: {}", e);
                 }
+                return Ok(reply);
+            }
+            ipc::Command::List => {
+                let tasks = self.tasks.read()?;
+                let infos = tasks
+                    .iter_ordered(self.config.task_order)
+                    .into_iter()
+                    .map(ipc::TaskInfo::new)
+                    .collect::<Result<Vec<_>>>()?;
+                reply.tasks = Some(infos);
+                return Ok(reply);
+            }
+            ipc::Command::History { limit } => {
+                let history = self.history.as_ref().context(InvalidCliArgsSnafu {
+                    msg: "无法确定数据目录，没有历史记录",
+                })?;
+                let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+                reply.history = Some(history::read_recent(history.path(), limit)?);
+                return Ok(reply);
+            }
+            ipc::Command::Add { input } => {
+                let task_input = parse_time_input(&input)?;
+                task_input.refuse_command("the control socket")?;
+                reply.id = Some(self.add_task(task_input)?);
+            }
+            ipc::Command::Start { id } => {
+                let mut tasks = self.tasks.write()?;
+                self.start_task(&mut tasks, id)?;
+            }
+            ipc::Command::Pause { id } => {
+                let mut tasks = self.tasks.write()?;
+                tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?.pause()?;
+                self.emit_task_event(TaskEvent::Paused(id));
+            }
+            ipc::Command::Reset { id } => {
+                self.tasks.write()?.reset(id)?;
+                self.emit_task_event(TaskEvent::Reset(id));
+            }
+            ipc::Command::Delete { id } => {
+                self.tasks.write()?.delete(id).context(TaskIdNotFoundSnafu { id })?;
+                self.emit_task_event(TaskEvent::Deleted(id));
+            }
+            ipc::Command::Snooze { id } => {
+                self.snooze_tasks(id, Duration::from_secs(self.config.snooze_minutes * 60))?;
             }
         }
+        self.refresh_menu()?;
+        self.save_tasks();
+        Ok(reply)
     }
 
-    fn draw_large_digit_0(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_1(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [0, 0, 1, 0, 0],
-            [0, 1, 1, 0, 0],
-            [0, 0, 1, 0, 0],
-            [0, 0, 1, 0, 0],
-            [0, 0, 1, 0, 0],
-            [0, 0, 1, 0, 0],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_2(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0],
-            [1, 0, 0, 0, 0],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_3(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_4(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_5(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0],
-            [1, 0, 0, 0, 0],
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_6(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 0],
-            [1, 0, 0, 0, 0],
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_7(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_8(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
-    }
-
-    fn draw_large_digit_9(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        let pattern = [
-            [1, 1, 1, 1, 1],
-            [1, 0, 0, 0, 1],
-            [1, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-            [0, 0, 0, 0, 1],
-            [0, 0, 0, 0, 1],
-            [1, 1, 1, 1, 1],
-        ];
-        self.draw_large_pattern(img, x, y, &pattern, color);
+    /// 处理新建任务
+    fn handle_new_task(&mut self) {
+        info!("📝 开始新建任务");
+        self.request_input(InputRequest {
+            purpose: InputPurpose::NewTask,
+            title: tr("input.new_title"),
+            message: tr("input.new_message"),
+            default_text: tr("input.new_default").to_string(),
+        });
     }
 
-    fn draw_large_colon(&self, img: &mut RgbaImage, x: u32, y: u32, color: Rgba<u8>) {
-        if x + 1 < img.width() && y + 6 < img.height() {
-            img.put_pixel(x, y + 2, color);
-            img.put_pixel(x, y + 4, color);
+    /// 请求用户输入：内置窗口在本次事件处理结束后打开，系统对话框直接等待结果
+    fn request_input(&mut self, request: InputRequest) {
+        if self.config.native_input_dialog {
+            self.input_request = Some(request);
+        } else {
+            self.prompt_with_dialog(&request);
         }
     }
 
-    #[allow(clippy::cognitive_complexity)]
-    fn handle_menu_event(&mut self, event: TrayMenuEvent) {
-        let menu_id = event.id;
+    /// 通过系统对话框输入，等待期间事件循环被阻塞
+    fn prompt_with_dialog(&mut self, request: &InputRequest) {
+        match self.dialog.input(request.title, request.message, &request.default_text) {
+            Some(user_input) => self.submit_input(request.purpose, &user_input),
+            None => info!("用户取消了输入: {:?}", request.purpose),
+        }
+    }
 
-        debug!("菜单事件触发，ID: {:?}", menu_id);
+    /// 打开等待中的输入窗口，已有输入窗口时把它移到前台
+    fn open_requested_input(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        let Some(request) = self.input_request.take() else {
+            return;
+        };
+        if let Some(window) = &self.input_window {
+            info!("输入窗口已经打开");
+            window.focus();
+            return;
+        }
+        match InputWindow::open(event_loop, &request) {
+            Ok(window) => self.input_window = Some(window),
+            Err(e) => {
+                error!("Failed to open input window, falling back to dialog: {}", e);
+                self.prompt_with_dialog(&request);
+            }
+        }
+    }
 
-        if let Some(action) = self.menu_ids.get(&menu_id).cloned() {
-            debug!("找到对应动作: {}", action);
-            if action == "quit" {
-                std::process::exit(0);
-            } else if action == "dock_show" {
-                info!("🖥️ 显示 Dock 图标");
-                #[cfg(target_os = "macos")]
-                {
-                    if let Err(e) = set_dock_visibility(true) {
-                        error!("Failed to show dock: {}", e);
-                    }
+    /// 显示快速添加窗口，第一次使用时创建；创建失败时改用新建任务的输入方式
+    fn open_quick_add(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if !std::mem::take(&mut self.quick_add_requested) {
+            return;
+        }
+        if self.quick_add_window.is_none() {
+            match InputWindow::open_quick_add(event_loop) {
+                Ok(window) => self.quick_add_window = Some(window),
+                Err(e) => {
+                    error!("Failed to open quick add window, falling back to new task input: {}", e);
+                    self.handle_new_task();
+                    self.open_requested_input(event_loop);
+                    return;
                 }
-                #[cfg(not(target_os = "macos"))]
-                {
-                    // For non-macOS, set_dock_visibility itself will warn.
-                    // We can call it to maintain consistent behavior if it has non-macOS logic,
-                    // or just warn here if it's purely a no-op that returns Ok(()).
-                    if let Err(e) = set_dock_visibility(true) {
-                        // Assuming it might do something or log
-                        error!("set_dock_visibility(true) failed on non-macOS (unexpected): {}", e);
-                    }
-                    warn!("Dock visibility control is primarily a macOS feature.");
+            }
+        }
+        if let Some(window) = &mut self.quick_add_window {
+            window.show();
+        }
+    }
+
+    /// 处理输入窗口和快速添加窗口的事件，窗口关闭时执行输入结果并返回 true
+    fn handle_input_window_event(
+        &mut self,
+        window_id: winit::window::WindowId,
+        event: &winit::event::WindowEvent,
+    ) -> bool {
+        if let Some(window) = self.quick_add_window.as_mut().filter(|window| window.id() == window_id) {
+            let Some(outcome) = window.handle_event(event) else {
+                return false;
+            };
+            // 快速添加窗口只隐藏，下次按快捷键时复用
+            window.hide();
+            match outcome {
+                InputOutcome::Submit(purpose, user_input) => self.submit_input(purpose, &user_input),
+                InputOutcome::Cancel(_) => debug!("快速添加窗口已关闭"),
+            }
+            return true;
+        }
+        let Some(window) = self.input_window.as_mut().filter(|window| window.id() == window_id) else {
+            return false;
+        };
+        let Some(outcome) = window.handle_event(event) else {
+            return false;
+        };
+        self.input_window = None;
+        match outcome {
+            InputOutcome::Submit(purpose, user_input) => self.submit_input(purpose, &user_input),
+            InputOutcome::Cancel(purpose) => info!("用户取消了输入: {:?}", purpose),
+        }
+        true
+    }
+
+    /// 按输入的用途新建或修改任务
+    fn submit_input(&mut self, purpose: InputPurpose, user_input: &str) {
+        info!("用户输入: {}", user_input);
+        match purpose {
+            InputPurpose::NewTask | InputPurpose::QuickAdd => self.create_task_from_input(user_input),
+            InputPurpose::EditTask(id) => self.apply_task_edit(id, user_input),
+        }
+    }
+
+    /// 解析输入并创建新任务
+    fn create_task_from_input(&mut self, user_input: &str) {
+        match parse_time_input(user_input) {
+            Ok(task_input) => {
+                // 创建新任务，成功后记入最近使用
+                match self.add_task(task_input) {
+                    Ok(_) => self.remember_input(user_input),
+                    Err(e) => self.report_error("Failed to create task", &e),
                 }
-            } else if action == "dock_hide" {
-                info!("🖥️ 隐藏 Dock 图标");
-                #[cfg(target_os = "macos")]
-                {
-                    if let Err(e) = set_dock_visibility(false) {
-                        error!("Failed to hide dock: {}", e);
-                    }
+                // 刷新菜单
+                if let Err(e) = self.refresh_menu() {
+                    error!("Failed to refresh menu after new task attempt: {}", e);
+                } else {
+                    info!("🔄 菜单已刷新 (new task attempt)");
                 }
-                #[cfg(not(target_os = "macos"))]
-                {
-                    if let Err(e) = set_dock_visibility(false) {
-                        error!("set_dock_visibility(false) failed on non-macOS (unexpected): {}", e);
-                    }
-                    warn!("Dock visibility control is primarily a macOS feature.");
+            }
+            Err(e) => self.report_error("Failed to parse task input", &e),
+        }
+    }
+
+    /// 按 timeticker://add?input=... 链接中的输入创建任务，与输入框走同一条路径；
+    /// 带有完成时命令（&&）的链接直接拒绝
+    fn handle_open_url(&mut self, url: &str) {
+        info!("🔗 收到链接: {}", url);
+        let task_input = url_scheme::parse_add_url(url).and_then(|input| {
+            let task_input = parse_time_input(&input)?;
+            task_input.refuse_command("links")?;
+            Ok(task_input)
+        });
+        match task_input {
+            Ok(task_input) => {
+                if let Err(e) = self.add_task(task_input) {
+                    self.report_error("Failed to create task from URL", &e);
                 }
-            } else if action == "dock_test_icon" {
-                info!("🔄 手动重新设置 Dock 图标");
-                #[cfg(target_os = "macos")]
-                {
-                    if let Err(e) = set_dock_icon() {
-                        error!("Failed to set dock icon: {}", e);
-                    }
+                if let Err(e) = self.refresh_menu() {
+                    error!("Failed to refresh menu after opening URL: {}", e);
                 }
-                #[cfg(not(target_os = "macos"))]
+            }
+            Err(e) => self.report_error("Failed to open URL", &e),
+        }
+    }
+
+    /// 按预设直接创建任务，时间段任务立即开始
+    fn handle_preset(&mut self, index: usize) {
+        let Some(preset) = self.presets.get(index).cloned() else {
+            error!("Preset not found at index {}", index);
+            return;
+        };
+        self.create_task_instantly(&preset.input);
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after preset: {}", e);
+        }
+    }
+
+    /// 按最近使用的输入直接创建任务，并移到最近使用的最前面
+    fn handle_recent_input(&mut self, index: usize) {
+        let Some(input) = self.recent_inputs.get(index).map(str::to_string) else {
+            error!("Recent input not found at index {}", index);
+            return;
+        };
+        if self.create_task_instantly(&input) {
+            self.remember_input(&input);
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after recent input: {}", e);
+        }
+    }
+
+    /// 不弹出输入框直接创建任务，时间段任务立即开始（定时开始的任务除外）；返回是否创建成功
+    fn create_task_instantly(&mut self, input: &str) -> bool {
+        let result = parse_time_input(input).and_then(|task_input| self.add_task(task_input));
+        match result {
+            Ok(id) => {
+                if let Ok(mut tasks) = self.tasks.write()
+                    && let Some(task) = tasks.get(id)
+                    && let TaskType::Duration(_) = task.task_type
+                    && task.scheduled_start.is_none()
                 {
-                    warn!("Dock icon control is only available on macOS.");
-                }
-            } else if action == "new_task" {
-                // 实现新建任务功能
-                self.handle_new_task();
-            } else if action.starts_with("task_") {
-                // 处理任务点击
-                println!("点击了任务");
-            } else if action.starts_with("toggle_") {
-                match action
-                    .strip_prefix("toggle_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "toggle_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if let Some(task) = tasks.get_mut(index) {
-                                if task.is_running {
-                                    if let Err(e) = task.pause() {
-                                        error!("Failed to pause task {}: {}", task.name, e);
-                                    } else {
-                                        info!("⏸️ 任务 '{}' 已暂停", task.name);
-                                    }
-                                } else {
-                                    task.start();
-                                    info!("▶️ 任务 '{}' 已开始", task.name);
-                                }
-                            } else {
-                                error!("Task not found at index {} for toggle", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for toggle");
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after toggle: {}", e);
-                        }
+                    info!("▶️ 任务 '{}' 已开始", task.name);
+                    if let Err(e) = self.start_task(&mut tasks, id) {
+                        error!("Failed to start task {}: {}", id, e);
                     }
-                    Err(e) => error!("Failed to process toggle action '{}': {}", action, e),
                 }
-            } else if action.starts_with("reset_") {
-                match action
-                    .strip_prefix("reset_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "reset_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if let Some(task) = tasks.get_mut(index) {
-                                if let Err(e) = task.reset() {
-                                    error!("Failed to reset task {}: {}", task.name, e);
-                                } else {
-                                    info!("🔄 任务 '{}' 已重置", task.name);
-                                }
-                            } else {
-                                error!("Task not found at index {} for reset", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for reset");
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after reset: {}", e);
-                        }
-                    }
-                    Err(e) => error!("Failed to process reset action '{}': {}", action, e),
+                true
+            }
+            Err(e) => {
+                self.report_error("Failed to create task", &e);
+                false
+            }
+        }
+    }
+
+    /// 记录成功创建任务的输入，保存失败只记录日志
+    fn remember_input(&mut self, input: &str) {
+        self.recent_inputs.push(input);
+        if let Some(path) = &self.recent_path
+            && let Err(e) = self.recent_inputs.save(path)
+        {
+            error!("Failed to save recent inputs to {}: {}", path.display(), e);
+        }
+    }
+
+    /// 设置任务完成后自动开始的任务
+    fn handle_chain_task(&mut self, id: TaskId, target: Option<TaskId>) {
+        let result = self.tasks.write().and_then(|mut tasks| tasks.set_next(id, target));
+
+        match result {
+            Ok(()) => info!("⛓️ 任务 {} 的后续任务已更新", id),
+            Err(e) => self.report_error("Failed to set next task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after chain: {}", e);
+        }
+    }
+
+    /// 推迟已完成的任务，固定图标随开始事件原地更新，
+    /// 主菜单重建以移动到计时中分段
+    fn handle_snooze_task(&mut self, id: TaskId, extra: Duration) {
+        if let Err(e) = self.snooze_tasks(Some(id), extra) {
+            self.report_error("Failed to snooze task", &e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after snooze: {}", e);
+        }
+    }
+
+    /// 推迟任务并停止重复提醒，未指定任务时推迟所有未确认的任务
+    fn snooze_tasks(&mut self, id: Option<TaskId>, extra: Duration) -> Result<Vec<TaskId>> {
+        let snoozed = self.tasks.write()?.snooze(id, extra, SystemTime::now())?;
+        for &id in &snoozed {
+            info!("😴 任务 {} 已推迟 {} 分钟", id, extra.as_secs() / 60);
+            self.nag.clear(id);
+            self.emit_task_event(TaskEvent::Started(id));
+        }
+        Ok(snoozed)
+    }
+
+    /// 检查到时的任务，发出完成事件以及自动开始后续任务的事件
+    fn check_finished_tasks(&mut self) -> Result<()> {
+        let events = self.tasks.write()?.tick(SystemTime::now(), self.config.single_running_task)?;
+        for event in events {
+            self.emit_task_event(event);
+        }
+        Ok(())
+    }
+
+    /// 按设置归档过期超过保留时长的截止时间任务，固定的任务同时移除固定图标
+    fn archive_expired_deadlines(&mut self) -> Result<()> {
+        let retention = self.config.expired_deadline_retention;
+        let archived = self.tasks.write()?.archive_expired(retention, SystemTime::now());
+        if archived.is_empty() {
+            return Ok(());
+        }
+        for (id, was_pinned) in archived {
+            info!("🗄️ 过期的截止时间任务 {} 已自动归档", id);
+            if was_pinned {
+                self.remove_pinned_tray_icon(id);
+            }
+            self.emit_task_event(TaskEvent::Deleted(id));
+        }
+        self.refresh_menu()
+    }
+
+    /// 在下一个过期任务到达保留时长时再检查一次，计时暂停时也能按时归档
+    fn schedule_expired_archive(&self) {
+        let retention = self.config.expired_deadline_retention;
+        let delay = match self.tasks.read() {
+            Ok(tasks) => tasks.next_expired_removal(retention, SystemTime::now()),
+            Err(e) => {
+                error!("Failed to lock tasks for expired archive: {}", e);
+                return;
+            }
+        };
+        if let Some(delay) = delay {
+            debug!("{:?} 后检查过期的截止时间任务", delay);
+            self.send_event_after(delay, UserEvent::ArchiveExpired);
+        }
+    }
+
+    /// 开始到达定时时刻的任务并发送通知；程序关闭或系统休眠期间错过的时间
+    /// 按休眠设置处理：休眠不计时时从现在开始计时，否则从定时时刻起计时
+    fn start_scheduled_tasks(&mut self) -> Result<()> {
+        let now = SystemTime::now();
+        let started: Vec<(TaskId, String)> = {
+            let mut tasks = self.tasks.write()?;
+            let ids = tasks.start_scheduled(now, !self.config.pause_during_sleep);
+            ids.into_iter()
+                .filter_map(|id| tasks.get(id).map(|task| (id, task.name.clone())))
+                .collect()
+        };
+        if started.is_empty() {
+            return Ok(());
+        }
+        for (id, name) in started {
+            info!("⏰ 任务 '{}' 已按计划开始", name);
+            self.emit_task_event(TaskEvent::Started(id));
+            if self.config.do_not_disturb {
+                continue;
+            }
+            let message = tr_args("notify.scheduled_start", &[("name", &name)]);
+            if let Err(e) = self.notifier.notify("Time Ticker", &message) {
+                error!("Failed to send scheduled start notification for '{}': {}", name, e);
+            }
+        }
+        self.save_tasks();
+        self.refresh_menu()
+    }
+
+    /// 在下一个定时开始的时刻再检查一次，计时暂停时也能按时开始
+    fn schedule_next_start(&self) {
+        let delay = match self.tasks.read() {
+            Ok(tasks) => tasks.next_scheduled_start(SystemTime::now()),
+            Err(e) => {
+                error!("Failed to lock tasks for scheduled start: {}", e);
+                return;
+            }
+        };
+        if let Some(delay) = delay {
+            debug!("{:?} 后检查定时开始的任务", delay);
+            self.send_event_after(delay, UserEvent::ScheduledStart);
+        }
+    }
+
+    /// 对开启持续提醒且尚未确认的已完成任务，按设置的间隔重复提醒
+    fn repeat_alerts(&mut self) -> Result<()> {
+        let now = Instant::now();
+        let interval = Duration::from_secs(self.config.nag_interval_secs);
+        let tasks = self.tasks.read()?;
+        for task in tasks.iter().filter(|t| t.needs_alert()) {
+            if !self.nag.due(task.id, now, interval) {
+                continue;
+            }
+            info!("🔔 再次提醒任务 '{}'", task.name);
+            if let Err(e) = notify::notify_task_finished(self.notifier.as_ref(), task, &self.config) {
+                error!("Failed to repeat alert for task {}: {}", task.name, e);
+            }
+            self.sound.play_task_finished(task, &self.config);
+        }
+        Ok(())
+    }
+
+    /// 有计时中的任务进入最后几秒时切换到快速刷新，之后恢复每秒刷新
+    fn update_tick_rate(&self) -> Result<()> {
+        let fast = {
+            let tasks = self.tasks.read()?;
+            let mut fast = false;
+            for task in tasks.running() {
+                if task.get_remaining_time_ms()? < FAST_TICK_THRESHOLD.as_millis() as u64 {
+                    fast = true;
+                    break;
                 }
-            } else if action.starts_with("edit_") {
-                warn!("✏️ 编辑功能待实现");
-            } else if action.starts_with("delete_") {
-                match action
-                    .strip_prefix("delete_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "delete_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if index < tasks.len() {
-                                let task_name = tasks.remove(index).name;
-                                warn!("🗑️ 任务 '{}' 已删除", task_name);
-                            } else {
-                                error!("Task index {} out of bounds for delete", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for delete");
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after delete: {}", e);
-                        }
-                    }
-                    Err(e) => error!("Failed to process delete action '{}': {}", action, e),
+            }
+            fast
+        };
+        if self.fast_tick.swap(fast, Ordering::Relaxed) != fast {
+            debug!(
+                "计时刷新间隔切换为 {:?}",
+                if fast { FAST_TICK_INTERVAL } else { TICK_INTERVAL }
+            );
+        }
+        Ok(())
+    }
+
+    /// 两次刷新间隔过长说明系统刚从休眠中唤醒：按设置把休眠时长
+    /// 补还给计时中的任务，休眠期间到时的任务由随后的刷新补发一次完成事件
+    fn handle_suspended_gap(&mut self, now: SystemTime) -> Result<()> {
+        let Some(gap) = suspended_gap(self.last_tick.replace(now), now) else {
+            return Ok(());
+        };
+        info!("💤 检测到系统休眠约 {}", format_remaining_time(gap, TimeFormat::Full));
+        if self.config.pause_during_sleep {
+            let credited = self.tasks.write()?.credit_suspended(gap);
+            if credited > 0 {
+                info!("⏸️ 休眠期间不计时，已为 {} 个任务补还时间", credited);
+                self.save_tasks();
+            }
+        }
+        Ok(())
+    }
+
+    /// 一次完整的计时刷新：检查休眠、到时和重复提醒，并用同一时刻更新所有显示
+    fn tick(&mut self) {
+        if let Err(e) = self.handle_suspended_gap(SystemTime::now()) {
+            self.report_error("Failed to handle suspended gap", &e);
+        }
+        // 只有绘制图标时才需要跟随系统外观
+        let follow_appearance = self.pinned_display_mode() == DisplayMode::Drawn && !self.pinned_tray_icons.is_empty();
+        let appearance_changed = follow_appearance && self.refresh_appearance();
+        if let Err(e) = self.start_scheduled_tasks() {
+            self.report_error("Failed to start scheduled tasks from timer", &e);
+        }
+        if let Err(e) = self.check_finished_tasks() {
+            self.report_error("Failed to check finished tasks from timer", &e);
+        }
+        match self.check_warnings(SystemTime::now()) {
+            // 已提醒的记录需要保存，重启后不再重复提醒；同一次计时只保存一次
+            Ok(true) => self.save_tasks(),
+            Ok(false) => {}
+            Err(e) => self.report_error("Failed to check warnings from timer", &e),
+        }
+        if let Err(e) = self.check_budgets(SystemTime::now()) {
+            self.report_error("Failed to check tag budgets from timer", &e);
+        }
+        if let Err(e) = self.archive_expired_deadlines() {
+            error!("Failed to archive expired deadline tasks from timer: {}", e);
+        }
+        if let Err(e) = self.repeat_alerts() {
+            self.report_error("Failed to repeat alerts from timer", &e);
+        }
+        self.update_blink();
+        // 本次刷新的所有标题和图标使用同一时刻
+        let now = SystemTime::now();
+        // 没有计时中的任务时显示内容不随时间变化，完整更新一次后就不必每秒重复
+        let running = self.tasks.read().map_or(true, |tasks| tasks.running().next().is_some());
+        if running || self.display_stale || appearance_changed {
+            if let Err(e) = self.update_tray_icon(now) {
+                self.report_error("Failed to update tray icon from timer", &e);
+            }
+        } else {
+            trace!("没有计时中的任务，跳过本次显示刷新");
+        }
+        self.display_stale = running; // 计时中的任务停下后再完整更新一次
+        if let Err(e) = self.update_dock_badge(now) {
+            self.report_error("Failed to update dock badge from timer", &e);
+        }
+        if let Err(e) = self.update_dock_countdown(now) {
+            self.report_error("Failed to update dock icon from timer", &e);
+        }
+        if let Err(e) = self.update_tick_rate() {
+            self.report_error("Failed to update tick rate from timer", &e);
+        }
+    }
+
+    /// 没有计时中的任务、待确认的提醒和固定图标时暂停计时线程，否则恢复
+    fn update_tick_gate(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) -> Result<()> {
+        let idle = !self.tasks.read()?.needs_tick() && self.pinned_tray_icons.is_empty() && !self.blinker.is_active();
+        if idle {
+            // 暂停期间没有刷新，恢复后的第一次刷新不应被当作休眠
+            self.last_tick = None;
+        }
+        if self.tick_gate.set_idle(idle) {
+            debug!("计时刷新{}", if idle { "已暂停" } else { "已恢复" });
+            if !idle {
+                // 暂停期间没有刷新，从恢复时开始重新计算
+                self.tick_watchdog.record(Instant::now());
+            }
+        }
+        if idle {
+            event_loop.set_control_flow(ControlFlow::Wait);
+        }
+        Ok(())
+    }
+
+    /// 启动新一代计时线程，之前的计时线程在下一次刷新前退出
+    fn spawn_timer_thread(&self) {
+        let Some(proxy) = self.event_proxy.clone() else {
+            return;
+        };
+        let fast_tick = self.fast_tick.clone();
+        let tick_gate = self.tick_gate.clone();
+        let tick_watchdog = self.tick_watchdog.clone();
+        let generation = tick_watchdog.next_generation();
+        tick_watchdog.record(Instant::now());
+        std::thread::spawn(move || {
+            let wait = || {
+                tick_gate.wait_while_idle();
+                let interval = if fast_tick.load(Ordering::Relaxed) {
+                    FAST_TICK_INTERVAL
+                } else {
+                    until_next_second(SystemTime::now())
+                };
+                std::thread::sleep(interval);
+            };
+            // 发送只会在事件循环关闭后失败，此时程序正在退出
+            let send = || proxy.send_event(UserEvent::UpdateTimer).is_ok();
+            watchdog::run_timer(&tick_watchdog, generation, wait, send);
+        });
+    }
+
+    /// 需要刷新却长时间没有收到计时刷新时重新启动计时线程
+    fn restart_stalled_timer(&self) {
+        // 事件在队列中等待期间可能已经恢复了刷新
+        if self.tick_gate.is_idle() || !self.tick_watchdog.is_stalled(Instant::now()) {
+            return;
+        }
+        warn!(
+            "No timer tick for over {:?} while tasks need updating, restarting timer thread",
+            watchdog::STALL_THRESHOLD
+        );
+        self.spawn_timer_thread();
+    }
+
+    fn emit_task_event(&self, event: TaskEvent) {
+        if let Err(e) = self.task_event_sender.send(event) {
+            error!("Failed to send task event {:?}: {}", event, e);
+        }
+    }
+
+    /// 将积压的任务事件分发给各个子系统
+    fn dispatch_task_events(&mut self) {
+        let mut dispatched = false;
+        let mut section_changed = false; // 计时中完成的任务需要重建菜单，移动到“已完成”分段
+        while let Ok(event) = self.task_event_receiver.try_recv() {
+            dispatched = true;
+            section_changed |= matches!(event, TaskEvent::Finished(_));
+            debug!("任务事件: {:?}", event);
+            // 重新开始、重置或删除的任务不再需要提醒
+            if let TaskEvent::Started(id) | TaskEvent::Reset(id) | TaskEvent::Deleted(id) = event {
+                self.stop_blinking(Some(id));
+            }
+            let result = match event {
+                TaskEvent::Finished(id) => self.on_task_finished(id),
+                TaskEvent::Deleted(id) => self.on_task_deleted(id),
+                TaskEvent::Started(id) | TaskEvent::Paused(id) | TaskEvent::Reset(id) | TaskEvent::Edited(id) => {
+                    self.on_task_changed(id)
                 }
-            } else if action.starts_with("pin_") {
-                match action
-                    .strip_prefix("pin_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "pin_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        let mut task_name_opt = None;
-                        let mut is_pinned_opt = None;
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if let Some(task) = tasks.get_mut(index) {
-                                task.pinned = !task.pinned;
-                                task_name_opt = Some(task.name.clone());
-                                is_pinned_opt = Some(task.pinned);
-                            } else {
-                                error!("Task not found at index {} for pin/unpin", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for pin/unpin");
-                        }
+            };
+            if let Err(e) = result {
+                error!("Failed to dispatch task event {:?}: {}", event, e);
+            }
+        }
+        if dispatched {
+            self.save_tasks();
+        }
+        if section_changed && let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after task finished: {}", e);
+        }
+        // 刚过期的截止时间任务在保留时长后归档
+        if section_changed {
+            self.schedule_expired_archive();
+        }
+    }
 
-                        if let (Some(task_name), Some(is_pinned)) = (task_name_opt, is_pinned_opt) {
-                            if is_pinned {
-                                if let Err(e) = self.create_pinned_tray_icon(index) {
-                                    error!("Failed to create pinned tray icon for task '{}': {}", task_name, e);
-                                } else {
-                                    info!("📌 任务 '{}' 已固定", task_name);
-                                }
-                            } else {
-                                self.remove_pinned_tray_icon(index);
-                                info!("📌 任务 '{}' 已取消固定", task_name);
-                            }
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after pin/unpin: {}", e);
-                        }
-                    }
-                    Err(e) => error!("Failed to process pin action '{}': {}", action, e),
+    /// 把任务列表保存到磁盘，失败只记录日志
+    fn save_tasks(&self) {
+        let Some(path) = &self.save_path else {
+            return;
+        };
+        let result = self.tasks.read().and_then(|tasks| persistence::save_file(path, &tasks));
+        if let Err(e) = result {
+            error!("Failed to save tasks to {}: {}", path.display(), e);
+        }
+    }
+
+    /// 任务完成：记录统计，执行完成命令（后续任务已在 tick 中自动开始）
+    fn on_task_finished(&mut self, id: TaskId) -> Result<()> {
+        let tasks = self.tasks.read()?;
+        let Some(task) = tasks.get(id) else {
+            return Ok(());
+        };
+
+        let stats = task.stats()?;
+        info!(
+            "⏰ 任务 '{}' 已完成（计划 {}，实际 {}，暂停 {}）",
+            task.name,
+            format_remaining_time(stats.planned, TimeFormat::Full),
+            format_remaining_time(stats.actual, TimeFormat::Full),
+            format_remaining_time(stats.paused, TimeFormat::Full)
+        );
+        match notify::notify_task_finished(self.notifier.as_ref(), task, &self.config) {
+            Ok(true) => debug!("已发送任务 '{}' 的完成通知", task.name),
+            Ok(false) => {}
+            Err(e) => error!("Failed to notify completion of task {}: {}", task.name, e),
+        }
+        self.sound.play_task_finished(task, &self.config);
+        self.nag.record(id, Instant::now());
+        // 勿扰模式下不闪烁，完成记入队列，关闭勿扰模式时汇总通知
+        if self.config.do_not_disturb {
+            self.quiet_queue.push(task);
+        } else if !task.muted {
+            self.blinker.finish(id);
+        }
+        // 历史记录独立于任务列表，删除或归档任务不影响已写入的记录
+        if let Some(history) = &mut self.history {
+            history.append(HistoryRecord::new(task, &stats));
+        }
+        if let Some(command) = &task.on_complete {
+            run_on_complete_command(command, &task.name, &stats);
+        }
+        if let Some(url) = webhook::resolve_url(task, self.config.webhook_url.as_deref())
+            && !self.config.do_not_disturb
+        {
+            self.send_webhook(url.to_string(), webhook::Payload::new(task, &stats));
+        }
+
+        if let Some(next_id) = task.next {
+            match tasks.get(next_id) {
+                Some(next_task) => info!("⛓️ 任务 '{}' 已完成，自动开始 '{}'", task.name, next_task.name),
+                None => warn!("任务 '{}' 的后续任务已不存在", task.name),
+            }
+        }
+        Ok(())
+    }
+
+    /// 闪烁中时切换主托盘图标，停止闪烁后切回原图标
+    fn update_blink(&mut self) {
+        let Some(frame) = self.blinker.tick() else {
+            return;
+        };
+        let Some(tray_icon) = &self.tray_icon else {
+            return;
+        };
+        let icon = match frame {
+            BlinkFrame::Normal => self.logo_icon(),
+            BlinkFrame::Alert => icon::alert_icon(),
+        };
+        let result = icon.and_then(|icon| {
+            tray_icon.set_icon(Some(icon)).context(TrayIconUpdateSnafu {
+                operation: "set_icon_blink".to_string(),
+            })
+        });
+        if let Err(e) = result {
+            error!("Failed to update blinking tray icon: {}", e);
+        }
+    }
+
+    /// 查看过完成的任务后停止闪烁，id 为 None 时清除全部；
+    /// 计时线程可能随后暂停，所以立即恢复原图标和标题
+    fn stop_blinking(&mut self, id: Option<TaskId>) {
+        if !self.blinker.is_active() {
+            return;
+        }
+        match id {
+            Some(id) => self.blinker.clear(id),
+            None => self.blinker.clear_all(),
+        }
+        if !self.blinker.is_active() {
+            self.update_blink();
+            if let Err(e) = self.update_tray_icon(SystemTime::now()) {
+                error!("Failed to update tray icon after blinking: {}", e);
+            }
+        }
+    }
+
+    /// 在后台发送任务完成的 webhook，失败默认只记录日志
+    fn send_webhook(&self, url: String, payload: webhook::Payload) {
+        if !webhook::ENABLED {
+            warn!(
+                "Skipping webhook for task '{}': built without the webhook feature",
+                payload.name
+            );
+            return;
+        }
+        let alert = self.config.webhook_alert_on_failure;
+        let proxy = self.event_proxy.clone();
+        let name = payload.name.clone();
+        webhook::spawn(url, payload, move |e| {
+            error!("Failed to deliver webhook for task '{}': {}", name, e);
+            if alert
+                && let Some(proxy) = proxy
+                && let Err(e) = proxy
+                    .send_event(UserEvent::WebhookFailed(tr_args("error.webhook_task", &[
+                        ("name", &name),
+                        ("error", &e.to_string()),
+                    ])))
+                    .context(EventLoopSendSnafu)
+            {
+                error!("Failed to report webhook failure: {}", e);
+            }
+        });
+    }
+
+    /// 任务删除：清除指向被删除任务的链接
+    fn on_task_deleted(&mut self, id: TaskId) -> Result<()> {
+        let mut tasks = self.tasks.write()?;
+        for task in tasks.iter_mut() {
+            if task.next == Some(id) {
+                task.next = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// 任务状态或内容变化：更新对应的固定托盘图标
+    fn on_task_changed(&mut self, id: TaskId) -> Result<()> {
+        if self.pinned_tray_icons.contains_key(&id) {
+            self.update_pinned_tray_icon(id, SystemTime::now())?;
+        }
+        Ok(())
+    }
+
+    /// 切换设置开关并写回配置文件
+    fn handle_toggle_setting(&mut self, setting: Setting) {
+        let enabled = setting.toggle(&mut self.config);
+        match setting {
+            Setting::DoNotDisturb if !enabled => self.flush_quiet_queue(),
+            Setting::LaunchAtLogin => {
+                if let Err(e) = set_launch_at_login(enabled) {
+                    error!("Failed to update launch at login: {}", e);
                 }
-            } else if action.starts_with("unpin_") {
-                match action
-                    .strip_prefix("unpin_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "unpin_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        let mut task_name_opt = None;
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if let Some(task) = tasks.get_mut(index) {
-                                task.pinned = false;
-                                task_name_opt = Some(task.name.clone());
-                            } else {
-                                error!("Task not found at index {} for unpin", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for unpin");
-                        }
+            }
+            _ => {}
+        }
+        info!(
+            "⚙️ 设置 {} 已{}",
+            setting.key(),
+            if enabled { "开启" } else { "关闭" }
+        );
 
-                        if let Some(task_name) = task_name_opt {
-                            self.remove_pinned_tray_icon(index);
-                            info!("📌 任务 '{}' 已取消固定", task_name);
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after unpin: {}", e);
-                        }
-                    }
-                    Err(e) => error!("Failed to process unpin action '{}': {}", action, e),
+        if let Some(path) = &self.config_path
+            && let Err(e) = self.config.save(path)
+        {
+            error!("Failed to save config to {}: {}", path.display(), e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after setting change: {}", e);
+        }
+    }
+
+    /// 关闭勿扰模式时把期间完成的任务和提前提醒合并成一条通知
+    fn flush_quiet_queue(&mut self) {
+        let count = self.quiet_queue.len();
+        match notify::notify_quiet_summary(self.notifier.as_ref(), &mut self.quiet_queue, &self.config) {
+            Ok(true) => info!("🔔 已汇总勿扰期间的 {} 条通知", count),
+            Ok(false) => {}
+            Err(e) => error!("Failed to show do-not-disturb summary: {}", e),
+        }
+    }
+
+    /// 切换任务排序方式并写回配置文件
+    fn handle_set_task_order(&mut self, order: TaskOrder) {
+        self.config.task_order = order;
+        info!("↕️ 排序方式已切换为 {:?}", self.config.task_order);
+
+        if let Some(path) = &self.config_path
+            && let Err(e) = self.config.save(path)
+        {
+            error!("Failed to save config to {}: {}", path.display(), e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after order change: {}", e);
+        }
+    }
+
+    /// 手动排序时与相邻的任务交换位置，新的顺序随任务存档保存
+    fn handle_move_task(&mut self, id: TaskId, direction: MoveDirection) {
+        let result = self.tasks.write().map(|mut tasks| tasks.move_task(id, direction));
+        match result {
+            Ok(true) => info!(
+                "↕️ 任务 {} 已{}",
+                id,
+                if direction == MoveDirection::Up {
+                    "上移"
+                } else {
+                    "下移"
                 }
-            } else if action.starts_with("pinned_toggle_") {
-                match action
-                    .strip_prefix("pinned_toggle_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "pinned_toggle_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if let Some(task) = tasks.get_mut(index) {
-                                if task.is_running {
-                                    if let Err(e) = task.pause() {
-                                        error!("Failed to pause pinned task {}: {}", task.name, e);
-                                    } else {
-                                        info!("⏸️ 固定任务 '{}' 已暂停", task.name);
-                                    }
-                                } else {
-                                    task.start();
-                                    info!("▶️ 固定任务 '{}' 已开始", task.name);
-                                }
-                            } else {
-                                error!("Pinned task not found at index {} for toggle", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for pinned_toggle");
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after pinned_toggle: {}", e);
-                        }
-                        if let Err(e) = self.update_pinned_tray_icon(index) {
-                            error!("Failed to update pinned tray icon after pinned_toggle: {}", e);
-                        }
-                    }
-                    Err(e) => error!("Failed to process pinned_toggle action '{}': {}", action, e),
+            ),
+            Ok(false) => warn!("Task {} cannot be moved {:?}", id, direction),
+            Err(e) => self.report_error("Failed to move task", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after move: {}", e);
+        }
+    }
+
+    /// 修改最多同时固定的任务数，已经固定的任务不受影响
+    fn handle_set_max_pinned(&mut self, max: usize) {
+        self.config.max_pinned = max;
+        info!("📌 最多固定任务数已设为 {}", max);
+        if let Some(path) = &self.config_path
+            && let Err(e) = self.config.save(path)
+        {
+            error!("Failed to save config to {}: {}", path.display(), e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after max pinned change: {}", e);
+        }
+    }
+
+    /// 切换菜单或标题的时间格式
+    fn handle_set_time_format(&mut self, target: FormatTarget, format: TimeFormat) {
+        match target {
+            FormatTarget::Menu => self.config.time_format = format,
+            FormatTarget::Title => self.config.title_time_format = format,
+        }
+        task::set_time_formats(self.config.time_format, self.config.title_time_format);
+        info!("🕐 {:?} 时间格式已切换为 {:?}", target, format);
+
+        if let Some(path) = &self.config_path
+            && let Err(e) = self.config.save(path)
+        {
+            error!("Failed to save config to {}: {}", path.display(), e);
+        }
+        // 固定图标的绘制缓存以标题文本为键，格式变化后重新绘制
+        self.pinned_icon_cache.borrow_mut().clear();
+        if let Err(e) = self.update_tray_icon(SystemTime::now()) {
+            error!("Failed to update tray icon after time format change: {}", e);
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after time format change: {}", e);
+        }
+    }
+
+    /// 暂停所有计时中的任务
+    fn handle_pause_all(&mut self) {
+        match self.tasks.write().and_then(|mut tasks| tasks.pause_all()) {
+            Ok(paused) => {
+                info!("⏸️ 已暂停 {} 个任务", paused.len());
+                for id in paused {
+                    self.emit_task_event(TaskEvent::Paused(id));
                 }
-            } else if action.starts_with("pinned_reset_") {
-                match action
-                    .strip_prefix("pinned_reset_")
-                    .ok_or_else(|| {
-                        InvalidActionFormatSnafu {
-                            action_string: action.clone(),
-                            expected_prefix: "pinned_reset_",
-                        }
-                        .build()
-                    })
-                    .and_then(|s| {
-                        s.parse::<usize>().context(ParseActionIndexSnafu {
-                            action_string: s.to_string(),
-                        })
-                    }) {
-                    Ok(index) => {
-                        if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                            if let Some(task) = tasks.get_mut(index) {
-                                if let Err(e) = task.reset() {
-                                    error!("Failed to reset pinned task {}: {}", task.name, e);
-                                } else {
-                                    info!("🔄 固定任务 '{}' 已重置", task.name);
-                                }
-                            } else {
-                                error!("Pinned task not found at index {} for reset", index);
-                            }
-                        } else {
-                            error!("Failed to lock tasks for pinned_reset");
-                        }
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after pinned_reset: {}", e);
-                        }
-                        if let Err(e) = self.update_pinned_tray_icon(index) {
-                            error!("Failed to update pinned tray icon after pinned_reset: {}", e);
-                        }
+            }
+            Err(e) => self.report_error("Failed to pause all tasks", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after pause_all: {}", e);
+        }
+    }
+
+    /// 继续由“全部暂停”暂停的任务
+    fn handle_resume_all(&mut self) {
+        match self.tasks.write().map(|mut tasks| tasks.resume_all()) {
+            Ok(resumed) => {
+                info!("▶️ 已继续 {} 个任务", resumed.len());
+                for id in resumed {
+                    self.emit_task_event(TaskEvent::Started(id));
+                }
+            }
+            Err(e) => self.report_error("Failed to resume all tasks", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after resume_all: {}", e);
+        }
+    }
+
+    /// 复制任务的剩余时间和名称到剪贴板
+    fn handle_copy_task(&self, id: TaskId) {
+        let text = self
+            .tasks
+            .read()
+            .and_then(|tasks| clipboard::copy_task(self.clipboard.as_ref(), &tasks, id));
+        self.report_copy(text);
+    }
+
+    /// 选择图片作为任务固定图标使用的图标
+    fn handle_set_task_icon(&mut self, id: TaskId) {
+        let Some(path) = self.dialog.open_image(tr("icon.choose_title")) else {
+            info!("🚫 已取消选择图标");
+            return;
+        };
+        if let Err(e) = self.set_task_icon(id, Some(path)) {
+            self.report_error("Failed to set task icon", &e);
+        }
+    }
+
+    fn handle_clear_task_icon(&mut self, id: TaskId) {
+        if let Err(e) = self.set_task_icon(id, None) {
+            self.report_error("Failed to clear task icon", &e);
+        }
+    }
+
+    /// 修改任务图标，已固定的任务重新创建固定图标
+    fn set_task_icon(&mut self, id: TaskId, path: Option<PathBuf>) -> Result<()> {
+        let pinned = {
+            let mut tasks = self.tasks.write()?;
+            let task = tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            match &path {
+                Some(path) => info!("🖼️ 任务 '{}' 使用图标 {}", task.name, path.display()),
+                None => info!("🖼️ 任务 '{}' 恢复默认图标", task.name),
+            }
+            task.icon_path = path.clone();
+            task.pinned
+        };
+        // 重新选择同一个文件时重新读取，图片内容可能已经改变
+        if let Some(path) = &path {
+            self.task_images.borrow_mut().remove(path);
+            self.icon_cache.borrow_mut().forget_image(path);
+        }
+        if pinned {
+            self.remove_pinned_tray_icon(id);
+            self.create_pinned_tray_icon_with_retry(id);
+        }
+        self.refresh_menu()
+    }
+
+    /// 复制托盘提示中的全部内容到剪贴板
+    fn handle_copy_all(&self) {
+        let text = self
+            .tasks
+            .read()
+            .and_then(|tasks| self.tooltip_text(&tasks, SystemTime::now()))
+            .and_then(|text| self.clipboard.copy_text(&text).map(|()| text));
+        self.report_copy(text);
+    }
+
+    /// 记录复制的文本，失败时提示用户
+    fn report_copy(&self, copied: Result<String>) {
+        match copied {
+            Ok(text) => info!("📋 已复制到剪贴板: {}", text),
+            Err(e) => {
+                error!("Failed to copy to clipboard: {}", e);
+                self.dialog.error(tr("error.copy"), &e.to_string());
+            }
+        }
+    }
+
+    /// 选择保存位置，把全部任务导出为 JSON
+    fn handle_export_tasks(&self) {
+        let default_name = format!("TimeTicker-{}.json", Local::now().format("%Y%m%d"));
+        let Some(path) = self.dialog.save_path(tr("export.title"), &default_name) else {
+            info!("🚫 已取消导出");
+            return;
+        };
+        let result = self
+            .tasks
+            .read()
+            .and_then(|tasks| persistence::export_file(&path, &tasks, SystemTime::now()));
+        match result {
+            Ok(()) => info!("📤 已导出任务到 {}", path.display()),
+            Err(e) => {
+                error!("Failed to export tasks: {}", e);
+                self.dialog.error(tr("error.export"), &e.to_string());
+            }
+        }
+    }
+
+    /// 在文件管理器中打开日志文件夹
+    fn handle_open_log_dir(&self) {
+        let Some(dir) = logging::default_dir() else {
+            warn!("No log directory available on this platform");
+            return;
+        };
+        match logging::open_dir(&dir) {
+            Ok(()) => info!("📂 已打开日志文件夹 {}", dir.display()),
+            Err(e) => {
+                error!("Failed to open log directory: {}", e);
+                self.dialog.error(tr("error.open_log_dir"), &e.to_string());
+            }
+        }
+    }
+
+    /// 显示版本和诊断信息
+    fn handle_about(&self) {
+        match self.diagnostics_text() {
+            Ok(text) => self.dialog.info(tr("about.title"), &text),
+            Err(e) => error!("Failed to collect diagnostics: {}", e),
+        }
+    }
+
+    /// 把诊断信息复制到剪贴板，提交问题时粘贴
+    fn handle_copy_diagnostics(&self) {
+        let text = self.diagnostics_text().and_then(|text| self.clipboard.copy_text(&text).map(|()| text));
+        self.report_copy(text);
+    }
+
+    fn diagnostics_text(&self) -> Result<String> {
+        let tasks = self.tasks.read()?;
+        let log_dir = logging::default_dir();
+        Ok(about::diagnostics_text(&about::Diagnostics {
+            version: about::VERSION,
+            git_hash: about::GIT_HASH,
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            config_path: self.config_path.as_deref(),
+            save_path: self.save_path.as_deref(),
+            history_path: self.history.as_ref().map(HistoryLog::path),
+            log_dir: log_dir.as_deref(),
+            task_count: tasks.len(),
+            archived_count: tasks.iter().filter(|task| task.archived).count(),
+            pinned_icon_count: self.pinned_tray_icons.len(),
+        }))
+    }
+
+    /// 删除所有已完成的任务，固定的任务按设置先取消固定或跳过
+    fn handle_clear_finished(&mut self) {
+        let include_pinned = self.config.clear_pinned_finished;
+        match self.tasks.write().map(|mut tasks| tasks.clear_finished(include_pinned)) {
+            Ok(removed) => {
+                info!("🧹 已清除 {} 个已完成任务", removed.len());
+                for task in removed {
+                    if task.pinned {
+                        self.remove_pinned_tray_icon(task.id);
                     }
-                    Err(e) => error!("Failed to process pinned_reset action '{}': {}", action, e),
+                    self.nag.clear(task.id);
+                    self.emit_task_event(TaskEvent::Deleted(task.id));
                 }
             }
-        } else {
-            warn!("❌ 未找到菜单ID对应的动作: {:?}", menu_id);
-            debug!("当前注册的所有菜单ID:");
-            for (id, action) in &self.menu_ids {
-                debug!("  {:?} -> {}", id, action);
+            Err(e) => self.report_error("Failed to clear finished tasks", &e),
+        }
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after clear_finished: {}", e);
+        }
+    }
+
+    /// 撤销最近一次删除、重置或编辑
+    fn handle_undo(&mut self) {
+        let restored = self.tasks.write().map(|mut tasks| {
+            tasks
+                .undo()
+                .and_then(|id| tasks.get(id).map(|task| (id, task.name.clone(), task.pinned)))
+        });
+        let restored = match restored {
+            Ok(restored) => restored,
+            Err(e) => {
+                self.report_error("Failed to undo", &e);
+                return;
             }
+        };
+
+        let Some((id, name, pinned)) = restored else {
+            info!("没有可撤销的操作");
+            return;
+        };
+        info!("↩️ 已撤销对任务 '{}' 的操作", name);
+
+        // 固定托盘图标跟随恢复后的固定状态：恢复固定的任务时重新创建，恢复为未固定时移除
+        match (pinned, self.pinned_tray_icons.contains_key(&id)) {
+            (true, false) => self.create_pinned_tray_icon_with_retry(id),
+            (false, true) => self.remove_pinned_tray_icon(id),
+            _ => {}
+        }
+        self.emit_task_event(TaskEvent::Edited(id));
+
+        if let Err(e) = self.refresh_menu() {
+            error!("Failed to refresh menu after undo: {}", e);
         }
     }
 
-    /// 处理新建任务
-    fn handle_new_task(&mut self) {
-        info!("📝 开始新建任务");
+    /// 处理编辑任务
+    fn handle_edit_task(&mut self, id: TaskId) {
+        // 用当前任务的时间和名称预填对话框
+        let current_input = self
+            .tasks
+            .read()
+            .and_then(|tasks| tasks.get(id).map(format_task_input).context(TaskIdNotFoundSnafu { id }));
+        let current_input = match current_input {
+            Ok(input) => input,
+            Err(e) => {
+                self.report_error("Failed to start editing task", &e);
+                return;
+            }
+        };
 
-        // 显示输入对话框
-        let input = show_input_dialog(
-            "新建任务",
-            "请输入任务信息：\n\n格式示例：\n• 时间段：1h30m#学习\n• 截止时间：@19:00#工作\n\n其中 # \
-             后面是任务名称（可选）",
-            "1h#新任务",
-        );
+        info!("✏️ 开始编辑任务: {}", current_input);
 
-        match input {
-            Some(user_input) => {
-                info!("用户输入: {}", user_input);
-
-                // 解析用户输入
-                match parse_time_input(&user_input) {
-                    Ok((task_name, task_type)) => {
-                        // 创建新任务
-                        match Task::new(task_name.clone(), task_type) {
-                            Ok(new_task_obj) => {
-                                // 添加到任务列表
-                                if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                                    // Use TaskLockSnafu directly
-                                    tasks.push(new_task_obj);
-                                    info!("✅ 成功创建任务: {}", task_name);
-                                } else {
-                                    error!("❌ 无法获取任务列表锁 (new task)");
-                                }
-                            }
-                            Err(e) => {
-                                error!("❌ 创建任务对象失败 (Task::new failed): {}", e);
-                            }
-                        }
-                        // 刷新菜单
-                        if let Err(e) = self.refresh_menu() {
-                            error!("Failed to refresh menu after new task attempt: {}", e);
-                        } else {
-                            info!("🔄 菜单已刷新 (new task attempt)");
-                        }
-                    }
-                    Err(e) => {
-                        // This is for parse_time_input error
-                        error!("❌ 解析任务输入失败: {}", e);
-                        // 显示错误信息给用户
-                        #[cfg(target_os = "macos")]
-                        {
-                            let error_script = format!(
-                                r#"display dialog "解析任务输入失败：\n\n{}\n\n请检查输入格式：\n• 时间段：1h30m#任务名\n• 截止时间：@19:00#任务名" with title "输入错误" buttons {{"确定"}} default button "确定" with icon stop"#,
-                                e
-                            );
-                            match Command::new("osascript").arg("-e").arg(&error_script).output() {
-                                Ok(_) => info!("Error dialog displayed for parse failure."),
-                                Err(cmd_err) => error!("Failed to display error dialog via osascript: {}", cmd_err),
-                            }
-                        }
+        self.request_input(InputRequest {
+            purpose: InputPurpose::EditTask(id),
+            title: tr("input.edit_title"),
+            message: tr("input.edit_message"),
+            default_text: current_input,
+        });
+    }
+
+    /// 解析输入并修改任务
+    fn apply_task_edit(&mut self, id: TaskId, user_input: &str) {
+        match parse_time_input(user_input) {
+            Ok(task_input) => {
+                let scheduled = task_input.scheduled_start.is_some();
+                // 先在副本上完成全部修改，新的时间无效时任务保持原样，也不记录撤销
+                let result = self.tasks.write().and_then(|mut tasks| {
+                    let task = tasks.get(id).context(TaskIdNotFoundSnafu { id })?.clone();
+                    // 输入中没有名称时保留原名称
+                    let edited = task_input.edit(task, user_input.contains('#'))?;
+                    info!("✅ 任务 '{}' 已更新", edited.name);
+                    tasks.record_edit(id);
+                    if let Some(task) = tasks.get_mut(id) {
+                        *task = edited;
                     }
+                    Ok(())
+                });
+                match result {
+                    Ok(()) => self.emit_task_event(TaskEvent::Edited(id)),
+                    Err(e) => self.report_error(&format!("Failed to edit task {id}"), &e),
+                }
+
+                if let Err(e) = self.refresh_menu() {
+                    error!("Failed to refresh menu after edit: {}", e);
+                }
+                if scheduled {
+                    self.schedule_next_start();
                 }
             }
-            None => {
-                info!("用户取消了新建任务");
-            }
+            Err(e) => self.report_error("Failed to parse task input", &e),
         }
     }
 }
@@ -1264,20 +3187,34 @@ impl ApplicationHandler<UserEvent> for Application {
 
     fn window_event(
         &mut self,
-        _event_loop: &winit::event_loop::ActiveEventLoop,
-        _window_id: winit::window::WindowId,
-        _event: winit::event::WindowEvent,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window_id: winit::window::WindowId,
+        event: winit::event::WindowEvent,
     ) {
+        if self.handle_input_window_event(window_id, &event) {
+            // 输入结果可能修改了任务
+            self.dispatch_task_events();
+            if let Err(e) = self.update_tick_gate(event_loop) {
+                error!("Failed to update tick gate: {}", e);
+            }
+        }
     }
 
     fn new_events(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop, cause: winit::event::StartCause) {
         if winit::event::StartCause::Init == cause {
-            match self.new_tray_icon() {
-                Ok(tray_icon) => self.tray_icon = Some(tray_icon),
-                Err(e) => {
-                    error!("Failed to create initial tray icon: {}", e);
-                }
+            self.init_tray_icon();
+
+            // 程序关闭期间过期的截止时间任务，此时才能安排延迟检查
+            if let Err(e) = self.archive_expired_deadlines() {
+                error!("Failed to archive expired deadline tasks: {}", e);
+            }
+            self.schedule_expired_archive();
+
+            // 程序关闭期间到达定时开始时刻的任务
+            if let Err(e) = self.start_scheduled_tasks() {
+                self.report_error("Failed to start scheduled tasks", &e);
             }
+            self.schedule_next_start();
 
             #[cfg(target_os = "macos")]
             unsafe {
@@ -1293,132 +3230,321 @@ impl ApplicationHandler<UserEvent> for Application {
 
     fn user_event(&mut self, event_loop: &winit::event_loop::ActiveEventLoop, event: UserEvent) {
         match event {
-            UserEvent::TrayIconEvent(_) => {}
+            UserEvent::TrayIconEvent(event) => {
+                if let Err(e) = self.handle_tray_event(event) {
+                    self.report_error("Failed to handle tray icon event", &e);
+                }
+            }
             UserEvent::MenuEvent(event) => {
                 self.handle_menu_event(event);
             }
             UserEvent::UpdateTimer => {
-                if let Err(e) = self.update_tray_icon() {
-                    error!("Failed to update tray icon from timer: {}", e);
+                // 每秒一次，只在 trace 级别记录 span
+                let span = trace_span!("update_timer", duration_ms = field::Empty);
+                let _entered = span.enter();
+                let _timer = logging::HandlerTimer::start("update_timer", &span);
+                self.tick_watchdog.record(Instant::now());
+                self.tick();
+                let next = until_next_second(SystemTime::now());
+                event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + next));
+            }
+            UserEvent::SystemWillSleep => {
+                // 从即将休眠的时刻开始计算休眠时长
+                info!("💤 系统即将休眠");
+                self.last_tick = Some(SystemTime::now());
+            }
+            UserEvent::OpenUrl(url) => self.handle_open_url(&url),
+            UserEvent::NewTaskHotkey => {
+                info!("⌨️ 通过快捷键新建任务");
+                self.handle_new_task();
+            }
+            UserEvent::WebhookFailed(message) => self.dialog.error(tr("error.webhook"), &message),
+            UserEvent::ArchiveExpired => {
+                if let Err(e) = self.archive_expired_deadlines() {
+                    error!("Failed to archive expired deadline tasks: {}", e);
                 }
-                event_loop.set_control_flow(ControlFlow::WaitUntil(Instant::now() + Duration::from_secs(1)));
-            }
-            UserEvent::StartTask(index) => {
-                if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                    // Use TaskLockSnafu directly
-                    if let Some(task) = tasks.get_mut(index) {
-                        task.start();
-                    } else {
-                        error!("Task not found at index {} for StartTask", index);
-                    }
-                } else {
-                    error!("Failed to lock tasks for StartTask");
+                self.schedule_expired_archive();
+            }
+            UserEvent::RetryTrayIcon => self.init_tray_icon(),
+            UserEvent::RetryPinnedTrayIcon(id) => self.retry_pinned_tray_icon(id),
+            UserEvent::TimerStalled => self.restart_stalled_timer(),
+            UserEvent::ConfigReloaded => self.handle_config_reloaded(),
+            UserEvent::ScheduledStart => {
+                if let Err(e) = self.start_scheduled_tasks() {
+                    self.report_error("Failed to start scheduled tasks", &e);
                 }
+                self.schedule_next_start();
             }
-            UserEvent::PauseTask(index) => {
-                if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                    // Use TaskLockSnafu directly
-                    if let Some(task) = tasks.get_mut(index) {
-                        if let Err(e) = task.pause() {
-                            error!("Failed to pause task {}: {}", task.name, e);
-                        }
-                    } else {
-                        error!("Task not found at index {} for PauseTask", index);
-                    }
-                } else {
-                    error!("Failed to lock tasks for PauseTask");
+            UserEvent::ClickTimeout => {
+                if self.click_tracker.expire(Instant::now())
+                    && let Err(e) = self.toggle_active_task()
+                {
+                    self.report_error("Failed to toggle active task from click", &e);
                 }
             }
-            UserEvent::ResetTask(index) => {
-                if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                    // Use TaskLockSnafu directly
-                    if let Some(task) = tasks.get_mut(index) {
-                        if let Err(e) = task.reset() {
-                            error!("Failed to reset task {}: {}", task.name, e);
-                        }
-                    } else {
-                        error!("Task not found at index {} for ResetTask", index);
-                    }
-                } else {
-                    error!("Failed to lock tasks for ResetTask");
+            UserEvent::ToggleActiveHotkey => {
+                if let Err(e) = self.toggle_active_task() {
+                    self.report_error("Failed to toggle active task from hotkey", &e);
                 }
             }
-            UserEvent::DeleteTask(index) => {
-                if let Ok(mut tasks) = self.tasks.lock().map_err(|_| TaskLockSnafu.build()) {
-                    // Use TaskLockSnafu directly
-                    if index < tasks.len() {
-                        tasks.remove(index);
-                    } else {
-                        error!("Task index {} out of bounds for DeleteTask", index);
-                    }
-                } else {
-                    error!("Failed to lock tasks for DeleteTask");
+            UserEvent::QuickAddHotkey => {
+                info!("⌨️ 通过快捷键快速添加任务");
+                self.quick_add_requested = true;
+            }
+            UserEvent::Command(command, reply) => {
+                let result = self.handle_command(command);
+                if reply.send(ipc::Reply::from_result(result)).is_err() {
+                    debug!("控制命令的发送方已断开");
+                }
+            }
+            UserEvent::SystemDidWake => {
+                // 唤醒后立即刷新，不等下一次计时
+                info!("☀️ 系统已唤醒");
+                self.tick();
+            }
+            UserEvent::StartTask(id) => {
+                let result = self.tasks.write().and_then(|mut tasks| self.start_task(&mut tasks, id));
+                if let Err(e) = result {
+                    self.report_error("Failed to start task", &e);
+                }
+            }
+            UserEvent::PauseTask(id) => {
+                let result = self
+                    .tasks
+                    .write()
+                    .and_then(|mut tasks| tasks.get_mut(id).context(TaskIdNotFoundSnafu { id })?.pause());
+                match result {
+                    Ok(()) => self.emit_task_event(TaskEvent::Paused(id)),
+                    Err(e) => self.report_error("Failed to pause task", &e),
+                }
+            }
+            UserEvent::ResetTask(id) => {
+                match self.tasks.write().and_then(|mut tasks| tasks.reset(id)) {
+                    Ok(()) => self.emit_task_event(TaskEvent::Reset(id)),
+                    Err(e) => self.report_error("Failed to reset task", &e),
+                }
+            }
+            UserEvent::DeleteTask(id) => {
+                let result = self
+                    .tasks
+                    .write()
+                    .and_then(|mut tasks| tasks.delete(id).map(drop).context(TaskIdNotFoundSnafu { id }));
+                match result {
+                    Ok(()) => self.emit_task_event(TaskEvent::Deleted(id)),
+                    Err(e) => self.report_error("Failed to delete task", &e),
                 }
             }
         }
+
+        // 分发本次事件处理中产生的任务事件
+        self.dispatch_task_events();
+
+        // 任务开始或固定后立即恢复计时，全部停止后暂停
+        if let Err(e) = self.update_tick_gate(event_loop) {
+            error!("Failed to update tick gate: {}", e);
+        }
+
+        // 本次事件中请求的输入窗口需要事件循环才能创建
+        self.open_requested_input(event_loop);
+        self.open_quick_add(event_loop);
+    }
+}
+
+/// 加载任务存档，文件不存在或无法读取时使用空列表
+fn load_tasks(path: &Path) -> TaskList {
+    if !path.exists() {
+        info!("📂 没有找到任务存档，从空列表开始: {}", path.display());
+        return TaskList::new();
+    }
+    match persistence::load_file(path) {
+        Ok(mut tasks) => {
+            info!("📂 已从 {} 加载 {} 个任务", path.display(), tasks.len());
+            let purged = tasks.purge_trash(SystemTime::now());
+            if purged > 0 {
+                info!("🗑️ 已清除回收站中超过保留期的 {} 个任务", purged);
+            }
+            tasks
+        }
+        Err(e) => {
+            warn!(
+                "Failed to load tasks from {}, starting with an empty list: {}",
+                path.display(),
+                e
+            );
+            TaskList::new()
+        }
     }
 }
 
-fn format_remaining_time(duration: Duration) -> String {
-    let total_seconds = duration.as_secs();
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let seconds = total_seconds % 60;
-    format!("{hours:02}:{minutes:02}:{seconds:02}")
+/// 任务完成时在后台执行用户设置的命令，任务信息通过环境变量传入，失败只记录日志
+fn run_on_complete_command(command: &str, task_name: &str, stats: &TaskStats) {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+
+    let spawn_result = cmd
+        .env("TIMETICKER_TASK_NAME", task_name)
+        .env("TIMETICKER_PLANNED_SECS", stats.planned.as_secs().to_string())
+        .env("TIMETICKER_ACTUAL_SECS", stats.actual.as_secs().to_string())
+        .spawn();
+
+    match spawn_result {
+        Ok(mut child) => {
+            info!("🚀 任务 '{}' 完成命令已启动: {}", task_name, command);
+            let task_name = task_name.to_string();
+            // 在后台等待命令结束，避免留下僵尸进程
+            std::thread::spawn(move || match child.wait() {
+                Ok(status) if status.success() => debug!("Completion command for task '{}' exited", task_name),
+                Ok(status) => warn!("Completion command for task '{}' exited with {}", task_name, status),
+                Err(e) => error!("Failed to wait for completion command of task '{}': {}", task_name, e),
+            });
+        }
+        Err(e) => error!("Failed to run completion command for task '{}': {}", task_name, e),
+    }
 }
 
-#[cfg(target_os = "macos")]
-fn show_input_dialog(title: &str, message: &str, default_text: &str) -> Option<String> {
-    let script = format!(
-        r#"display dialog "{}" with title "{}" default answer "{}" buttons {{"取消", "确定"}} default button "确定""#,
-        message, title, default_text
-    );
+// 渲染出的主菜单项，菜单结构不变时按位置原地更新
+enum MenuHandle {
+    Item(MenuItem),
+    Check(CheckMenuItem),
+    Submenu(Submenu),
+    Separator,
+}
+
+impl MenuHandle {
+    fn apply(&self, change: &MenuChange) {
+        match (self, change) {
+            (MenuHandle::Item(item), MenuChange::Text(text)) => item.set_text(text),
+            (MenuHandle::Item(item), MenuChange::Enabled(enabled)) => item.set_enabled(*enabled),
+            (MenuHandle::Check(item), MenuChange::Text(text)) => item.set_text(text),
+            (MenuHandle::Check(item), MenuChange::Enabled(enabled)) => item.set_enabled(*enabled),
+            (MenuHandle::Check(item), MenuChange::Checked(checked)) => item.set_checked(*checked),
+            (MenuHandle::Submenu(submenu), MenuChange::Text(text)) => submenu.set_text(text),
+            _ => {}
+        }
+    }
+}
 
-    let output_res = Command::new("osascript").arg("-e").arg(&script).output();
+// 任务子菜单中反映任务状态的勾选项：静音、持续提醒和固定
+#[derive(Default)]
+struct TaskCheckItems {
+    items: Vec<(MenuAction, CheckMenuItem)>,
+}
 
-    match output_res {
-        Ok(output) => {
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                if let Some(text_part) = output_str.split("text returned:").nth(1) {
-                    let user_input = text_part.trim().to_string();
-                    if !user_input.is_empty() {
-                        return Some(user_input);
-                    }
-                }
+impl TaskCheckItems {
+    fn push(&mut self, action: MenuAction, item: CheckMenuItem) {
+        self.items.push((action, item));
+    }
+
+    fn sync(&self, task: &Task) {
+        let toggles = task.toggles();
+        for (action, item) in &self.items {
+            match action {
+                MenuAction::Mute(_) => item.set_checked(toggles.muted),
+                MenuAction::Nag(_) => item.set_checked(toggles.nag),
+                MenuAction::Pin(_) => item.set_checked(toggles.pinned),
+                _ => {}
             }
-            None
-        }
-        Err(e) => {
-            error!("显示输入对话框失败 (osascript execution): {}", e);
-            None
         }
     }
 }
 
-#[cfg(not(target_os = "macos"))]
-fn show_input_dialog(title: &str, message: &str, default_text: &str) -> Option<String> {
-    warn!("输入对话框在此平台不支持，使用默认值: '{}'", default_text);
-    Some(default_text.to_string())
+// 每个推迟时长一个按钮，与推迟的时长一起返回
+fn snooze_menu_items(enabled: bool) -> Vec<(MenuItem, Duration)> {
+    SNOOZE_MINUTES
+        .iter()
+        .map(|minutes| {
+            let label = tr_args("menu.snooze", &[("minutes", &minutes.to_string())]);
+            (MenuItem::new(label, enabled, None), Duration::from_secs(minutes * 60))
+        })
+        .collect()
+}
+
+// 解析任务输入失败时附上输入格式说明，其他输入错误直接显示错误信息
+fn show_input_error_dialog(dialog: &dyn Dialog, e: &Error) {
+    let message = match e {
+        Error::InvalidInputFormat { .. }
+        | Error::MissingTimeInput { .. }
+        | Error::ChronoParse { .. }
+        | Error::TimezoneConversion { .. }
+        | Error::ParseNumber { .. }
+        | Error::InvalidDurationUnit { .. } => tr_args("error.parse", &[("error", &e.to_string())]),
+        _ => e.to_string(),
+    };
+    dialog.error(tr("error.input_title"), &message);
+}
+
+/// 命令行模式：把命令发送给正在运行的实例并打印 JSON 回复
+fn run_cli(args: &[String]) -> Result<bool> {
+    let command = ipc::parse_args(args)?;
+    let dir = instance::default_dir().context(InvalidCliArgsSnafu {
+        msg: "无法确定数据目录",
+    })?;
+    let reply = instance::send(&dir, &command)?;
+    println!("{}", serde_json::to_string(&reply).context(IpcMessageSnafu)?);
+    Ok(reply.ok)
 }
 
 fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "time_ticker=debug,info".into()),
-        )
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_file(false)
-        .with_line_number(false)
-        .init();
+    // 带参数启动时作为命令行客户端运行，不初始化日志，保证输出只有 JSON
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match run_cli(&args) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // 日志在读取配置之前初始化，这里只读取日志格式，配置错误由 Application::new
+    // 报告
+    let json_logs = Config::default_path()
+        .and_then(|path| Config::load(&path).ok())
+        .is_some_and(|config| config.json_logs);
+    logging::init(logging::default_dir().as_deref(), json_logs);
 
     info!("🚀 TimeTicker 应用程序启动");
 
+    // 同一时间只运行一个实例，已有实例时通知它后退出
+    let instance_dir = instance::default_dir();
+    let instance_lock = match instance_dir.as_deref().map(instance::acquire) {
+        Some(Ok(Acquire::Acquired(lock))) => Some(lock),
+        Some(Ok(Acquire::Running(pid))) => {
+            info!("👋 TimeTicker 已在运行（PID {:?}），退出", pid);
+            if let Some(dir) = &instance_dir
+                && let Err(e) = instance::send(dir, &ipc::Command::Ping)
+            {
+                warn!("Failed to notify the running instance: {}", e);
+            }
+            return Ok(());
+        }
+        Some(Err(e)) => {
+            error!("Failed to acquire instance lock: {}", e);
+            None
+        }
+        None => None,
+    };
+
+    let mut app = Application::new();
+    app.instance_lock = instance_lock;
+
     #[cfg(target_os = "macos")]
     {
         info!("🔧 预设置 Dock 图标，减少启动延迟");
-        if let Err(e) = set_dock_visibility(true) {
+        let dock_icon = app.custom_icon_path(app.config.dock_icon_path.as_deref());
+        if let Err(e) = set_dock_visibility(!app.config.hide_dock_on_launch, dock_icon.as_deref()) {
             error!("Failed to set initial dock visibility: {}", e);
         }
     }
@@ -1449,45 +3575,136 @@ fn main() -> Result<()> {
         }
     }));
 
-    let mut app = Application::new();
+    #[cfg(target_os = "macos")]
+    observe_system_sleep(event_loop.create_proxy());
 
-    let proxy_timer = event_loop.create_proxy();
-    std::thread::spawn(move || {
-        loop {
-            std::thread::sleep(Duration::from_secs(1));
-            if let Err(e) = proxy_timer
-                .send_event(UserEvent::UpdateTimer)
+    // 需要在应用启动完成之前注册，才能收到启动应用的那个链接
+    #[cfg(target_os = "macos")]
+    observe_url_events(event_loop.create_proxy());
+
+    app.event_proxy = Some(event_loop.create_proxy());
+
+    // 快捷键管理器需要在主线程中创建
+    app.register_hotkeys(event_loop.create_proxy());
+
+    if app.instance_lock.is_some()
+        && let Some(dir) = &instance_dir
+    {
+        // 控制命令转发到事件循环，所有修改都在主线程中进行
+        let proxy_command = event_loop.create_proxy();
+        let result = instance::listen(dir, move |command| {
+            let (reply_sender, reply_receiver) = mpsc::channel();
+            if let Err(e) = proxy_command
+                .send_event(UserEvent::Command(command, reply_sender))
                 .context(EventLoopSendSnafu)
             {
-                // Use EventLoopSendSnafu directly
-                error!(
-                    "Failed to send UpdateTimer event to event loop: {}. Timer thread exiting.",
-                    e
-                );
-                break;
+                return ipc::Reply::error(e);
             }
+            reply_receiver
+                .recv_timeout(COMMAND_REPLY_TIMEOUT)
+                .unwrap_or_else(|e| ipc::Reply::error(format!("no reply from event loop: {e}")))
+        });
+        if let Err(e) = result {
+            error!("Failed to listen on control socket: {}", e);
         }
-    });
+    }
+
+    app.spawn_timer_thread();
+    spawn_tick_watchdog(
+        event_loop.create_proxy(),
+        app.tick_gate.clone(),
+        app.tick_watchdog.clone(),
+    );
+    if let Some(path) = app.config_path.clone() {
+        spawn_config_watcher(event_loop.create_proxy(), path);
+    }
 
     event_loop.run_app(&mut app).context(EventLoopCreationSnafu)?; // Use EventLoopCreationSnafu directly
 
     Ok(())
 }
 
+// 定期检查计时刷新是否停止，停止时通知事件循环重新启动计时线程
+fn spawn_tick_watchdog(proxy: EventLoopProxy<UserEvent>, tick_gate: Arc<TickGate>, tick_watchdog: Arc<TickWatchdog>) {
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(watchdog::STALL_THRESHOLD);
+            if tick_gate.is_idle() || !tick_watchdog.is_stalled(Instant::now()) {
+                continue;
+            }
+            if proxy.send_event(UserEvent::TimerStalled).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// 定期检查配置文件的修改时间，变化时通知事件循环重新加载；
+// 文件被删除时不通知，避免把设置重置为默认值
+fn spawn_config_watcher(proxy: EventLoopProxy<UserEvent>, path: PathBuf) {
+    std::thread::spawn(move || {
+        let mut last_modified = config::modified_time(&path);
+        loop {
+            std::thread::sleep(CONFIG_POLL_INTERVAL);
+            let modified = config::modified_time(&path);
+            if modified.is_none() || modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            if proxy.send_event(UserEvent::ConfigReloaded).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+const LOGO_PNG: &[u8] = include_bytes!("../assets/logo.png"); // 内嵌的默认托盘图标
+#[cfg(target_os = "macos")]
+const DOCK_PNG: &[u8] = include_bytes!("../assets/dock.png"); // 内嵌的默认 Dock 图标
+
 fn load_icon(path: &std::path::Path) -> Result<tray_icon::Icon> {
-    let image = image::open(path)
-        .map_err(|e| Error::Image {
-            source: e,
-            backtrace: Backtrace::capture(),
-        })?
-        .into_rgba8();
+    let image = image::open(path).map_err(|e| Error::Image {
+        source: e,
+        backtrace: Backtrace::capture(),
+    })?;
+    rgba_icon(image)
+}
+
+// 从内嵌的图片数据创建图标，不依赖当前工作目录
+fn decode_icon(bytes: &[u8]) -> Result<tray_icon::Icon> {
+    let image = image::load_from_memory(bytes).map_err(|e| Error::Image {
+        source: e,
+        backtrace: Backtrace::capture(),
+    })?;
+    rgba_icon(image)
+}
+
+fn rgba_icon(image: image::DynamicImage) -> Result<tray_icon::Icon> {
+    let image = image.into_rgba8();
     let (width, height) = image.dimensions();
     let rgba = image.into_raw();
     tray_icon::Icon::from_rgba(rgba, width, height).context(IconConversionSnafu) // Use IconConversionSnafu directly
 }
 
+// 设置 Dock 图标的角标文本，None 时清除
+#[cfg(target_os = "macos")]
+fn set_dock_badge(label: Option<&str>) -> Result<()> {
+    let mtm = MainThreadMarker::new().context(MainThreadMarkerSnafu)?;
+    let app = NSApplication::sharedApplication(mtm);
+    let label = label.map(NSString::from_str);
+    unsafe {
+        app.dockTile().setBadgeLabel(label.as_deref());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_dock_badge(_label: Option<&str>) -> Result<()> {
+    Ok(())
+}
+
 #[cfg(target_os = "macos")]
-fn set_dock_visibility(visible: bool) -> Result<()> {
+fn set_dock_visibility(visible: bool, dock_icon: Option<&Path>) -> Result<()> {
     unsafe {
         let mtm = MainThreadMarker::new().context(MainThreadMarkerSnafu)?; // Use MainThreadMarkerSnafu directly
         let app = NSApplication::sharedApplication(mtm);
@@ -1498,8 +3715,8 @@ fn set_dock_visibility(visible: bool) -> Result<()> {
         };
         app.setActivationPolicy(policy);
         if visible {
-            set_dock_icon()?;
-            info!("✅ Dock 图标已显示，使用 dock.png");
+            set_dock_icon(dock_icon)?;
+            info!("✅ Dock 图标已显示");
         } else {
             info!("✅ Dock 图标已隐藏");
         }
@@ -1507,34 +3724,224 @@ fn set_dock_visibility(visible: bool) -> Result<()> {
     Ok(())
 }
 
+#[cfg(not(target_os = "macos"))]
+fn set_dock_visibility(_visible: bool, _dock_icon: Option<&Path>) -> Result<()> {
+    Ok(())
+}
+
+// 开机自启：在 ~/Library/LaunchAgents 中写入或删除启动项
+#[cfg(target_os = "macos")]
+fn set_launch_at_login(enabled: bool) -> Result<()> {
+    let Some(dirs) = directories::BaseDirs::new() else {
+        warn!("Could not determine home directory for launch agent");
+        return Ok(());
+    };
+    let plist_path = dirs
+        .home_dir()
+        .join("Library/LaunchAgents/com.yazhouio.timeticker.plist");
+    if !enabled {
+        if plist_path.exists() {
+            std::fs::remove_file(&plist_path).context(IoSnafu { path: &plist_path })?;
+        }
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe().context(IoSnafu {
+        path: PathBuf::from("current_exe"),
+    })?;
+    let plist = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.yazhouio.timeticker</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        exe.display()
+    );
+    if let Some(dir) = plist_path.parent() {
+        std::fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+    }
+    std::fs::write(&plist_path, plist).context(IoSnafu { path: &plist_path })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_launch_at_login(_enabled: bool) -> Result<()> {
+    warn!("Launch at login is only supported on macOS.");
+    Ok(())
+}
+
+// 设置 Dock 图标：配置了自定义图标时从文件加载，否则使用内嵌的 dock.png
 #[cfg(target_os = "macos")]
-fn set_dock_icon() -> Result<()> {
+fn set_dock_icon(custom: Option<&Path>) -> Result<()> {
     use objc2::rc::Retained;
     unsafe {
         let mtm = MainThreadMarker::new().context(MainThreadMarkerSnafu)?; // Use MainThreadMarkerSnafu directly
         let app = NSApplication::sharedApplication(mtm);
-        let dock_icon_path = std::path::Path::new("./assets/dock.png");
-        if dock_icon_path.exists() {
-            let absolute_path = std::fs::canonicalize(dock_icon_path).context(CanonicalizePathSnafu {
-                path: dock_icon_path.to_path_buf(),
-            })?; // Use CanonicalizePathSnafu directly
-            let absolute_path_str = absolute_path.to_string_lossy();
-            let path_str = NSString::from_str(&absolute_path_str);
-            if let Some(image) = NSImage::initWithContentsOfFile(NSImage::alloc(), &path_str) {
-                app.setApplicationIconImage(Some(&image));
-                info!("🖼️ 成功设置 Dock 图标为 dock.png");
+        if let Some(dock_icon_path) = custom {
+            if dock_icon_path.exists() {
+                let absolute_path = std::fs::canonicalize(dock_icon_path).context(CanonicalizePathSnafu {
+                    path: dock_icon_path.to_path_buf(),
+                })?; // Use CanonicalizePathSnafu directly
+                let absolute_path_str = absolute_path.to_string_lossy();
+                let path_str = NSString::from_str(&absolute_path_str);
+                if let Some(image) = NSImage::initWithContentsOfFile(NSImage::alloc(), &path_str) {
+                    app.setApplicationIconImage(Some(&image));
+                    info!("🖼️ 成功设置 Dock 图标为 {}", dock_icon_path.display());
+                    return Ok(());
+                }
+                warn!("⚠️ 无法加载 Dock 图标文件 {}，使用内嵌图标", dock_icon_path.display());
             } else {
-                warn!("⚠️ 无法加载 dock.png 图像文件");
-                set_default_dock_icon()?;
+                warn!("⚠️ 找不到 Dock 图标文件: {}，使用内嵌图标", dock_icon_path.display());
             }
+        }
+
+        let data = NSData::with_bytes(DOCK_PNG);
+        if let Some(image) = NSImage::initWithData(NSImage::alloc(), &data) {
+            app.setApplicationIconImage(Some(&image));
+            info!("🖼️ 成功设置 Dock 图标为内嵌的 dock.png");
         } else {
-            warn!("⚠️ 找不到 dock.png 文件: {}", dock_icon_path.display());
+            warn!("⚠️ 无法解码内嵌的 dock.png");
             set_default_dock_icon()?;
         }
     }
     Ok(())
 }
 
+#[cfg(not(target_os = "macos"))]
+fn set_dock_icon(_custom: Option<&Path>) -> Result<()> {
+    Ok(())
+}
+
+// 监听系统休眠和唤醒通知，转发到事件循环；观察者在程序运行期间一直有效
+#[cfg(target_os = "macos")]
+fn observe_system_sleep(proxy: winit::event_loop::EventLoopProxy<UserEvent>) {
+    use std::ptr::NonNull;
+
+    use block2::RcBlock;
+    use objc2_app_kit::{NSWorkspace, NSWorkspaceDidWakeNotification, NSWorkspaceWillSleepNotification};
+    use objc2_foundation::NSNotification;
+
+    unsafe {
+        let center = NSWorkspace::sharedWorkspace().notificationCenter();
+        for (name, wake) in [
+            (NSWorkspaceWillSleepNotification, false),
+            (NSWorkspaceDidWakeNotification, true),
+        ] {
+            let proxy = proxy.clone();
+            let block = RcBlock::new(move |_: NonNull<NSNotification>| {
+                let event = if wake {
+                    UserEvent::SystemDidWake
+                } else {
+                    UserEvent::SystemWillSleep
+                };
+                if let Err(e) = proxy.send_event(event).context(EventLoopSendSnafu) {
+                    error!("Failed to send sleep/wake event to event loop: {}", e);
+                }
+            });
+            let observer = center.addObserverForName_object_queue_usingBlock(Some(name), None, None, &block);
+            std::mem::forget(observer);
+        }
+    }
+}
+
+// 接收 kAEGetURL Apple Event 的对象，把 timeticker:// 链接转发到事件循环
+#[cfg(target_os = "macos")]
+mod url_event_handler {
+    use objc2::{
+        ClassType, DeclaredClass, declare_class, msg_send_id, mutability, rc::Retained, runtime::NSObject,
+    };
+    use objc2_foundation::NSAppleEventDescriptor;
+    use snafu::ResultExt;
+    use tracing::{error, warn};
+    use winit::event_loop::EventLoopProxy;
+
+    use crate::{UserEvent, error::EventLoopSendSnafu};
+
+    const KEY_DIRECT_OBJECT: u32 = u32::from_be_bytes(*b"----");
+
+    declare_class!(
+        pub struct UrlEventHandler;
+
+        unsafe impl ClassType for UrlEventHandler {
+            type Super = NSObject;
+            type Mutability = mutability::InteriorMutable;
+            const NAME: &'static str = "TimeTickerUrlEventHandler";
+        }
+
+        impl DeclaredClass for UrlEventHandler {
+            type Ivars = EventLoopProxy<UserEvent>;
+        }
+
+        unsafe impl UrlEventHandler {
+            #[method(handleGetURLEvent:withReplyEvent:)]
+            fn handle_get_url_event(&self, event: &NSAppleEventDescriptor, _reply: &NSAppleEventDescriptor) {
+                let url = unsafe { event.paramDescriptorForKeyword(KEY_DIRECT_OBJECT) }
+                    .and_then(|descriptor| unsafe { descriptor.stringValue() });
+                let Some(url) = url else {
+                    warn!("⚠️ 收到没有链接的 URL 事件");
+                    return;
+                };
+                let result = self.ivars().send_event(UserEvent::OpenUrl(url.to_string()));
+                if let Err(e) = result.context(EventLoopSendSnafu) {
+                    error!("Failed to send URL event to event loop: {}", e);
+                }
+            }
+        }
+    );
+
+    impl UrlEventHandler {
+        pub fn new(proxy: EventLoopProxy<UserEvent>) -> Retained<Self> {
+            let this = Self::alloc().set_ivars(proxy);
+            unsafe { msg_send_id![super(this), init] }
+        }
+    }
+}
+
+// 注册 timeticker:// 链接的 Apple Event 处理器；处理器在程序运行期间一直有效
+#[cfg(target_os = "macos")]
+fn observe_url_events(proxy: winit::event_loop::EventLoopProxy<UserEvent>) {
+    use objc2::sel;
+    use objc2_foundation::NSAppleEventManager;
+
+    const INTERNET_EVENT_CLASS: u32 = u32::from_be_bytes(*b"GURL"); // kInternetEventClass
+    const GET_URL: u32 = u32::from_be_bytes(*b"GURL"); // kAEGetURL
+
+    let handler = url_event_handler::UrlEventHandler::new(proxy);
+    unsafe {
+        NSAppleEventManager::sharedAppleEventManager().setEventHandler_andSelector_forEventClass_andEventID(
+            &handler,
+            sel!(handleGetURLEvent:withReplyEvent:),
+            INTERNET_EVENT_CLASS,
+            GET_URL,
+        );
+    }
+    std::mem::forget(handler);
+}
+
+// 用 PNG 数据设置 Dock 图标，用于绘制的倒计时图标
+#[cfg(target_os = "macos")]
+fn set_dock_image(png: &[u8]) -> Result<()> {
+    let mtm = MainThreadMarker::new().context(MainThreadMarkerSnafu)?;
+    let app = NSApplication::sharedApplication(mtm);
+    let data = NSData::with_bytes(png);
+    unsafe {
+        match NSImage::initWithData(NSImage::alloc(), &data) {
+            Some(image) => app.setApplicationIconImage(Some(&image)),
+            None => warn!("⚠️ 无法解码绘制的 Dock 图标"),
+        }
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "macos")]
 fn set_default_dock_icon() -> Result<()> {
     unsafe {
@@ -1545,3 +3952,53 @@ fn set_default_dock_icon() -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64, nanos: u32) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::new(secs, nanos)
+    }
+
+    #[test]
+    fn ticks_wait_until_the_next_whole_second() {
+        assert_eq!(until_next_second(at(1_700_000_000, 250_000_000)), Duration::from_millis(750));
+        assert_eq!(until_next_second(at(1_700_000_000, 999_999_999)), Duration::from_nanos(1));
+        assert_eq!(until_next_second(at(1_700_000_000, 1)), Duration::from_nanos(999_999_999));
+    }
+
+    #[test]
+    fn ticks_on_a_second_boundary_wait_a_full_second() {
+        assert_eq!(until_next_second(at(1_700_000_000, 0)), TICK_INTERVAL);
+        assert_eq!(until_next_second(SystemTime::UNIX_EPOCH), TICK_INTERVAL);
+    }
+
+    #[test]
+    fn gaps_count_as_sleep_only_past_the_threshold() {
+        let last = at(1_700_000_000, 0);
+        let just_under = last + WAKE_GAP_THRESHOLD - Duration::from_millis(1);
+        assert_eq!(suspended_gap(Some(last), just_under), None);
+        assert_eq!(suspended_gap(Some(last), last + WAKE_GAP_THRESHOLD), None);
+        let just_over = WAKE_GAP_THRESHOLD + Duration::from_millis(1);
+        assert_eq!(suspended_gap(Some(last), last + just_over), Some(just_over - TICK_INTERVAL));
+        assert_eq!(suspended_gap(Some(last), last + 10 * TICK_INTERVAL), Some(9 * TICK_INTERVAL));
+    }
+
+    #[test]
+    fn first_tick_and_clock_changes_are_not_sleep() {
+        let now = at(1_700_000_000, 0);
+        assert_eq!(suspended_gap(None, now), None);
+        assert_eq!(suspended_gap(Some(now + Duration::from_secs(60)), now), None);
+    }
+
+    #[test]
+    fn pinned_tray_ids_map_back_to_tasks() {
+        let id = TaskId::next();
+        assert_eq!(pinned_task_id(&pinned_tray_id(id)), Some(id));
+        // 主图标和缺少任务 id 的图标不对应任何任务
+        for other in [MAIN_TRAY_ID, "pinned_", "pinned_x", "pinned"] {
+            assert_eq!(pinned_task_id(&TrayIconId::new(other)), None, "{other}");
+        }
+    }
+}
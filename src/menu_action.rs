@@ -0,0 +1,325 @@
+use std::time::Duration;
+
+use crate::{
+    config::Config,
+    task::{MoveDirection, TaskId, TaskOrder, TimeFormat},
+};
+
+// 设置菜单中保存在配置文件里的开关
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    HideDockOnLaunch,
+    NotifyOnFinish,
+    SoundEnabled,
+    DoNotDisturb,
+    PinnedDrawnIcon,
+    LaunchAtLogin,
+    SingleRunningTask,
+}
+
+impl Setting {
+    // 配置文件中的字段名，用于日志
+    pub fn key(self) -> &'static str {
+        match self {
+            Setting::HideDockOnLaunch => "hide_dock_on_launch",
+            Setting::NotifyOnFinish => "notify_on_finish",
+            Setting::SoundEnabled => "sound_enabled",
+            Setting::DoNotDisturb => "do_not_disturb",
+            Setting::PinnedDrawnIcon => "pinned_drawn_icon",
+            Setting::LaunchAtLogin => "launch_at_login",
+            Setting::SingleRunningTask => "single_running_task",
+        }
+    }
+
+    pub fn enabled(self, config: &Config) -> bool {
+        *self.field(config)
+    }
+
+    // 切换配置中对应的开关，返回切换后的值
+    pub fn toggle(self, config: &mut Config) -> bool {
+        let field = self.field_mut(config);
+        *field = !*field;
+        *field
+    }
+
+    fn field(self, config: &Config) -> &bool {
+        match self {
+            Setting::HideDockOnLaunch => &config.hide_dock_on_launch,
+            Setting::NotifyOnFinish => &config.notify_on_finish,
+            Setting::SoundEnabled => &config.sound_enabled,
+            Setting::DoNotDisturb => &config.do_not_disturb,
+            Setting::PinnedDrawnIcon => &config.pinned_drawn_icon,
+            Setting::LaunchAtLogin => &config.launch_at_login,
+            Setting::SingleRunningTask => &config.single_running_task,
+        }
+    }
+
+    fn field_mut(self, config: &mut Config) -> &mut bool {
+        match self {
+            Setting::HideDockOnLaunch => &mut config.hide_dock_on_launch,
+            Setting::NotifyOnFinish => &mut config.notify_on_finish,
+            Setting::SoundEnabled => &mut config.sound_enabled,
+            Setting::DoNotDisturb => &mut config.do_not_disturb,
+            Setting::PinnedDrawnIcon => &mut config.pinned_drawn_icon,
+            Setting::LaunchAtLogin => &mut config.launch_at_login,
+            Setting::SingleRunningTask => &mut config.single_running_task,
+        }
+    }
+}
+
+// 时间格式作用的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatTarget {
+    Menu,  // 菜单、提示和通知
+    Title, // 托盘和固定图标标题
+}
+
+// 菜单项点击后执行的动作，在构建菜单时与菜单ID一起登记
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Quit,
+    Undo,
+    PauseAll,
+    ResumeAll,
+    ClearFinished,
+    CopyAll,
+    ExportTasks,
+    OpenLogDir,
+    About,
+    CopyDiagnostics,
+    NewTask,
+    DockShow,
+    DockHide,
+    DockReloadIcon,
+    Preset(usize), // 预设在有效预设列表中的位置
+    Recent(usize), // 最近使用的输入的位置
+    ToggleSetting(Setting),
+    SetOrder(TaskOrder),
+    SetMaxPinned(usize),
+    SetTimeFormat(FormatTarget, TimeFormat),
+    Toggle(TaskId), // 开始/暂停，主菜单和固定菜单共用
+    Reset(TaskId),
+    Chain(TaskId, TaskId), // 任务完成后自动开始第二个任务
+    Unchain(TaskId),
+    Snooze(TaskId, Duration),
+    Edit(TaskId),
+    Move(TaskId, MoveDirection),
+    Mute(TaskId),
+    Nag(TaskId),
+    Ack(TaskId),
+    CopyTask(TaskId),
+    Duplicate(TaskId),
+    Archive(TaskId),
+    Restore(TaskId),
+    Delete(TaskId),
+    RestoreTrash(TaskId), // 回收站中的任务，按删除时的 id 查找
+    DeleteTrash(TaskId),
+    EmptyTrash,
+    Pin(TaskId),   // 主菜单中的固定勾选项，切换固定状态
+    Unpin(TaskId), // 固定菜单中的勾选项，总是取消固定
+    SetIcon(TaskId),
+    ClearIcon(TaskId),
+}
+
+impl MenuAction {
+    // 动作针对的任务，用于日志
+    pub fn task_id(self) -> Option<TaskId> {
+        match self {
+            MenuAction::Toggle(id)
+            | MenuAction::Reset(id)
+            | MenuAction::Chain(id, _)
+            | MenuAction::Unchain(id)
+            | MenuAction::Snooze(id, _)
+            | MenuAction::Edit(id)
+            | MenuAction::Move(id, _)
+            | MenuAction::Mute(id)
+            | MenuAction::Nag(id)
+            | MenuAction::Ack(id)
+            | MenuAction::CopyTask(id)
+            | MenuAction::Duplicate(id)
+            | MenuAction::Archive(id)
+            | MenuAction::Restore(id)
+            | MenuAction::Delete(id)
+            | MenuAction::RestoreTrash(id)
+            | MenuAction::DeleteTrash(id)
+            | MenuAction::Pin(id)
+            | MenuAction::Unpin(id)
+            | MenuAction::SetIcon(id)
+            | MenuAction::ClearIcon(id) => Some(id),
+            MenuAction::Quit
+            | MenuAction::Undo
+            | MenuAction::PauseAll
+            | MenuAction::ResumeAll
+            | MenuAction::ClearFinished
+            | MenuAction::EmptyTrash
+            | MenuAction::CopyAll
+            | MenuAction::ExportTasks
+            | MenuAction::OpenLogDir
+            | MenuAction::About
+            | MenuAction::CopyDiagnostics
+            | MenuAction::NewTask
+            | MenuAction::DockShow
+            | MenuAction::DockHide
+            | MenuAction::DockReloadIcon
+            | MenuAction::Preset(_)
+            | MenuAction::Recent(_)
+            | MenuAction::ToggleSetting(_)
+            | MenuAction::SetOrder(_)
+            | MenuAction::SetMaxPinned(_)
+            | MenuAction::SetTimeFormat(..) => None,
+        }
+    }
+
+    // 动作是否会修改任务：只有这些动作需要停止闪烁并保存任务，
+    // 打开对话框或修改设置的动作不需要
+    pub fn changes_tasks(self) -> bool {
+        match self {
+            MenuAction::Undo
+            | MenuAction::PauseAll
+            | MenuAction::ResumeAll
+            | MenuAction::ClearFinished
+            | MenuAction::Preset(_)
+            | MenuAction::Recent(_)
+            | MenuAction::Toggle(_)
+            | MenuAction::Reset(_)
+            | MenuAction::Chain(..)
+            | MenuAction::Unchain(_)
+            | MenuAction::Snooze(..)
+            | MenuAction::Move(..)
+            | MenuAction::Mute(_)
+            | MenuAction::Nag(_)
+            | MenuAction::Ack(_)
+            | MenuAction::Duplicate(_)
+            | MenuAction::Archive(_)
+            | MenuAction::Restore(_)
+            | MenuAction::Delete(_)
+            | MenuAction::RestoreTrash(_)
+            | MenuAction::DeleteTrash(_)
+            | MenuAction::EmptyTrash
+            | MenuAction::Pin(_)
+            | MenuAction::Unpin(_)
+            | MenuAction::SetIcon(_)
+            | MenuAction::ClearIcon(_) => true,
+            // 新建和编辑在输入完成后才修改任务，届时另行保存
+            MenuAction::Quit
+            | MenuAction::CopyAll
+            | MenuAction::ExportTasks
+            | MenuAction::OpenLogDir
+            | MenuAction::About
+            | MenuAction::CopyDiagnostics
+            | MenuAction::NewTask
+            | MenuAction::Edit(_)
+            | MenuAction::CopyTask(_)
+            | MenuAction::DockShow
+            | MenuAction::DockHide
+            | MenuAction::DockReloadIcon
+            | MenuAction::ToggleSetting(_)
+            | MenuAction::SetOrder(_)
+            | MenuAction::SetMaxPinned(_)
+            | MenuAction::SetTimeFormat(..) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 针对一个任务的全部动作
+    fn task_actions(id: TaskId) -> Vec<MenuAction> {
+        vec![
+            MenuAction::Toggle(id),
+            MenuAction::Reset(id),
+            MenuAction::Chain(id, TaskId::next()),
+            MenuAction::Unchain(id),
+            MenuAction::Snooze(id, Duration::from_secs(300)),
+            MenuAction::Edit(id),
+            MenuAction::Move(id, MoveDirection::Up),
+            MenuAction::Mute(id),
+            MenuAction::Nag(id),
+            MenuAction::Ack(id),
+            MenuAction::CopyTask(id),
+            MenuAction::Duplicate(id),
+            MenuAction::Archive(id),
+            MenuAction::Restore(id),
+            MenuAction::Delete(id),
+            MenuAction::Pin(id),
+            MenuAction::Unpin(id),
+            MenuAction::SetIcon(id),
+            MenuAction::ClearIcon(id),
+        ]
+    }
+
+    #[test]
+    fn task_actions_report_their_task() {
+        let id = TaskId::next();
+        // 后续任务和推迟的时长不会被当成任务 id
+        for action in task_actions(id) {
+            assert_eq!(action.task_id(), Some(id), "{action:?}");
+        }
+        for action in [
+            MenuAction::Quit,
+            MenuAction::Preset(3),
+            MenuAction::Recent(1),
+            MenuAction::SetMaxPinned(5),
+            MenuAction::SetOrder(TaskOrder::Manual),
+            MenuAction::SetTimeFormat(FormatTarget::Title, TimeFormat::Compact),
+            MenuAction::ToggleSetting(Setting::DoNotDisturb),
+        ] {
+            assert_eq!(action.task_id(), None, "{action:?}");
+        }
+    }
+
+    #[test]
+    fn only_task_changes_are_saved() {
+        let id = TaskId::next();
+        let opens_dialog = [MenuAction::Edit(id), MenuAction::CopyTask(id)];
+        for action in task_actions(id) {
+            assert_eq!(action.changes_tasks(), !opens_dialog.contains(&action), "{action:?}");
+        }
+        for action in [MenuAction::Undo, MenuAction::PauseAll, MenuAction::ClearFinished, MenuAction::Preset(0)] {
+            assert!(action.changes_tasks(), "{action:?}");
+        }
+        for action in [
+            MenuAction::Quit,
+            MenuAction::NewTask,
+            MenuAction::About,
+            MenuAction::DockHide,
+            MenuAction::ToggleSetting(Setting::SoundEnabled),
+            MenuAction::SetTimeFormat(FormatTarget::Menu, TimeFormat::Full),
+        ] {
+            assert!(!action.changes_tasks(), "{action:?}");
+        }
+    }
+
+    // 配置按 TOML 表比较，找出哪些字段不同
+    fn changed_keys(before: &Config, after: &Config) -> Vec<String> {
+        let before = toml::Value::try_from(before).unwrap();
+        let after = toml::Value::try_from(after).unwrap();
+        let (before, after) = (before.as_table().unwrap(), after.as_table().unwrap());
+        after.iter().filter(|(key, value)| before.get(*key) != Some(value)).map(|(key, _)| key.clone()).collect()
+    }
+
+    #[test]
+    fn settings_round_trip_through_the_config() {
+        for setting in [
+            Setting::HideDockOnLaunch,
+            Setting::NotifyOnFinish,
+            Setting::SoundEnabled,
+            Setting::DoNotDisturb,
+            Setting::PinnedDrawnIcon,
+            Setting::LaunchAtLogin,
+            Setting::SingleRunningTask,
+        ] {
+            let original = Config::default();
+            let mut config = original.clone();
+            let enabled = setting.toggle(&mut config);
+            assert_eq!(enabled, !setting.enabled(&original), "{setting:?}");
+            assert_eq!(setting.enabled(&config), enabled, "{setting:?}");
+            // 只修改以 key 命名的字段
+            assert_eq!(changed_keys(&original, &config), [setting.key()], "{setting:?}");
+            assert_eq!(setting.toggle(&mut config), !enabled, "{setting:?}");
+            assert_eq!(config, original, "{setting:?}");
+        }
+    }
+}
@@ -0,0 +1,1191 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use tray_icon::menu::accelerator::Accelerator;
+
+use crate::{
+    accelerator::Accelerators,
+    config::{Config, Preset},
+    error::Result,
+    i18n::{tr, tr_args},
+    icon::Platform,
+    menu_action::{FormatTarget, MenuAction, Setting},
+    recent::RecentInputs,
+    stats,
+    task::{self, MoveDirection, SummaryStyle, Task, TaskId, TaskList, TaskOrder, TaskState, TaskType, TimeFormat},
+};
+
+pub const SNOOZE_MINUTES: [u64; 2] = [5, 10]; // 任务菜单中推迟按钮的分钟数
+pub const MAX_PINNED_OPTIONS: [usize; 5] = [1, 2, 3, 5, 8]; // 设置菜单中可选的固定数量上限
+
+// 菜单中的一项，只描述要显示什么，由 Application::render_menu 转换为 muda 菜单
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuEntry {
+    Item {
+        label: String,
+        enabled: bool,
+        action: Option<MenuAction>, // 没有动作的项只用于显示
+        accelerator: Option<Accelerator>,
+    },
+    Check {
+        label: String,
+        enabled: bool,
+        checked: bool,
+        action: MenuAction,
+    },
+    Submenu {
+        label: String,
+        task: Option<TaskId>, // 任务子菜单，刷新时按 id 更新标题
+        children: Vec<MenuEntry>,
+    },
+    Separator,
+}
+
+impl MenuEntry {
+    // 可点击的菜单项
+    pub fn action(label: impl Into<String>, action: MenuAction) -> Self {
+        MenuEntry::Item {
+            label: label.into(),
+            enabled: true,
+            action: Some(action),
+            accelerator: None,
+        }
+    }
+
+    // 不可点击、只显示文本的菜单项
+    pub fn text(label: impl Into<String>) -> Self {
+        MenuEntry::Item {
+            label: label.into(),
+            enabled: false,
+            action: None,
+            accelerator: None,
+        }
+    }
+
+    pub fn check(label: impl Into<String>, checked: bool, action: MenuAction) -> Self {
+        MenuEntry::Check {
+            label: label.into(),
+            enabled: true,
+            checked,
+            action,
+        }
+    }
+
+    pub fn submenu(label: impl Into<String>, children: Vec<MenuEntry>) -> Self {
+        MenuEntry::Submenu {
+            label: label.into(),
+            task: None,
+            children,
+        }
+    }
+
+    // 设置菜单项是否可用，对分隔线和子菜单无效
+    pub fn enabled(mut self, value: bool) -> Self {
+        if let MenuEntry::Item { enabled, .. } | MenuEntry::Check { enabled, .. } = &mut self {
+            *enabled = value;
+        }
+        self
+    }
+
+    pub fn with_accelerator(mut self, value: Option<Accelerator>) -> Self {
+        if let MenuEntry::Item { accelerator, .. } = &mut self {
+            *accelerator = value;
+        }
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            MenuEntry::Item { label, .. } | MenuEntry::Check { label, .. } | MenuEntry::Submenu { label, .. } => label,
+            MenuEntry::Separator => "separator",
+        }
+    }
+}
+
+// 菜单结构不变时对单个菜单项的原地修改
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuChange {
+    Text(String),
+    Enabled(bool),
+    Checked(bool),
+}
+
+// index 是菜单项按先序遍历的位置，与渲染时保存菜单项句柄的顺序一致
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuUpdate {
+    pub index: usize,
+    pub change: MenuChange,
+}
+
+// 比较两次生成的菜单：菜单项的种类、动作、快捷键和子菜单层级都相同时，
+// 返回需要原地更新的文本、可用状态和勾选状态；结构不同时返回 None，需要整体重建
+pub fn diff(old: &[MenuEntry], new: &[MenuEntry]) -> Option<Vec<MenuUpdate>> {
+    let mut updates = Vec::new();
+    let mut index = 0;
+    diff_entries(old, new, &mut index, &mut updates).then_some(updates)
+}
+
+fn diff_entries(old: &[MenuEntry], new: &[MenuEntry], index: &mut usize, updates: &mut Vec<MenuUpdate>) -> bool {
+    if old.len() != new.len() {
+        return false;
+    }
+    for (old, new) in old.iter().zip(new) {
+        let position = *index;
+        *index += 1;
+        let mut push = |change| updates.push(MenuUpdate {
+            index: position,
+            change,
+        });
+        match (old, new) {
+            (
+                MenuEntry::Item {
+                    label: old_label,
+                    enabled: old_enabled,
+                    action: old_action,
+                    accelerator: old_accelerator,
+                },
+                MenuEntry::Item {
+                    label,
+                    enabled,
+                    action,
+                    accelerator,
+                },
+            ) => {
+                if old_action != action || old_accelerator != accelerator {
+                    return false;
+                }
+                if old_label != label {
+                    push(MenuChange::Text(label.clone()));
+                }
+                if old_enabled != enabled {
+                    push(MenuChange::Enabled(*enabled));
+                }
+            }
+            (
+                MenuEntry::Check {
+                    label: old_label,
+                    enabled: old_enabled,
+                    checked: old_checked,
+                    action: old_action,
+                },
+                MenuEntry::Check {
+                    label,
+                    enabled,
+                    checked,
+                    action,
+                },
+            ) => {
+                if old_action != action {
+                    return false;
+                }
+                if old_label != label {
+                    push(MenuChange::Text(label.clone()));
+                }
+                if old_enabled != enabled {
+                    push(MenuChange::Enabled(*enabled));
+                }
+                if old_checked != checked {
+                    push(MenuChange::Checked(*checked));
+                }
+            }
+            (
+                MenuEntry::Submenu {
+                    label: old_label,
+                    task: old_task,
+                    children: old_children,
+                },
+                MenuEntry::Submenu { label, task, children },
+            ) => {
+                if old_task != task {
+                    return false;
+                }
+                if old_label != label {
+                    push(MenuChange::Text(label.clone()));
+                }
+                if !diff_entries(old_children, children, index, updates) {
+                    return false;
+                }
+            }
+            (MenuEntry::Separator, MenuEntry::Separator) => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+// 生成主菜单需要的全部状态
+pub struct MenuInput<'a> {
+    pub tasks: &'a TaskList,
+    pub config: &'a Config,
+    pub accelerators: &'a Accelerators,
+    pub presets: &'a [Preset],
+    pub recent_inputs: &'a RecentInputs,
+    pub stats: &'a stats::Summary,
+    pub platform: Platform,
+    pub now: SystemTime,
+}
+
+// 主菜单的内容：任务分段、新建、撤销、批量操作、统计、设置、关于和退出
+pub fn main_menu(input: &MenuInput) -> Result<Vec<MenuEntry>> {
+    let MenuInput {
+        tasks,
+        config,
+        accelerators,
+        ..
+    } = *input;
+    let mut entries = task_entries(input)?;
+    entries.push(MenuEntry::Separator);
+
+    entries.push(MenuEntry::action(tr("menu.new_task"), MenuAction::NewTask).with_accelerator(accelerators.new_task));
+
+    // 快速添加预设任务，不弹出输入框
+    if !input.presets.is_empty() {
+        let presets = input
+            .presets
+            .iter()
+            .enumerate()
+            .map(|(index, preset)| MenuEntry::action(&preset.label, MenuAction::Preset(index)))
+            .collect();
+        entries.push(MenuEntry::submenu(tr("menu.presets"), presets));
+    }
+
+    // 最近使用的输入，点击后直接创建任务
+    if !input.recent_inputs.is_empty() {
+        let recent = input
+            .recent_inputs
+            .iter()
+            .enumerate()
+            .map(|(index, recent)| MenuEntry::action(recent, MenuAction::Recent(index)))
+            .collect();
+        entries.push(MenuEntry::submenu(tr("menu.recent"), recent));
+    }
+
+    // 撤销，没有可撤销的操作时禁用
+    entries.push(MenuEntry::action(tr("menu.undo"), MenuAction::Undo).enabled(tasks.can_undo()));
+
+    // 全部暂停/全部继续
+    let any_running = tasks.running().any(|t| matches!(t.task_type, TaskType::Duration(_)));
+    entries.push(
+        MenuEntry::action(tr("menu.pause_all"), MenuAction::PauseAll)
+            .enabled(any_running)
+            .with_accelerator(accelerators.pause_all),
+    );
+    entries.push(MenuEntry::action(tr("menu.resume_all"), MenuAction::ResumeAll).enabled(tasks.can_resume_all()));
+
+    // 勿扰模式：暂停通知、提示音、webhook 和图标闪烁
+    entries.push(MenuEntry::check(
+        tr("menu.do_not_disturb"),
+        config.do_not_disturb,
+        MenuAction::ToggleSetting(Setting::DoNotDisturb),
+    ));
+
+    entries.push(MenuEntry::action(tr("menu.copy_all"), MenuAction::CopyAll));
+
+    // 清除已完成，显示可清除的数量
+    let clear_count = tasks.finished_to_clear(config.clear_pinned_finished).len();
+    entries.push(
+        MenuEntry::action(
+            tr_args("menu.clear_finished", &[("count", &clear_count.to_string())]),
+            MenuAction::ClearFinished,
+        )
+        .enabled(clear_count > 0),
+    );
+
+    entries.push(stats_submenu(input.stats, input.config));
+    entries.push(settings_submenu(config, input.platform));
+
+    // 关于和诊断信息，提交问题时使用
+    entries.push(MenuEntry::action(tr("menu.about"), MenuAction::About));
+    entries.push(MenuEntry::action(tr("menu.copy_diagnostics"), MenuAction::CopyDiagnostics));
+
+    entries.push(MenuEntry::Separator);
+    entries.push(MenuEntry::action(tr("menu.quit"), MenuAction::Quit).with_accelerator(accelerators.quit));
+
+    Ok(entries)
+}
+
+// 按状态分段的任务子菜单，每段前有不可点击的标题，同一段中同一分组的任务合并到分组子菜单；
+// 超过顶层上限的任务按同样的分段放入“更多任务”子菜单，已归档的任务显示在最后的归档子菜单中
+fn task_entries(input: &MenuInput) -> Result<Vec<MenuEntry>> {
+    let MenuInput { tasks, config, .. } = *input;
+    let sections = tasks.sections(config.task_order);
+    let task_count: usize = sections.iter().map(|(_, section_tasks)| section_tasks.len()).sum();
+    let limit = match config.menu_max_tasks {
+        0 => usize::MAX,
+        max => max,
+    };
+    let mut entries = Vec::with_capacity(sections.len() + task_count.min(limit) + 2);
+    let mut overflow = Vec::new();
+    let mut task_index = 0; // 任务在菜单中的位置，前九个任务的开始/暂停有数字快捷键
+    for (state, section_tasks) in sections {
+        // 只有超出上限的部分进入“更多任务”，同一段可能被拆成两部分
+        let shown = section_tasks.len().min(limit.saturating_sub(task_index));
+        let (top, rest) = section_tasks.split_at(shown);
+        for (target, section_tasks) in [(&mut entries, top), (&mut overflow, rest)] {
+            if section_tasks.is_empty() {
+                continue;
+            }
+            target.push(MenuEntry::text(state.section_title()));
+            push_section(target, section_tasks, &mut task_index, input)?;
+        }
+    }
+    if !overflow.is_empty() {
+        let hidden = task_count - limit;
+        entries.push(MenuEntry::submenu(
+            tr_args("menu.more_tasks", &[("count", &hidden.to_string())]),
+            overflow,
+        ));
+    }
+
+    // 已归档的任务，可以恢复或删除到回收站
+    let archived: Vec<&Task> = tasks.iter().filter(|t| t.archived).collect();
+    if !archived.is_empty() {
+        let children = archived
+            .iter()
+            .map(|task| {
+                MenuEntry::submenu(&task.name, vec![
+                    MenuEntry::action(tr("menu.restore"), MenuAction::Restore(task.id)),
+                    MenuEntry::action(tr("menu.delete"), MenuAction::Delete(task.id)),
+                ])
+            })
+            .collect();
+        entries.push(MenuEntry::submenu(
+            tr_args("menu.archived", &[("count", &archived.len().to_string())]),
+            children,
+        ));
+    }
+
+    // 回收站中的任务，按删除时间从新到旧排列，可以恢复或彻底删除
+    let trash = tasks.trash();
+    if !trash.is_empty() {
+        let mut children: Vec<MenuEntry> = trash
+            .iter()
+            .rev()
+            .map(|(task, deleted_at)| {
+                let deleted_at: DateTime<Local> = (*deleted_at).into();
+                let label = format!("{} · {}", task.name, deleted_at.format("%m-%d %H:%M"));
+                MenuEntry::submenu(label, vec![
+                    MenuEntry::action(tr("menu.restore"), MenuAction::RestoreTrash(task.id)),
+                    MenuEntry::action(tr("menu.delete_forever"), MenuAction::DeleteTrash(task.id)),
+                ])
+            })
+            .collect();
+        children.push(MenuEntry::Separator);
+        children.push(MenuEntry::action(tr("menu.empty_trash"), MenuAction::EmptyTrash));
+        entries.push(MenuEntry::submenu(
+            tr_args("menu.trash", &[("count", &trash.len().to_string())]),
+            children,
+        ));
+    }
+    Ok(entries)
+}
+
+// 一段中的任务子菜单，同一分组的任务合并到分组子菜单
+fn push_section(
+    entries: &mut Vec<MenuEntry>,
+    section_tasks: &[&Task],
+    task_index: &mut usize,
+    input: &MenuInput,
+) -> Result<()> {
+    let accelerators = input.accelerators;
+    let mut groups: Vec<(&str, usize)> = Vec::new(); // 分组名到分组子菜单的位置，只在本段内合并
+    for task in section_tasks {
+        let submenu = task_submenu(task, input, accelerators.toggle_task(*task_index), input.now)?;
+        *task_index += 1;
+        let Some(group) = &task.group else {
+            entries.push(submenu);
+            continue;
+        };
+        let position = match groups.iter().find(|(name, _)| *name == group.as_str()) {
+            Some((_, position)) => *position,
+            None => {
+                entries.push(MenuEntry::submenu(format!("📁 {group}"), Vec::new()));
+                groups.push((group.as_str(), entries.len() - 1));
+                entries.len() - 1
+            }
+        };
+        if let MenuEntry::Submenu { children, .. } = &mut entries[position] {
+            children.push(submenu);
+        }
+    }
+    Ok(())
+}
+
+// 一个任务的子菜单，标题显示进度和剩余时间
+fn task_submenu(
+    task: &Task,
+    input: &MenuInput,
+    toggle_accelerator: Option<Accelerator>,
+    now: SystemTime,
+) -> Result<MenuEntry> {
+    let MenuInput { tasks, config, .. } = *input;
+    let id = task.id;
+    let mut children = Vec::new();
+
+    // 持续提醒中的任务在最上方显示确认项
+    if task.needs_alert() {
+        children.push(MenuEntry::action(tr("menu.ack"), MenuAction::Ack(id)));
+    }
+
+    // 截止时间或预计结束时间，重建菜单时更新
+    if let Some(end_label) = task.end_time_label(now)? {
+        children.push(MenuEntry::text(end_label));
+    }
+
+    // 推迟，只在已完成或已过截止时间时可用
+    children.extend(snooze_entries(id, task.can_snooze(now)));
+
+    // 截止时间类型任务不需要开始/暂停/重置
+    if let TaskType::Duration(_) = task.task_type {
+        children.push(
+            MenuEntry::action(start_pause_label(task.is_running), MenuAction::Toggle(id))
+                .with_accelerator(toggle_accelerator),
+        );
+        children.push(MenuEntry::action(tr("menu.reset"), MenuAction::Reset(id)));
+        children.push(chain_submenu(task, tasks));
+    }
+
+    children.push(MenuEntry::Separator);
+    children.push(MenuEntry::action(tr("menu.new"), MenuAction::NewTask));
+    children.push(MenuEntry::action(tr("menu.edit"), MenuAction::Edit(id)));
+
+    // 上移/下移，只在手动排序时显示，分段边界处禁用
+    if config.task_order == TaskOrder::Manual {
+        for (label, direction) in [
+            (tr("menu.move_up"), MoveDirection::Up),
+            (tr("menu.move_down"), MoveDirection::Down),
+        ] {
+            let item = MenuEntry::action(label, MenuAction::Move(id, direction));
+            children.push(item.enabled(tasks.can_move(id, direction)));
+        }
+    }
+
+    // 勾选项：静音、持续提醒
+    let toggles = task.toggles();
+    children.push(MenuEntry::check(tr("menu.mute"), toggles.muted, MenuAction::Mute(id)));
+    children.push(MenuEntry::check(tr("menu.nag"), toggles.nag, MenuAction::Nag(id)));
+
+    children.push(MenuEntry::action(tr("menu.copy_remaining"), MenuAction::CopyTask(id)));
+    children.push(MenuEntry::action(tr("menu.duplicate"), MenuAction::Duplicate(id)));
+
+    // 归档（仅已完成的任务）
+    if task.state() == TaskState::Finished {
+        children.push(MenuEntry::action(tr("menu.archive"), MenuAction::Archive(id)));
+    }
+    children.push(MenuEntry::action(tr("menu.delete"), MenuAction::Delete(id)));
+    children.push(MenuEntry::check(tr("menu.pin"), toggles.pinned, MenuAction::Pin(id)));
+
+    // 固定图标使用的图片
+    children.push(MenuEntry::action(tr("menu.set_icon"), MenuAction::SetIcon(id)));
+    if task.icon_path.is_some() {
+        children.push(MenuEntry::action(tr("menu.clear_icon"), MenuAction::ClearIcon(id)));
+    }
+
+    Ok(MenuEntry::Submenu {
+        label: task_title(task, config, now)?,
+        task: Some(id),
+        children,
+    })
+}
+
+// 完成后自动开始的任务，可选其他未归档的时间段任务
+fn chain_submenu(task: &Task, tasks: &TaskList) -> MenuEntry {
+    let checked = |label: &str, selected: bool| {
+        if selected {
+            format!("✓ {label}")
+        } else {
+            label.to_string()
+        }
+    };
+    let mut children = vec![MenuEntry::action(
+        checked(tr("menu.no_chain"), task.next.is_none()),
+        MenuAction::Unchain(task.id),
+    )];
+    for other in tasks.iter() {
+        if other.id == task.id || other.archived || !matches!(other.task_type, TaskType::Duration(_)) {
+            continue;
+        }
+        children.push(MenuEntry::action(
+            checked(&other.name, task.next == Some(other.id)),
+            MenuAction::Chain(task.id, other.id),
+        ));
+    }
+    MenuEntry::submenu(tr("menu.chain"), children)
+}
+
+// 每个推迟时长一个按钮
+pub fn snooze_entries(id: TaskId, enabled: bool) -> Vec<MenuEntry> {
+    SNOOZE_MINUTES
+        .iter()
+        .map(|minutes| {
+            let label = tr_args("menu.snooze", &[("minutes", &minutes.to_string())]);
+            MenuEntry::action(
+                label,
+                MenuAction::Snooze(id, Duration::from_secs(minutes * 60)),
+            )
+            .enabled(enabled)
+        })
+        .collect()
+}
+
+// 统计子菜单：今天和本周的用时，以及本周用时最多的任务；只在重建菜单时更新
+fn stats_submenu(summary: &stats::Summary, config: &Config) -> MenuEntry {
+    let format = |time| task::format_remaining_time(time, task::time_format());
+    let mut children = vec![
+        MenuEntry::text(tr_args("stats.today", &[("time", &format(summary.today))])),
+        MenuEntry::text(tr_args("stats.week", &[("time", &format(summary.week))])),
+        MenuEntry::Separator,
+    ];
+    if summary.top.is_empty() {
+        children.push(MenuEntry::text(tr("stats.empty")));
+    }
+    for (rank, (name, time)) in summary.top.iter().enumerate() {
+        children.push(MenuEntry::text(format!("{}. {} {}", rank + 1, name, format(*time))));
+    }
+
+    // 分组预算：已用/预算，用完的分组加标记
+    if !summary.budgets.is_empty() {
+        children.push(MenuEntry::Separator);
+        children.push(MenuEntry::text(tr("stats.budgets")));
+    }
+    for budget in &summary.budgets {
+        let bar = if config.progress_bar_width == 0 {
+            String::new()
+        } else {
+            let bar = task::progress_bar(
+                budget.fraction(),
+                config.progress_bar_width,
+                config.progress_bar_filled,
+                config.progress_bar_empty,
+            );
+            format!(" {}", bar)
+        };
+        let mark = if budget.exhausted() { " ⛔" } else { "" };
+        children.push(MenuEntry::text(format!(
+            "{}{} {} / {}{}",
+            budget.tag,
+            bar,
+            format(budget.used),
+            format(budget.budget),
+            mark
+        )));
+    }
+    MenuEntry::submenu(tr("stats.title"), children)
+}
+
+// 设置子菜单
+fn settings_submenu(config: &Config, platform: Platform) -> MenuEntry {
+    let mut children = Vec::new();
+
+    // Dock 设置，只有 macOS 有 Dock
+    if platform == Platform::MacOs {
+        children.push(MenuEntry::submenu(tr("dock.title"), vec![
+            MenuEntry::action(tr("dock.show"), MenuAction::DockShow),
+            MenuEntry::action(tr("dock.hide"), MenuAction::DockHide),
+            MenuEntry::Separator,
+            MenuEntry::action(tr("dock.reload_icon"), MenuAction::DockReloadIcon),
+        ]));
+        children.push(MenuEntry::Separator);
+    }
+
+    // 保存在配置文件中的开关
+    let toggles = [
+        (tr("settings.hide_dock_on_launch"), Setting::HideDockOnLaunch),
+        (tr("settings.notify_on_finish"), Setting::NotifyOnFinish),
+        (tr("settings.sound_enabled"), Setting::SoundEnabled),
+        (tr("settings.launch_at_login"), Setting::LaunchAtLogin),
+        (tr("settings.pinned_drawn_icon"), Setting::PinnedDrawnIcon),
+        (tr("settings.single_running_task"), Setting::SingleRunningTask),
+    ];
+    for (label, setting) in toggles {
+        // Dock 只在 macOS 上存在，不支持标题的平台上只能使用绘制模式
+        if (setting == Setting::HideDockOnLaunch && platform != Platform::MacOs)
+            || (setting == Setting::PinnedDrawnIcon && !platform.supports_tray_title())
+        {
+            continue;
+        }
+        children.push(MenuEntry::check(label, setting.enabled(config), MenuAction::ToggleSetting(setting)));
+    }
+
+    // 任务排序方式
+    let orders = [
+        (tr("settings.order_manual"), TaskOrder::Manual),
+        (tr("settings.order_created"), TaskOrder::Created),
+        (tr("settings.order_remaining"), TaskOrder::Remaining),
+    ]
+    .into_iter()
+    .map(|(label, order)| MenuEntry::check(label, config.task_order == order, MenuAction::SetOrder(order)))
+    .collect();
+    children.push(MenuEntry::submenu(tr("settings.order"), orders));
+
+    // 最多同时固定的任务数
+    let max_pinned = MAX_PINNED_OPTIONS
+        .into_iter()
+        .map(|max| MenuEntry::check(max.to_string(), config.max_pinned == max, MenuAction::SetMaxPinned(max)))
+        .collect();
+    children.push(MenuEntry::submenu(tr("settings.max_pinned"), max_pinned));
+
+    // 菜单和标题分别选择时间格式
+    let format_targets = [
+        (tr("settings.time_format"), FormatTarget::Menu, config.time_format),
+        (
+            tr("settings.title_time_format"),
+            FormatTarget::Title,
+            config.title_time_format,
+        ),
+    ];
+    for (title, target, current) in format_targets {
+        let formats = TimeFormat::ALL
+            .into_iter()
+            .map(|format| {
+                MenuEntry::check(
+                    tr(time_format_label(format)),
+                    current == format,
+                    MenuAction::SetTimeFormat(target, format),
+                )
+            })
+            .collect();
+        children.push(MenuEntry::submenu(title, formats));
+    }
+
+    children.push(MenuEntry::Separator);
+    children.push(MenuEntry::action(tr("settings.export"), MenuAction::ExportTasks));
+    children.push(MenuEntry::action(tr("settings.open_log_dir"), MenuAction::OpenLogDir));
+    MenuEntry::submenu(tr("settings.title"), children)
+}
+
+// 主菜单中任务子菜单的标题：进度条加任务摘要，已完成的任务显示满格和 ✔
+pub fn task_title(task: &Task, config: &Config, now: SystemTime) -> Result<String> {
+    let summary = task.summary_at(SummaryStyle::Menu, now)?;
+    if config.progress_bar_width == 0 {
+        return Ok(summary);
+    }
+    let bar = task::progress_bar(
+        task.progress_at(now)?,
+        config.progress_bar_width,
+        config.progress_bar_filled,
+        config.progress_bar_empty,
+    );
+    let done = if task.state() == TaskState::Finished {
+        " ✔"
+    } else {
+        ""
+    };
+    Ok(format!("{bar}{done} {summary}"))
+}
+
+pub fn start_pause_label(is_running: bool) -> &'static str {
+    tr(if is_running { "menu.pause" } else { "menu.start" })
+}
+
+// 时间格式在设置菜单中的文本键
+fn time_format_label(format: TimeFormat) -> &'static str {
+    match format {
+        TimeFormat::Full => "settings.time_format_full",
+        TimeFormat::Compact => "settings.time_format_compact",
+        TimeFormat::Adaptive => "settings.time_format_adaptive",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    // 生成菜单需要的其他状态，任务之外都用默认值
+    struct Fixture {
+        config: Config,
+        accelerators: Accelerators,
+        recent_inputs: RecentInputs,
+        stats: stats::Summary,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            Self {
+                config: Config::default(),
+                accelerators: Accelerators::default(),
+                recent_inputs: RecentInputs::default(),
+                stats: stats::Summary::default(),
+            }
+        }
+
+        fn menu_at(&self, tasks: &TaskList, now: SystemTime) -> Vec<MenuEntry> {
+            main_menu(&MenuInput {
+                tasks,
+                config: &self.config,
+                accelerators: &self.accelerators,
+                presets: &[],
+                recent_inputs: &self.recent_inputs,
+                stats: &self.stats,
+                platform: Platform::Linux,
+                now,
+            })
+            .unwrap()
+        }
+
+        fn menu(&self, tasks: &TaskList) -> Vec<MenuEntry> {
+            self.menu_at(tasks, SystemTime::now())
+        }
+    }
+
+    // 从指定时长之前开始计时的任务
+    fn started(name: &str, ago: Duration) -> Task {
+        let mut task = Task::new(name.to_string(), TaskType::Duration(10 * MINUTE)).unwrap();
+        task.start();
+        task.start_time = Some(SystemTime::now() - ago);
+        task
+    }
+
+    fn task(name: &str, state: TaskState) -> Task {
+        match state {
+            TaskState::Idle => Task::new(name.to_string(), TaskType::Duration(10 * MINUTE)).unwrap(),
+            TaskState::Running => started(name, Duration::ZERO),
+            TaskState::Paused => {
+                let mut task = started(name, MINUTE);
+                task.pause().unwrap();
+                task
+            }
+            TaskState::Finished => {
+                let mut task = started(name, 10 * MINUTE);
+                task.check_finished().unwrap();
+                task
+            }
+        }
+    }
+
+    fn grouped(name: &str, state: TaskState, group: &str) -> Task {
+        let mut task = task(name, state);
+        task.group = Some(group.to_string());
+        task
+    }
+
+    // 顶层任务区域的简要描述：分段标题、任务名、分组名加组内任务名
+    fn outline(entries: &[MenuEntry], tasks: &TaskList) -> Vec<String> {
+        entries
+            .iter()
+            .take_while(|entry| **entry != MenuEntry::Separator)
+            .map(|entry| match entry {
+                MenuEntry::Submenu { task: Some(id), .. } => tasks.get(*id).unwrap().name.clone(),
+                MenuEntry::Submenu { label, children, .. } => {
+                    let names: Vec<String> = outline(children, tasks);
+                    format!("{label}[{}]", names.join(","))
+                }
+                entry => entry.label().to_string(),
+            })
+            .collect()
+    }
+
+    fn title(state: TaskState) -> String {
+        state.section_title().to_string()
+    }
+
+    fn find(entries: &[MenuEntry], action: MenuAction) -> Option<&MenuEntry> {
+        entries.iter().find_map(|entry| match entry {
+            MenuEntry::Item { action: Some(a), .. } | MenuEntry::Check { action: a, .. } if *a == action => {
+                Some(entry)
+            }
+            MenuEntry::Submenu { children, .. } => find(children, action),
+            _ => None,
+        })
+    }
+
+    fn is_enabled(entries: &[MenuEntry], action: MenuAction) -> bool {
+        match find(entries, action) {
+            Some(MenuEntry::Item { enabled, .. } | MenuEntry::Check { enabled, .. }) => *enabled,
+            other => panic!("{action:?} not found: {other:?}"),
+        }
+    }
+
+    fn is_checked(entries: &[MenuEntry], action: MenuAction) -> bool {
+        match find(entries, action) {
+            Some(MenuEntry::Check { checked, .. }) => *checked,
+            other => panic!("{action:?} is not a check item: {other:?}"),
+        }
+    }
+
+    fn top_level_actions(entries: &[MenuEntry]) -> Vec<Option<MenuAction>> {
+        entries
+            .iter()
+            .map(|entry| match entry {
+                MenuEntry::Item { action, .. } => *action,
+                MenuEntry::Check { action, .. } => Some(*action),
+                MenuEntry::Submenu { .. } | MenuEntry::Separator => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_menu_layout() {
+        let fixture = Fixture::new();
+        let entries = fixture.menu(&TaskList::new());
+        assert_eq!(
+            top_level_actions(&entries),
+            [
+                None,
+                Some(MenuAction::NewTask),
+                Some(MenuAction::Undo),
+                Some(MenuAction::PauseAll),
+                Some(MenuAction::ResumeAll),
+                Some(MenuAction::ToggleSetting(Setting::DoNotDisturb)),
+                Some(MenuAction::CopyAll),
+                Some(MenuAction::ClearFinished),
+                None,
+                None,
+                Some(MenuAction::About),
+                Some(MenuAction::CopyDiagnostics),
+                None,
+                Some(MenuAction::Quit),
+            ]
+        );
+        assert_eq!(entries[0], MenuEntry::Separator);
+        assert_eq!(entries[8].label(), tr("stats.title"));
+        assert_eq!(entries[9].label(), tr("settings.title"));
+        for action in [MenuAction::Undo, MenuAction::PauseAll, MenuAction::ResumeAll, MenuAction::ClearFinished] {
+            assert!(!is_enabled(&entries, action), "{action:?}");
+        }
+    }
+
+    #[test]
+    fn sections_follow_state_order() {
+        let fixture = Fixture::new();
+        let tasks: TaskList = [
+            task("未开始", TaskState::Idle),
+            task("已完成", TaskState::Finished),
+            task("计时中", TaskState::Running),
+            task("已暂停", TaskState::Paused),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            outline(&fixture.menu(&tasks), &tasks),
+            [
+                title(TaskState::Running),
+                "计时中".to_string(),
+                title(TaskState::Paused),
+                "已暂停".to_string(),
+                title(TaskState::Idle),
+                "未开始".to_string(),
+                title(TaskState::Finished),
+                "已完成".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_merge_within_a_section() {
+        let fixture = Fixture::new();
+        let tasks: TaskList = [
+            grouped("写作", TaskState::Idle, "工作"),
+            task("散步", TaskState::Idle),
+            grouped("评审", TaskState::Idle, "工作"),
+            grouped("汇报", TaskState::Running, "工作"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            outline(&fixture.menu(&tasks), &tasks),
+            [
+                title(TaskState::Running),
+                "📁 工作[汇报]".to_string(),
+                title(TaskState::Idle),
+                "📁 工作[写作,评审]".to_string(),
+                "散步".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn archived_tasks_leave_the_sections() {
+        let fixture = Fixture::new();
+        let mut done = task("已完成", TaskState::Finished);
+        done.archive();
+        let tasks: TaskList = [task("未开始", TaskState::Idle), done].into_iter().collect();
+        let outline = outline(&fixture.menu(&tasks), &tasks);
+        assert_eq!(outline[..2], [title(TaskState::Idle), "未开始".to_string()]);
+        assert!(outline[2].starts_with(&tr_args("menu.archived", &[("count", "1")])), "{outline:?}");
+    }
+
+    #[test]
+    fn trash_lists_the_newest_deletion_first() {
+        let fixture = Fixture::new();
+        let mut tasks: TaskList = [task("写作", TaskState::Idle), task("阅读", TaskState::Idle)].into_iter().collect();
+        let ids: Vec<TaskId> = tasks.iter().map(|t| t.id).collect();
+        assert!(find(&fixture.menu(&tasks), MenuAction::EmptyTrash).is_none());
+
+        for id in &ids {
+            tasks.delete(*id);
+        }
+        let entries = fixture.menu(&tasks);
+        let label = tr_args("menu.trash", &[("count", "2")]);
+        let Some(MenuEntry::Submenu { children, .. }) = entries.iter().find(|entry| entry.label() == label) else {
+            panic!("no trash submenu: {entries:?}");
+        };
+        let names: Vec<&str> = children.iter().map(MenuEntry::label).collect();
+        assert!(names[0].starts_with("阅读 · ") && names[1].starts_with("写作 · "), "{names:?}");
+        assert!(find(children, MenuAction::RestoreTrash(ids[0])).is_some());
+        assert!(find(children, MenuAction::DeleteTrash(ids[1])).is_some());
+        assert_eq!(children.last().map(MenuEntry::label), Some(tr("menu.empty_trash")));
+    }
+
+    #[test]
+    fn task_items_reflect_task_state() {
+        let fixture = Fixture::new();
+        let mut running = task("计时中", TaskState::Running);
+        running.muted = true;
+        running.pinned = true;
+        let mut tasks: TaskList = [running, task("已完成", TaskState::Finished)].into_iter().collect();
+        let (running, finished) = (tasks.id_at(0).unwrap(), tasks.id_at(1).unwrap());
+
+        let entries = fixture.menu(&tasks);
+        assert!(is_checked(&entries, MenuAction::Mute(running)));
+        assert!(!is_checked(&entries, MenuAction::Nag(running)));
+        assert!(is_checked(&entries, MenuAction::Pin(running)));
+        assert!(!is_checked(&entries, MenuAction::Pin(finished)));
+        assert_eq!(find(&entries, MenuAction::Toggle(running)).unwrap().label(), tr("menu.pause"));
+        assert_eq!(find(&entries, MenuAction::Toggle(finished)).unwrap().label(), tr("menu.start"));
+
+        // 推迟只对已完成的任务可用，归档只对已完成的任务显示
+        let snooze = Duration::from_secs(SNOOZE_MINUTES[0] * 60);
+        assert!(is_enabled(&entries, MenuAction::Snooze(finished, snooze)));
+        assert!(!is_enabled(&entries, MenuAction::Snooze(running, snooze)));
+        assert!(find(&entries, MenuAction::Archive(finished)).is_some());
+        assert!(find(&entries, MenuAction::Archive(running)).is_none());
+
+        // 手动排序时第一个任务不能上移
+        assert!(!is_enabled(&entries, MenuAction::Move(running, MoveDirection::Up)));
+
+        assert!(is_enabled(&entries, MenuAction::PauseAll));
+        assert!(is_enabled(&entries, MenuAction::ClearFinished));
+        assert!(!is_enabled(&entries, MenuAction::Undo));
+        tasks.delete(finished);
+        assert!(is_enabled(&fixture.menu(&tasks), MenuAction::Undo));
+    }
+
+    #[test]
+    fn settings_follow_config_and_platform() {
+        let mut fixture = Fixture::new();
+        fixture.config.sound_enabled = false;
+        fixture.config.single_running_task = true;
+        fixture.config.task_order = TaskOrder::Remaining;
+        let entries = fixture.menu(&TaskList::new());
+        assert!(!is_checked(&entries, MenuAction::ToggleSetting(Setting::SoundEnabled)));
+        assert!(is_checked(&entries, MenuAction::ToggleSetting(Setting::NotifyOnFinish)));
+        assert!(is_checked(&entries, MenuAction::ToggleSetting(Setting::SingleRunningTask)));
+        assert!(is_checked(&entries, MenuAction::SetOrder(TaskOrder::Remaining)));
+        assert!(!is_checked(&entries, MenuAction::SetOrder(TaskOrder::Manual)));
+        // Linux 上没有 Dock
+        assert!(find(&entries, MenuAction::ToggleSetting(Setting::HideDockOnLaunch)).is_none());
+        assert!(find(&entries, MenuAction::DockShow).is_none());
+    }
+
+    #[test]
+    fn stats_list_budgets_with_bars() {
+        let mut fixture = Fixture::new();
+        fixture.config.progress_bar_width = 4;
+        fixture.config.progress_bar_filled = '#';
+        fixture.config.progress_bar_empty = '-';
+        let budget = |tag: &str, used: u32| stats::BudgetUsage {
+            tag: tag.to_string(),
+            used: MINUTE * used,
+            budget: MINUTE * 60,
+        };
+        let summary = stats::Summary {
+            budgets: vec![budget("工作", 30), budget("娱乐", 90)],
+            ..Default::default()
+        };
+        let MenuEntry::Submenu { children, .. } = stats_submenu(&summary, &fixture.config) else {
+            panic!("stats is a submenu");
+        };
+        let labels: Vec<&str> = children.iter().map(MenuEntry::label).collect();
+        let budgets = &labels[labels.len() - 3..];
+        assert_eq!(budgets[0], tr("stats.budgets"));
+        assert!(budgets[1].starts_with("工作 ##--") && !budgets[1].ends_with("⛔"), "{}", budgets[1]);
+        assert!(budgets[2].starts_with("娱乐 ####") && budgets[2].ends_with(" ⛔"), "{}", budgets[2]);
+
+        // 没有预算时不显示预算部分
+        let MenuEntry::Submenu { children, .. } = stats_submenu(&stats::Summary::default(), &fixture.config) else {
+            panic!("stats is a submenu");
+        };
+        assert!(!children.iter().any(|entry| entry.label() == tr("stats.budgets")));
+    }
+
+    fn update(index: usize, change: MenuChange) -> MenuUpdate {
+        MenuUpdate { index, change }
+    }
+
+    fn sample_menu() -> Vec<MenuEntry> {
+        vec![
+            MenuEntry::action("新建", MenuAction::NewTask),
+            MenuEntry::submenu("设置", vec![
+                MenuEntry::check("勿扰", false, MenuAction::ToggleSetting(Setting::DoNotDisturb)),
+                MenuEntry::Separator,
+                MenuEntry::action("导出", MenuAction::ExportTasks),
+            ]),
+            MenuEntry::text("今天 0m"),
+            MenuEntry::action("退出", MenuAction::Quit),
+        ]
+    }
+
+    #[test]
+    fn diff_of_identical_menus_is_empty() {
+        assert_eq!(diff(&sample_menu(), &sample_menu()), Some(Vec::new()));
+        assert_eq!(diff(&[], &[]), Some(Vec::new()));
+    }
+
+    #[test]
+    fn diff_updates_labels_and_flags_in_place() {
+        let old = sample_menu();
+        let mut new = sample_menu();
+        new[1] = MenuEntry::submenu("设置 ·", vec![
+            MenuEntry::check("勿扰", true, MenuAction::ToggleSetting(Setting::DoNotDisturb)).enabled(false),
+            MenuEntry::Separator,
+            MenuEntry::action("导出", MenuAction::ExportTasks),
+        ]);
+        new[2] = MenuEntry::text("今天 5m");
+        new[3] = MenuEntry::action("退出", MenuAction::Quit).enabled(false);
+        // 下标按先序遍历计算，子菜单的子项紧跟在子菜单之后
+        assert_eq!(
+            diff(&old, &new),
+            Some(vec![
+                update(1, MenuChange::Text("设置 ·".to_string())),
+                update(2, MenuChange::Enabled(false)),
+                update(2, MenuChange::Checked(true)),
+                update(5, MenuChange::Text("今天 5m".to_string())),
+                update(6, MenuChange::Enabled(false)),
+            ])
+        );
+    }
+
+    #[test]
+    fn diff_rejects_structural_changes() {
+        let old = sample_menu();
+        let structural = [
+            // 多了一项
+            {
+                let mut new = sample_menu();
+                new.push(MenuEntry::Separator);
+                new
+            },
+            // 子菜单中少了一项
+            {
+                let mut new = sample_menu();
+                if let MenuEntry::Submenu { children, .. } = &mut new[1] {
+                    children.pop();
+                }
+                new
+            },
+            // 动作改变
+            {
+                let mut new = sample_menu();
+                new[0] = MenuEntry::action("新建", MenuAction::About);
+                new
+            },
+            // 快捷键改变
+            {
+                let mut new = sample_menu();
+                let accelerator = "CmdOrCtrl+N".parse().ok();
+                new[0] = MenuEntry::action("新建", MenuAction::NewTask).with_accelerator(accelerator);
+                new
+            },
+            // 种类改变
+            {
+                let mut new = sample_menu();
+                new[2] = MenuEntry::Separator;
+                new
+            },
+        ];
+        for new in structural {
+            assert_eq!(diff(&old, &new), None, "{new:?}");
+        }
+    }
+
+    #[test]
+    fn diff_of_real_menus() {
+        let fixture = Fixture::new();
+        let mut tasks: TaskList = [task("计时中", TaskState::Running), task("未开始", TaskState::Idle)]
+            .into_iter()
+            .collect();
+        let now = SystemTime::now();
+        let before = fixture.menu_at(&tasks, now);
+
+        // 时间流逝只改变标题文本
+        let later = fixture.menu_at(&tasks, now + MINUTE);
+        let updates = diff(&before, &later).unwrap();
+        assert!(!updates.is_empty());
+        assert!(updates.iter().all(|u| matches!(u.change, MenuChange::Text(_))), "{updates:?}");
+
+        // 静音改变勾选状态和标题中的 🔕
+        let id = tasks.id_at(1).unwrap();
+        tasks.get_mut(id).unwrap().muted = true;
+        let muted = fixture.menu_at(&tasks, now);
+        let changes: Vec<MenuChange> = diff(&before, &muted).unwrap().into_iter().map(|u| u.change).collect();
+        assert_eq!(changes.len(), 2, "{changes:?}");
+        assert!(matches!(&changes[0], MenuChange::Text(title) if title.contains('🔕')));
+        assert_eq!(changes[1], MenuChange::Checked(true));
+
+        // 开始任务后任务换到另一段，需要重建
+        let task = tasks.get_mut(id).unwrap();
+        task.start();
+        task.start_time = Some(now);
+        assert_eq!(diff(&before, &fixture.menu_at(&tasks, now)), None);
+    }
+
+    #[test]
+    fn overflow_moves_extra_tasks_into_more_submenu() {
+        let mut fixture = Fixture::new();
+        fixture.config.menu_max_tasks = 3;
+        let tasks: TaskList = [
+            task("空闲 1", TaskState::Idle),
+            task("计时 1", TaskState::Running),
+            task("空闲 2", TaskState::Idle),
+            task("计时 2", TaskState::Running),
+            task("空闲 3", TaskState::Idle),
+        ]
+        .into_iter()
+        .collect();
+        let more = tr_args("menu.more_tasks", &[("count", "2")]);
+        assert_eq!(
+            outline(&fixture.menu(&tasks), &tasks),
+            [
+                title(TaskState::Running),
+                "计时 1".to_string(),
+                "计时 2".to_string(),
+                title(TaskState::Idle),
+                "空闲 1".to_string(),
+                format!("{more}[{},空闲 2,空闲 3]", title(TaskState::Idle)),
+            ]
+        );
+
+        // 不超过上限或不限制时没有“更多任务”
+        for max in [5, 0] {
+            fixture.config.menu_max_tasks = max;
+            let outline = outline(&fixture.menu(&tasks), &tasks);
+            assert_eq!(outline.len(), 7, "{outline:?}");
+        }
+    }
+
+    // 500 个任务时生成并比较一次菜单描述，调试构建下约 70ms，留出足够余量避免在慢机器上误报
+    #[test]
+    fn menu_build_stays_within_budget() {
+        let fixture = Fixture::new();
+        let tasks: TaskList = (0..500)
+            .map(|i| {
+                let state = TaskState::SECTIONS[i % TaskState::SECTIONS.len()];
+                if i % 3 == 0 {
+                    grouped(&format!("任务 {i}"), state, &format!("分组 {}", i % 7))
+                } else {
+                    task(&format!("任务 {i}"), state)
+                }
+            })
+            .collect();
+        let now = SystemTime::now();
+        let previous = fixture.menu_at(&tasks, now);
+        let start = std::time::Instant::now();
+        let entries = fixture.menu_at(&tasks, now + Duration::from_secs(1));
+        assert!(diff(&previous, &entries).is_some());
+        let elapsed = start.elapsed();
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    }
+}
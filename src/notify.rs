@@ -0,0 +1,363 @@
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use snafu::ResultExt;
+use tracing::{debug, error};
+
+use crate::{
+    config::Config,
+    error::Result,
+    i18n::{tr, tr_args},
+    task::{Task, TaskId, format_remaining_time, time_format},
+};
+
+const DEFAULT_SOUND: &str = "/System/Library/Sounds/Glass.aiff"; // 未配置提示音时使用的系统声音
+const SOUND_THROTTLE: Duration = Duration::from_secs(1); // 两次提示音的最短间隔
+
+// 系统通知的发送方式，便于替换实现
+pub trait NotificationSink {
+    fn notify(&self, title: &str, body: &str) -> Result<()>;
+}
+
+// 使用系统通知中心：macOS 通过 osascript，其他平台通过 notify-rust
+#[derive(Debug, Default)]
+pub struct SystemNotifier;
+
+impl NotificationSink for SystemNotifier {
+    #[cfg(target_os = "macos")]
+    fn notify(&self, title: &str, body: &str) -> Result<()> {
+        let script = format!(
+            r#"display notification "{}" with title "{}""#,
+            escape_applescript(body),
+            escape_applescript(title)
+        );
+        Command::new("osascript")
+            .arg("-e")
+            .arg(&script)
+            .output()
+            .context(crate::error::AppleScriptExecutionSnafu)?;
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn notify(&self, title: &str, body: &str) -> Result<()> {
+        notify_rust::Notification::new()
+            .summary(title)
+            .body(body)
+            .show()
+            .context(crate::error::NotificationSnafu)?;
+        Ok(())
+    }
+}
+
+// 任务完成时发送通知；静音任务、关闭通知或勿扰模式下不发送，返回是否已发送
+pub fn notify_task_finished(sink: &dyn NotificationSink, task: &Task, config: &Config) -> Result<bool> {
+    if task.muted || !config.notify_on_finish || config.do_not_disturb {
+        return Ok(false);
+    }
+    let planned = format_remaining_time(task.planned, time_format());
+    let body = tr_args("notify.finished_body", &[("name", &task.name), ("planned", &planned)]);
+    sink.notify(tr("notify.finished_title"), &body)?;
+    Ok(true)
+}
+
+// 完成提示音，在后台进程中播放，短时间内的多次完成只播放一次
+#[derive(Debug, Default)]
+pub struct SoundPlayer {
+    last_played: Cell<Option<Instant>>,
+}
+
+impl SoundPlayer {
+    // 任务完成时播放提示音；静音任务、关闭提示音或勿扰模式下不播放，返回是否已播放
+    pub fn play_task_finished(&self, task: &Task, config: &Config) -> bool {
+        if task.muted || !config.sound_enabled || config.do_not_disturb {
+            return false;
+        }
+        self.play(config)
+    }
+
+    // 距上次播放不足 SOUND_THROTTLE 时跳过，避免声音重叠
+    pub fn play(&self, config: &Config) -> bool {
+        let now = Instant::now();
+        if self
+            .last_played
+            .get()
+            .is_some_and(|last| now.duration_since(last) < SOUND_THROTTLE)
+        {
+            return false;
+        }
+        self.last_played.set(Some(now));
+
+        let path = config.sound_file.as_deref().unwrap_or(Path::new(DEFAULT_SOUND));
+        play_sound_file(path, config.sound_volume);
+        true
+    }
+}
+
+// 持续提醒的调度：记录每个任务上次提醒的时刻
+#[derive(Debug, Default)]
+pub struct NagScheduler {
+    last_alert: HashMap<TaskId, Instant>,
+}
+
+impl NagScheduler {
+    pub fn record(&mut self, id: TaskId, now: Instant) {
+        self.last_alert.insert(id, now);
+    }
+
+    // 距上次提醒已满 interval（或从未提醒过）时返回 true，并记录本次提醒
+    pub fn due(&mut self, id: TaskId, now: Instant, interval: Duration) -> bool {
+        match self.last_alert.get(&id) {
+            Some(last) if now.duration_since(*last) < interval => false,
+            _ => {
+                self.record(id, now);
+                true
+            }
+        }
+    }
+
+    pub fn clear(&mut self, id: TaskId) {
+        self.last_alert.remove(&id);
+    }
+}
+
+// 勿扰模式期间完成的任务和提前提醒，关闭勿扰模式时合并成一条通知
+#[derive(Debug, Default)]
+pub struct QuietQueue {
+    names: Vec<String>,
+    warnings: Vec<String>, // 提前提醒的完整文本，按发生顺序排列
+}
+
+impl QuietQueue {
+    // 记录勿扰期间的完成，静音的任务本来就不提醒，不计入
+    pub fn push(&mut self, task: &Task) {
+        if !task.muted {
+            self.names.push(task.name.clone());
+        }
+    }
+
+    // 记录勿扰期间的提前提醒，同样跳过静音的任务
+    pub fn push_warning(&mut self, task: &Task, message: String) {
+        if !task.muted {
+            self.warnings.push(message);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len() + self.warnings.len()
+    }
+
+    // 取出积压的完成和提醒并生成汇总通知的正文，没有积压时返回 None
+    pub fn flush(&mut self) -> Option<String> {
+        if self.len() == 0 {
+            return None;
+        }
+        let names = std::mem::take(&mut self.names);
+        let mut lines = std::mem::take(&mut self.warnings);
+        if !names.is_empty() {
+            lines.insert(0, tr_args("notify.quiet_summary_body", &[
+                ("count", &names.len().to_string()),
+                ("names", &names.join(", ")),
+            ]));
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+// 关闭勿扰模式时清空队列并发送一条汇总通知；没有积压或关闭了通知时不发送，返回是否已发送
+pub fn notify_quiet_summary(sink: &dyn NotificationSink, queue: &mut QuietQueue, config: &Config) -> Result<bool> {
+    let Some(body) = queue.flush() else {
+        return Ok(false);
+    };
+    if !config.notify_on_finish {
+        return Ok(false);
+    }
+    sink.notify(tr("notify.quiet_summary_title"), &body)?;
+    Ok(true)
+}
+
+#[cfg(target_os = "macos")]
+fn play_sound_file(path: &Path, volume: f32) {
+    let spawn_result = Command::new("afplay")
+        .arg("-v")
+        .arg(volume.clamp(0.0, 1.0).to_string())
+        .arg(path)
+        .spawn();
+    match spawn_result {
+        Ok(mut child) => {
+            // 在后台等待播放结束，避免阻塞事件循环
+            std::thread::spawn(move || {
+                if let Err(e) = child.wait() {
+                    error!("Failed to wait for afplay: {}", e);
+                }
+            });
+        }
+        Err(e) => error!("Failed to play sound {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn play_sound_file(path: &Path, _volume: f32) {
+    debug!("Completion sound is only supported on macOS: {}", path.display());
+}
+
+#[cfg(target_os = "macos")]
+pub fn escape_applescript(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, time::Duration};
+
+    use super::*;
+    use crate::task::TaskType;
+
+    // 记录收到的通知，代替系统通知中心
+    #[derive(Default)]
+    struct FakeNotifier {
+        sent: RefCell<Vec<(String, String)>>,
+    }
+
+    impl NotificationSink for FakeNotifier {
+        fn notify(&self, title: &str, body: &str) -> Result<()> {
+            self.sent.borrow_mut().push((title.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+
+    fn task(name: &str) -> Task {
+        Task::new(name.to_string(), TaskType::Duration(Duration::from_secs(25 * 60))).unwrap()
+    }
+
+    #[test]
+    fn finished_notification_body() {
+        let sink = FakeNotifier::default();
+        assert!(notify_task_finished(&sink, &task("写作"), &Config::default()).unwrap());
+        assert_eq!(*sink.sent.borrow(), [("⏰ 任务完成".to_string(), "写作 已完成（计划 00:25:00）".to_string())]);
+    }
+
+    #[test]
+    fn muted_tasks_are_silent_while_others_fire() {
+        let sink = FakeNotifier::default();
+        let mut muted = task("坐姿提醒");
+        muted.muted = true;
+
+        assert!(!notify_task_finished(&sink, &muted, &Config::default()).unwrap());
+        assert!(notify_task_finished(&sink, &task("写作"), &Config::default()).unwrap());
+        let sent = sink.sent.borrow();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].1.starts_with("写作"));
+    }
+
+    #[test]
+    fn do_not_disturb_and_the_setting_gate_notifications() {
+        let sink = FakeNotifier::default();
+        let dnd = Config { do_not_disturb: true, ..Config::default() };
+        assert!(!notify_task_finished(&sink, &task("写作"), &dnd).unwrap());
+        let off = Config { notify_on_finish: false, ..Config::default() };
+        assert!(!notify_task_finished(&sink, &task("写作"), &off).unwrap());
+        assert!(sink.sent.borrow().is_empty());
+    }
+
+    #[test]
+    fn quiet_completions_are_summarized_once() {
+        let sink = FakeNotifier::default();
+        let mut queue = QuietQueue::default();
+        let mut muted = task("坐姿提醒");
+        muted.muted = true;
+        for finished in [task("写作"), muted, task("阅读"), task("写作")] {
+            queue.push(&finished);
+        }
+        assert_eq!(queue.len(), 3);
+
+        assert!(notify_quiet_summary(&sink, &mut queue, &Config::default()).unwrap());
+        assert_eq!(queue.len(), 0);
+        assert!(!notify_quiet_summary(&sink, &mut queue, &Config::default()).unwrap());
+        let summary = ("🔕 勿扰期间的通知".to_string(), "3 个任务已完成：写作, 阅读, 写作".to_string());
+        assert_eq!(*sink.sent.borrow(), [summary]);
+    }
+
+    #[test]
+    fn quiet_warnings_follow_the_completions() {
+        let sink = FakeNotifier::default();
+        let mut queue = QuietQueue::default();
+        let mut muted = task("坐姿提醒");
+        muted.muted = true;
+        queue.push_warning(&task("写作"), "⏳ '写作' 还剩 00:05:00".to_string());
+        queue.push_warning(&muted, "⏳ '坐姿提醒' 还剩 00:01:00".to_string());
+        assert_eq!(queue.len(), 1);
+        queue.push(&task("阅读"));
+
+        assert!(notify_quiet_summary(&sink, &mut queue, &Config::default()).unwrap());
+        assert_eq!(queue.len(), 0);
+        let body = "1 个任务已完成：阅读\n⏳ '写作' 还剩 00:05:00";
+        assert_eq!(sink.sent.borrow()[0].1, body);
+    }
+
+    #[test]
+    fn quiet_summary_respects_the_notification_setting() {
+        let sink = FakeNotifier::default();
+        let mut queue = QuietQueue::default();
+        queue.push(&task("写作"));
+        let off = Config { notify_on_finish: false, ..Config::default() };
+        assert!(!notify_quiet_summary(&sink, &mut queue, &off).unwrap());
+        // 关闭通知时积压的完成直接丢弃，之后开启也不再补发
+        assert!(!notify_quiet_summary(&sink, &mut queue, &Config::default()).unwrap());
+        assert!(sink.sent.borrow().is_empty());
+    }
+
+    // 测试中不播放真实的声音
+    fn sound_config() -> Config {
+        Config { sound_file: Some("/nonexistent/timeticker-test.aiff".into()), ..Config::default() }
+    }
+
+    #[test]
+    fn sounds_are_throttled() {
+        let player = SoundPlayer::default();
+        let config = sound_config();
+        assert!(player.play_task_finished(&task("写作"), &config));
+        assert!(!player.play_task_finished(&task("阅读"), &config));
+
+        // 超过间隔后可以再次播放
+        player.last_played.set(Some(Instant::now() - SOUND_THROTTLE));
+        assert!(player.play(&config));
+    }
+
+    #[test]
+    fn muted_tasks_and_settings_gate_sounds() {
+        let player = SoundPlayer::default();
+        let mut muted = task("坐姿提醒");
+        muted.muted = true;
+        assert!(!player.play_task_finished(&muted, &sound_config()));
+        let off = Config { sound_enabled: false, ..sound_config() };
+        assert!(!player.play_task_finished(&task("写作"), &off));
+        let dnd = Config { do_not_disturb: true, ..sound_config() };
+        assert!(!player.play_task_finished(&task("写作"), &dnd));
+        assert!(player.last_played.get().is_none());
+    }
+
+    #[test]
+    fn nag_scheduler_waits_for_the_interval() {
+        let mut scheduler = NagScheduler::default();
+        let [writing, reading] = [task("写作").id, task("阅读").id];
+        let now = Instant::now();
+        let interval = Duration::from_secs(60);
+
+        scheduler.record(writing, now);
+        assert!(!scheduler.due(writing, now + Duration::from_secs(59), interval));
+        assert!(scheduler.due(reading, now, interval));
+        assert!(scheduler.due(writing, now + interval, interval));
+        assert!(!scheduler.due(writing, now + interval + Duration::from_secs(1), interval));
+
+        // 确认后清除记录，下次完成时立即提醒
+        scheduler.clear(writing);
+        assert!(scheduler.due(writing, now + interval + Duration::from_secs(1), interval));
+    }
+}
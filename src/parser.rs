@@ -1,15 +1,82 @@
 use std::time::{Duration, SystemTime};
 
-use chrono::{Local, NaiveTime};
+use chrono::{DateTime, Local, NaiveTime};
 use regex::Regex;
-use snafu::{ResultExt, OptionExt, Backtrace}; // Ensure Backtrace is imported if used directly, though snafu macros handle it.
-use crate::error::{Result, Error, RegexCompileSnafu, InvalidInputFormatSnafu, MissingTimeInputSnafu, ChronoParseSnafu, TimezoneConversionSnafu, ParseNumberSnafu, InvalidDurationUnitSnafu, ZeroDurationSnafu};
-use crate::task::TaskType;
+use snafu::{ensure, ResultExt, OptionExt, Backtrace}; // Ensure Backtrace is imported if used directly, though snafu macros handle it.
+use crate::error::{Result, Error, RegexCompileSnafu, InvalidInputFormatSnafu, MissingTimeInputSnafu, ChronoParseSnafu, TimezoneConversionSnafu, ParseNumberSnafu, InvalidDurationUnitSnafu, ZeroDurationSnafu, UntrustedCommandSnafu};
+use crate::task::{Task, TaskType};
 
 
-pub fn parse_time_input(input: &str) -> Result<(String, TaskType)> {
-    let re = Regex::new(r"^(.*?)(?:#(.+))?$").context(RegexCompileSnafu)?;
-    let caps = re.captures(input).context(InvalidInputFormatSnafu { msg: "Input does not match expected format (time_string#name)".to_string() })?;
+// 解析后的任务输入
+#[derive(Debug, Clone)]
+pub struct TaskInput {
+    pub name: String,
+    pub task_type: TaskType,
+    pub group: Option<String>, // 分组，输入格式：25m#评审 /工作
+    pub on_complete: Option<String>, // 完成时执行的命令，输入格式：10m#build && say done
+    pub scheduled_start: Option<SystemTime>, // 定时开始的时刻，输入格式：@14:00>25m#专注
+    pub warn_offsets: Option<Vec<Duration>>, // 提前提醒的时长，输入格式：@18:00#下班 ~5m ~1m
+    pub warn_milestones: Option<Vec<f64>>, // 按已用比例提醒，输入格式：2h#写作 ~50% ~90%
+}
+
+impl TaskInput {
+    // 按输入创建新任务，输入中的分组、完成命令、定时开始和提前提醒都带到任务上
+    pub fn into_task(self) -> Result<Task> {
+        let mut task = Task::new(self.name, self.task_type)?;
+        task.group = self.group;
+        task.on_complete = self.on_complete;
+        task.scheduled_start = self.scheduled_start;
+        task.warn_offsets = self.warn_offsets;
+        task.warn_milestones = self.warn_milestones;
+        Ok(task)
+    }
+
+    // 按编辑后的输入修改任务，保留 id、固定状态和创建时间；rename 为 false 时保留原名称。
+    // 先替换计时方式，无效时返回错误，其余设置都不会生效
+    pub fn edit(self, mut task: Task, rename: bool) -> Result<Task> {
+        task.set_type(self.task_type)?;
+        if rename {
+            task.name = self.name;
+        }
+        task.group = self.group;
+        task.on_complete = self.on_complete;
+        task.scheduled_start = self.scheduled_start;
+        task.warn_offsets = self.warn_offsets;
+        task.warn_milestones = self.warn_milestones;
+        Ok(task)
+    }
+
+    // 链接和控制套接字的输入可能来自任何程序，不接受完成时执行的命令，否则打开链接就能执行任意命令
+    pub fn refuse_command(&self, origin: &str) -> Result<()> {
+        ensure!(self.on_complete.is_none(), UntrustedCommandSnafu { origin });
+        Ok(())
+    }
+}
+
+pub fn parse_time_input(input: &str) -> Result<TaskInput> {
+    // && 之后的部分是完成时执行的命令
+    let (input, on_complete) = match input.split_once("&&") {
+        Some((task_part, command)) => (task_part.trim(), Some(command.trim().to_string()).filter(|c| !c.is_empty())),
+        None => (input, None),
+    };
+
+    // ~5m 表示剩余 5 分钟时提前提醒，~50% 表示用掉一半时提醒，可以写多个
+    let re_warn = Regex::new(r"\s+~(\S+)").context(RegexCompileSnafu)?;
+    let mut warn_offsets = Vec::new();
+    let mut warn_milestones = Vec::new();
+    for cap in re_warn.captures_iter(input) {
+        match cap[1].strip_suffix('%') {
+            Some(percent) => warn_milestones.push(parse_warn_milestone(percent)?),
+            None => warn_offsets.push(parse_warn_offset(&cap[1])?),
+        }
+    }
+    let warn_offsets = Some(warn_offsets).filter(|offsets| !offsets.is_empty());
+    let warn_milestones = Some(warn_milestones).filter(|milestones| !milestones.is_empty());
+    let input = re_warn.replace_all(input, "");
+    let input = input.as_ref();
+
+    let re = Regex::new(r"^(.*?)(?:#(.*?))?(?:\s+/(.+))?$").context(RegexCompileSnafu)?;
+    let caps = re.captures(input).context(InvalidInputFormatSnafu { msg: "Input does not match expected format (time_string#name /group)".to_string() })?;
 
     let time_str = caps.get(1)
         .map(|m| m.as_str().trim())
@@ -17,21 +84,28 @@ pub fn parse_time_input(input: &str) -> Result<(String, TaskType)> {
         .context(MissingTimeInputSnafu { msg: "Time string is missing or empty".to_string() })?;
 
     let name = caps.get(2).map_or("未命名", |m| m.as_str().trim()).to_string();
+    let group = caps.get(3).map(|m| m.as_str().trim().to_string()).filter(|g| !g.is_empty());
 
-    if let Some(deadline_time_str) = time_str.strip_prefix('@') {
-        // 处理截止时间格式 (@HH:MM)
-        let time = NaiveTime::parse_from_str(deadline_time_str, "%H:%M").context(ChronoParseSnafu)?;
-
-        let now = Local::now();
-        let mut deadline_datetime_naive = now.date_naive().and_time(time);
-        if deadline_datetime_naive < now.naive_local() {
-            deadline_datetime_naive += chrono::Duration::days(1);
+    // @HH:MM>时长 表示到该时刻自动开始的时间段任务
+    let (time_str, scheduled_start) = match time_str.split_once('>') {
+        Some((start_str, duration_str)) => {
+            let msg = format!("Scheduled start must look like @HH:MM>25m: '{}'", time_str);
+            let start_str = start_str.trim().strip_prefix('@').context(InvalidInputFormatSnafu { msg })?;
+            let duration_str = duration_str.trim();
+            if duration_str.starts_with('@') {
+                let msg = "Only duration tasks can have a scheduled start".to_string();
+                return InvalidInputFormatSnafu { msg }.fail();
+            }
+            (duration_str, Some(next_clock_time(start_str)?))
         }
-        
-        let deadline_datetime_local = deadline_datetime_naive.and_local_timezone(Local).single()
-            .context(TimezoneConversionSnafu { msg: format!("Failed to convert NaiveDateTime {} to local timezone", deadline_datetime_naive) })?;
+        None => (time_str, None),
+    };
 
-        Ok((name, TaskType::Deadline(deadline_datetime_local.into())))
+    if let Some(deadline_time_str) = time_str.strip_prefix('@') {
+        // 处理截止时间格式 (@HH:MM)
+        let deadline = next_clock_time(deadline_time_str)?;
+        let task_type = TaskType::Deadline(deadline);
+        Ok(TaskInput { name, task_type, group, on_complete, scheduled_start, warn_offsets, warn_milestones })
     } else {
         // 处理时间段格式 (1h30m)
         let mut total_duration = Duration::ZERO;
@@ -70,6 +144,313 @@ pub fn parse_time_input(input: &str) -> Result<(String, TaskType)> {
          }
 
 
-        Ok((name, TaskType::Duration(total_duration)))
+        let task_type = TaskType::Duration(total_duration);
+        Ok(TaskInput { name, task_type, group, on_complete, scheduled_start, warn_offsets, warn_milestones })
+    }
+}
+
+// 提前提醒的时长，例如 5m、90s、1h30m
+fn parse_warn_offset(offset_str: &str) -> Result<Duration> {
+    let invalid = || {
+        let msg = format!("Warning offset must look like ~5m or ~30s: '~{}'", offset_str);
+        InvalidInputFormatSnafu { msg }
+    };
+    let re_offset = Regex::new(r"^(?:\d+[hms])+$").context(RegexCompileSnafu)?;
+    if !re_offset.is_match(offset_str) {
+        return invalid().fail();
+    }
+    let re_part = Regex::new(r"(\d+)([hms])").context(RegexCompileSnafu)?;
+    let mut offset = Duration::ZERO;
+    for cap in re_part.captures_iter(offset_str) {
+        let value: u64 = cap[1].parse().context(ParseNumberSnafu)?;
+        offset += match &cap[2] {
+            "h" => Duration::from_secs(value * 3600),
+            "m" => Duration::from_secs(value * 60),
+            _ => Duration::from_secs(value),
+        };
+    }
+    if offset.is_zero() {
+        return invalid().fail();
+    }
+    Ok(offset)
+}
+
+// 按已用比例提醒的里程碑，输入 1 到 99 的百分数
+fn parse_warn_milestone(percent_str: &str) -> Result<f64> {
+    let percent: u32 = percent_str.parse().context(ParseNumberSnafu)?;
+    if !(1..=99).contains(&percent) {
+        let msg = format!("Warning milestone must be between 1% and 99%: '~{}%'", percent);
+        return InvalidInputFormatSnafu { msg }.fail();
+    }
+    Ok(f64::from(percent) / 100.0)
+}
+
+// HH:MM 的下一次出现：今天已经过去时为明天
+fn next_clock_time(clock: &str) -> Result<SystemTime> {
+    let time = NaiveTime::parse_from_str(clock, "%H:%M").context(ChronoParseSnafu)?;
+
+    let now = Local::now();
+    let mut datetime_naive = now.date_naive().and_time(time);
+    if datetime_naive < now.naive_local() {
+        datetime_naive += chrono::Duration::days(1);
+    }
+
+    let datetime_local = datetime_naive.and_local_timezone(Local).single()
+        .context(TimezoneConversionSnafu {
+            msg: format!("Failed to convert NaiveDateTime {} to local timezone", datetime_naive),
+        })?;
+    Ok(datetime_local.into())
+}
+
+// 将任务还原为完整的输入格式（用于编辑时预填对话框）
+pub fn format_task_input(task: &Task) -> String {
+    let mut input = format!("{}#{}", format_time_input(&task.task_type), task.name);
+    if let Some(start) = task.scheduled_start {
+        let start: DateTime<Local> = start.into();
+        input.insert_str(0, &start.format("@%H:%M>").to_string());
+    }
+    if let Some(group) = &task.group {
+        input.push_str(&format!(" /{}", group));
+    }
+    for offset in task.warn_offsets.iter().flatten() {
+        input.push_str(&format!(" ~{}", format_warn_offset(*offset)));
+    }
+    for milestone in task.warn_milestones.iter().flatten() {
+        input.push_str(&format!(" ~{}%", (milestone * 100.0).round()));
+    }
+    if let Some(command) = &task.on_complete {
+        input.push_str(&format!(" && {}", command));
+    }
+    input
+}
+
+// 将任务类型还原为输入格式（用于编辑时预填对话框）
+pub fn format_time_input(task_type: &TaskType) -> String {
+    match task_type {
+        TaskType::Duration(d) => {
+            let total_minutes = d.as_secs() / 60;
+            let hours = total_minutes / 60;
+            let minutes = total_minutes % 60;
+            match (hours, minutes) {
+                (0, m) => format!("{}m", m),
+                (h, 0) => format!("{}h", h),
+                (h, m) => format!("{}h{}m", h, m),
+            }
+        }
+        TaskType::Deadline(t) => {
+            let deadline: DateTime<Local> = (*t).into();
+            deadline.format("@%H:%M").to_string()
+        }
+    }
+}
+
+// 将提前提醒的时长还原为输入格式：整分钟写作 5m，否则写作秒数
+fn format_warn_offset(offset: Duration) -> String {
+    let secs = offset.as_secs();
+    if secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Warning, WarningDefaults};
+
+    #[test]
+    fn formatted_duration_parses_back() {
+        for secs in [60, 25 * 60, 3600, 5400, 2 * 3600 + 5 * 60] {
+            let task_type = TaskType::Duration(Duration::from_secs(secs));
+            let input = format!("{}#写作", format_time_input(&task_type));
+            let parsed = parse_time_input(&input).unwrap();
+            assert_eq!(parsed.name, "写作");
+            assert!(matches!(parsed.task_type, TaskType::Duration(d) if d.as_secs() == secs), "{input}");
+        }
+        assert_eq!(format_time_input(&TaskType::Duration(Duration::from_secs(5400))), "1h30m");
+    }
+
+    #[test]
+    fn parses_group_suffix() {
+        let input = parse_time_input("25m#评审 /工作").unwrap();
+        assert_eq!((input.name.as_str(), input.group.as_deref()), ("评审", Some("工作")));
+        assert!(matches!(input.task_type, TaskType::Duration(d) if d.as_secs() == 25 * 60));
+
+        assert_eq!(parse_time_input("25m#评审").unwrap().group, None);
+        assert_eq!(parse_time_input("25m#评审 / ").unwrap().group, None);
+    }
+
+    #[test]
+    fn edit_keeps_identity_and_optionally_name() {
+        let mut task = Task::new("写作".to_string(), TaskType::Duration(Duration::from_secs(60))).unwrap();
+        task.pinned = true;
+
+        let edited = parse_time_input("1h").unwrap().edit(task.clone(), false).unwrap();
+        assert_eq!((edited.id, edited.name.as_str(), edited.pinned), (task.id, "写作", true));
+        assert_eq!(edited.remaining, Duration::from_secs(3600));
+
+        let edited = parse_time_input("1h#阅读 /学习").unwrap().edit(task, true).unwrap();
+        assert_eq!((edited.name.as_str(), edited.group.as_deref()), ("阅读", Some("学习")));
+    }
+
+    #[test]
+    fn edit_applies_every_setting() {
+        let mut task = parse_time_input("25m#写作 /工作").unwrap().into_task().unwrap();
+        task.pinned = true;
+        let id = task.id;
+
+        let task = parse_time_input("1h ~5m ~50% && say done").unwrap().edit(task, false).unwrap();
+        assert_eq!((task.id, task.name.as_str(), task.pinned, task.group.as_deref()), (id, "写作", true, None));
+        assert_eq!(task.remaining, Duration::from_secs(3600));
+        assert_eq!(task.on_complete.as_deref(), Some("say done"));
+        assert_eq!(task.warn_offsets, Some(vec![Duration::from_secs(300)]));
+        assert_eq!(task.warn_milestones, Some(vec![0.5]));
+
+        // 没有写提醒设置的编辑恢复为默认提醒
+        let task = parse_time_input("@23:59#下班").unwrap().edit(task, true).unwrap();
+        assert_eq!(task.name, "下班");
+        assert!(matches!(task.task_type, TaskType::Deadline(_)));
+        assert_eq!((task.warn_offsets, task.warn_milestones), (None, None));
+    }
+
+    #[test]
+    fn rejected_edit_keeps_the_original() {
+        let task = parse_time_input("25m#写作 /工作").unwrap().into_task().unwrap();
+        let input = parse_time_input("200h#改名 /别的 && say done").unwrap();
+        assert!(input.edit(task.clone(), true).is_err());
+        assert_eq!((task.name.as_str(), task.group.as_deref(), task.on_complete), ("写作", Some("工作"), None));
+    }
+
+    #[test]
+    fn parses_on_complete_command() {
+        let input = parse_time_input("10m#构建 /工作 && say done").unwrap();
+        assert_eq!((input.name.as_str(), input.group.as_deref()), ("构建", Some("工作")));
+        assert_eq!(input.on_complete.as_deref(), Some("say done"));
+        assert_eq!(parse_time_input("10m#构建 && ").unwrap().on_complete, None);
+    }
+
+    #[test]
+    fn untrusted_input_refuses_completion_commands() {
+        let input = parse_time_input("10m#构建 && rm -rf ~").unwrap();
+        assert!(matches!(input.refuse_command("the control socket"), Err(Error::UntrustedCommand { .. })));
+
+        // 没有命令的输入照常接受，空命令也不算命令
+        assert!(parse_time_input("10m#构建 /工作").unwrap().refuse_command("links").is_ok());
+        assert!(parse_time_input("10m#构建 && ").unwrap().refuse_command("links").is_ok());
+    }
+
+    #[test]
+    fn formatted_task_parses_back() {
+        let mut task = Task::new("构建".to_string(), TaskType::Duration(Duration::from_secs(600))).unwrap();
+        task.group = Some("工作".to_string());
+        task.on_complete = Some("say done".to_string());
+
+        let input = parse_time_input(&format_task_input(&task)).unwrap();
+        assert_eq!(input.name, "构建");
+        assert_eq!(input.group, task.group);
+        assert_eq!(input.on_complete, task.on_complete);
+    }
+
+    #[test]
+    fn parses_warn_offsets() {
+        for (offset, secs) in [("5m", 300), ("90s", 90), ("1h30m", 5400), ("1m30s", 90)] {
+            assert_eq!(parse_warn_offset(offset).unwrap(), Duration::from_secs(secs), "{offset}");
+        }
+        for invalid in ["0s", "0h0m", "5x", "m", "5", "-5m", ""] {
+            assert!(parse_warn_offset(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn warn_offsets_in_input() {
+        let input = parse_time_input("25m#写作 ~5m ~90s").unwrap();
+        assert_eq!(input.warn_offsets, Some(vec![Duration::from_secs(300), Duration::from_secs(90)]));
+        assert_eq!(input.name, "写作");
+        assert_eq!(parse_time_input("25m#写作").unwrap().warn_offsets, None);
+        for invalid in ["25m#写作 ~0s", "25m#写作 ~5x"] {
+            assert!(parse_time_input(invalid).is_err(), "{invalid}");
+        }
+
+        // 编辑时预填的输入可以原样解析回来
+        let task = input.into_task().unwrap();
+        let formatted = format_task_input(&task);
+        assert_eq!(formatted, "25m#写作 ~5m ~90s");
+        assert_eq!(parse_time_input(&formatted).unwrap().warn_offsets, task.warn_offsets);
+    }
+
+    #[test]
+    fn input_settings_reach_the_task() {
+        let task = parse_time_input("25m#写作 /工作 ~2m ~50% && say done").unwrap().into_task().unwrap();
+        assert_eq!(task.name, "写作");
+        assert_eq!(task.group.as_deref(), Some("工作"));
+        assert_eq!(task.on_complete.as_deref(), Some("say done"));
+        assert_eq!(task.warn_offsets, Some(vec![Duration::from_secs(120)]));
+        assert_eq!(task.warn_milestones, Some(vec![0.5]));
+        assert_eq!(format_task_input(&task), "25m#写作 /工作 ~2m ~50% && say done");
+
+        // 编辑同样带上里程碑
+        let edited = parse_time_input("25m ~90%").unwrap().edit(task, false).unwrap();
+        assert_eq!((edited.warn_offsets, edited.warn_milestones), (None, Some(vec![0.9])));
+        for invalid in ["25m#写作 ~0%", "25m#写作 ~100%", "25m#写作 ~x%"] {
+            assert!(parse_time_input(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn alerts_over_a_task_lifetime() {
+        let mut task = parse_time_input("10m#写作 ~2m ~50% ~90%").unwrap().into_task().unwrap();
+        // 任务自己的设置覆盖默认值
+        let defaults = WarningDefaults {
+            offsets: vec![Duration::from_secs(300)],
+            milestones: vec![0.25],
+        };
+        let start = SystemTime::now();
+        task.start_at(start);
+
+        let mut alerts = Vec::new();
+        for secs in 0..=600 {
+            let now = start + Duration::from_secs(secs);
+            if let Some(warning) = task.due_warning(&defaults, now).unwrap() {
+                alerts.push((secs, warning));
+            }
+        }
+        assert_eq!(alerts, [
+            (300, Warning::Progress(0.5)),
+            (480, Warning::Remaining(Duration::from_secs(120))),
+            (540, Warning::Progress(0.9)),
+        ]);
+    }
+
+    #[test]
+    fn parses_scheduled_start() {
+        let input = parse_time_input("@14:00>25m#专注 /工作").unwrap();
+        assert_eq!((input.name.as_str(), input.group.as_deref()), ("专注", Some("工作")));
+        assert!(matches!(input.task_type, TaskType::Duration(d) if d.as_secs() == 25 * 60));
+        let start: DateTime<Local> = input.scheduled_start.unwrap().into();
+        assert_eq!(start.format("%H:%M").to_string(), "14:00");
+        assert!(input.scheduled_start.unwrap() > SystemTime::now());
+
+        assert_eq!(parse_time_input("25m#专注").unwrap().scheduled_start, None);
+        // 只有时间段任务可以定时开始，开始时刻必须以 @ 开头
+        for input in ["@14:00>@18:00#下班", "14:00>25m#专注", "@25:00>25m#专注"] {
+            assert!(parse_time_input(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn scheduled_task_formats_and_edits_back() {
+        let mut task = Task::new("专注".to_string(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        let input = parse_time_input("@09:30>25m#专注").unwrap();
+        task.scheduled_start = input.scheduled_start;
+
+        let formatted = format_task_input(&task);
+        assert!(formatted.starts_with("@09:30>25m#专注"), "{formatted}");
+        let parsed = parse_time_input(&formatted).unwrap();
+        assert_eq!(parsed.scheduled_start, task.scheduled_start);
+
+        // 编辑时去掉定时部分即取消定时
+        let edited = parse_time_input("25m#专注").unwrap().edit(task, false).unwrap();
+        assert_eq!(edited.scheduled_start, None);
     }
 }
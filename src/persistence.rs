@@ -0,0 +1,532 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use directories::ProjectDirs;
+use serde_json::{Map, Value, json};
+use snafu::{OptionExt, ResultExt, ensure};
+
+use crate::{
+    error::{Error, InvalidSaveFileSnafu, IoSnafu, Result, SaveFormatSnafu, UnsupportedSaveVersionSnafu},
+    task::{Task, TaskId, TaskList},
+};
+
+// 存档格式版本，修改 Task 的存档字段时加一，并在 MIGRATIONS
+// 末尾添加对应的升级函数
+pub const CURRENT_VERSION: u64 = 9;
+
+// 把单个任务升级一个版本
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+// MIGRATIONS[i] 把单个任务从版本 i + 1 升级到版本 i + 2
+const MIGRATIONS: [Migration; (CURRENT_VERSION - 1) as usize] = [
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+    migrate_v8_to_v9,
+];
+
+// 默认存档位置：平台数据目录下的 TimeTicker/tasks.json
+pub fn default_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "TimeTicker").map(|dirs| dirs.data_dir().join("tasks.json"))
+}
+
+// 保存任务列表，先写入临时文件再替换，避免写到一半时留下损坏的存档
+pub fn save_file(path: &Path, tasks: &TaskList) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+    }
+    let json = to_json(tasks)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).context(IoSnafu { path: &tmp })?;
+    fs::rename(&tmp, path).context(IoSnafu { path })
+}
+
+// 把任务列表序列化为当前版本的存档：{ "version": N, "tasks": [...], "trash": [[task, 删除时刻], ...] }
+pub fn to_json(tasks: &TaskList) -> Result<String> {
+    serde_json::to_string_pretty(&document(tasks.iter(), tasks.trash())).context(SaveFormatSnafu)
+}
+
+// 导出任务列表，格式与存档相同；计时中的时间段任务在 now 暂停，
+// 导出的是当时的剩余时间，导入后不会扣除导出以来经过的时间
+pub fn export_json(tasks: &TaskList, now: SystemTime) -> Result<String> {
+    let frozen = tasks
+        .iter()
+        .map(|task| task.clone().frozen_at(now))
+        .collect::<Result<Vec<Task>>>()?;
+    serde_json::to_string_pretty(&document(frozen.iter(), &[])).context(SaveFormatSnafu)
+}
+
+// 导出到用户选择的文件，直接覆盖已有文件
+pub fn export_file(path: &Path, tasks: &TaskList, now: SystemTime) -> Result<()> {
+    let json = export_json(tasks, now)?;
+    fs::write(path, json).context(IoSnafu { path })
+}
+
+// 存档和导出共用的文档结构，导出时不包含回收站
+fn document<'a>(tasks: impl Iterator<Item = &'a Task>, trash: &[(Task, SystemTime)]) -> Value {
+    json!({
+        "version": CURRENT_VERSION,
+        "tasks": tasks.collect::<Vec<&Task>>(),
+        "trash": trash,
+    })
+}
+
+// 读取任意已知版本的存档，旧版本逐级升级到当前版本
+pub fn from_json(json: &str) -> Result<TaskList> {
+    let document: Value = serde_json::from_str(json).context(SaveFormatSnafu)?;
+    let mut document = migrate(document)?;
+
+    let tasks: Vec<Task> = serde_json::from_value(document["tasks"].take()).context(SaveFormatSnafu)?;
+    // 旧存档和导出文件没有回收站
+    let trash: Vec<(Task, SystemTime)> = match document["trash"].take() {
+        Value::Null => Vec::new(),
+        trash => serde_json::from_value(trash).context(SaveFormatSnafu)?,
+    };
+    for task in tasks.iter().chain(trash.iter().map(|(task, _)| task)) {
+        task.id.mark_used();
+    }
+    let mut list: TaskList = tasks.into_iter().collect();
+    list.set_trash(trash);
+    Ok(list)
+}
+
+// 从文件加载存档；版本比当前程序新时保留一份 .bak 副本，避免之后被覆盖
+pub fn load_file(path: &Path) -> Result<TaskList> {
+    let json = fs::read_to_string(path).context(IoSnafu { path })?;
+    match from_json(&json) {
+        Err(e @ Error::UnsupportedSaveVersion { .. }) => {
+            let backup = backup_path(path);
+            fs::copy(path, &backup).context(IoSnafu { path: backup })?;
+            Err(e)
+        }
+        result => result,
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+fn migrate(document: Value) -> Result<Value> {
+    // 版本 2 之前任务列表不能保存，版本 1 从未写入磁盘；没有外层结构的任务数组（例如手工编写的文件）按版本 1 读取
+    let mut document = match document {
+        Value::Array(tasks) => json!({ "version": 1, "tasks": tasks }),
+        document => document,
+    };
+
+    let version = document
+        .get("version")
+        .and_then(Value::as_u64)
+        .context(InvalidSaveFileSnafu {
+            reason: "missing version",
+        })?;
+    ensure!(version <= CURRENT_VERSION, UnsupportedSaveVersionSnafu {
+        version,
+        supported: CURRENT_VERSION
+    });
+    ensure!(version >= 1, InvalidSaveFileSnafu {
+        reason: format!("unknown version {}", version)
+    });
+
+    let tasks = document
+        .get_mut("tasks")
+        .and_then(Value::as_array_mut)
+        .context(InvalidSaveFileSnafu {
+            reason: "missing tasks",
+        })?;
+    migrate_tasks(tasks.iter_mut(), version)?;
+    // 回收站中的每一项是 [task, 删除时刻]
+    if let Some(trash) = document.get_mut("trash").and_then(Value::as_array_mut) {
+        migrate_tasks(trash.iter_mut().filter_map(|entry| entry.get_mut(0)), version)?;
+    }
+    document["version"] = json!(CURRENT_VERSION);
+    Ok(document)
+}
+
+// 把每个任务从 version 逐级升级到当前版本
+fn migrate_tasks<'a>(tasks: impl Iterator<Item = &'a mut Value>, version: u64) -> Result<()> {
+    for task in tasks {
+        let task = task.as_object_mut().context(InvalidSaveFileSnafu {
+            reason: "task is not an object",
+        })?;
+        for migration in &MIGRATIONS[(version - 1) as usize..] {
+            migration(task)?;
+        }
+    }
+    Ok(())
+}
+
+// 版本 1 只有名称、类型、计时状态和固定标记，其余字段使用新建任务时的默认值
+// 只用于读取手工编写的任务数组，实际存档从版本 2 开始
+fn migrate_v1_to_v2(task: &mut Map<String, Value>) -> Result<()> {
+    let now = serde_json::to_value(SystemTime::now()).context(SaveFormatSnafu)?;
+    let zero = serde_json::to_value(Duration::ZERO).context(SaveFormatSnafu)?;
+
+    // 时间段任务的计划用时就是设定的时长，截止时间任务只能用保存时的剩余时间近似
+    let planned = match task.get("task_type").and_then(|t| t.get("Duration")) {
+        Some(duration) => duration.clone(),
+        None => task.get("remaining").cloned().unwrap_or_else(|| zero.clone()),
+    };
+    let id = serde_json::to_value(TaskId::next()).context(SaveFormatSnafu)?;
+
+    task.entry("id").or_insert(id);
+    task.entry("created_at").or_insert(now);
+    task.entry("finished").or_insert(json!(false));
+    task.entry("next").or_insert(Value::Null);
+    task.entry("group").or_insert(Value::Null);
+    task.entry("planned").or_insert(planned);
+    task.entry("elapsed").or_insert(zero.clone());
+    task.entry("total_paused").or_insert(zero);
+    task.entry("paused_at").or_insert(Value::Null);
+    task.entry("finished_at").or_insert(Value::Null);
+    task.entry("on_complete").or_insert(Value::Null);
+    task.entry("archived").or_insert(json!(false));
+    task.entry("muted").or_insert(json!(false));
+    Ok(())
+}
+
+// 版本 3 增加持续提醒；旧存档中的任务视为已确认，避免升级后立即重复提醒
+fn migrate_v2_to_v3(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("nag").or_insert(json!(false));
+    task.entry("acknowledged").or_insert(json!(true));
+    Ok(())
+}
+
+// 版本 4 记录最近一次开始计时的时间，旧存档中没有记录
+fn migrate_v3_to_v4(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("last_started_at").or_insert(Value::Null);
+    Ok(())
+}
+
+// 版本 5 增加任务自己的 webhook 地址，旧存档中的任务使用全局设置
+fn migrate_v4_to_v5(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("webhook").or_insert(Value::Null);
+    Ok(())
+}
+
+// 版本 6 增加固定图标使用的图片
+fn migrate_v5_to_v6(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("icon_path").or_insert(Value::Null);
+    Ok(())
+}
+
+// 版本 7 增加定时开始的时刻
+fn migrate_v6_to_v7(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("scheduled_start").or_insert(Value::Null);
+    Ok(())
+}
+
+// 版本 8 增加提前提醒的时长和已提醒的记录
+fn migrate_v7_to_v8(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("warn_offsets").or_insert(Value::Null);
+    task.entry("warned").or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+// 版本 9 增加按已用比例提醒的里程碑和已提醒的记录
+fn migrate_v8_to_v9(task: &mut Map<String, Value>) -> Result<()> {
+    task.entry("warn_milestones").or_insert(Value::Null);
+    task.entry("warned_milestones").or_insert(Value::Array(Vec::new()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::TaskType;
+
+    // 历史版本程序实际写出的存档：写作（计时中、固定、分组、完成后开始评审）、阅读（已暂停、完成命令）、
+    // 下班（截止时间）、评审（已完成、静音）；版本 5 的存档中阅读开启了持续提醒，评审设置了 webhook
+    const SAVE_V2: &str = include_str!("../tests/fixtures/save-v2.json");
+    const SAVE_V5: &str = include_str!("../tests/fixtures/save-v5.json");
+
+    fn names(tasks: &TaskList) -> Vec<&str> {
+        tasks.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    // 两个版本共有的字段在读取后保持不变
+    fn assert_common_fields(tasks: &TaskList) {
+        assert_eq!(names(tasks), ["写作", "阅读", "下班", "评审"]);
+        let [writing, reading, leaving, review] = [0, 1, 2, 3].map(|i| tasks.nth(i).unwrap());
+
+        assert!(matches!(writing.task_type, TaskType::Duration(d) if d == Duration::from_secs(1500)));
+        assert!(writing.is_running && writing.start_time.is_some());
+        assert!(writing.pinned);
+        assert_eq!(writing.group.as_deref(), Some("工作"));
+        assert_eq!(writing.next, Some(review.id));
+
+        assert!(!reading.is_running);
+        assert!(reading.paused_at.is_some());
+        assert_eq!(reading.remaining.as_secs(), 2399);
+        assert_eq!(reading.planned, Duration::from_secs(3600));
+        assert_eq!(reading.on_complete.as_deref(), Some("say done"));
+
+        assert!(matches!(leaving.task_type, TaskType::Deadline(_)));
+        assert!(review.finished && review.muted);
+        assert_eq!(review.remaining, Duration::ZERO);
+
+        // 之后版本增加的字段取默认值
+        for task in tasks.iter() {
+            assert_eq!(task.icon_path, None);
+            assert_eq!(task.scheduled_start, None);
+            assert_eq!(task.warn_offsets, None);
+            assert!(task.warned.is_empty());
+            assert_eq!(task.warn_milestones, None);
+            assert!(task.warned_milestones.is_empty());
+        }
+    }
+
+    #[test]
+    fn reads_version_2_save() {
+        let tasks = from_json(SAVE_V2).unwrap();
+        assert_common_fields(&tasks);
+        for task in tasks.iter() {
+            // 版本 3 之前没有持续提醒，已有的完成提醒视为已确认
+            assert!(!task.nag && task.acknowledged);
+            assert_eq!(task.last_started_at, None);
+            assert_eq!(task.webhook, None);
+        }
+    }
+
+    #[test]
+    fn reads_version_5_save() {
+        let tasks = from_json(SAVE_V5).unwrap();
+        assert_common_fields(&tasks);
+        let [writing, reading, leaving, review] = [0, 1, 2, 3].map(|i| tasks.nth(i).unwrap());
+        assert!(reading.nag);
+        assert!(!writing.nag && !review.nag);
+        assert!(writing.last_started_at.is_some() && reading.last_started_at.is_some());
+        assert_eq!(leaving.last_started_at, None);
+        assert_eq!(review.webhook.as_deref(), Some("https://example.com/hook"));
+        assert_eq!(writing.webhook, None);
+    }
+
+    #[test]
+    fn migrates_a_bare_version_1_array() {
+        let json = r#"[{"name": "写作", "task_type": {"Duration": {"secs": 1500, "nanos": 0}}, "is_running": false,
+            "start_time": null, "remaining": {"secs": 900, "nanos": 0}, "pinned": true}]"#;
+        let tasks = from_json(json).unwrap();
+        let task = tasks.nth(0).unwrap();
+        assert_eq!((task.name.as_str(), task.pinned, task.remaining.as_secs()), ("写作", true, 900));
+        assert_eq!(task.planned, Duration::from_secs(1500));
+        assert!(!task.finished && !task.archived && !task.muted);
+    }
+
+    #[test]
+    fn saved_file_is_written_as_current_version() {
+        let tasks = from_json(SAVE_V2).unwrap();
+        let document: Value = serde_json::from_str(&to_json(&tasks).unwrap()).unwrap();
+        assert_eq!(document["version"], json!(CURRENT_VERSION));
+        assert_common_fields(&from_json(&to_json(&tasks).unwrap()).unwrap());
+    }
+
+    // 每个可选字段都有值的任务，存档中不会出现 null
+    fn full_task(name: &str, next: TaskId) -> Task {
+        let at = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        let mut task = Task::new(name.into(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        task.is_running = true;
+        task.start_time = Some(at);
+        task.remaining = Duration::new(900, 5);
+        task.pinned = true;
+        task.created_at = at - Duration::from_secs(3600);
+        task.next = Some(next);
+        task.group = Some("工作".into());
+        task.elapsed = Duration::from_secs(600);
+        task.total_paused = Duration::from_secs(30);
+        task.paused_at = Some(at - Duration::from_secs(60));
+        task.finished_at = Some(at + Duration::from_secs(900));
+        task.on_complete = Some("say done".into());
+        task.archived = true;
+        task.muted = true;
+        task.nag = true;
+        task.acknowledged = true;
+        task.last_started_at = Some(at);
+        task.webhook = Some("https://example.com/hook".into());
+        task.icon_path = Some(PathBuf::from("/tmp/icon.png"));
+        task.scheduled_start = Some(at + Duration::from_secs(7200));
+        task.warn_offsets = Some(vec![Duration::from_secs(300), Duration::from_secs(90)]);
+        task.warned = vec![Duration::from_secs(300)];
+        task.warn_milestones = Some(vec![0.5, 0.9]);
+        task.warned_milestones = vec![0.5];
+        task
+    }
+
+    #[test]
+    fn save_round_trips_every_field() {
+        let mut tasks = TaskList::new();
+        let review = tasks.push(Task::new("评审".into(), TaskType::Duration(Duration::from_secs(60))).unwrap());
+        tasks.push(full_task("写作", review));
+
+        let json = to_json(&tasks).unwrap();
+        let document: Value = serde_json::from_str(&json).unwrap();
+        let task = document["tasks"][1].as_object().unwrap();
+        let empty: Vec<_> = task.iter().filter(|(_, v)| v.is_null()).map(|(k, _)| k).collect();
+        assert!(empty.is_empty(), "fields not covered by the round trip: {empty:?}");
+
+        let loaded = from_json(&json).unwrap();
+        assert_eq!(to_json(&loaded).unwrap(), json);
+        assert_eq!(loaded.nth(1).unwrap().next, Some(review));
+    }
+
+    #[test]
+    fn trash_round_trips_and_is_left_out_of_exports() {
+        let mut tasks = TaskList::new();
+        let review = tasks.push(Task::new("评审".into(), TaskType::Duration(Duration::from_secs(60))).unwrap());
+        let writing = tasks.push(full_task("写作", review));
+        tasks.delete(writing).unwrap();
+        assert_eq!(tasks.trash().len(), 1);
+
+        let json = to_json(&tasks).unwrap();
+        let loaded = from_json(&json).unwrap();
+        assert_eq!(names(&loaded), ["评审"]);
+        assert_eq!(to_json(&loaded).unwrap(), json);
+        let (trashed, deleted_at) = &loaded.trash()[0];
+        assert_eq!((trashed.id, *deleted_at), (writing, tasks.trash()[0].1));
+
+        let exported = from_json(&export_json(&tasks, SystemTime::now()).unwrap()).unwrap();
+        assert!(exported.trash().is_empty());
+        // 旧存档没有回收站
+        assert!(from_json(SAVE_V2).unwrap().trash().is_empty());
+    }
+
+    #[test]
+    fn trashed_tasks_are_migrated() {
+        let json = r#"{"version": 1, "tasks": [], "trash": [[{"name": "写作", "task_type": {"Duration":
+            {"secs": 1500, "nanos": 0}}, "is_running": false, "start_time": null, "remaining": {"secs": 900,
+            "nanos": 0}, "pinned": false}, {"secs_since_epoch": 1700000000, "nanos_since_epoch": 0}]]}"#;
+        let tasks = from_json(json).unwrap();
+        let (task, deleted_at) = &tasks.trash()[0];
+        assert_eq!((task.name.as_str(), task.planned), ("写作", Duration::from_secs(1500)));
+        assert_eq!(*deleted_at, SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+    }
+
+    #[test]
+    fn loaded_ids_are_not_reused() {
+        let mut tasks = TaskList::new();
+        let mut task = Task::new("任务".into(), TaskType::Duration(Duration::from_secs(60))).unwrap();
+        task.id = serde_json::from_value(json!(5_000_000)).unwrap();
+        tasks.push(task);
+
+        let loaded = from_json(&to_json(&tasks).unwrap()).unwrap();
+        assert_eq!(loaded.len(), 1);
+        let next: u64 = serde_json::from_value(serde_json::to_value(TaskId::next()).unwrap()).unwrap();
+        assert!(next > 5_000_000);
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for json in [r#"{"tasks": []}"#, r#"{"version": 0, "tasks": []}"#, r#"{"version": 2}"#] {
+            assert!(matches!(from_json(json), Err(Error::InvalidSaveFile { .. })), "{json}");
+        }
+        assert!(matches!(from_json("not json"), Err(Error::SaveFormat { .. })));
+    }
+
+    #[test]
+    fn newer_save_is_backed_up() {
+        let dir = std::env::temp_dir().join(format!("timeticker-persistence-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tasks.json");
+        let json = r#"{"version": 99, "tasks": [{"future": true}]}"#;
+        fs::write(&path, json).unwrap();
+
+        let result = load_file(&path);
+        assert!(matches!(result, Err(Error::UnsupportedSaveVersion { version: 99, .. })));
+        // 原文件保持不变，另有一份完整的副本
+        assert_eq!(fs::read_to_string(&path).unwrap(), json);
+        assert_eq!(fs::read_to_string(dir.join("tasks.json.bak")).unwrap(), json);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn save_file_round_trips_through_disk() {
+        let dir = std::env::temp_dir().join(format!("timeticker-save-{}", std::process::id()));
+        let path = dir.join("nested").join("tasks.json");
+        let mut tasks = TaskList::new();
+        tasks.push(Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap());
+        let deadline = SystemTime::now() + Duration::from_secs(3600);
+        let leaving = tasks.push(Task::new("下班".into(), TaskType::Deadline(deadline)).unwrap());
+
+        save_file(&path, &tasks).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        // 模拟重启前的剩余时间已经过时：截止时间任务按截止时间重新计算
+        let mut document: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        document["tasks"][1]["remaining"] = json!({ "secs": 7200, "nanos": 0 });
+        fs::write(&path, document.to_string()).unwrap();
+
+        let loaded = load_file(&path).unwrap();
+        assert_eq!(names(&loaded), ["写作", "下班"]);
+        let remaining = loaded.get(leaving).unwrap().get_remaining_time().unwrap();
+        assert!(remaining <= Duration::from_secs(3600) && remaining > Duration::from_secs(3500), "{remaining:?}");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_freezes_running_duration_tasks() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let now = start + Duration::from_secs(300);
+        let mut tasks = TaskList::new();
+        let mut writing = Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        writing.is_running = true;
+        writing.start_time = Some(start);
+        writing.remaining = Duration::from_secs(1500);
+        let writing = tasks.push(writing);
+        let paused = tasks.push(Task::new("阅读".into(), TaskType::Duration(Duration::from_secs(600))).unwrap());
+        let leaving = SystemTime::now() + Duration::from_secs(3600);
+        let deadline = tasks.push(Task::new("下班".into(), TaskType::Deadline(leaving)).unwrap());
+
+        let json = export_json(&tasks, now).unwrap();
+        let document: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(document["version"], json!(CURRENT_VERSION));
+
+        let exported = from_json(&json).unwrap();
+        let task = exported.get(writing).unwrap();
+        assert!(!task.is_running);
+        assert_eq!(task.start_time, None);
+        assert_eq!(task.remaining, Duration::from_secs(1200));
+        assert_eq!(task.elapsed, Duration::from_secs(300));
+        assert_eq!(task.paused_at, Some(now));
+        // 未计时的任务和截止时间任务原样导出
+        for id in [paused, deadline] {
+            let original = serde_json::to_value(tasks.get(id).unwrap()).unwrap();
+            assert_eq!(serde_json::to_value(exported.get(id).unwrap()).unwrap(), original);
+        }
+        // 导出不会改动正在运行的任务
+        assert!(tasks.get(writing).unwrap().is_running);
+    }
+
+    #[test]
+    fn exported_file_can_be_loaded() {
+        let dir = std::env::temp_dir().join(format!("timeticker-export-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("export.json");
+        let mut tasks = TaskList::new();
+        tasks.push(Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap());
+
+        export_file(&path, &tasks, SystemTime::now()).unwrap();
+        assert_eq!(names(&load_file(&path).unwrap()), ["写作"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_or_corrupt_files_are_errors() {
+        let dir = std::env::temp_dir().join(format!("timeticker-corrupt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tasks.json");
+        assert!(matches!(load_file(&path), Err(Error::Io { .. })));
+        fs::write(&path, "{\"version\": 2, \"tasks\": [").unwrap();
+        assert!(matches!(load_file(&path), Err(Error::SaveFormat { .. })));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::error::{IoSnafu, RecentFormatSnafu, Result};
+
+pub const MAX_RECENT: usize = 8; // 最多记住的输入数
+
+// 与配置文件放在同一目录下的 recent.json
+pub fn path_for(config_path: &Path) -> PathBuf {
+    config_path.with_file_name("recent.json")
+}
+
+// 最近成功创建任务的输入，最近使用的在最前面，不重复
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RecentInputs {
+    inputs: Vec<String>,
+}
+
+impl RecentInputs {
+    // 读取最近使用的输入，文件不存在时为空
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path).context(IoSnafu { path })?;
+        let saved: Vec<String> = serde_json::from_str(&json).context(RecentFormatSnafu)?;
+        // 按保存的顺序重新加入，手动修改过的文件也满足去重和数量上限
+        let mut recent = Self::default();
+        for input in saved.iter().rev() {
+            recent.push(input);
+        }
+        Ok(recent)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context(IoSnafu { path: dir })?;
+        }
+        let json = serde_json::to_string_pretty(self).context(RecentFormatSnafu)?;
+        fs::write(path, json).context(IoSnafu { path })
+    }
+
+    // 把输入移到最前面，已有相同的输入时去掉旧的，超出上限时丢弃最久未用的
+    pub fn push(&mut self, input: &str) {
+        let input = input.trim();
+        if input.is_empty() {
+            return;
+        }
+        self.inputs.retain(|existing| existing != input);
+        self.inputs.insert(0, input.to_string());
+        self.inputs.truncate(MAX_RECENT);
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.inputs.get(index).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.inputs.iter().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("timeticker-recent-{}-{}", name, std::process::id())).join("recent.json")
+    }
+
+    fn inputs(recent: &RecentInputs) -> Vec<&str> {
+        recent.iter().collect()
+    }
+
+    #[test]
+    fn push_moves_repeated_inputs_to_the_front() {
+        let mut recent = RecentInputs::default();
+        for input in ["25m#写作", "5m#休息", " 25m#写作 ", "", "   "] {
+            recent.push(input);
+        }
+        assert_eq!(inputs(&recent), ["25m#写作", "5m#休息"]);
+        assert_eq!(recent.get(1), Some("5m#休息"));
+        assert_eq!(recent.get(2), None);
+    }
+
+    #[test]
+    fn push_drops_the_least_recent_beyond_the_cap() {
+        let mut recent = RecentInputs::default();
+        for minutes in 1..=MAX_RECENT + 2 {
+            recent.push(&format!("{minutes}m"));
+        }
+        assert_eq!(recent.iter().count(), MAX_RECENT);
+        assert_eq!(recent.get(0), Some(format!("{}m", MAX_RECENT + 2).as_str()));
+        assert_eq!(recent.get(MAX_RECENT - 1), Some("3m"));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_path("save");
+        assert!(RecentInputs::load(&path).unwrap().is_empty());
+
+        let mut recent = RecentInputs::default();
+        recent.push("@18:00#下班");
+        recent.push("25m#写作");
+        recent.save(&path).unwrap();
+        assert_eq!(RecentInputs::load(&path).unwrap(), recent);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn hand_edited_files_are_deduplicated_and_capped() {
+        let path = temp_path("edited");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut saved = vec!["a", "b", "a"];
+        saved.extend(["c"; MAX_RECENT]);
+        saved.extend(["d", "e", "f", "g", "h", "i", "j"]);
+        fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let recent = RecentInputs::load(&path).unwrap();
+        assert_eq!(inputs(&recent), ["a", "b", "c", "d", "e", "f", "g", "h"]);
+
+        fs::write(&path, "{}").unwrap();
+        assert!(RecentInputs::load(&path).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
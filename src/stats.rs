@@ -0,0 +1,298 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Duration,
+};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::history::HistoryRecord;
+
+const TOP_TASKS: usize = 3; // 统计菜单中显示的用时最多的任务数
+
+// 参与统计的一段计时：任务名称、分组、用时和计入的时刻
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub tag: Option<String>,
+    pub time: Duration,
+    pub at: DateTime<Local>,
+}
+
+impl Entry {
+    // 完成记录按完成时刻计入，完成时间无法解析时跳过
+    pub fn from_record(record: &HistoryRecord) -> Option<Self> {
+        let finished_at = DateTime::parse_from_rfc3339(&record.finished_at).ok()?;
+        Some(Self {
+            name: record.name.clone(),
+            tag: record.tags.first().cloned(),
+            time: Duration::from_secs(record.actual_secs),
+            at: finished_at.with_timezone(&Local),
+        })
+    }
+}
+
+// 今天和本周的用时汇总
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub today: Duration,
+    pub week: Duration,
+    pub top: Vec<(String, Duration)>, // 本周用时最多的任务，按用时从多到少排列
+    pub budgets: Vec<BudgetUsage>,    // 设置了预算的分组今天的用时
+}
+
+// 按本地日期汇总：今天指与 now 同一天，本周指与 now 同一 ISO 周（周一开始）
+pub fn summarize(entries: impl IntoIterator<Item = Entry>, now: DateTime<Local>) -> Summary {
+    let today = now.date_naive();
+    let week = now.iso_week();
+    let mut summary = Summary::default();
+    let mut by_name: HashMap<String, Duration> = HashMap::new();
+    for entry in entries {
+        let date = entry.at.date_naive();
+        if date == today {
+            summary.today += entry.time;
+        }
+        if date.iso_week() == week {
+            summary.week += entry.time;
+            *by_name.entry(entry.name).or_default() += entry.time;
+        }
+    }
+    let mut top: Vec<(String, Duration)> = by_name.into_iter().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    top.truncate(TOP_TASKS);
+    summary.top = top;
+    summary
+}
+
+// 一个分组今天的用时和每日预算
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetUsage {
+    pub tag: String,
+    pub used: Duration,
+    pub budget: Duration,
+}
+
+impl BudgetUsage {
+    pub fn exhausted(&self) -> bool {
+        self.used >= self.budget
+    }
+
+    // 已用比例，用于绘制进度条
+    pub fn fraction(&self) -> f64 {
+        if self.budget.is_zero() {
+            return 1.0;
+        }
+        (self.used.as_secs_f64() / self.budget.as_secs_f64()).min(1.0)
+    }
+}
+
+// 跟踪各分组当天的预算：增量计入历史记录中当天的用时，并记住已经提醒过的分组，
+// 每个分组每天只提醒一次；日期变化后重新计算
+#[derive(Debug, Default)]
+pub struct BudgetTracker {
+    day: Option<NaiveDate>,
+    recorded: usize,                    // 已计入的历史记录条数
+    history: HashMap<String, Duration>, // 历史记录中当天各分组的用时
+    notified: HashSet<String>,          // 当天已提醒过预算用完的分组
+}
+
+impl BudgetTracker {
+    // 计入新增的历史记录；日期变化或历史记录被替换时从头计算
+    fn sync(&mut self, records: &[HistoryRecord], today: NaiveDate) {
+        if self.day != Some(today) || records.len() < self.recorded {
+            self.day = Some(today);
+            self.recorded = 0;
+            self.history.clear();
+            self.notified.clear();
+        }
+        for entry in records[self.recorded..].iter().filter_map(Entry::from_record) {
+            if entry.at.date_naive() == today
+                && let Some(tag) = entry.tag
+            {
+                *self.history.entry(tag).or_default() += entry.time;
+            }
+        }
+        self.recorded = records.len();
+    }
+
+    // 设置了预算的各分组今天的用时，按分组名排列；live 是尚未完成的任务，
+    // 跨过午夜的任务只计入今天的部分
+    pub fn usage(
+        &mut self,
+        records: &[HistoryRecord],
+        live: &[Entry],
+        budgets: &BTreeMap<String, u64>,
+        now: DateTime<Local>,
+    ) -> Vec<BudgetUsage> {
+        let today = now.date_naive();
+        self.sync(records, today);
+        let since_midnight = today
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .and_then(|midnight| (now - midnight).to_std().ok())
+            .unwrap_or_default();
+        budgets
+            .iter()
+            .map(|(tag, minutes)| {
+                let live_time: Duration = live
+                    .iter()
+                    .filter(|entry| entry.tag.as_ref() == Some(tag))
+                    .map(|entry| entry.time.min(since_midnight))
+                    .sum();
+                BudgetUsage {
+                    tag: tag.clone(),
+                    used: self.history.get(tag).copied().unwrap_or_default() + live_time,
+                    budget: Duration::from_secs(minutes * 60),
+                }
+            })
+            .collect()
+    }
+
+    // 从 usage 中取出刚用完预算、今天还没有提醒过的分组
+    pub fn newly_exhausted(&mut self, usage: &[BudgetUsage]) -> Vec<BudgetUsage> {
+        usage
+            .iter()
+            .filter(|budget| budget.exhausted() && self.notified.insert(budget.tag.clone()))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    // 2026-10-14 是周三，同一 ISO 周从 10-12 周一开始
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+    }
+
+    fn entry(name: &str, tag: Option<&str>, minutes: u32, at: DateTime<Local>) -> Entry {
+        Entry {
+            name: name.to_string(),
+            tag: tag.map(str::to_string),
+            time: MINUTE * minutes,
+            at,
+        }
+    }
+
+    #[test]
+    fn summarizes_today_and_this_week() {
+        let now = at(14, 15, 0);
+        let entries = [
+            entry("写作", None, 25, at(14, 9, 0)),
+            entry("阅读", None, 30, at(14, 14, 30)),
+            entry("写作", None, 50, at(13, 10, 0)),
+            entry("评审", None, 20, at(12, 0, 5)),
+            entry("周报", None, 90, at(11, 23, 55)), // 上周日
+        ];
+        let summary = summarize(entries, now);
+        assert_eq!(summary.today, MINUTE * 55);
+        assert_eq!(summary.week, MINUTE * 125);
+        assert_eq!(summary.top, [
+            ("写作".to_string(), MINUTE * 75),
+            ("阅读".to_string(), MINUTE * 30),
+            ("评审".to_string(), MINUTE * 20),
+        ]);
+        assert!(summary.budgets.is_empty());
+    }
+
+    #[test]
+    fn top_tasks_are_limited_and_ties_sorted_by_name() {
+        let now = at(14, 15, 0);
+        let entries = ["丁", "丙", "乙", "甲"].map(|name| entry(name, None, 10, at(14, 9, 0)));
+        let names: Vec<String> = summarize(entries, now).top.into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), TOP_TASKS);
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn entries_from_history_records() {
+        let record = HistoryRecord {
+            name: "写作".into(),
+            task_type: "duration".into(),
+            planned_secs: 1500,
+            actual_secs: 1620,
+            paused_secs: 0,
+            finished_at: at(14, 9, 0).to_rfc3339(),
+            tags: vec!["工作".into(), "写作".into()],
+        };
+        let from_record = Entry::from_record(&record).unwrap();
+        assert_eq!(from_record, entry("写作", Some("工作"), 27, at(14, 9, 0)));
+
+        let broken = HistoryRecord {
+            finished_at: "yesterday".into(),
+            ..record
+        };
+        assert_eq!(Entry::from_record(&broken), None);
+    }
+
+    fn record(tag: &str, minutes: u64, finished_at: DateTime<Local>) -> HistoryRecord {
+        HistoryRecord {
+            name: format!("{tag}任务"),
+            task_type: "duration".into(),
+            planned_secs: minutes * 60,
+            actual_secs: minutes * 60,
+            paused_secs: 0,
+            finished_at: finished_at.to_rfc3339(),
+            tags: vec![tag.to_string()],
+        }
+    }
+
+    fn budgets() -> BTreeMap<String, u64> {
+        BTreeMap::from([("工作".to_string(), 60), ("阅读".to_string(), 30)])
+    }
+
+    #[test]
+    fn budget_usage_counts_today_only() {
+        let mut tracker = BudgetTracker::default();
+        let mut records = vec![record("工作", 40, at(13, 23, 0)), record("工作", 20, at(14, 9, 0))];
+        let live = [entry("写作", Some("工作"), 15, at(14, 15, 0))];
+
+        let usage = tracker.usage(&records, &live, &budgets(), at(14, 15, 0));
+        assert_eq!(usage, [
+            BudgetUsage { tag: "工作".into(), used: MINUTE * 35, budget: MINUTE * 60 },
+            BudgetUsage { tag: "阅读".into(), used: Duration::ZERO, budget: MINUTE * 30 },
+        ]);
+        assert!((usage[0].fraction() - 35.0 / 60.0).abs() < 1e-9);
+
+        // 新增的记录只计入一次
+        records.push(record("阅读", 10, at(14, 15, 30)));
+        let usage = tracker.usage(&records, &[], &budgets(), at(14, 16, 0));
+        assert_eq!((usage[0].used, usage[1].used), (MINUTE * 20, MINUTE * 10));
+        let usage = tracker.usage(&records, &[], &budgets(), at(14, 16, 1));
+        assert_eq!((usage[0].used, usage[1].used), (MINUTE * 20, MINUTE * 10));
+    }
+
+    #[test]
+    fn live_task_across_midnight_counts_since_midnight() {
+        let mut tracker = BudgetTracker::default();
+        let live = [entry("写作", Some("工作"), 30, at(15, 0, 10))];
+        let usage = tracker.usage(&[], &live, &budgets(), at(15, 0, 10));
+        assert_eq!(usage[0].used, MINUTE * 10);
+    }
+
+    #[test]
+    fn exhausted_budget_is_reported_once_a_day() {
+        let mut tracker = BudgetTracker::default();
+        let records = [record("工作", 60, at(14, 9, 0))];
+
+        let usage = tracker.usage(&records, &[], &budgets(), at(14, 10, 0));
+        assert!(usage[0].exhausted() && !usage[1].exhausted());
+        let exhausted = tracker.newly_exhausted(&usage);
+        assert_eq!(exhausted.iter().map(|b| b.tag.as_str()).collect::<Vec<_>>(), ["工作"]);
+        let usage = tracker.usage(&records, &[], &budgets(), at(14, 11, 0));
+        assert!(tracker.newly_exhausted(&usage).is_empty());
+
+        // 第二天重新计算，前一天的记录不再计入
+        let records = [record("工作", 60, at(14, 9, 0)), record("工作", 70, at(15, 9, 0))];
+        let usage = tracker.usage(&records, &[], &budgets(), at(15, 10, 0));
+        assert_eq!(usage[0].used, MINUTE * 70);
+        assert_eq!(tracker.newly_exhausted(&usage).len(), 1);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use snafu::OptionExt;
+use tracing::warn;
+
+use crate::{
+    error::{Result, TaskLockSnafu},
+    task::TaskList,
+};
+
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2); // 等待锁的最长时间，超时视为死锁
+
+// 任务列表的共享存储：刷新显示只需读锁，菜单操作才需要写锁
+// parking_lot 的锁不会因持锁线程 panic 而中毒，但数据可能只改了一半，下次访问前先检查并修复
+#[derive(Debug, Clone, Default)]
+pub struct TaskStore {
+    inner: Arc<RwLock<TaskList>>,
+    writer_panicked: Arc<AtomicBool>, // 有线程在持有写锁时 panic，任务列表需要检查
+}
+
+// 可写访问，持有期间线程 panic 时记下来
+pub struct TaskListGuard<'a> {
+    guard: RwLockWriteGuard<'a, TaskList>,
+    writer_panicked: &'a AtomicBool,
+}
+
+impl TaskStore {
+    pub fn new(tasks: TaskList) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(tasks)),
+            writer_panicked: Arc::default(),
+        }
+    }
+
+    // 获取只读访问，超时返回 TaskLock 错误
+    pub fn read(&self) -> Result<RwLockReadGuard<'_, TaskList>> {
+        if self.writer_panicked.load(Ordering::Acquire) {
+            // 获取写锁时会修复任务列表
+            drop(self.write()?);
+        }
+        self.inner.try_read_for(LOCK_TIMEOUT).context(TaskLockSnafu)
+    }
+
+    // 获取可写访问，超时返回 TaskLock 错误
+    pub fn write(&self) -> Result<TaskListGuard<'_>> {
+        let mut guard = self.inner.try_write_for(LOCK_TIMEOUT).context(TaskLockSnafu)?;
+        if self.writer_panicked.swap(false, Ordering::AcqRel) {
+            let repaired = guard.repair();
+            warn!("A thread panicked while modifying tasks, repaired {} inconsistencies", repaired);
+        }
+        Ok(TaskListGuard {
+            guard,
+            writer_panicked: &self.writer_panicked,
+        })
+    }
+}
+
+impl Deref for TaskListGuard<'_> {
+    type Target = TaskList;
+
+    fn deref(&self) -> &TaskList {
+        &self.guard
+    }
+}
+
+impl DerefMut for TaskListGuard<'_> {
+    fn deref_mut(&mut self) -> &mut TaskList {
+        &mut self.guard
+    }
+}
+
+impl Drop for TaskListGuard<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.writer_panicked.store(true, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::Error,
+        task::{Task, TaskType},
+    };
+
+    fn store_with_tasks(count: usize) -> TaskStore {
+        let tasks = (0..count)
+            .map(|i| Task::new(format!("任务 {i}"), TaskType::Duration(Duration::from_secs(60))).unwrap())
+            .collect();
+        TaskStore::new(tasks)
+    }
+
+    #[test]
+    fn concurrent_readers_and_writers() {
+        const WRITERS: usize = 4;
+        const PUSHES: usize = 100;
+        let store = store_with_tasks(1);
+        let mut handles = Vec::new();
+        for writer in 0..WRITERS {
+            let store = store.clone();
+            handles.push(thread::spawn(move || {
+                for i in 0..PUSHES {
+                    let mut tasks = store.write().unwrap();
+                    let task = Task::new(format!("{writer}-{i}"), TaskType::Duration(Duration::from_secs(60))).unwrap();
+                    let id = tasks.push(task);
+                    tasks.get_mut(id).unwrap().start();
+                    tasks.get_mut(id).unwrap().pause().unwrap();
+                }
+            }));
+        }
+        for _ in 0..4 {
+            let store = store.clone();
+            handles.push(thread::spawn(move || {
+                for _ in 0..PUSHES {
+                    let tasks = store.read().unwrap();
+                    // 读到的列表总是完整的：id 不重复，没有改到一半的任务
+                    let mut ids: Vec<_> = tasks.iter().map(|t| t.id).collect();
+                    ids.sort();
+                    ids.dedup();
+                    assert_eq!(ids.len(), tasks.len());
+                    assert!(tasks.iter().all(|t| !t.is_running));
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(store.read().unwrap().len(), 1 + WRITERS * PUSHES);
+    }
+
+    #[test]
+    fn lock_times_out_while_another_thread_writes() {
+        let store = store_with_tasks(1);
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let holder = {
+            let store = store.clone();
+            thread::spawn(move || {
+                let _tasks = store.write().unwrap();
+                locked_tx.send(()).unwrap();
+                // 持有写锁直到测试结束
+                done_rx.recv().ok();
+            })
+        };
+        locked_rx.recv().unwrap();
+
+        assert!(matches!(store.write(), Err(Error::TaskLock { .. })));
+        assert!(matches!(store.read(), Err(Error::TaskLock { .. })));
+
+        done_tx.send(()).unwrap();
+        holder.join().unwrap();
+        assert_eq!(store.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn recovers_after_a_writer_panics() {
+        let store = store_with_tasks(2);
+        let first = store.read().unwrap().id_at(0).unwrap();
+
+        let writer = store.clone();
+        let result = thread::spawn(move || {
+            let mut tasks = writer.write().unwrap();
+            // 改到一半时 panic：两个任务的 id 相同，计时状态也不完整
+            let task = tasks.nth_mut(1).unwrap();
+            task.id = first;
+            task.is_running = true;
+            task.remaining = Duration::from_secs(u64::MAX / 2);
+            panic!("simulated panic while holding the task lock");
+        })
+        .join();
+        assert!(result.is_err());
+
+        let tasks = store.read().unwrap();
+        assert_eq!(tasks.len(), 2);
+        let second = tasks.nth(1).unwrap();
+        assert_ne!(second.id, first);
+        assert!(!second.is_running);
+        assert_eq!(second.remaining, Duration::from_secs(60));
+        drop(tasks);
+
+        // 修复只做一次，之后的写入照常进行
+        store.write().unwrap().push(Task::new("新任务".into(), TaskType::Duration(Duration::from_secs(60))).unwrap());
+        assert_eq!(store.read().unwrap().len(), 3);
+    }
+}
@@ -1,80 +1,621 @@
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
 use std::time::{Duration, SystemTime};
-use crate::error::{Result, system_time_to_duration, SystemTimeSnafu}; // Import Result and helpers
+use crate::error::{Result, system_time_to_duration, SystemTimeSnafu, TaskOperationSnafu, ChainCycleSnafu, TaskIdNotFoundSnafu, TaskNotSnoozableSnafu, ZeroDurationSnafu, DeadlinePassedSnafu, DurationTooLongSnafu}; // Import Result and helpers
+use crate::i18n::{tr, tr_args};
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt}; // For .context on Option and Result
 
-#[derive(Debug, Clone)]
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+static MENU_TIME_FORMAT: AtomicU8 = AtomicU8::new(TimeFormat::Full as u8); // 菜单中的时间格式，由设置控制
+static TITLE_TIME_FORMAT: AtomicU8 = AtomicU8::new(TimeFormat::Compact as u8); // 标题中的时间格式
+static MAX_DURATION_SECS: AtomicU64 = AtomicU64::new(MAX_DURATION.as_secs()); // 时长上限，由设置控制
+
+pub const MAX_DURATION: Duration = Duration::from_secs(7 * 24 * 3600); // 时间段任务的默认最长时长
+const UNDO_LIMIT: usize = 10; // 最多保留的撤销记录数
+pub const TRASH_LIMIT: usize = 20; // 回收站最多保留的任务数
+pub const TRASH_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600); // 回收站中的任务保留多久
+
+// 任务的唯一标识，删除或移动其他任务时保持不变
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TaskId(u64);
+
+impl TaskId {
+    pub fn next() -> Self {
+        Self(NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    // 从存档加载任务后调用，保证之后分配的 id 不会与已加载的任务重复
+    pub fn mark_used(self) {
+        NEXT_TASK_ID.fetch_max(self.0 + 1, Ordering::Relaxed);
+    }
+}
+
+// 菜单动作字符串中的任务 id，例如 toggle_3
+impl std::str::FromStr for TaskId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl std::fmt::Display for TaskId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TaskType {
     Duration(Duration),   // 时间段类型
     Deadline(SystemTime), // 截止时间类型
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Idle,     // 未开始
+    Running,  // 计时中（截止时间任务始终处于此状态直到到期）
+    Paused,   // 已暂停
+    Finished, // 已完成
+}
+
+impl TaskState {
+    // 菜单和提示中的分段顺序
+    pub const SECTIONS: [TaskState; 4] = [TaskState::Running, TaskState::Paused, TaskState::Idle, TaskState::Finished];
+
+    // 分段标题
+    pub fn section_title(self) -> &'static str {
+        tr(match self {
+            TaskState::Running => "section.running",
+            TaskState::Paused => "section.paused",
+            TaskState::Idle => "section.idle",
+            TaskState::Finished => "section.finished",
+        })
+    }
+}
+
+// 计时结束前的提醒：剩余时间降到提前量以内，或已用比例达到里程碑
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Warning {
+    Remaining(Duration), // 到达的提前量
+    Progress(f64),       // 到达的里程碑，0.0 到 1.0
+}
+
+// 提前提醒的默认设置，任务没有单独设置时使用
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WarningDefaults {
+    pub offsets: Vec<Duration>,
+    pub milestones: Vec<f64>,
+}
+
+// 任务状态变化事件，由各修改路径发出，再由 Application 统一分发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskEvent {
+    Started(TaskId),
+    Paused(TaskId),
+    Reset(TaskId),
+    Finished(TaskId),
+    Deleted(TaskId),
+    Edited(TaskId),
+}
+
+// 任务的计划与实际用时统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskStats {
+    pub planned: Duration, // 计划用时
+    pub actual: Duration,  // 实际用时（计时时间加超时时间）
+    pub paused: Duration,  // 暂停总时长
+    pub overrun: Duration, // 超出计划的时间
+}
+
+// 任务子菜单中勾选项的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskToggles {
+    pub muted: bool,
+    pub nag: bool,
+    pub pinned: bool,
+}
+
+// 任务在不同位置的显示格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryStyle {
+    Menu,        // 菜单项：剩余时间#名称，静音任务加 🔕
+    Tooltip,     // 托盘提示：剩余时间#名称 /分组
+    PinnedTitle, // 固定图标标题：按标题时间格式显示，最后一分钟显示 42s，超时加负号
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
+    pub id: TaskId,
     pub name: String, // 任务名称（标签）
     pub task_type: TaskType,
     pub is_running: bool,               // 是否正在运行
-    pub start_time: Option<SystemTime>, // 开始时间
+    pub start_time: Option<SystemTime>, // 开始时间（墙上时间，重启后据此扣除程序关闭的时长）
     pub remaining: Duration,            // 剩余时间
     pub pinned: bool,                   // 是否固定
+    pub created_at: SystemTime,         // 创建时间
+    pub finished: bool,                 // 是否已到时
+    pub next: Option<TaskId>,           // 完成后自动开始的任务
+    pub group: Option<String>,          // 所属分组
+    pub planned: Duration,              // 计划用时，不随计时变化
+    pub elapsed: Duration,              // 已结束的计时段累计用时
+    pub total_paused: Duration,         // 已结束的暂停段累计时长
+    pub paused_at: Option<SystemTime>,  // 暂停时间
+    pub finished_at: Option<SystemTime>, // 到时时间，用于计算超时
+    pub on_complete: Option<String>,    // 完成时执行的命令
+    pub archived: bool,                 // 是否已归档（不在菜单和提示中显示）
+    pub muted: bool,                    // 是否静音（完成时不发出提醒）
+    pub nag: bool,                      // 完成后持续提醒，直到确认
+    pub acknowledged: bool,             // 本次完成的提醒是否已确认
+    pub last_started_at: Option<SystemTime>, // 最近一次开始计时的时间，暂停后保留
+    pub webhook: Option<String>,        // 完成时 POST 的地址，覆盖全局设置，空字符串表示不发送
+    pub icon_path: Option<PathBuf>,     // 固定图标使用的图片，未设置时使用默认图标
+    pub scheduled_start: Option<SystemTime>, // 定时开始的时刻，到达后自动开始计时
+    pub warn_offsets: Option<Vec<Duration>>, // 剩余时间低于这些时长时提前提醒，未设置时使用配置中的默认值
+    pub warned: Vec<Duration>,          // 本次计时中已经提醒过的提前量，重置后清空
+    pub warn_milestones: Option<Vec<f64>>, // 已用比例达到这些值时提醒，未设置时使用配置中的默认值
+    pub warned_milestones: Vec<f64>,    // 本次计时中已经提醒过的里程碑，重置后清空
 }
 
 impl Task {
     // Changed to return Result to handle potential errors from duration_since
     pub fn new(name: String, task_type: TaskType) -> Result<Self> {
+        Self::create(name, task_type, false)
+    }
+
+    // 从磁盘恢复任务时使用，允许截止时间已经过去
+    pub fn new_allowing_overdue(name: String, task_type: TaskType) -> Result<Self> {
+        Self::create(name, task_type, true)
+    }
+
+    fn create(name: String, task_type: TaskType, allow_overdue: bool) -> Result<Self> {
+        validate_task_type(&task_type, allow_overdue, max_duration())?;
+        if let TaskType::Deadline(t) = &task_type {
+            system_time_to_duration(*t)?; // 截止时间需要晚于 UNIX_EPOCH
+        }
+        Ok(Self::idle(name, task_type))
+    }
+
+    // 以未开始状态创建任务，使用新的 id 和创建时间
+    fn idle(name: String, task_type: TaskType) -> Self {
+        let created_at = SystemTime::now();
         let remaining = match &task_type {
             TaskType::Duration(d) => *d,
-            TaskType::Deadline(t) => {
-                system_time_to_duration(*t)? // Use helper
-                    .saturating_sub(system_time_to_duration(SystemTime::now())?) // Use helper
-            }
+            TaskType::Deadline(t) => t.duration_since(created_at).unwrap_or_default(),
         };
+        let planned = planned_duration(&task_type, created_at);
 
-        Ok(Self {
+        Self {
+            id: TaskId::next(),
             name,
             task_type,
             is_running: false,
             start_time: None,
             remaining,
             pinned: false,
-        })
+            created_at,
+            finished: false,
+            next: None,
+            group: None,
+            planned,
+            elapsed: Duration::ZERO,
+            total_paused: Duration::ZERO,
+            paused_at: None,
+            finished_at: None,
+            on_complete: None,
+            archived: false,
+            muted: false,
+            nag: false,
+            acknowledged: false,
+            last_started_at: None,
+            webhook: None,
+            icon_path: None,
+            scheduled_start: None,
+            warn_offsets: None,
+            warned: Vec::new(),
+            warn_milestones: None,
+            warned_milestones: Vec::new(),
+        }
+    }
+
+    // 复制任务：名称加上编号后缀，保留计时方式、分组、完成命令、webhook、图标和提前提醒，状态重新开始，使用新的 id 和创建时间
+    pub fn duplicate(&self) -> Task {
+        let mut copy = Task::idle(duplicate_name(&self.name), self.task_type.clone());
+        copy.group = self.group.clone();
+        copy.on_complete = self.on_complete.clone();
+        copy.webhook = self.webhook.clone();
+        copy.icon_path = self.icon_path.clone();
+        copy.warn_offsets = self.warn_offsets.clone();
+        copy.warn_milestones = self.warn_milestones.clone();
+        copy
+    }
+
+    // 归档任务：从菜单中移出但保留记录，归档时取消固定
+    pub fn archive(&mut self) {
+        self.archived = true;
+        self.pinned = false;
+    }
+
+    // 从归档中恢复
+    pub fn restore(&mut self) {
+        self.archived = false;
     }
 
     pub fn start(&mut self) {
-        if !self.is_running {
+        self.start_at(SystemTime::now());
+    }
+
+    // 从给定时刻开始计时，定时开始的任务错过的时间按开始时刻计入；手动开始时取消定时
+    pub fn start_at(&mut self, now: SystemTime) {
+        self.scheduled_start = None;
+        // 已完成的时间段任务重新开始时从头计时
+        if self.finished
+            && let TaskType::Duration(d) = self.task_type
+        {
+            self.finished = false;
+            self.remaining = d;
+            self.elapsed = Duration::ZERO;
+            self.total_paused = Duration::ZERO;
+            self.paused_at = None;
+            self.finished_at = None;
+            self.warned.clear();
+            self.warned_milestones.clear();
+        }
+        if !self.is_running && !self.finished {
+            if let Some(paused_at) = self.paused_at.take() {
+                self.total_paused += now.duration_since(paused_at).unwrap_or_default();
+            }
             self.is_running = true;
-            self.start_time = Some(SystemTime::now());
+            self.start_time = Some(now);
+            self.last_started_at = Some(now);
+        }
+    }
+
+    // 系统休眠期间不计入计时：开始时刻后移，休眠时长记为暂停
+    pub fn credit_suspended(&mut self, gap: Duration) -> bool {
+        match (&self.task_type, self.is_running, self.start_time) {
+            (TaskType::Duration(_), true, Some(start)) => {
+                self.start_time = Some(start + gap);
+                self.total_paused += gap;
+                true
+            }
+            _ => false,
         }
     }
 
+    // 给任务操作中的错误加上任务名称和 id
+    fn in_context<T>(&self, operation: &'static str, result: Result<T>) -> Result<T> {
+        result.with_context(|_| TaskOperationSnafu { id: self.id, name: self.name.clone(), operation })
+    }
+
     // Changed to return Result to handle potential errors from start.elapsed()
     pub fn pause(&mut self) -> Result<()> {
         if self.is_running {
-            self.is_running = false;
             if let Some(start) = self.start_time {
-                let elapsed = start.elapsed().context(SystemTimeSnafu)?; // Handle error
+                let elapsed = self.in_context("pause", start.elapsed().context(SystemTimeSnafu))?;
+                self.elapsed += elapsed.min(self.remaining);
                 self.remaining = self.remaining.saturating_sub(elapsed);
             }
+            self.is_running = false;
             self.start_time = None;
+            self.paused_at = Some(SystemTime::now());
         }
         Ok(())
     }
 
     // Changed to return Result to handle potential errors from duration_since
     pub fn reset(&mut self) -> Result<()> {
+        let remaining = match &self.task_type {
+            TaskType::Duration(d) => *d,
+            TaskType::Deadline(t) => self.in_context("reset", deadline_remaining(*t, SystemTime::now()))?,
+        };
         self.is_running = false;
         self.start_time = None;
-        self.remaining = match &self.task_type {
-            TaskType::Duration(d) => *d,
-            TaskType::Deadline(t) => {
-                system_time_to_duration(*t)? // Use helper
-                    .saturating_sub(system_time_to_duration(SystemTime::now())?) // Use helper
-            }
+        self.remaining = remaining;
+        // 已过期的截止时间任务保持完成状态，避免重复触发完成
+        self.finished = matches!(self.task_type, TaskType::Deadline(_)) && self.remaining.is_zero();
+        self.elapsed = Duration::ZERO;
+        self.total_paused = Duration::ZERO;
+        self.paused_at = None;
+        self.finished_at = match &self.task_type {
+            TaskType::Deadline(t) if self.finished => Some(*t),
+            _ => None,
         };
+        self.warned.clear();
+        self.warned_milestones.clear();
         Ok(())
     }
 
+    // 菜单中静音、持续提醒和固定勾选项应有的状态
+    pub fn toggles(&self) -> TaskToggles {
+        TaskToggles {
+            muted: self.muted,
+            nag: self.nag,
+            pinned: self.pinned,
+        }
+    }
+
+    // 剩余时间第一次降到提前量以内、或已用比例第一次达到里程碑时返回提醒，
+    // 每个提前量和里程碑在一次计时中只提醒一次；不短于计划用时的提前量和不在 0 到 1 之间的里程碑直接跳过。
+    // 同一次检查中到达多个时（例如程序刚启动）只返回一个：里程碑优先，其次是最接近的提前量
+    pub fn due_warning(&mut self, defaults: &WarningDefaults, now: SystemTime) -> Result<Option<Warning>> {
+        if self.state() != TaskState::Running {
+            return Ok(None);
+        }
+        let remaining = self.remaining_time_at(now)?;
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+
+        let offsets = self.warn_offsets.as_deref().unwrap_or(&defaults.offsets);
+        let due_offsets: Vec<Duration> = offsets
+            .iter()
+            .copied()
+            .filter(|offset| remaining <= *offset && *offset < self.planned && !self.warned.contains(offset))
+            .collect();
+        self.warned.extend(&due_offsets);
+
+        let progress = self.progress_at(now)?;
+        let milestones = self.warn_milestones.as_deref().unwrap_or(&defaults.milestones);
+        let due_milestones: Vec<f64> = milestones
+            .iter()
+            .copied()
+            .filter(|m| *m > 0.0 && *m < 1.0 && progress >= *m && !self.warned_milestones.contains(m))
+            .collect();
+        self.warned_milestones.extend(&due_milestones);
+
+        if let Some(milestone) = due_milestones.into_iter().reduce(f64::max) {
+            return Ok(Some(Warning::Progress(milestone)));
+        }
+        Ok(due_offsets.into_iter().min().map(Warning::Remaining))
+    }
+
+    // 已完成或截止时间已过的任务可以推迟
+    pub fn can_snooze(&self, now: SystemTime) -> bool {
+        match &self.task_type {
+            TaskType::Deadline(t) => self.finished || *t <= now,
+            TaskType::Duration(_) => self.finished,
+        }
+    }
+
+    // 推迟到时：从现在起再计时 extra，截止时间任务把截止时间改为现在加 extra；
+    // 本次完成的提醒视为已确认，不能推迟时返回 false
+    pub fn snooze(&mut self, extra: Duration, now: SystemTime) -> bool {
+        if !self.can_snooze(now) {
+            return false;
+        }
+        match &mut self.task_type {
+            TaskType::Duration(_) => {
+                self.is_running = true;
+                self.start_time = Some(now);
+                self.last_started_at = Some(now);
+            }
+            TaskType::Deadline(t) => *t = now + extra,
+        }
+        self.remaining = extra;
+        self.finished = false;
+        self.finished_at = None;
+        self.paused_at = None;
+        self.acknowledged = true;
+        true
+    }
+
+    // 计时中的时间段任务在 now 暂停，保留当时的剩余时间，之后经过的时间不再扣除
+    pub fn frozen_at(mut self, now: SystemTime) -> Result<Task> {
+        if self.is_running
+            && let TaskType::Duration(_) = self.task_type
+        {
+            let remaining = self.remaining_time_at(now)?;
+            self.elapsed += self.remaining.saturating_sub(remaining);
+            self.remaining = remaining;
+            self.is_running = false;
+            self.start_time = None;
+            self.paused_at = Some(now);
+        }
+        Ok(self)
+    }
+
+    // 编辑任务时替换计时方式，保留名称和固定状态，重新计算剩余时间并停止计时
+    pub fn set_type(&mut self, new_type: TaskType) -> Result<()> {
+        validate_task_type(&new_type, false, max_duration())?;
+        self.planned = planned_duration(&new_type, SystemTime::now());
+        self.task_type = new_type;
+        self.reset()
+    }
+
+    // 按显示位置格式化任务，到时后显示超时时长并加负号
+    pub fn summary(&self, style: SummaryStyle) -> Result<String> {
+        self.summary_at(style, SystemTime::now())
+    }
+
+    // 以给定时刻格式化任务，同一次刷新中的所有显示使用同一时刻
+    pub fn summary_at(&self, style: SummaryStyle, now: SystemTime) -> Result<String> {
+        let (sign, time) = match self.finished_at {
+            Some(finished_at) if self.finished => {
+                let overtime = now.duration_since(finished_at).context(SystemTimeSnafu)?;
+                (if overtime.is_zero() { "" } else { "-" }, overtime)
+            }
+            _ => ("", self.remaining_time_at(now)?),
+        };
+        let format = match style {
+            SummaryStyle::PinnedTitle => title_time_format(),
+            SummaryStyle::Menu | SummaryStyle::Tooltip => time_format(),
+        };
+        let time_str = match style {
+            // 最后一分钟只显示秒数，补零到两位，读秒时标题宽度不变
+            SummaryStyle::PinnedTitle if !self.finished && time < Duration::from_secs(60) => {
+                format!("{:02}s", time.as_secs())
+            }
+            _ => format_remaining_time(time, format),
+        };
+
+        Ok(match style {
+            SummaryStyle::Menu => {
+                let mute = if self.muted { "🔕 " } else { "" };
+                format!("{}{}{}#{}", mute, sign, time_str, self.name)
+            }
+            SummaryStyle::Tooltip => match &self.group {
+                Some(group) => format!("{}{}#{} /{}", sign, time_str, self.name, group),
+                None => format!("{}{}#{}", sign, time_str, self.name),
+            },
+            SummaryStyle::PinnedTitle => format!("{}{}", sign, time_str),
+        })
+    }
+
+    // 菜单中显示的结束时刻：截止时间任务显示截止时间，计时中的时间段任务显示预计结束时间，
+    // 定时开始的任务显示开始时刻
+    pub fn end_time_label(&self, now: SystemTime) -> Result<Option<String>> {
+        Ok(match &self.task_type {
+            TaskType::Deadline(t) => Some(tr_args("time.deadline", &[("time", &format_clock_time(*t, now))])),
+            TaskType::Duration(_) if self.is_running && !self.finished => {
+                let end = now + self.remaining_time_at(now)?;
+                Some(tr_args("time.expected_end", &[("time", &format_clock_time(end, now))]))
+            }
+            TaskType::Duration(_) => self
+                .scheduled_start
+                .map(|at| tr_args("time.scheduled_start", &[("time", &format_clock_time(at, now))])),
+        })
+    }
+
+    // 计时进度，0.0 到 1.0；截止时间任务按创建时间到截止时间计算
+    pub fn progress(&self) -> Result<f64> {
+        self.progress_at(SystemTime::now())
+    }
+
+    // 以给定时刻计算进度
+    pub fn progress_at(&self, now: SystemTime) -> Result<f64> {
+        if self.finished {
+            return Ok(1.0);
+        }
+        let remaining = self.remaining_time_at(now)?;
+        let total = match &self.task_type {
+            TaskType::Duration(_) => self.planned,
+            TaskType::Deadline(t) => t.duration_since(self.created_at).unwrap_or(Duration::ZERO),
+        };
+        if total.is_zero() {
+            return Ok(1.0);
+        }
+        Ok((1.0 - remaining.as_secs_f64() / total.as_secs_f64()).clamp(0.0, 1.0))
+    }
+
+    // 距离按保留设置自动归档还有多久；不是未归档的已过期截止时间任务或一直保留时为 None
+    pub fn expired_removal_in(&self, retention: ExpiredRetention, now: SystemTime) -> Option<Duration> {
+        let TaskType::Deadline(deadline) = self.task_type else {
+            return None;
+        };
+        if self.archived || deadline > now {
+            return None;
+        }
+        let overdue = now.duration_since(deadline).unwrap_or_default();
+        Some(retention.keep()?.saturating_sub(overdue))
+    }
+
+    // 过期时间是否已超过保留时长，需要自动归档
+    pub fn expired_beyond_retention(&self, retention: ExpiredRetention, now: SystemTime) -> bool {
+        self.expired_removal_in(retention, now) == Some(Duration::ZERO)
+    }
+
+    // 已完成、开启了持续提醒且尚未确认
+    pub fn needs_alert(&self) -> bool {
+        self.nag && self.finished && !self.acknowledged
+    }
+
+    pub fn state(&self) -> TaskState {
+        if self.finished {
+            return TaskState::Finished;
+        }
+        match &self.task_type {
+            TaskType::Deadline(_) => TaskState::Running,
+            TaskType::Duration(_) if self.is_running => TaskState::Running,
+            TaskType::Duration(d) if self.remaining < *d => TaskState::Paused,
+            TaskType::Duration(_) => TaskState::Idle,
+        }
+    }
+
+    // 检查任务是否到时，到时则切换为已完成状态；只有发生切换的那一次返回 true
+    pub fn check_finished(&mut self) -> Result<bool> {
+        self.check_finished_at(SystemTime::now())
+    }
+
+    // 以给定时刻检查是否到时，计时检查被延迟时同样只返回一次 true
+    pub fn check_finished_at(&mut self, now: SystemTime) -> Result<bool> {
+        if self.finished {
+            return Ok(false);
+        }
+        let due = match &self.task_type {
+            TaskType::Duration(_) => self.is_running && self.remaining_time_at(now)?.is_zero(),
+            TaskType::Deadline(_) => self.remaining_time_at(now)?.is_zero(),
+        };
+        if due {
+            // 记录准确的到时时刻，即使计时检查被延迟也不会把延迟算进计时时间
+            self.finished_at = Some(match &self.task_type {
+                TaskType::Duration(_) => self.start_time.map_or_else(SystemTime::now, |start| start + self.remaining),
+                TaskType::Deadline(t) => *t,
+            });
+            if let TaskType::Duration(_) = self.task_type {
+                self.elapsed += self.remaining;
+            }
+            self.is_running = false;
+            self.start_time = None;
+            self.remaining = Duration::ZERO;
+            self.finished = true;
+            self.acknowledged = false;
+        }
+        Ok(due)
+    }
+
+    // 计划用时与实际用时统计，实际用时包括到时后的超时时间
+    pub fn stats(&self) -> Result<TaskStats> {
+        self.stats_at(SystemTime::now())
+    }
+
+    // 以给定时刻计算统计；从旧存档迁移或恢复的截止时间任务创建时间可能晚于截止时间，此时计时时间为零
+    pub fn stats_at(&self, now: SystemTime) -> Result<TaskStats> {
+        let worked = match &self.task_type {
+            TaskType::Duration(_) => match (self.is_running, self.start_time) {
+                (true, Some(start)) => self.elapsed + now.duration_since(start).context(SystemTimeSnafu)?.min(self.remaining),
+                _ => self.elapsed,
+            },
+            TaskType::Deadline(t) => (*t).min(now).duration_since(self.created_at).unwrap_or_default(),
+        };
+        let overtime = match self.finished_at {
+            Some(finished_at) if self.finished => now.duration_since(finished_at).context(SystemTimeSnafu)?,
+            _ => Duration::ZERO,
+        };
+        let paused = match self.paused_at {
+            Some(paused_at) => self.total_paused + now.duration_since(paused_at).context(SystemTimeSnafu)?,
+            None => self.total_paused,
+        };
+
+        let actual = worked + overtime;
+        Ok(TaskStats {
+            planned: self.planned,
+            actual,
+            paused,
+            overrun: actual.saturating_sub(self.planned),
+        })
+    }
+
+    // 毫秒精度的剩余时间，用于最后几秒的平滑刷新
+    pub fn get_remaining_time_ms(&self) -> Result<u64> {
+        Ok(self.get_remaining_time()?.as_millis() as u64)
+    }
+
     // Changed to return Result to handle potential errors
     pub fn get_remaining_time(&self) -> Result<Duration> {
+        self.remaining_time_at(SystemTime::now())
+    }
+
+    // 以给定时刻计算剩余时间
+    pub fn remaining_time_at(&self, now: SystemTime) -> Result<Duration> {
+        self.in_context("compute remaining time", self.raw_remaining_time_at(now))
+    }
+
+    fn raw_remaining_time_at(&self, now: SystemTime) -> Result<Duration> {
         match &self.task_type {
             TaskType::Duration(_) => {
                 if !self.is_running {
@@ -82,15 +623,2233 @@ impl Task {
                 }
 
                 if let Some(start) = self.start_time {
-                    let elapsed = start.elapsed().context(SystemTimeSnafu)?; // Handle error
+                    let elapsed = now.duration_since(start).context(SystemTimeSnafu)?; // Handle error
                     return Ok(self.remaining.saturating_sub(elapsed));
                 }
                 Ok(self.remaining)
             }
-            TaskType::Deadline(deadline) => {
-                Ok(system_time_to_duration(*deadline)? // Use helper
-                    .saturating_sub(system_time_to_duration(SystemTime::now())?)) // Use helper
+            TaskType::Deadline(deadline) => deadline_remaining(*deadline, now),
+        }
+    }
+}
+
+// 菜单中任务的排列顺序，已完成的任务总是排在最后
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOrder {
+    #[default]
+    Manual,    // 按添加顺序
+    Created,   // 按创建时间
+    Remaining, // 按剩余时间，最紧急的在前
+}
+
+// 过期的截止时间任务保留多久后自动归档
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiredRetention {
+    #[default]
+    Forever,        // 一直保留，由用户手动删除
+    Immediately,    // 过期后立即归档
+    AfterSecs(u64), // 过期超过指定秒数后归档
+}
+
+impl ExpiredRetention {
+    // 过期后保留的时长，一直保留时为 None
+    pub fn keep(self) -> Option<Duration> {
+        match self {
+            ExpiredRetention::Forever => None,
+            ExpiredRetention::Immediately => Some(Duration::ZERO),
+            ExpiredRetention::AfterSecs(secs) => Some(Duration::from_secs(secs)),
+        }
+    }
+}
+
+// 手动排序时移动任务的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveDirection {
+    Up,
+    Down,
+}
+
+// 可撤销的操作，保存操作前的任务快照
+#[derive(Debug, Clone)]
+enum UndoEntry {
+    Delete { task: Task, position: usize },
+    Reset { task: Task },
+    Edit { task: Task },
+}
+
+// 任务列表：按 id 访问任务，保证 id 唯一，并提供菜单使用的顺序
+#[derive(Debug, Clone, Default)]
+pub struct TaskList {
+    tasks: Vec<Task>,
+    undo_stack: VecDeque<UndoEntry>,
+    paused_by_all: Vec<TaskId>, // 由“全部暂停”暂停的任务，“全部继续”只恢复这些任务
+    trash: Vec<(Task, SystemTime)>, // 已删除的任务和删除时刻，最早删除的在前
+}
+
+impl TaskList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // 添加任务到末尾，返回任务 id
+    pub fn push(&mut self, mut task: Task) -> TaskId {
+        self.ensure_unique_id(&mut task);
+        let id = task.id;
+        self.tasks.push(task);
+        id
+    }
+
+    // 在指定任务之后插入任务，找不到指定任务时添加到末尾
+    pub fn insert_after(&mut self, after: TaskId, mut task: Task) -> TaskId {
+        self.ensure_unique_id(&mut task);
+        let id = task.id;
+        match self.position(after) {
+            Some(index) => self.tasks.insert(index + 1, task),
+            None => self.tasks.push(task),
+        }
+        id
+    }
+
+    // 修复不满足约束的任务：id 重复的重新分配，已完成或没有开始时刻却在计时的停止计时，
+    // 时间段任务的剩余时间不超过最长时长；持锁线程 panic 后数据可能只改了一半，返回修复的项数
+    pub fn repair(&mut self) -> usize {
+        let mut repaired = 0;
+        let mut seen = HashSet::new();
+        for task in &mut self.tasks {
+            if !seen.insert(task.id) {
+                task.id = TaskId::next();
+                seen.insert(task.id);
+                repaired += 1;
+            }
+            if task.is_running && (task.finished || task.start_time.is_none()) {
+                task.is_running = false;
+                task.start_time = None;
+                repaired += 1;
+            }
+            if let TaskType::Duration(d) = task.task_type
+                && task.remaining > MAX_DURATION
+            {
+                task.remaining = d;
+                repaired += 1;
+            }
+        }
+        repaired
+    }
+
+    // id 与已有任务重复时重新分配
+    fn ensure_unique_id(&self, task: &mut Task) {
+        if self.get(task.id).is_some() {
+            task.id = TaskId::next();
+        }
+    }
+
+    pub fn get(&self, id: TaskId) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: TaskId) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    pub fn remove(&mut self, id: TaskId) -> Option<Task> {
+        let index = self.position(id)?;
+        Some(self.tasks.remove(index))
+    }
+
+    // 删除任务并记录，可通过 undo 恢复；删除的任务同时放入回收站
+    pub fn delete(&mut self, id: TaskId) -> Option<Task> {
+        let position = self.position(id)?;
+        let task = self.tasks.remove(position);
+        self.push_undo(UndoEntry::Delete { task: task.clone(), position });
+        self.push_trash(task.clone(), SystemTime::now());
+        Some(task)
+    }
+
+    // 放入回收站：计时中的时间段任务在删除时停止计时，超过上限时丢弃最早删除的任务
+    fn push_trash(&mut self, task: Task, now: SystemTime) {
+        // 系统时间回拨导致无法计算剩余时间时保留原状态
+        let task = task.clone().frozen_at(now).unwrap_or(task);
+        self.purge_trash(now);
+        self.trash.push((task, now));
+        if self.trash.len() > TRASH_LIMIT {
+            let excess = self.trash.len() - TRASH_LIMIT;
+            self.trash.drain(..excess);
+        }
+    }
+
+    // 回收站中的任务和删除时刻，最早删除的在前
+    pub fn trash(&self) -> &[(Task, SystemTime)] {
+        &self.trash
+    }
+
+    // 加载存档时恢复回收站
+    pub fn set_trash(&mut self, trash: Vec<(Task, SystemTime)>) {
+        self.trash = trash;
+    }
+
+    // 清除删除超过保留时长的任务，返回清除的数量
+    pub fn purge_trash(&mut self, now: SystemTime) -> usize {
+        let before = self.trash.len();
+        self.trash
+            .retain(|(_, deleted_at)| now.duration_since(*deleted_at).unwrap_or_default() < TRASH_RETENTION);
+        before - self.trash.len()
+    }
+
+    // 从回收站恢复任务：使用新的 id 添加到列表末尾，不恢复固定和归档状态
+    pub fn restore_from_trash(&mut self, id: TaskId) -> Option<TaskId> {
+        let position = self.trash.iter().position(|(task, _)| task.id == id)?;
+        let (mut task, _) = self.trash.remove(position);
+        task.id = TaskId::next();
+        task.pinned = false;
+        task.archived = false;
+        Some(self.push(task))
+    }
+
+    // 从回收站彻底删除任务
+    pub fn delete_from_trash(&mut self, id: TaskId) -> Option<Task> {
+        let position = self.trash.iter().position(|(task, _)| task.id == id)?;
+        Some(self.trash.remove(position).0)
+    }
+
+    // 清空回收站，返回清除的数量
+    pub fn empty_trash(&mut self) -> usize {
+        std::mem::take(&mut self.trash).len()
+    }
+
+    // “清除已完成”会清除的任务：已完成、未归档，固定的任务仅在 include_pinned 时计入
+    pub fn finished_to_clear(&self, include_pinned: bool) -> Vec<TaskId> {
+        self.tasks
+            .iter()
+            .filter(|t| t.state() == TaskState::Finished && !t.archived && (include_pinned || !t.pinned))
+            .map(|t| t.id)
+            .collect()
+    }
+
+    // 删除所有可清除的已完成任务，返回被删除的任务
+    pub fn clear_finished(&mut self, include_pinned: bool) -> Vec<Task> {
+        self.finished_to_clear(include_pinned)
+            .into_iter()
+            .filter_map(|id| self.delete(id))
+            .collect()
+    }
+
+    // 重置任务并记录重置前的状态
+    pub fn reset(&mut self, id: TaskId) -> Result<()> {
+        let task = self.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+        let snapshot = task.clone();
+        task.reset()?;
+        self.push_undo(UndoEntry::Reset { task: snapshot });
+        Ok(())
+    }
+
+    // 编辑任务前调用，记录编辑前的状态
+    pub fn record_edit(&mut self, id: TaskId) {
+        if let Some(task) = self.get(id) {
+            let snapshot = task.clone();
+            self.push_undo(UndoEntry::Edit { task: snapshot });
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    // 撤销最近一次删除、重置或编辑，返回被恢复的任务 id
+    pub fn undo(&mut self) -> Option<TaskId> {
+        match self.undo_stack.pop_back()? {
+            UndoEntry::Delete { mut task, position } => {
+                // 撤销删除后任务不再留在回收站中
+                self.trash.retain(|(trashed, _)| trashed.id != task.id);
+                self.ensure_unique_id(&mut task);
+                let id = task.id;
+                self.tasks.insert(position.min(self.tasks.len()), task);
+                Some(id)
+            }
+            UndoEntry::Reset { task } | UndoEntry::Edit { task } => {
+                let id = task.id;
+                let current = self.get_mut(id)?;
+                *current = task;
+                Some(id)
+            }
+        }
+    }
+
+    fn push_undo(&mut self, entry: UndoEntry) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(entry);
+    }
+
+    pub fn position(&self, id: TaskId) -> Option<usize> {
+        self.tasks.iter().position(|t| t.id == id)
+    }
+
+    // 同一分段中相邻的未归档任务，手动排序时与之交换位置
+    fn neighbor(&self, id: TaskId, direction: MoveDirection) -> Option<usize> {
+        let index = self.position(id)?;
+        let state = self.tasks[index].state();
+        let same_section = |t: &Task| !t.archived && t.state() == state;
+        match direction {
+            MoveDirection::Up => self.tasks[..index].iter().rposition(same_section),
+            MoveDirection::Down => self.tasks[index + 1..].iter().position(same_section).map(|i| index + 1 + i),
+        }
+    }
+
+    // 已经在分段的第一个或最后一个时不能再向该方向移动
+    pub fn can_move(&self, id: TaskId, direction: MoveDirection) -> bool {
+        self.neighbor(id, direction).is_some()
+    }
+
+    // 与同一分段中相邻的任务交换位置，不能移动时返回 false
+    pub fn move_task(&mut self, id: TaskId, direction: MoveDirection) -> bool {
+        match (self.position(id), self.neighbor(id, direction)) {
+            (Some(index), Some(other)) => {
+                self.tasks.swap(index, other);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // 按菜单中的位置访问任务
+    pub fn id_at(&self, index: usize) -> Option<TaskId> {
+        self.tasks.get(index).map(|t| t.id)
+    }
+
+    pub fn nth(&self, index: usize) -> Option<&Task> {
+        self.tasks.get(index)
+    }
+
+    pub fn nth_mut(&mut self, index: usize) -> Option<&mut Task> {
+        self.tasks.get_mut(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Task> {
+        self.tasks.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Task> {
+        self.tasks.iter_mut()
+    }
+
+    pub fn iter_ordered(&self, order: TaskOrder) -> Vec<&Task> {
+        self.ordered_at(order, SystemTime::now())
+    }
+
+    // 按指定时刻的剩余时间排序，便于菜单和提示使用同一时刻的结果
+    pub fn ordered_at(&self, order: TaskOrder, now: SystemTime) -> Vec<&Task> {
+        let mut ordered: Vec<&Task> = self.tasks.iter().collect();
+        match order {
+            TaskOrder::Manual => {}
+            TaskOrder::Created => ordered.sort_by_key(|t| t.created_at),
+            TaskOrder::Remaining => ordered.sort_by_cached_key(|t| t.remaining_time_at(now).unwrap_or(Duration::ZERO)),
+        }
+        // 稳定排序，已完成的任务沉到底部且保持原有相对顺序
+        ordered.sort_by_key(|t| t.state() == TaskState::Finished);
+        ordered
+    }
+
+    // 按状态分段的任务，跳过已归档的任务和空分段，段内保持 order 指定的顺序
+    pub fn sections(&self, order: TaskOrder) -> Vec<(TaskState, Vec<&Task>)> {
+        let ordered = self.iter_ordered(order);
+        TaskState::SECTIONS
+            .into_iter()
+            .map(|state| {
+                let tasks: Vec<&Task> = ordered.iter().copied().filter(|t| !t.archived && t.state() == state).collect();
+                (state, tasks)
+            })
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect()
+    }
+
+    // 剩余时间最短的计时中任务，用于主托盘标题
+    pub fn soonest(&self, now: SystemTime) -> Option<&Task> {
+        self.running()
+            .filter(|t| !t.archived)
+            .min_by_key(|t| t.remaining_time_at(now).unwrap_or(Duration::ZERO))
+    }
+
+    // 已固定的任务名称，按添加顺序
+    pub fn pinned_names(&self) -> Vec<&str> {
+        self.tasks.iter().filter(|t| t.pinned).map(|t| t.name.as_str()).collect()
+    }
+
+    // 已固定的任务数达到上限时不能再固定新的任务
+    pub fn pin_limit_reached(&self, max: usize) -> bool {
+        self.tasks.iter().filter(|t| t.pinned).count() >= max
+    }
+
+    // 达到固定上限时的说明，列出当前已固定的任务
+    pub fn pin_limit_message(&self, max: usize) -> String {
+        tr_args("error.pin_limit_detail", &[
+            ("max", &max.to_string()),
+            ("tasks", &self.pinned_names().join("\n")),
+        ])
+    }
+
+    // 快捷键切换的“当前任务”：最近开始过的未完成时间段任务，
+    // 没有开始记录时取第一个计时中的时间段任务
+    pub fn active_task(&self) -> Option<TaskId> {
+        let toggleable = || {
+            self.tasks
+                .iter()
+                .filter(|t| !t.archived && !t.finished && matches!(t.task_type, TaskType::Duration(_)))
+        };
+        toggleable()
+            .filter_map(|t| t.last_started_at.map(|at| (at, t.id)))
+            .max()
+            .map(|(_, id)| id)
+            .or_else(|| toggleable().find(|t| t.is_running).map(|t| t.id))
+    }
+
+    // 是否有需要每秒刷新的任务：计时中，或完成后等待确认的持续提醒
+    pub fn needs_tick(&self) -> bool {
+        self.tasks
+            .iter()
+            .any(|t| !t.archived && (t.state() == TaskState::Running || t.needs_alert()))
+    }
+
+    // 正在计时的任务（包括未到期的截止时间任务）
+    pub fn running(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter().filter(|t| t.state() == TaskState::Running)
+    }
+
+    // 已固定的任务，按列表顺序
+    pub fn pinned(&self) -> Vec<TaskId> {
+        self.tasks.iter().filter(|t| t.pinned).map(|t| t.id).collect()
+    }
+
+    // 取消固定，返回原本固定的任务；不存在的任务忽略
+    pub fn unpin(&mut self, ids: &[TaskId]) -> Vec<TaskId> {
+        let mut unpinned = Vec::new();
+        for task in self.tasks.iter_mut().filter(|t| t.pinned && ids.contains(&t.id)) {
+            task.pinned = false;
+            unpinned.push(task.id);
+        }
+        unpinned
+    }
+
+    // 推迟已完成的任务，返回被推迟的任务；未指定任务时推迟所有未确认的任务
+    pub fn snooze(&mut self, id: Option<TaskId>, extra: Duration, now: SystemTime) -> Result<Vec<TaskId>> {
+        if let Some(id) = id {
+            let task = self.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+            if !task.snooze(extra, now) {
+                return TaskNotSnoozableSnafu { id }.fail();
+            }
+            return Ok(vec![id]);
+        }
+        Ok(self
+            .tasks
+            .iter_mut()
+            .filter(|t| !t.acknowledged)
+            .filter_map(|t| t.snooze(extra, now).then_some(t.id))
+            .collect())
+    }
+
+    // 把休眠时长补还给计时中的时间段任务，返回受影响的任务数
+    pub fn credit_suspended(&mut self, gap: Duration) -> usize {
+        self.tasks.iter_mut().map(|t| t.credit_suspended(gap)).filter(|&credited| credited).count()
+    }
+
+    // 对每个正在计时的任务执行操作
+    pub fn for_each_running(&mut self, f: impl FnMut(&mut Task)) {
+        self.tasks.iter_mut().filter(|t| t.state() == TaskState::Running).for_each(f);
+    }
+
+    // 暂停所有计时中的时间段任务（截止时间任务不受影响），返回被暂停的任务
+    pub fn pause_all(&mut self) -> Result<Vec<TaskId>> {
+        let mut paused = Vec::new();
+        for task in self.tasks.iter_mut() {
+            if matches!(task.task_type, TaskType::Duration(_)) && task.state() == TaskState::Running {
+                task.pause()?;
+                paused.push(task.id);
+            }
+        }
+        self.paused_by_all.extend(&paused);
+        Ok(paused)
+    }
+
+    // 开始任务并暂停其他计时中的时间段任务，返回被暂停的任务；
+    // 被暂停的任务之后再开始时只会暂停当前任务，不会自动恢复其他任务
+    pub fn start_exclusive(&mut self, id: TaskId, now: SystemTime) -> Result<Vec<TaskId>> {
+        let task = self.get_mut(id).context(TaskIdNotFoundSnafu { id })?;
+        task.start_at(now);
+        if !matches!(task.task_type, TaskType::Duration(_)) {
+            return Ok(Vec::new());
+        }
+        let mut paused = Vec::new();
+        for task in self.tasks.iter_mut() {
+            if task.id != id
+                && matches!(task.task_type, TaskType::Duration(_))
+                && task.state() == TaskState::Running
+            {
+                task.pause()?;
+                paused.push(task.id);
+            }
+        }
+        Ok(paused)
+    }
+
+    // 继续由 pause_all 暂停且仍处于暂停状态的任务，返回被继续的任务
+    pub fn resume_all(&mut self) -> Vec<TaskId> {
+        let mut resumed = Vec::new();
+        for id in std::mem::take(&mut self.paused_by_all) {
+            if let Some(task) = self.get_mut(id)
+                && task.state() == TaskState::Paused
+            {
+                task.start();
+                resumed.push(id);
+            }
+        }
+        resumed
+    }
+
+    pub fn can_resume_all(&self) -> bool {
+        !self.paused_by_all.is_empty()
+    }
+
+    // 检查所有任务是否到时：每个任务只在到时那一刻产生一次 Finished 事件，
+    // 并自动开始链接的后续任务；已归档或已完成的后续任务不开始，exclusive 时按单任务计时暂停其他任务
+    pub fn tick(&mut self, now: SystemTime, exclusive: bool) -> Result<Vec<TaskEvent>> {
+        let mut events = Vec::new();
+        let mut chained = Vec::new();
+        for task in self.tasks.iter_mut() {
+            if task.check_finished_at(now)? {
+                events.push(TaskEvent::Finished(task.id));
+                chained.extend(task.next);
+            }
+        }
+        for next_id in chained {
+            let Some(next_task) = self.get_mut(next_id) else {
+                continue;
+            };
+            if next_task.archived || next_task.finished {
+                continue;
+            }
+            if exclusive {
+                let paused = self.start_exclusive(next_id, now)?;
+                events.push(TaskEvent::Started(next_id));
+                events.extend(paused.into_iter().map(TaskEvent::Paused));
+            } else {
+                next_task.start_at(now);
+                events.push(TaskEvent::Started(next_id));
+            }
+        }
+        Ok(events)
+    }
+
+    // 检查所有任务的提前提醒，返回需要提醒的任务和对应的提醒
+    pub fn due_warnings(&mut self, defaults: &WarningDefaults, now: SystemTime) -> Result<Vec<(TaskId, Warning)>> {
+        let mut due = Vec::new();
+        for task in self.tasks.iter_mut().filter(|t| !t.archived) {
+            if let Some(warning) = task.due_warning(defaults, now)? {
+                due.push((task.id, warning));
+            }
+        }
+        Ok(due)
+    }
+
+    // 开始到达定时时刻的任务，返回任务 id；count_missed 为 true 时从定时时刻起计时，
+    // 程序关闭或系统休眠期间错过的时间计入用时，否则从 now 起计时
+    pub fn start_scheduled(&mut self, now: SystemTime, count_missed: bool) -> Vec<TaskId> {
+        let mut started = Vec::new();
+        for task in self.tasks.iter_mut().filter(|t| !t.archived) {
+            let Some(at) = task.scheduled_start.filter(|at| *at <= now) else {
+                continue;
+            };
+            task.start_at(if count_missed { at } else { now });
+            started.push(task.id);
+        }
+        started
+    }
+
+    // 距离下一个定时开始还有多久，没有定时开始的任务时为 None
+    pub fn next_scheduled_start(&self, now: SystemTime) -> Option<Duration> {
+        self.tasks
+            .iter()
+            .filter(|t| !t.archived)
+            .filter_map(|t| t.scheduled_start)
+            .map(|at| at.duration_since(now).unwrap_or_default())
+            .min()
+    }
+
+    // 归档过期超过保留时长的截止时间任务，返回任务 id 以及归档前是否固定
+    pub fn archive_expired(&mut self, retention: ExpiredRetention, now: SystemTime) -> Vec<(TaskId, bool)> {
+        let mut archived = Vec::new();
+        for task in self.tasks.iter_mut().filter(|t| t.expired_beyond_retention(retention, now)) {
+            archived.push((task.id, task.pinned));
+            task.archive();
+        }
+        archived
+    }
+
+    // 距离下一个过期任务需要归档还有多久，没有需要归档的任务时为 None
+    pub fn next_expired_removal(&self, retention: ExpiredRetention, now: SystemTime) -> Option<Duration> {
+        self.tasks
+            .iter()
+            .filter_map(|t| t.expired_removal_in(retention, now))
+            .min()
+    }
+
+    // 设置任务完成后自动开始的任务，拒绝会形成循环的链接
+    pub fn set_next(&mut self, id: TaskId, next: Option<TaskId>) -> Result<()> {
+        let from_name = self.get(id).map(|t| t.name.clone()).context(TaskIdNotFoundSnafu { id })?;
+
+        if let Some(next_id) = next
+            && link_creates_cycle(&self.tasks, id, next_id)
+        {
+            let to_name = self.get(next_id).map_or_else(String::new, |t| t.name.clone());
+            return ChainCycleSnafu { from: from_name, to: to_name }.fail();
+        }
+
+        if let Some(task) = self.get_mut(id) {
+            task.next = next;
+        }
+        Ok(())
+    }
+
+    // 副本名称已被占用时继续递增编号
+    pub fn unique_copy_name(&self, name: &str) -> String {
+        unique_copy_name(name, &self.tasks)
+    }
+}
+
+impl FromIterator<Task> for TaskList {
+    fn from_iter<I: IntoIterator<Item = Task>>(iter: I) -> Self {
+        let mut list = TaskList::new();
+        for task in iter {
+            list.push(task);
+        }
+        list
+    }
+}
+
+// 剩余时间的显示格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    #[default]
+    Full,     // 始终显示 HH:MM:SS
+    Compact,  // 不足一小时时显示 MM:SS
+    Adaptive, // 按长短显示 2d 3h、1h05m、12:34 或 45s
+}
+
+// 设置时间段任务的最长时长，新建和编辑任务时按此校验
+pub fn set_max_duration(max: Duration) {
+    MAX_DURATION_SECS.store(max.as_secs(), Ordering::Relaxed);
+}
+
+fn max_duration() -> Duration {
+    Duration::from_secs(MAX_DURATION_SECS.load(Ordering::Relaxed))
+}
+
+impl TimeFormat {
+    pub const ALL: [TimeFormat; 3] = [TimeFormat::Full, TimeFormat::Compact, TimeFormat::Adaptive];
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL.into_iter().find(|format| *format as u8 == value).unwrap_or_default()
+    }
+}
+
+// 设置菜单（含提示和通知）与标题使用的时间格式
+pub fn set_time_formats(menu: TimeFormat, title: TimeFormat) {
+    MENU_TIME_FORMAT.store(menu as u8, Ordering::Relaxed);
+    TITLE_TIME_FORMAT.store(title as u8, Ordering::Relaxed);
+}
+
+// 菜单、提示和通知中的时间格式
+pub fn time_format() -> TimeFormat {
+    TimeFormat::from_u8(MENU_TIME_FORMAT.load(Ordering::Relaxed))
+}
+
+// 托盘和固定图标标题的时间格式
+pub fn title_time_format() -> TimeFormat {
+    TimeFormat::from_u8(TITLE_TIME_FORMAT.load(Ordering::Relaxed))
+}
+
+pub fn format_remaining_time(duration: Duration, format: TimeFormat) -> String {
+    let total_seconds = duration.as_secs();
+    let days = total_seconds / 86400;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    match format {
+        TimeFormat::Full => format!("{hours:02}:{minutes:02}:{seconds:02}"),
+        TimeFormat::Compact if hours == 0 => format!("{minutes:02}:{seconds:02}"),
+        TimeFormat::Compact => format!("{hours:02}:{minutes:02}:{seconds:02}"),
+        TimeFormat::Adaptive if days > 0 => format!("{days}d {}h", hours % 24),
+        TimeFormat::Adaptive if hours > 0 => format!("{hours}h{minutes:02}m"),
+        TimeFormat::Adaptive if minutes > 0 => format!("{minutes:02}:{seconds:02}"),
+        TimeFormat::Adaptive => format!("{seconds}s"),
+    }
+}
+
+// 本地时间的时刻：前后一天内显示 19:00 (今天)，其他日期显示 6月3日 09:00，不在今年时加上年份
+pub fn format_clock_time(time: SystemTime, now: SystemTime) -> String {
+    let (time, now) = (DateTime::<Local>::from(time), DateTime::<Local>::from(now));
+    let clock = time.format("%H:%M").to_string();
+    let (year, month, day) = (time.year().to_string(), time.month().to_string(), time.day().to_string());
+    let args: [(&str, &str); 4] = [("clock", &clock), ("year", &year), ("month", &month), ("day", &day)];
+    match (time.date_naive() - now.date_naive()).num_days() {
+        -1 => tr_args("time.yesterday", &args),
+        0 => tr_args("time.today", &args),
+        1 => tr_args("time.tomorrow", &args),
+        _ if time.year() == now.year() => tr_args("time.date", &args),
+        _ => tr_args("time.date_year", &args),
+    }
+}
+
+// Dock 角标上的简短剩余时间，例如 1h5m、24m、42s；分钟向上取整，避免还剩几秒时显示 0m
+pub fn format_badge(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    if total_seconds < 60 {
+        return format!("{total_seconds}s");
+    }
+    let total_minutes = total_seconds.div_ceil(60);
+    if total_minutes < 60 {
+        return format!("{total_minutes}m");
+    }
+    let (hours, minutes) = (total_minutes / 60, total_minutes % 60);
+    if minutes == 0 {
+        format!("{hours}h")
+    } else {
+        format!("{hours}h{minutes}m")
+    }
+}
+
+// 生成托盘提示所需的任务信息
+#[derive(Debug, Clone)]
+pub struct TaskSnapshot {
+    pub state: TaskState,
+    pub remaining: Duration,
+    pub line: String, // 提示中显示的一行文本
+}
+
+impl TaskSnapshot {
+    pub fn new(task: &Task, now: SystemTime) -> Result<Self> {
+        Ok(Self {
+            state: task.state(),
+            remaining: task.remaining_time_at(now)?,
+            line: task.summary_at(SummaryStyle::Tooltip, now)?,
+        })
+    }
+}
+
+// 托盘提示：按状态分段、段内按剩余时间排序，最多显示 max_tasks 个任务，超出的部分只显示数量；
+// 已完成的任务只在没有其他任务时显示
+pub fn build_tooltip(snapshots: &[TaskSnapshot], max_tasks: usize) -> String {
+    let active: Vec<&TaskSnapshot> = snapshots.iter().filter(|s| s.state != TaskState::Finished).collect();
+    let mut shown = if active.is_empty() { snapshots.iter().collect() } else { active };
+    shown.sort_by_key(|s| (TaskState::SECTIONS.iter().position(|&state| state == s.state), s.remaining));
+    let hidden = shown.len().saturating_sub(max_tasks);
+    shown.truncate(max_tasks);
+
+    let mut lines = Vec::new();
+    for state in TaskState::SECTIONS {
+        let section: Vec<&&TaskSnapshot> = shown.iter().filter(|s| s.state == state).collect();
+        if section.is_empty() {
+            continue;
+        }
+        lines.push(format!("{} ({})", state.section_title(), section.len()));
+        lines.extend(section.iter().map(|s| s.line.clone()));
+    }
+    if hidden > 0 {
+        lines.push(tr_args("tooltip.more", &[("count", &hidden.to_string())]));
+    }
+    if lines.is_empty() {
+        return tr("tooltip.empty").to_string();
+    }
+    lines.join("\n")
+}
+
+// 用字符画出进度条，进度超出 0.0 到 1.0 的部分按满格或空格处理
+pub fn progress_bar(progress: f64, width: usize, filled: char, empty: char) -> String {
+    let cells = ((progress.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    std::iter::repeat_n(filled, cells).chain(std::iter::repeat_n(empty, width - cells)).collect()
+}
+
+// 副本的名称：写作 -> 写作 (2)，写作 (2) -> 写作 (3)
+fn duplicate_name(name: &str) -> String {
+    match name.rsplit_once(" (") {
+        Some((base, suffix)) => match suffix.strip_suffix(')').and_then(|n| n.parse::<u32>().ok()) {
+            Some(n) => format!("{} ({})", base, n + 1),
+            None => format!("{} (2)", name),
+        },
+        None => format!("{} (2)", name),
+    }
+}
+
+// 副本名称已被占用时继续递增编号，直到名称不重复
+fn unique_copy_name(name: &str, tasks: &[Task]) -> String {
+    let mut name = name.to_string();
+    while tasks.iter().any(|t| t.name == name) {
+        name = duplicate_name(&name);
+    }
+    name
+}
+
+// 检查计时方式是否有效：时长不能为零或超过 max_duration，截止时间不能已经过去
+fn validate_task_type(task_type: &TaskType, allow_overdue: bool, max_duration: Duration) -> Result<()> {
+    match task_type {
+        TaskType::Duration(d) if d.is_zero() => ZeroDurationSnafu.fail(),
+        TaskType::Duration(d) if *d > max_duration => DurationTooLongSnafu { max_hours: max_duration.as_secs() / 3600 }.fail(),
+        TaskType::Duration(_) => Ok(()),
+        TaskType::Deadline(t) if !allow_overdue && *t <= SystemTime::now() => DeadlinePassedSnafu.fail(),
+        TaskType::Deadline(_) => Ok(()),
+    }
+}
+
+// 截止时间距离 now 还剩多少，已过去时为零
+fn deadline_remaining(deadline: SystemTime, now: SystemTime) -> Result<Duration> {
+    Ok(system_time_to_duration(deadline)?.saturating_sub(system_time_to_duration(now)?))
+}
+
+// 任务的计划用时：时间段任务为设定的时长，截止时间任务为从创建到截止的时长
+fn planned_duration(task_type: &TaskType, created_at: SystemTime) -> Duration {
+    match task_type {
+        TaskType::Duration(d) => *d,
+        TaskType::Deadline(t) => t.duration_since(created_at).unwrap_or_default(),
+    }
+}
+
+// 沿着 next 链接从 to 出发，如果能回到 from 则说明会形成循环
+fn link_creates_cycle(tasks: &[Task], from: TaskId, to: TaskId) -> bool {
+    let mut current = Some(to);
+    let mut steps = 0;
+    while let Some(id) = current {
+        if id == from || steps > tasks.len() {
+            return true;
+        }
+        steps += 1;
+        current = tasks.iter().find(|t| t.id == id).and_then(|t| t.next);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    const SECOND: Duration = Duration::from_secs(1);
+    const MINUTE: Duration = Duration::from_secs(60);
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn duration_task(name: &str, length: Duration) -> Task {
+        Task::new(name.to_string(), TaskType::Duration(length)).unwrap()
+    }
+
+    fn deadline_task(name: &str, after: Duration) -> Task {
+        Task::new(name.to_string(), TaskType::Deadline(SystemTime::now() + after)).unwrap()
+    }
+
+    #[test]
+    fn set_type_converts_duration_to_deadline() {
+        let mut task = duration_task("写作", 25 * MINUTE);
+        task.pinned = true;
+        task.start();
+
+        task.set_type(TaskType::Deadline(SystemTime::now() + HOUR)).unwrap();
+        assert_eq!((task.name.as_str(), task.pinned), ("写作", true));
+        assert!(!task.is_running && task.start_time.is_none());
+        let remaining = task.get_remaining_time().unwrap();
+        assert!(remaining <= HOUR && remaining > HOUR - MINUTE, "{remaining:?}");
+    }
+
+    #[test]
+    fn set_type_converts_deadline_to_duration() {
+        let mut task = deadline_task("下班", HOUR);
+
+        task.set_type(TaskType::Duration(25 * MINUTE)).unwrap();
+        assert_eq!(task.remaining, 25 * MINUTE);
+        assert_eq!(task.get_remaining_time().unwrap(), 25 * MINUTE);
+        assert!(!task.is_running);
+    }
+
+    #[test]
+    fn set_next_refuses_cycles() {
+        let mut tasks: TaskList =
+            [duration_task("a", MINUTE), duration_task("b", MINUTE), duration_task("c", MINUTE)].into_iter().collect();
+        let (a, b, c) = (tasks.id_at(0).unwrap(), tasks.id_at(1).unwrap(), tasks.id_at(2).unwrap());
+
+        assert!(tasks.set_next(a, Some(a)).is_err());
+        tasks.set_next(a, Some(b)).unwrap();
+        tasks.set_next(b, Some(c)).unwrap();
+        assert!(matches!(tasks.set_next(c, Some(a)), Err(Error::ChainCycle { .. })));
+        assert_eq!(tasks.get(c).unwrap().next, None);
+
+        // 断开链接后原本会形成循环的链接可以设置
+        tasks.set_next(a, None).unwrap();
+        tasks.set_next(c, Some(a)).unwrap();
+        assert!(tasks.set_next(TaskId::next(), None).is_err());
+    }
+
+    #[test]
+    fn check_finished_fires_once_and_restart_resets() {
+        let mut task = duration_task("茶", 3 * MINUTE);
+        task.remaining = Duration::ZERO;
+        assert!(!task.check_finished().unwrap(), "idle tasks never finish");
+
+        task.start();
+        assert!(task.check_finished().unwrap());
+        assert!(!task.check_finished().unwrap());
+        assert_eq!(task.state(), TaskState::Finished);
+
+        task.start();
+        assert_eq!(task.remaining, 3 * MINUTE);
+        assert_eq!(task.state(), TaskState::Running);
+    }
+
+    #[test]
+    fn stats_split_worked_and_paused_time() {
+        let mut task = duration_task("写作", 25 * MINUTE);
+        task.start();
+        task.start_time = task.start_time.map(|t| t - 10 * MINUTE);
+        task.pause().unwrap();
+        let paused_at = task.paused_at.unwrap();
+
+        let stats = task.stats_at(paused_at + 5 * MINUTE).unwrap();
+        assert_eq!(stats.planned, 25 * MINUTE);
+        assert!(stats.actual >= 10 * MINUTE && stats.actual < 10 * MINUTE + SECOND, "{:?}", stats.actual);
+        assert_eq!(stats.paused, 5 * MINUTE);
+        assert_eq!(stats.overrun, Duration::ZERO);
+    }
+
+    fn offsets_task(length: Duration, offsets: &[u64]) -> Task {
+        let mut task = duration_task("写作", length);
+        task.warn_offsets = Some(offsets.iter().map(|secs| Duration::from_secs(*secs)).collect());
+        task
+    }
+
+    #[test]
+    fn due_warning_reports_the_closest_offset_once() {
+        let defaults = WarningDefaults::default();
+        let mut task = offsets_task(10 * MINUTE, &[300, 60, 90]);
+        let start = SystemTime::now();
+        task.start_at(start);
+
+        assert_eq!(task.due_warning(&defaults, start + 4 * MINUTE).unwrap(), None);
+        assert_eq!(
+            task.due_warning(&defaults, start + 5 * MINUTE).unwrap(),
+            Some(Warning::Remaining(5 * MINUTE))
+        );
+        // 一次检查越过了两个提前量，只提醒更接近的一个，另一个也不再提醒
+        assert_eq!(
+            task.due_warning(&defaults, start + 9 * MINUTE).unwrap(),
+            Some(Warning::Remaining(MINUTE))
+        );
+        assert_eq!(task.warned.len(), 3);
+        assert_eq!(task.due_warning(&defaults, start + 9 * MINUTE + 30 * SECOND).unwrap(), None);
+        // 到时后不再提醒
+        assert_eq!(task.due_warning(&defaults, start + 10 * MINUTE).unwrap(), None);
+    }
+
+    #[test]
+    fn due_warning_skips_paused_tasks_and_long_offsets() {
+        let defaults = WarningDefaults {
+            offsets: vec![10 * MINUTE, HOUR],
+            milestones: vec![],
+        };
+        let mut task = duration_task("写作", 10 * MINUTE);
+        let start = SystemTime::now();
+        task.start_at(start);
+        // 不短于计划用时的提前量没有意义
+        assert_eq!(task.due_warning(&defaults, start + MINUTE).unwrap(), None);
+
+        let mut task = offsets_task(10 * MINUTE, &[300]);
+        assert_eq!(task.due_warning(&defaults, start + 9 * MINUTE).unwrap(), None);
+        assert!(task.warned.is_empty());
+    }
+
+    #[test]
+    fn milestones_win_over_offsets_in_the_same_check() {
+        let defaults = WarningDefaults {
+            offsets: vec![5 * MINUTE],
+            milestones: vec![0.5, 0.0, 1.5],
+        };
+        let mut task = duration_task("写作", 10 * MINUTE);
+        let start = SystemTime::now();
+        task.start_at(start);
+        assert_eq!(task.due_warning(&defaults, start + 5 * MINUTE).unwrap(), Some(Warning::Progress(0.5)));
+        // 同时到达的提前量已记为提醒过，不再单独提醒；不在 0 到 1 之间的里程碑被忽略
+        assert_eq!(task.warned, [5 * MINUTE]);
+        assert_eq!(task.due_warning(&defaults, start + 9 * MINUTE).unwrap(), None);
+    }
+
+    #[test]
+    fn reset_rearms_warnings() {
+        let defaults = WarningDefaults {
+            offsets: vec![],
+            milestones: vec![0.5],
+        };
+        let mut task = offsets_task(10 * MINUTE, &[60]);
+        let start = SystemTime::now() - 10 * MINUTE;
+        task.start_at(start);
+        assert_eq!(task.due_warning(&defaults, start + 5 * MINUTE).unwrap(), Some(Warning::Progress(0.5)));
+        assert_eq!(task.due_warning(&defaults, start + 9 * MINUTE).unwrap(), Some(Warning::Remaining(MINUTE)));
+
+        task.reset().unwrap();
+        assert!(task.warned.is_empty() && task.warned_milestones.is_empty());
+        let restart = SystemTime::now();
+        task.start_at(restart);
+        assert_eq!(task.due_warning(&defaults, restart + 5 * MINUTE).unwrap(), Some(Warning::Progress(0.5)));
+        assert_eq!(task.due_warning(&defaults, restart + 9 * MINUTE).unwrap(), Some(Warning::Remaining(MINUTE)));
+    }
+
+    #[test]
+    fn list_warnings_skip_archived_tasks() {
+        let start = SystemTime::now();
+        let (mut list, ids) = list_of([running_task("写作", 10 * MINUTE, start), running_task("阅读", 10 * MINUTE, start)]);
+        list.get_mut(ids[1]).unwrap().archive();
+        let defaults = WarningDefaults {
+            offsets: vec![MINUTE],
+            milestones: vec![],
+        };
+        let due = list.due_warnings(&defaults, start + 9 * MINUTE).unwrap();
+        assert_eq!(due, [(ids[0], Warning::Remaining(MINUTE))]);
+    }
+
+    #[test]
+    fn stats_for_deadline_created_after_it_passed() {
+        let now = SystemTime::now();
+        let mut task = deadline_task("下班", HOUR);
+        // 模拟从旧存档迁移的任务：创建时间取迁移时刻，晚于截止时间
+        task.task_type = TaskType::Deadline(now - HOUR);
+        task.created_at = now;
+        task.planned = Duration::ZERO;
+        task.finished = true;
+        task.finished_at = Some(now - HOUR);
+
+        let stats = task.stats_at(now + MINUTE).unwrap();
+        assert_eq!(stats.planned, Duration::ZERO);
+        assert_eq!(stats.actual, HOUR + MINUTE);
+        assert_eq!(stats.overrun, HOUR + MINUTE);
+    }
+
+    #[test]
+    fn stats_for_a_deadline_in_progress() {
+        let task = deadline_task("下班", HOUR);
+        let now = task.created_at + 10 * MINUTE;
+        let stats = task.stats_at(now).unwrap();
+        assert_eq!(stats.actual, 10 * MINUTE);
+        assert_eq!(stats.overrun, Duration::ZERO);
+    }
+
+    #[test]
+    fn remaining_time_ms_counts_down_while_running() {
+        let mut task = duration_task("倒计时", 5 * SECOND);
+        assert_eq!(task.get_remaining_time_ms().unwrap(), 5000);
+
+        task.start();
+        task.start_time = task.start_time.map(|t| t - 2 * SECOND);
+        let ms = task.get_remaining_time_ms().unwrap();
+        assert!(ms <= 3000 && ms > 2500, "{ms}");
+
+        task.start_time = task.start_time.map(|t| t - 10 * SECOND);
+        assert_eq!(task.get_remaining_time_ms().unwrap(), 0);
+    }
+
+    #[test]
+    fn progress_follows_the_elapsed_share() {
+        let now = SystemTime::now();
+        assert_eq!(duration_task("写作", HOUR).progress().unwrap(), 0.0);
+        let progress = running_task("写作", HOUR, now - 15 * MINUTE).progress().unwrap();
+        assert!((progress - 0.25).abs() < 0.001, "{progress}");
+
+        let mut deadline = deadline_task("下班", 3 * HOUR);
+        deadline.created_at = now - HOUR;
+        let progress = deadline.progress().unwrap();
+        assert!((progress - 0.25).abs() < 0.001, "{progress}");
+
+        let mut finished = duration_task("评审", HOUR);
+        finished.finished = true;
+        assert_eq!(finished.progress().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn displays_use_the_given_snapshot_time() {
+        let start = SystemTime::now();
+        let task = running_task("写作", HOUR, start);
+        let now = start + 15 * MINUTE + 500 * Duration::from_millis(1);
+        assert_eq!(task.summary_at(SummaryStyle::Menu, now).unwrap(), "00:44:59#写作");
+        assert_eq!(task.summary_at(SummaryStyle::PinnedTitle, now).unwrap(), "44:59");
+        assert_eq!(task.summary_at(SummaryStyle::Menu, now + 2 * SECOND).unwrap(), "00:44:57#写作");
+        assert_eq!(task.progress_at(start + 30 * MINUTE).unwrap(), 0.5);
+        assert_eq!(TaskSnapshot::new(&task, now).unwrap().line, "00:44:59#写作");
+    }
+
+    #[test]
+    fn progress_bar_rounds_and_clamps() {
+        assert_eq!(progress_bar(0.0, 4, '#', '-'), "----");
+        assert_eq!(progress_bar(0.3, 4, '#', '-'), "#---");
+        assert_eq!(progress_bar(0.5, 4, '#', '-'), "##--");
+        assert_eq!(progress_bar(1.5, 4, '#', '-'), "####");
+        assert_eq!(progress_bar(-1.0, 4, '#', '-'), "----");
+        assert_eq!(progress_bar(0.5, 0, '#', '-'), "");
+    }
+
+    #[test]
+    fn duplicate_adds_a_numbered_suffix() {
+        let task = duration_task("写作", 25 * MINUTE);
+        let copy = task.duplicate();
+        assert_eq!(copy.name, "写作 (2)");
+        assert_eq!(copy.duplicate().name, "写作 (3)");
+        assert_eq!(duration_task("会议 (B)", MINUTE).duplicate().name, "会议 (B) (2)");
+
+        // 编号已被占用时继续递增
+        let tasks: TaskList = [task.clone(), copy.clone(), copy.duplicate()].into_iter().collect();
+        assert_eq!(tasks.unique_copy_name(&task.duplicate().name), "写作 (4)");
+        assert_eq!(tasks.unique_copy_name("写作 (9)"), "写作 (9)");
+    }
+
+    #[test]
+    fn duplicate_does_not_copy_running_state() {
+        let mut task = duration_task("写作", 25 * MINUTE);
+        task.group = Some("工作".to_string());
+        task.on_complete = Some("say done".to_string());
+        task.webhook = Some("https://example.com/hook".to_string());
+        task.icon_path = Some(PathBuf::from("/tmp/icon.png"));
+        task.pinned = true;
+        task.start();
+        task.start_time = task.start_time.map(|t| t - 5 * MINUTE);
+        task.pause().unwrap();
+
+        let copy = task.duplicate();
+        assert_ne!(copy.id, task.id);
+        assert_eq!(copy.state(), TaskState::Idle);
+        assert!(!copy.is_running && !copy.pinned && copy.paused_at.is_none());
+        assert_eq!((copy.remaining, copy.elapsed, copy.planned), (25 * MINUTE, Duration::ZERO, 25 * MINUTE));
+        assert_eq!((copy.group, copy.on_complete, copy.webhook), (task.group, task.on_complete, task.webhook));
+        assert_eq!(copy.icon_path, task.icon_path);
+    }
+
+    #[test]
+    fn unpin_clears_only_pinned_tasks() {
+        let [mut writing, reading, mut review] = ["写作", "阅读", "评审"].map(|name| duration_task(name, MINUTE));
+        writing.pinned = true;
+        review.pinned = true;
+        let (mut list, ids) = list_of([writing, reading, review]);
+        assert_eq!(list.pinned(), [ids[0], ids[2]]);
+
+        // 未固定和不存在的任务不受影响
+        let missing = duration_task("已删除", MINUTE).id;
+        assert_eq!(list.unpin(&[ids[1], ids[2], missing]), [ids[2]]);
+        assert_eq!(list.pinned(), [ids[0]]);
+        assert!(list.unpin(&[ids[2]]).is_empty());
+    }
+
+    #[test]
+    fn archive_unpins_and_restore_keeps_it_unpinned() {
+        let mut task = duration_task("写作", 25 * MINUTE);
+        task.pinned = true;
+
+        task.archive();
+        assert!(task.archived && !task.pinned);
+        assert!(!task.duplicate().archived);
+
+        task.restore();
+        assert!(!task.archived && !task.pinned);
+    }
+
+    #[test]
+    fn new_rejects_invalid_timing() {
+        let zero = Task::new("零".to_string(), TaskType::Duration(Duration::ZERO)).unwrap_err();
+        assert!(matches!(zero, Error::ZeroDuration { .. }), "{zero}");
+        let long = Task::new("长".to_string(), TaskType::Duration(MAX_DURATION + MINUTE)).unwrap_err();
+        assert!(matches!(long, Error::DurationTooLong { max_hours: 168, .. }), "{long}");
+        let passed = Task::new("过".to_string(), TaskType::Deadline(SystemTime::now() - MINUTE)).unwrap_err();
+        assert!(matches!(passed, Error::DeadlinePassed { .. }), "{passed}");
+
+        assert!(Task::new("满".to_string(), TaskType::Duration(MAX_DURATION)).is_ok());
+    }
+
+    #[test]
+    fn set_type_rejects_invalid_timing_and_keeps_the_task() {
+        let mut task = duration_task("写作", 25 * MINUTE);
+        for invalid in [
+            TaskType::Duration(Duration::ZERO),
+            TaskType::Duration(MAX_DURATION + MINUTE),
+            TaskType::Deadline(SystemTime::now() - MINUTE),
+        ] {
+            assert!(task.set_type(invalid).is_err());
+            assert!(matches!(task.task_type, TaskType::Duration(d) if d == 25 * MINUTE));
+        }
+    }
+
+    #[test]
+    fn restoring_allows_a_passed_deadline_only() {
+        let deadline = SystemTime::now() - HOUR;
+        let task = Task::new_allowing_overdue("下班".to_string(), TaskType::Deadline(deadline)).unwrap();
+        assert_eq!(task.remaining, Duration::ZERO);
+
+        let zero = Task::new_allowing_overdue("零".to_string(), TaskType::Duration(Duration::ZERO));
+        assert!(matches!(zero, Err(Error::ZeroDuration { .. })));
+        let long = Task::new_allowing_overdue("长".to_string(), TaskType::Duration(MAX_DURATION + MINUTE));
+        assert!(matches!(long, Err(Error::DurationTooLong { .. })));
+    }
+
+    #[test]
+    fn mute_flag_survives_reset_and_edit() {
+        let mut task = duration_task("坐姿提醒", HOUR);
+        assert!(!task.muted);
+        task.muted = true;
+
+        task.start();
+        task.reset().unwrap();
+        task.set_type(TaskType::Duration(30 * MINUTE)).unwrap();
+        assert!(task.muted);
+    }
+
+    fn names(tasks: &TaskList) -> Vec<&str> {
+        tasks.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn task_list_keeps_ids_unique() {
+        let mut tasks = TaskList::new();
+        let task = duration_task("a", MINUTE);
+        let first = tasks.push(task.clone());
+        let second = tasks.push(task.clone());
+        assert_eq!(first, task.id);
+        assert_ne!(second, first);
+        let third = tasks.insert_after(first, task);
+        assert!(third != first && third != second);
+        assert_eq!(tasks.len(), 3);
+    }
+
+    #[test]
+    fn task_ids_round_trip_through_menu_actions() {
+        let id = TaskId::next();
+        let action = format!("toggle_{id}");
+        assert_eq!(action.strip_prefix("toggle_").unwrap().parse::<TaskId>(), Ok(id));
+        assert!("".parse::<TaskId>().is_err());
+        assert!("3a".parse::<TaskId>().is_err());
+    }
+
+    #[test]
+    fn task_list_access_by_id_survives_removal() {
+        let mut tasks: TaskList =
+            [duration_task("a", MINUTE), duration_task("b", MINUTE), duration_task("c", MINUTE)].into_iter().collect();
+        let (a, b, c) = (tasks.id_at(0).unwrap(), tasks.id_at(1).unwrap(), tasks.id_at(2).unwrap());
+
+        assert_eq!(tasks.remove(a).map(|t| t.name), Some("a".to_string()));
+        assert!(tasks.remove(a).is_none() && tasks.get(a).is_none());
+        assert_eq!(tasks.get(c).unwrap().name, "c");
+        assert_eq!((tasks.position(b), tasks.position(c)), (Some(0), Some(1)));
+        assert_eq!(tasks.nth(1).map(|t| t.id), Some(c));
+        assert!(tasks.nth(2).is_none() && tasks.id_at(2).is_none());
+
+        tasks.get_mut(b).unwrap().name = "b2".to_string();
+        tasks.nth_mut(1).unwrap().name = "c2".to_string();
+        assert_eq!(names(&tasks), ["b2", "c2"]);
+    }
+
+    #[test]
+    fn task_list_insert_after_falls_back_to_the_end() {
+        let mut tasks: TaskList = [duration_task("a", MINUTE), duration_task("c", MINUTE)].into_iter().collect();
+        let a = tasks.id_at(0).unwrap();
+        tasks.insert_after(a, duration_task("b", MINUTE));
+        tasks.insert_after(TaskId::next(), duration_task("d", MINUTE));
+        assert_eq!(names(&tasks), ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn task_list_orders_and_filters_running_tasks() {
+        let mut late = duration_task("late", MINUTE);
+        let early = duration_task("early", MINUTE);
+        late.created_at = early.created_at + MINUTE;
+        let mut tasks: TaskList = [late, early, deadline_task("deadline", HOUR)].into_iter().collect();
+        tasks.nth_mut(2).unwrap().created_at = SystemTime::now() + HOUR;
+
+        let manual: Vec<_> = tasks.iter_ordered(TaskOrder::Manual).iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(manual, ["late", "early", "deadline"]);
+        let created: Vec<_> = tasks.iter_ordered(TaskOrder::Created).iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(created, ["early", "late", "deadline"]);
+
+        // 截止时间任务始终处于计时状态
+        assert_eq!(tasks.running().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["deadline"]);
+        tasks.nth_mut(0).unwrap().start();
+        let mut seen = Vec::new();
+        tasks.for_each_running(|t| seen.push(t.name.clone()));
+        assert_eq!(seen, ["late", "deadline"]);
+    }
+
+    #[test]
+    fn remaining_order_puts_urgent_tasks_first_and_finished_last() {
+        let now = SystemTime::now();
+        let mut done = duration_task("done", MINUTE);
+        done.finished = true;
+        let mut also_done = duration_task("also done", HOUR);
+        also_done.finished = true;
+        let tasks: TaskList = [
+            done,
+            duration_task("long", HOUR),
+            running_task("soon", 5 * MINUTE, now - MINUTE),
+            also_done,
+            deadline_task("deadline", 30 * MINUTE),
+        ]
+        .into_iter()
+        .collect();
+
+        let remaining: Vec<_> = tasks.ordered_at(TaskOrder::Remaining, now).iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(remaining, ["soon", "deadline", "long", "done", "also done"]);
+        let manual: Vec<_> = tasks.ordered_at(TaskOrder::Manual, now).iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(manual, ["long", "soon", "deadline", "done", "also done"]);
+    }
+
+    #[test]
+    fn soonest_picks_the_running_task_closest_to_finishing() {
+        let now = SystemTime::now();
+        let mut archived = running_task("归档", MINUTE, now);
+        archived.archived = true;
+        let (mut list, ids) = list_of([
+            duration_task("未开始", SECOND),
+            running_task("写作", HOUR, now),
+            deadline_task("下班", 30 * MINUTE),
+            archived,
+        ]);
+        assert_eq!(list.soonest(now).map(|t| t.id), Some(ids[2]));
+
+        list.get_mut(ids[1]).unwrap().start_time = Some(now - 50 * MINUTE);
+        assert_eq!(list.soonest(now).map(|t| t.id), Some(ids[1]));
+        assert!(TaskList::new().soonest(now).is_none());
+    }
+
+    #[test]
+    fn active_task_is_the_most_recently_started_duration_task() {
+        let now = SystemTime::now();
+        let started = |mut task: Task, at: SystemTime| {
+            task.last_started_at = Some(at);
+            task
+        };
+        let mut finished = running_task("完成", MINUTE, now);
+        finished.finished = true;
+        let (mut list, ids) = list_of([
+            started(paused_task("写作"), now - HOUR),
+            started(paused_task("阅读"), now - MINUTE),
+            started(finished, now),
+            started(deadline_task("下班", HOUR), now),
+        ]);
+        assert_eq!(list.active_task(), Some(ids[1]));
+        list.get_mut(ids[1]).unwrap().archive();
+        assert_eq!(list.active_task(), Some(ids[0]));
+
+        // 旧存档中没有开始记录时取第一个计时中的时间段任务
+        let mut running = running_task("写作", HOUR, now);
+        running.last_started_at = None;
+        let (list, ids) = list_of([duration_task("未开始", MINUTE), running]);
+        assert_eq!(list.active_task(), Some(ids[1]));
+        assert_eq!(list_of([duration_task("未开始", MINUTE)]).0.active_task(), None);
+    }
+
+    #[test]
+    fn badge_rounds_minutes_up() {
+        let cases = [
+            (0, "0s"),
+            (42, "42s"),
+            (59, "59s"),
+            (60, "1m"),
+            (61, "2m"),
+            (24 * 60, "24m"),
+            (3599, "1h"),
+            (3600, "1h"),
+            (3601, "1h1m"),
+            (3900, "1h5m"),
+            (25 * 3600, "25h"),
+        ];
+        for (secs, badge) in cases {
+            assert_eq!(format_badge(Duration::from_secs(secs)), badge, "{secs}");
+        }
+    }
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> SystemTime {
+        use chrono::TimeZone;
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).single().unwrap().into()
+    }
+
+    #[test]
+    fn clock_time_names_nearby_days_and_dates() {
+        let now = local(2026, 6, 3, 12, 0);
+        let cases = [
+            (local(2026, 6, 3, 19, 0), "19:00 (今天)"),
+            (local(2026, 6, 2, 23, 30), "23:30 (昨天)"),
+            (local(2026, 6, 4, 8, 5), "08:05 (明天)"),
+            (local(2026, 6, 6, 9, 0), "6月6日 09:00"),
+            (local(2026, 5, 1, 9, 0), "5月1日 09:00"),
+            (local(2027, 1, 2, 0, 0), "2027年1月2日 00:00"),
+        ];
+        for (time, label) in cases {
+            assert_eq!(format_clock_time(time, now), label);
+        }
+    }
+
+    #[test]
+    fn end_time_label_for_deadlines_and_running_tasks() {
+        let now = SystemTime::now();
+        let leaving = deadline_task("下班", HOUR);
+        let TaskType::Deadline(deadline) = leaving.task_type else { unreachable!() };
+        let expected = format!("截止 {}", format_clock_time(deadline, now));
+        assert_eq!(leaving.end_time_label(now).unwrap(), Some(expected));
+
+        let writing = running_task("写作", HOUR, now - 10 * MINUTE);
+        let expected = format!("预计结束 {}", format_clock_time(now + 50 * MINUTE, now));
+        assert_eq!(writing.end_time_label(now).unwrap(), Some(expected));
+
+        let mut finished = running_task("完成", MINUTE, now - 2 * MINUTE);
+        finished.finished = true;
+        for task in [duration_task("未开始", MINUTE), paused_task("阅读"), finished] {
+            assert_eq!(task.end_time_label(now).unwrap(), None, "{}", task.name);
+        }
+    }
+
+    #[test]
+    fn needs_tick_while_running_or_nagging() {
+        let (mut list, ids) = list_of([duration_task("写作", MINUTE), paused_task("阅读")]);
+        assert!(!list.needs_tick());
+        assert!(!TaskList::new().needs_tick());
+
+        list.get_mut(ids[0]).unwrap().start();
+        assert!(list.needs_tick());
+        list.get_mut(ids[0]).unwrap().archived = true;
+        assert!(!list.needs_tick());
+
+        let task = list.get_mut(ids[1]).unwrap();
+        task.finished = true;
+        task.nag = true;
+        assert!(list.needs_tick());
+        list.get_mut(ids[1]).unwrap().acknowledged = true;
+        assert!(!list.needs_tick());
+    }
+
+    fn list_of(tasks: impl IntoIterator<Item = Task>) -> (TaskList, Vec<TaskId>) {
+        let list: TaskList = tasks.into_iter().collect();
+        let ids = list.iter().map(|t| t.id).collect();
+        (list, ids)
+    }
+
+    // 计时一段时间后暂停，剩余时间不再是整数
+    fn paused_task(name: &str) -> Task {
+        let mut task = duration_task(name, 25 * MINUTE);
+        task.start();
+        task.start_time = task.start_time.map(|t| t - 10 * MINUTE);
+        task.pause().unwrap();
+        task
+    }
+
+    #[test]
+    fn undo_delete_restores_position_remaining_and_pin() {
+        let mut pinned = paused_task("写作");
+        pinned.pinned = true;
+        let remaining = pinned.remaining;
+        let (mut list, ids) = list_of([duration_task("a", MINUTE), pinned, duration_task("b", MINUTE)]);
+
+        list.delete(ids[1]).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.can_undo());
+
+        let restored = list.undo().unwrap();
+        assert_eq!(list.position(restored), Some(1));
+        let task = list.get(restored).unwrap();
+        assert_eq!(task.remaining, remaining);
+        assert!(task.pinned);
+        assert_eq!(task.state(), TaskState::Paused);
+        assert!(!list.can_undo());
+    }
+
+    #[test]
+    fn undo_delete_takes_the_task_out_of_the_trash() {
+        let (mut list, ids) = list_of([duration_task("写作", MINUTE)]);
+        list.delete(ids[0]).unwrap();
+        assert_eq!(list.trash().len(), 1);
+        assert_eq!(list.undo(), Some(ids[0]));
+        assert!(list.trash().is_empty());
+    }
+
+    #[test]
+    fn restore_from_trash_uses_a_new_id() {
+        let mut pinned = paused_task("写作");
+        pinned.pinned = true;
+        let remaining = pinned.remaining;
+        let (mut list, ids) = list_of([pinned, duration_task("阅读", MINUTE)]);
+        list.delete(ids[0]);
+        assert_eq!(list.trash()[0].0.id, ids[0]);
+
+        let restored = list.restore_from_trash(ids[0]).unwrap();
+        assert_ne!(restored, ids[0]);
+        assert_eq!(list.position(restored), Some(1));
+        let task = list.get(restored).unwrap();
+        assert_eq!(task.remaining, remaining);
+        assert!(!task.pinned && !task.archived);
+        assert!(list.trash().is_empty());
+        assert_eq!(list.restore_from_trash(ids[0]), None);
+    }
+
+    #[test]
+    fn trashed_running_task_stops() {
+        let mut running = duration_task("写作", 25 * MINUTE);
+        running.start_at(SystemTime::now() - 10 * MINUTE);
+        let (mut list, ids) = list_of([running]);
+        list.delete(ids[0]);
+
+        let (task, _) = &list.trash()[0];
+        assert!(!task.is_running);
+        let remaining = task.remaining.as_secs();
+        assert!((899..=900).contains(&remaining), "{remaining}");
+    }
+
+    #[test]
+    fn trash_is_purged_after_retention() {
+        let (mut list, ids) = list_of([duration_task("写作", MINUTE), duration_task("阅读", MINUTE)]);
+        list.delete(ids[0]);
+        let deleted_at = list.trash()[0].1;
+
+        assert_eq!(list.purge_trash(deleted_at + TRASH_RETENTION - MINUTE), 0);
+        assert_eq!(list.purge_trash(deleted_at + TRASH_RETENTION), 1);
+        assert!(list.trash().is_empty());
+
+        // 删除新任务时顺便清除过期的任务
+        list.delete(ids[1]);
+        let mut trash = list.trash().to_vec();
+        trash[0].1 -= TRASH_RETENTION;
+        list.set_trash(trash);
+        let extra = list.push(duration_task("评审", MINUTE));
+        list.delete(extra);
+        let names: Vec<&str> = list.trash().iter().map(|(t, _)| t.name.as_str()).collect();
+        assert_eq!(names, ["评审"]);
+    }
+
+    #[test]
+    fn trash_keeps_the_latest_deletions() {
+        let (mut list, ids) = list_of((0..TRASH_LIMIT + 3).map(|i| duration_task(&i.to_string(), MINUTE)));
+        for id in &ids {
+            list.delete(*id);
+        }
+        let names: Vec<&str> = list.trash().iter().map(|(t, _)| t.name.as_str()).collect();
+        assert_eq!(names, (3..TRASH_LIMIT + 3).map(|i| i.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(list.delete_from_trash(ids[3]).unwrap().name, "3");
+        assert_eq!(list.delete_from_trash(ids[3]).map(|t| t.name), None);
+        assert_eq!(list.empty_trash(), TRASH_LIMIT - 1);
+        assert!(list.trash().is_empty());
+    }
+
+    #[test]
+    fn undo_reset_restores_remaining_and_pin() {
+        let mut task = paused_task("写作");
+        task.pinned = true;
+        let remaining = task.remaining;
+        let (mut list, ids) = list_of([task]);
+
+        list.reset(ids[0]).unwrap();
+        assert_eq!(list.get(ids[0]).unwrap().remaining, 25 * MINUTE);
+
+        assert_eq!(list.undo(), Some(ids[0]));
+        let task = list.get(ids[0]).unwrap();
+        assert_eq!(task.remaining, remaining);
+        assert!(task.pinned);
+    }
+
+    #[test]
+    fn undo_edit_restores_previous_state() {
+        let (mut list, ids) = list_of([duration_task("写作", 25 * MINUTE)]);
+        list.record_edit(ids[0]);
+        let task = list.get_mut(ids[0]).unwrap();
+        task.name = "改名".to_string();
+        task.set_type(TaskType::Duration(HOUR)).unwrap();
+
+        list.undo();
+        let task = list.get(ids[0]).unwrap();
+        assert_eq!(task.name, "写作");
+        assert_eq!(task.remaining, 25 * MINUTE);
+    }
+
+    #[test]
+    fn undo_stack_keeps_the_latest_entries() {
+        let (mut list, ids) = list_of((0..UNDO_LIMIT + 2).map(|i| duration_task(&i.to_string(), MINUTE)));
+        for id in &ids {
+            list.delete(*id);
+        }
+        let mut restored = 0;
+        while list.undo().is_some() {
+            restored += 1;
+        }
+        assert_eq!(restored, UNDO_LIMIT);
+        let names: Vec<&str> = list.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, (2..UNDO_LIMIT + 2).map(|i| i.to_string()).collect::<Vec<_>>());
+    }
+
+    // 从指定时刻开始计时的时间段任务
+    fn running_task(name: &str, length: Duration, start: SystemTime) -> Task {
+        let mut task = duration_task(name, length);
+        task.start();
+        task.start_time = Some(start);
+        task
+    }
+
+    #[test]
+    fn tick_finishes_once_on_time() {
+        let start = SystemTime::now();
+        let (mut list, ids) = list_of([running_task("写作", MINUTE, start)]);
+
+        for secs in 0..60 {
+            let now = start + Duration::from_secs(secs);
+            assert!(list.tick(now, false).unwrap().is_empty(), "{secs}s");
+            let remaining = list.get(ids[0]).unwrap().remaining_time_at(now).unwrap();
+            assert_eq!(remaining, MINUTE - Duration::from_secs(secs));
+        }
+        assert_eq!(list.tick(start + MINUTE, false).unwrap(), [TaskEvent::Finished(ids[0])]);
+        assert!(list.tick(start + MINUTE + SECOND, false).unwrap().is_empty());
+
+        let task = list.get(ids[0]).unwrap();
+        assert_eq!(task.state(), TaskState::Finished);
+        assert_eq!(task.finished_at, Some(start + MINUTE));
+        assert_eq!(task.elapsed, MINUTE);
+    }
+
+    #[test]
+    fn delayed_tick_records_the_exact_finish_time() {
+        // 系统休眠或事件循环阻塞，下一次计时检查晚了很久
+        let start = SystemTime::now();
+        let deadline = deadline_task("下班", 10 * MINUTE);
+        let deadline_at = match deadline.task_type {
+            TaskType::Deadline(t) => t,
+            TaskType::Duration(_) => unreachable!(),
+        };
+        let (mut list, ids) = list_of([running_task("写作", 25 * MINUTE, start), deadline]);
+
+        let late = start + HOUR;
+        assert_eq!(list.tick(late, false).unwrap(), [TaskEvent::Finished(ids[0]), TaskEvent::Finished(ids[1])]);
+        assert!(list.tick(late + MINUTE, false).unwrap().is_empty());
+
+        let task = list.get(ids[0]).unwrap();
+        assert_eq!(task.finished_at, Some(start + 25 * MINUTE));
+        assert_eq!(task.elapsed, 25 * MINUTE);
+        assert_eq!(task.remaining, Duration::ZERO);
+        assert_eq!(list.get(ids[1]).unwrap().finished_at, Some(deadline_at));
+        // 超时从到时时刻算起，延迟的时间不计入计时时间
+        let stats = task.stats_at(late).unwrap();
+        assert_eq!(stats.actual, HOUR);
+        assert_eq!(stats.overrun, 35 * MINUTE);
+    }
+
+    fn deadline_of(task: &Task) -> SystemTime {
+        match task.task_type {
+            TaskType::Deadline(t) => t,
+            TaskType::Duration(_) => panic!("{} is not a deadline task", task.name),
+        }
+    }
+
+    #[test]
+    fn expired_deadlines_are_archived_after_retention() {
+        let mut pinned = deadline_task("下班", MINUTE);
+        pinned.pinned = true;
+        let deadline = deadline_of(&pinned);
+        let (mut list, ids) = list_of([pinned, deadline_task("周报", HOUR), duration_task("写作", MINUTE)]);
+        let retention = ExpiredRetention::AfterSecs(600);
+
+        assert_eq!(list.next_expired_removal(retention, deadline - MINUTE), None);
+        let now = deadline + 5 * MINUTE;
+        assert!(list.archive_expired(retention, now).is_empty());
+        assert_eq!(list.next_expired_removal(retention, now), Some(5 * MINUTE));
+
+        // 只归档过期超过保留时长的任务，返回归档前的固定状态
+        let now = deadline + 10 * MINUTE;
+        assert_eq!(list.archive_expired(retention, now), [(ids[0], true)]);
+        let task = list.get(ids[0]).unwrap();
+        assert!(task.archived && !task.pinned);
+        assert!(list.iter().skip(1).all(|t| !t.archived));
+        // 已归档的任务不会再次归档
+        assert!(list.archive_expired(retention, now + 30 * MINUTE).is_empty());
+        assert_eq!(list.next_expired_removal(retention, now), None);
+    }
+
+    #[test]
+    fn retention_settings() {
+        let task = deadline_task("下班", MINUTE);
+        let deadline = deadline_of(&task);
+        let (mut list, ids) = list_of([task]);
+
+        let forever = ExpiredRetention::Forever;
+        assert_eq!(list.next_expired_removal(forever, deadline + 24 * HOUR), None);
+        assert!(list.archive_expired(forever, deadline + 24 * HOUR).is_empty());
+
+        let immediately = ExpiredRetention::Immediately;
+        assert_eq!(list.next_expired_removal(immediately, deadline - MINUTE), None);
+        assert_eq!(list.next_expired_removal(immediately, deadline), Some(Duration::ZERO));
+        assert_eq!(list.archive_expired(immediately, deadline), [(ids[0], false)]);
+    }
+
+    #[test]
+    fn tick_starts_the_chained_task() {
+        let start = SystemTime::now();
+        let (mut list, ids) = list_of([running_task("写作", MINUTE, start), duration_task("休息", 5 * MINUTE)]);
+        list.set_next(ids[0], Some(ids[1])).unwrap();
+
+        let events = list.tick(start + MINUTE, false).unwrap();
+        assert_eq!(events, [TaskEvent::Finished(ids[0]), TaskEvent::Started(ids[1])]);
+        let next = list.get(ids[1]).unwrap();
+        assert_eq!(next.state(), TaskState::Running);
+        assert_eq!(next.start_time, Some(start + MINUTE));
+        assert!(list.tick(start + MINUTE + SECOND, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tick_skips_archived_and_finished_chained_tasks() {
+        let start = SystemTime::now();
+        let (mut list, ids) = list_of([running_task("写作", MINUTE, start), duration_task("休息", 5 * MINUTE)]);
+        list.set_next(ids[0], Some(ids[1])).unwrap();
+        list.get_mut(ids[1]).unwrap().archive();
+        assert_eq!(list.tick(start + MINUTE, false).unwrap(), [TaskEvent::Finished(ids[0])]);
+        assert_eq!(list.get(ids[1]).unwrap().state(), TaskState::Idle);
+
+        let (mut list, ids) =
+            list_of([running_task("写作", MINUTE, start), running_task("休息", MINUTE, start - MINUTE)]);
+        list.set_next(ids[0], Some(ids[1])).unwrap();
+        assert_eq!(list.tick(start, false).unwrap(), [TaskEvent::Finished(ids[1])]);
+        assert_eq!(list.tick(start + MINUTE, false).unwrap(), [TaskEvent::Finished(ids[0])]);
+        assert_eq!(list.get(ids[1]).unwrap().state(), TaskState::Finished);
+    }
+
+    #[test]
+    fn tick_keeps_a_single_running_task() {
+        let start = SystemTime::now();
+        let (mut list, ids) = list_of([
+            running_task("写作", MINUTE, start),
+            duration_task("休息", 5 * MINUTE),
+            running_task("阅读", HOUR, start),
+        ]);
+        list.set_next(ids[0], Some(ids[1])).unwrap();
+
+        let events = list.tick(start + MINUTE, true).unwrap();
+        assert_eq!(events, [TaskEvent::Finished(ids[0]), TaskEvent::Started(ids[1]), TaskEvent::Paused(ids[2])]);
+        let running: Vec<TaskId> = list.running().map(|t| t.id).collect();
+        assert_eq!(running, [ids[1]]);
+    }
+
+    #[test]
+    fn start_exclusive_pauses_other_running_durations() {
+        let now = SystemTime::now();
+        let (mut list, ids) = list_of([
+            duration_task("写作", HOUR),
+            running_task("阅读", HOUR, now - MINUTE),
+            running_task("休息", HOUR, now - MINUTE),
+            deadline_task("交稿", HOUR),
+        ]);
+        list.get_mut(ids[3]).unwrap().start_at(now - MINUTE);
+        assert_eq!(list.start_exclusive(ids[0], now).unwrap(), [ids[1], ids[2]]);
+        assert_eq!(list.get(ids[0]).unwrap().start_time, Some(now));
+        // 截止时间任务不受影响
+        let running: Vec<TaskId> = list.running().map(|t| t.id).collect();
+        assert_eq!(running, [ids[0], ids[3]]);
+
+        // 开始截止时间任务不暂停时间段任务
+        let (mut list, ids) = list_of([deadline_task("交稿", HOUR), running_task("阅读", HOUR, now)]);
+        assert!(list.start_exclusive(ids[0], now).unwrap().is_empty());
+        assert_eq!(list.get(ids[1]).unwrap().state(), TaskState::Running);
+        assert!(list.start_exclusive(TaskId::next(), now).is_err());
+    }
+
+    #[test]
+    fn nagging_tasks_need_alerts_until_acknowledged() {
+        let start = SystemTime::now();
+        let mut nagging = running_task("写作", MINUTE, start);
+        nagging.nag = true;
+        let (mut list, ids) = list_of([nagging, running_task("阅读", MINUTE, start)]);
+        assert!(!list.get(ids[0]).unwrap().needs_alert());
+
+        list.tick(start + MINUTE, false).unwrap();
+        assert!(list.get(ids[0]).unwrap().needs_alert());
+        assert!(!list.get(ids[1]).unwrap().needs_alert());
+
+        let task = list.get_mut(ids[0]).unwrap();
+        task.acknowledged = true;
+        assert!(!task.needs_alert());
+
+        // 再次完成时重新需要提醒
+        task.reset().unwrap();
+        task.start();
+        task.start_time = Some(start + MINUTE);
+        list.tick(start + 2 * MINUTE, false).unwrap();
+        assert!(list.get(ids[0]).unwrap().needs_alert());
+    }
+
+    #[test]
+    fn resume_all_only_resumes_tasks_paused_by_pause_all() {
+        let mut writing = duration_task("写作", 25 * MINUTE);
+        writing.start();
+        let mut reading = duration_task("阅读", 25 * MINUTE);
+        reading.start();
+        let (mut list, ids) =
+            list_of([writing, reading, paused_task("评审"), deadline_task("下班", HOUR), duration_task("休息", MINUTE)]);
+        assert!(!list.can_resume_all());
+
+        assert_eq!(list.pause_all().unwrap(), [ids[0], ids[1]]);
+        assert!(list.can_resume_all());
+        assert_eq!(list.get(ids[3]).unwrap().state(), TaskState::Running);
+
+        // 期间手动继续的任务不会被重复处理
+        list.get_mut(ids[1]).unwrap().start();
+        assert_eq!(list.resume_all(), [ids[0]]);
+        let states: Vec<_> = list.iter().map(Task::state).collect();
+        let expected = [TaskState::Running, TaskState::Running, TaskState::Paused, TaskState::Running, TaskState::Idle];
+        assert_eq!(states, expected);
+
+        assert!(!list.can_resume_all());
+        assert!(list.resume_all().is_empty());
+    }
+
+    #[test]
+    fn clear_finished_skips_archived_and_optionally_pinned_tasks() {
+        let finished = |name: &str| {
+            let mut task = duration_task(name, MINUTE);
+            task.finished = true;
+            task
+        };
+        let mut pinned = finished("固定");
+        pinned.pinned = true;
+        let mut archived = finished("归档");
+        archived.archived = true;
+        let (mut list, ids) = list_of([finished("写作"), pinned, archived, paused_task("阅读")]);
+
+        assert_eq!(list.finished_to_clear(false), [ids[0]]);
+        assert_eq!(list.finished_to_clear(true), [ids[0], ids[1]]);
+
+        let cleared: Vec<_> = list.clear_finished(true).iter().map(|t| t.id).collect();
+        assert_eq!(cleared, [ids[0], ids[1]]);
+        assert_eq!(list.iter().map(|t| t.id).collect::<Vec<_>>(), [ids[2], ids[3]]);
+        assert!(list.finished_to_clear(true).is_empty());
+    }
+
+    #[test]
+    fn time_formats_at_boundaries() {
+        let cases = [
+            (Duration::ZERO, "00:00:00", "00:00", "0s"),
+            (59 * SECOND, "00:00:59", "00:59", "59s"),
+            (MINUTE, "00:01:00", "01:00", "01:00"),
+            (HOUR - SECOND, "00:59:59", "59:59", "59:59"),
+            (HOUR, "01:00:00", "01:00:00", "1h00m"),
+            (HOUR + 5 * MINUTE + 30 * SECOND, "01:05:30", "01:05:30", "1h05m"),
+            (24 * HOUR - SECOND, "23:59:59", "23:59:59", "23h59m"),
+            (24 * HOUR, "24:00:00", "24:00:00", "1d 0h"),
+            (51 * HOUR + 59 * MINUTE, "51:59:00", "51:59:00", "2d 3h"),
+        ];
+        for (duration, full, compact, adaptive) in cases {
+            assert_eq!(format_remaining_time(duration, TimeFormat::Full), full, "{duration:?}");
+            assert_eq!(format_remaining_time(duration, TimeFormat::Compact), compact, "{duration:?}");
+            assert_eq!(format_remaining_time(duration, TimeFormat::Adaptive), adaptive, "{duration:?}");
+        }
+        // 不足一秒的部分舍去
+        assert_eq!(format_remaining_time(Duration::from_millis(999), TimeFormat::Adaptive), "0s");
+    }
+
+    #[test]
+    fn time_formats_survive_the_atomic_encoding() {
+        for format in TimeFormat::ALL {
+            assert_eq!(TimeFormat::from_u8(format as u8), format);
+        }
+        assert_eq!(TimeFormat::from_u8(u8::MAX), TimeFormat::Full);
+    }
+
+    #[test]
+    fn summary_formats_each_style() {
+        let mut task = duration_task("写作", HOUR + 2 * MINUTE + 3 * SECOND);
+        task.group = Some("工作".to_string());
+        assert_eq!(task.summary(SummaryStyle::Menu).unwrap(), "01:02:03#写作");
+        assert_eq!(task.summary(SummaryStyle::Tooltip).unwrap(), "01:02:03#写作 /工作");
+        assert_eq!(task.summary(SummaryStyle::PinnedTitle).unwrap(), "01:02:03");
+        task.set_type(TaskType::Duration(2 * MINUTE + 3 * SECOND)).unwrap();
+        assert_eq!(task.summary(SummaryStyle::PinnedTitle).unwrap(), "02:03");
+        task.set_type(TaskType::Duration(HOUR + 2 * MINUTE + 3 * SECOND)).unwrap();
+
+        task.muted = true;
+        assert_eq!(task.summary(SummaryStyle::Menu).unwrap(), "🔕 01:02:03#写作");
+        assert_eq!(task.summary(SummaryStyle::Tooltip).unwrap(), "01:02:03#写作 /工作");
+    }
+
+    #[test]
+    fn summary_shows_overtime_with_a_sign() {
+        let mut task = duration_task("写作", MINUTE);
+        task.finished = true;
+        task.finished_at = Some(SystemTime::now() - 90 * SECOND);
+        assert_eq!(task.summary(SummaryStyle::Menu).unwrap(), "-00:01:30#写作");
+        assert_eq!(task.summary(SummaryStyle::PinnedTitle).unwrap(), "-01:30");
+    }
+
+    #[test]
+    fn toggles_track_pin_cycles() {
+        let (mut list, ids) = list_of([duration_task("写作", MINUTE), duration_task("阅读", MINUTE)]);
+        let off = TaskToggles { muted: false, nag: false, pinned: false };
+        assert_eq!(list.get(ids[0]).unwrap().toggles(), off);
+
+        for _ in 0..2 {
+            let task = list.get_mut(ids[0]).unwrap();
+            task.pinned = !task.pinned;
+            assert_eq!(task.toggles(), TaskToggles { pinned: true, ..off });
+            task.pinned = !task.pinned;
+            assert_eq!(task.toggles(), off);
+        }
+
+        // 固定图标无法恢复时取消固定，只影响对应任务的勾选
+        for id in &ids {
+            list.get_mut(*id).unwrap().pinned = true;
+        }
+        list.get_mut(ids[1]).unwrap().muted = true;
+        assert_eq!(list.unpin(&[ids[0]]), [ids[0]]);
+        assert_eq!(list.get(ids[0]).unwrap().toggles(), off);
+        assert_eq!(list.get(ids[1]).unwrap().toggles(), TaskToggles { muted: true, nag: false, pinned: true });
+    }
+
+    #[test]
+    fn move_task_stops_at_the_list_edges() {
+        let (mut list, ids) = list_of(["a", "b", "c"].map(|name| duration_task(name, MINUTE)));
+        let order = |list: &TaskList| list.iter().map(|t| t.name.clone()).collect::<Vec<_>>();
+        assert!(!list.can_move(ids[0], MoveDirection::Up));
+        assert!(!list.can_move(ids[2], MoveDirection::Down));
+        assert!(!list.move_task(ids[0], MoveDirection::Up));
+        assert!(!list.move_task(ids[2], MoveDirection::Down));
+        assert_eq!(order(&list), ["a", "b", "c"]);
+
+        assert!(list.move_task(ids[0], MoveDirection::Down));
+        assert_eq!(order(&list), ["b", "a", "c"]);
+        assert!(list.move_task(ids[0], MoveDirection::Down));
+        assert_eq!(order(&list), ["b", "c", "a"]);
+        assert!(!list.can_move(ids[0], MoveDirection::Down));
+        assert!(list.move_task(ids[2], MoveDirection::Up));
+        assert_eq!(order(&list), ["c", "b", "a"]);
+        assert!(!list.move_task(TaskId::next(), MoveDirection::Up));
+    }
+
+    #[test]
+    fn move_task_skips_other_sections_and_keeps_ids() {
+        let mut archived = duration_task("归档", MINUTE);
+        archived.archived = true;
+        let mut pinned = running_task("计时", MINUTE, SystemTime::now());
+        pinned.pinned = true;
+        let tasks = [duration_task("a", MINUTE), archived, pinned, duration_task("b", MINUTE)];
+        let (mut list, ids) = list_of(tasks);
+
+        // 同一分段中的相邻任务隔着归档和计时中的任务
+        assert!(list.move_task(ids[3], MoveDirection::Up));
+        let order: Vec<_> = list.iter().map(|t| t.id).collect();
+        assert_eq!(order, [ids[3], ids[1], ids[2], ids[0]]);
+        assert!(!list.can_move(ids[2], MoveDirection::Up) && !list.can_move(ids[2], MoveDirection::Down));
+
+        // 按 id 查找的固定图标等映射不受顺序影响
+        let moved = list.get(ids[2]).unwrap();
+        assert!(moved.pinned && moved.name == "计时");
+        assert_eq!(list.pinned(), [ids[2]]);
+    }
+
+    #[test]
+    fn repair_restores_list_invariants() {
+        let (mut list, ids) = list_of([duration_task("a", MINUTE), duration_task("b", MINUTE), paused_task("c")]);
+        assert_eq!(list.repair(), 0);
+
+        let task = list.nth_mut(1).unwrap();
+        task.id = ids[0];
+        task.is_running = true;
+        task.remaining = MAX_DURATION + SECOND;
+        let finished = list.nth_mut(2).unwrap();
+        finished.finished = true;
+        finished.is_running = true;
+        finished.start_time = Some(SystemTime::now());
+
+        assert_eq!(list.repair(), 4);
+        let repaired: Vec<_> = list.iter().collect();
+        assert_eq!(repaired[0].id, ids[0]);
+        assert!(repaired[1].id != ids[0] && repaired[1].id != ids[2]);
+        assert!(!repaired[1].is_running && repaired[1].remaining == MINUTE);
+        assert!(!repaired[2].is_running && repaired[2].start_time.is_none());
+        assert_eq!(list.repair(), 0);
+    }
+
+    #[test]
+    fn pin_limit_counts_pinned_tasks() {
+        let (mut list, ids) = list_of([duration_task("写作", MINUTE), duration_task("阅读", MINUTE)]);
+        assert!(!list.pin_limit_reached(1));
+        assert!(list.pin_limit_reached(0));
+
+        list.get_mut(ids[1]).unwrap().pinned = true;
+        assert!(list.pin_limit_reached(1));
+        assert!(!list.pin_limit_reached(2));
+        let message = list.pin_limit_message(1);
+        assert!(message.contains('1') && message.contains("阅读") && !message.contains("写作"), "{message}");
+
+        // 取消固定后立即空出名额
+        list.unpin(&[ids[1]]);
+        assert!(!list.pin_limit_reached(1));
+    }
+
+    #[test]
+    fn pin_limit_message_lists_pinned_tasks_in_order() {
+        let (mut list, ids) = list_of([duration_task("写作", MINUTE), duration_task("阅读", MINUTE)]);
+        for id in &ids {
+            list.get_mut(*id).unwrap().pinned = true;
+        }
+        assert_eq!(list.pinned_names(), ["写作", "阅读"]);
+        assert!(list.pin_limit_message(2).contains("写作\n阅读"));
+    }
+
+    #[test]
+    fn pinned_title_shows_only_seconds_in_the_last_minute() {
+        let start = SystemTime::now();
+        let task = running_task("写作", 2 * MINUTE, start);
+        let title_at = |remaining: u64| {
+            let now = start + 2 * MINUTE - Duration::from_secs(remaining);
+            task.summary_at(SummaryStyle::PinnedTitle, now).unwrap()
+        };
+        for (remaining, title) in [(61, "01:01"), (60, "01:00"), (59, "59s"), (9, "09s"), (0, "00s")] {
+            assert_eq!(title_at(remaining), title, "{remaining}s");
+        }
+        // 其他样式不受影响
+        let now = start + MINUTE + SECOND;
+        assert_eq!(task.summary_at(SummaryStyle::Menu, now).unwrap(), "00:00:59#写作");
+
+        // 完成后显示超时时间
+        let mut finished = task.clone();
+        finished.check_finished_at(start + 2 * MINUTE).unwrap();
+        assert_eq!(finished.summary_at(SummaryStyle::PinnedTitle, start + 2 * MINUTE).unwrap(), "00:00");
+        assert_eq!(finished.summary_at(SummaryStyle::PinnedTitle, start + 3 * MINUTE).unwrap(), "-01:00");
+    }
+
+    fn snapshot(state: TaskState, remaining_secs: u64, line: &str) -> TaskSnapshot {
+        TaskSnapshot { state, remaining: Duration::from_secs(remaining_secs), line: line.to_string() }
+    }
+
+    #[test]
+    fn tooltip_groups_by_state_and_lists_urgent_tasks_first() {
+        let snapshots = [
+            snapshot(TaskState::Idle, 600, "休息"),
+            snapshot(TaskState::Running, 900, "写作"),
+            snapshot(TaskState::Finished, 0, "评审"),
+            snapshot(TaskState::Paused, 300, "阅读"),
+            snapshot(TaskState::Running, 60, "下班"),
+        ];
+        assert_eq!(
+            build_tooltip(&snapshots, 10),
+            "▶ 进行中 (2)\n下班\n写作\n⏸ 已暂停 (1)\n阅读\n○ 未开始 (1)\n休息"
+        );
+        assert_eq!(build_tooltip(&snapshots, 2), "▶ 进行中 (2)\n下班\n写作\n…以及另外 2 个任务");
+    }
+
+    #[test]
+    fn tooltip_shows_finished_tasks_only_when_nothing_else_is_left() {
+        let finished = [snapshot(TaskState::Finished, 0, "评审"), snapshot(TaskState::Finished, 0, "写作")];
+        assert_eq!(build_tooltip(&finished, 1), "✔ 已完成 (1)\n评审\n…以及另外 1 个任务");
+        assert_eq!(build_tooltip(&[], 5), tr("tooltip.empty"));
+        assert_eq!(build_tooltip(&finished, 0), "…以及另外 2 个任务");
+    }
+
+    #[test]
+    fn restored_running_tasks_deduct_the_downtime() {
+        // 任务在 40 分钟前开始计时，其间程序关闭后重新启动，按保存的开始时刻继续计算
+        let now = SystemTime::now();
+        let started = now - 40 * MINUTE;
+        let (mut list, ids) = list_of([
+            running_task("写作", HOUR, started),
+            running_task("番茄", 25 * MINUTE, started),
+            duration_task("阅读", 25 * MINUTE),
+        ]);
+
+        assert_eq!(list.tick(now, false).unwrap(), [TaskEvent::Finished(ids[1])]);
+        assert_eq!(list.get(ids[0]).unwrap().remaining_time_at(now).unwrap(), 20 * MINUTE);
+        assert_eq!(list.get(ids[1]).unwrap().finished_at, Some(started + 25 * MINUTE));
+        assert_eq!(list.get(ids[2]).unwrap().remaining_time_at(now).unwrap(), 25 * MINUTE);
+    }
+
+    #[test]
+    fn sleep_is_credited_to_running_duration_tasks_only() {
+        let start = SystemTime::now() - 10 * MINUTE;
+        let (mut list, ids) = list_of([
+            running_task("写作", HOUR, start),
+            paused_task("阅读"),
+            deadline_task("下班", HOUR),
+            duration_task("休息", MINUTE),
+        ]);
+        let before: Vec<_> = list.iter().map(|t| (t.start_time, t.remaining)).collect();
+
+        assert_eq!(list.credit_suspended(5 * MINUTE), 1);
+        let writing = list.get(ids[0]).unwrap();
+        assert_eq!(writing.start_time, Some(start + 5 * MINUTE));
+        assert_eq!(writing.total_paused, 5 * MINUTE);
+        let remaining = writing.remaining_time_at(start + 15 * MINUTE).unwrap();
+        assert_eq!(remaining, 50 * MINUTE);
+        let after: Vec<_> = list.iter().map(|t| (t.start_time, t.remaining)).collect();
+        assert_eq!(after[1..], before[1..]);
+    }
+
+    #[test]
+    fn validation_uses_the_given_limit() {
+        let two_hours = TaskType::Duration(2 * HOUR);
+        assert!(validate_task_type(&two_hours, false, 2 * HOUR).is_ok());
+        let err = validate_task_type(&two_hours, false, HOUR).unwrap_err();
+        assert!(matches!(err, Error::DurationTooLong { max_hours: 1, .. }), "{err}");
+        assert!(validate_task_type(&TaskType::Duration(MAX_DURATION), false, MAX_DURATION).is_ok());
+    }
+
+    #[test]
+    fn only_finished_or_overdue_tasks_can_snooze() {
+        let start = SystemTime::now();
+        let mut task = running_task("写作", MINUTE, start);
+        assert!(!duration_task("阅读", MINUTE).can_snooze(start));
+        assert!(!task.can_snooze(start + MINUTE));
+        task.check_finished_at(start + MINUTE).unwrap();
+        assert!(task.can_snooze(start + MINUTE));
+
+        let deadline = deadline_task("下班", HOUR);
+        assert!(!deadline.can_snooze(start));
+        assert!(deadline.can_snooze(start + 2 * HOUR));
+    }
+
+    #[test]
+    fn snooze_restarts_finished_tasks() {
+        let start = SystemTime::now();
+        let mut task = running_task("写作", MINUTE, start);
+        task.check_finished_at(start + MINUTE).unwrap();
+        assert!(task.snooze(5 * MINUTE, start + 2 * MINUTE));
+        assert_eq!(task.state(), TaskState::Running);
+        assert!(task.acknowledged && task.finished_at.is_none());
+        assert_eq!(task.remaining_time_at(start + 3 * MINUTE).unwrap(), 4 * MINUTE);
+        // 正在计时的任务不能再推迟
+        assert!(!task.snooze(5 * MINUTE, start + 3 * MINUTE));
+
+        let later = start + 2 * HOUR;
+        let mut deadline = deadline_task("下班", HOUR);
+        assert!(deadline.snooze(10 * MINUTE, later));
+        assert!(matches!(deadline.task_type, TaskType::Deadline(t) if t == later + 10 * MINUTE));
+        assert!(!deadline.can_snooze(later));
+    }
+
+    #[test]
+    fn list_snooze_targets_one_or_all_unacknowledged_tasks() {
+        let start = SystemTime::now();
+        let tasks = [running_task("a", MINUTE, start), running_task("b", MINUTE, start), duration_task("c", MINUTE)];
+        let (mut list, ids) = list_of(tasks);
+        list.tick(start + MINUTE, false).unwrap();
+        list.get_mut(ids[1]).unwrap().acknowledged = true;
+
+        let err = list.snooze(Some(ids[2]), MINUTE, start + MINUTE).unwrap_err();
+        assert!(matches!(err, Error::TaskNotSnoozable { id, .. } if id == ids[2]), "{err}");
+        let missing = TaskId::next();
+        let err = list.snooze(Some(missing), MINUTE, start + MINUTE).unwrap_err();
+        assert!(matches!(err, Error::TaskIdNotFound { .. }), "{err}");
+
+        // 未指定任务时跳过已确认的提醒
+        assert_eq!(list.snooze(None, MINUTE, start + MINUTE).unwrap(), [ids[0]]);
+        assert_eq!(list.get(ids[1]).unwrap().state(), TaskState::Finished);
+        // 指定任务时已确认的也可以推迟
+        assert_eq!(list.snooze(Some(ids[1]), MINUTE, start + MINUTE).unwrap(), [ids[1]]);
+        assert!(list.snooze(None, MINUTE, start + MINUTE).unwrap().is_empty());
+        assert!(list.iter().take(2).all(|t| t.state() == TaskState::Running));
+    }
+
+    #[test]
+    fn task_errors_name_the_task() {
+        let mut task = deadline_task("报税", HOUR);
+        task.start();
+        // 早于 UNIX_EPOCH 的截止时间无法计算剩余时间
+        task.task_type = TaskType::Deadline(SystemTime::UNIX_EPOCH - SECOND);
+
+        let err = task.remaining_time_at(SystemTime::now()).unwrap_err();
+        assert!(matches!(&err, Error::TaskOperation { id, operation: "compute remaining time", .. } if *id == task.id));
+        assert!(err.to_string().starts_with(&format!("Task '报税' (#{}) failed to compute remaining time", task.id)));
+
+        // 重置失败时任务保持原样
+        let err = task.reset().unwrap_err();
+        assert!(matches!(err, Error::TaskOperation { operation: "reset", .. }), "{err}");
+        assert!(task.is_running);
+    }
+
+    // 在 at 时刻定时开始的时间段任务
+    fn scheduled_task(name: &str, length: Duration, at: SystemTime) -> Task {
+        let mut task = duration_task(name, length);
+        task.scheduled_start = Some(at);
+        task
+    }
+
+    #[test]
+    fn scheduled_tasks_start_when_their_moment_arrives() {
+        let now = SystemTime::now();
+        let (mut list, ids) = list_of([
+            scheduled_task("早", 25 * MINUTE, now - MINUTE),
+            scheduled_task("正好", 25 * MINUTE, now),
+            scheduled_task("晚", 25 * MINUTE, now + HOUR),
+            duration_task("手动", 25 * MINUTE),
+        ]);
+
+        assert_eq!(list.start_scheduled(now, false), [ids[0], ids[1]]);
+        for id in &ids[..2] {
+            let task = list.get(*id).unwrap();
+            assert!(task.is_running);
+            assert_eq!(task.scheduled_start, None);
+        }
+        assert!(!list.get(ids[2]).unwrap().is_running);
+        assert!(!list.get(ids[3]).unwrap().is_running);
+        // 已经开始的任务不会再次开始
+        assert!(list.start_scheduled(now + SECOND, false).is_empty());
+        assert_eq!(list.start_scheduled(now + HOUR, false), [ids[2]]);
+    }
+
+    #[test]
+    fn missed_schedules_follow_the_sleep_setting() {
+        // 程序关闭期间错过了定时开始的时刻，十分钟后才启动
+        let at = SystemTime::now() - 10 * MINUTE;
+        let now = at + 10 * MINUTE;
+        // 错过的时间计入用时：从定时时刻起计时
+        let (mut list, ids) = list_of([scheduled_task("计入", 25 * MINUTE, at)]);
+        list.start_scheduled(now, true);
+        let task = list.get(ids[0]).unwrap();
+        assert_eq!(task.start_time, Some(at));
+        assert_eq!(task.remaining_time_at(now).unwrap(), 15 * MINUTE);
+
+        // 不计入：从启动时刻起计时，剩余完整时长
+        let (mut list, ids) = list_of([scheduled_task("不计入", 25 * MINUTE, at)]);
+        list.start_scheduled(now, false);
+        let task = list.get(ids[0]).unwrap();
+        assert_eq!(task.start_time, Some(now));
+        assert_eq!(task.remaining_time_at(now).unwrap(), 25 * MINUTE);
+    }
+
+    #[test]
+    fn archived_tasks_are_not_started_or_scheduled() {
+        let now = SystemTime::now();
+        let mut task = scheduled_task("归档", 25 * MINUTE, now - MINUTE);
+        task.archive();
+        let (mut list, ids) = list_of([task]);
+        assert!(list.start_scheduled(now, false).is_empty());
+        assert!(!list.get(ids[0]).unwrap().is_running);
+        assert_eq!(list.next_scheduled_start(now), None);
+    }
+
+    #[test]
+    fn next_scheduled_start_is_the_earliest() {
+        let now = SystemTime::now();
+        let (mut list, ids) = list_of([
+            scheduled_task("晚", 25 * MINUTE, now + HOUR),
+            scheduled_task("早", 25 * MINUTE, now + 10 * MINUTE),
+            duration_task("手动", 25 * MINUTE),
+        ]);
+        assert_eq!(list.next_scheduled_start(now), Some(10 * MINUTE));
+        // 已经到达的时刻立即检查
+        assert_eq!(list.next_scheduled_start(now + 20 * MINUTE), Some(Duration::ZERO));
+
+        // 手动开始取消定时
+        for id in ids {
+            if let Some(task) = list.get_mut(id) {
+                task.start();
             }
         }
+        assert_eq!(list.next_scheduled_start(now), None);
     }
 }
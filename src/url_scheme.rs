@@ -0,0 +1,106 @@
+use snafu::OptionExt;
+
+use crate::error::{InvalidUrlSnafu, Result};
+
+pub const SCHEME: &str = "timeticker"; // Info.plist 中注册的 URL scheme
+
+// 从 timeticker://add?input=25m%23写作 中取出解码后的任务输入
+pub fn parse_add_url(url: &str) -> Result<String> {
+    let invalid = |msg: &str| InvalidUrlSnafu {
+        url: url.to_string(),
+        msg: msg.to_string(),
+    };
+    let (scheme, rest) = url.split_once("://").context(invalid("缺少 scheme"))?;
+    if !scheme.eq_ignore_ascii_case(SCHEME) {
+        return invalid("scheme 不是 timeticker").fail();
+    }
+    // 忽略片段，#写作 这样的任务名必须编码为 %23
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if !action.trim_end_matches('/').eq_ignore_ascii_case("add") {
+        return invalid("只支持 add").fail();
+    }
+    let input = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "input")
+        .context(invalid("缺少 input 参数"))?
+        .1;
+    let input = percent_decode(input).context(invalid("input 参数编码无效"))?;
+    let input = input.trim();
+    if input.is_empty() {
+        return invalid("input 参数为空").fail();
+    }
+    Ok(input.to_string())
+}
+
+// 解码 %XX 和表示空格的 +，解码结果不是 UTF-8 时返回 None
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'%' => {
+                let high = char::from(iter.next()?).to_digit(16)?;
+                let low = char::from(iter.next()?).to_digit(16)?;
+                bytes.push((high * 16 + low) as u8);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, parser::parse_time_input};
+
+    #[test]
+    fn parses_add_url() {
+        assert_eq!(parse_add_url("timeticker://add?input=25m").unwrap(), "25m");
+        assert_eq!(parse_add_url("TimeTicker://ADD/?input=25m").unwrap(), "25m");
+        assert_eq!(parse_add_url("timeticker://add?foo=1&input=25m%23%E5%86%99%E4%BD%9C").unwrap(), "25m#写作");
+        assert_eq!(parse_add_url("timeticker://add?input=+25m+#ignored").unwrap(), "25m");
+    }
+
+    #[test]
+    fn rejects_invalid_urls() {
+        for url in [
+            "add?input=25m",
+            "http://add?input=25m",
+            "timeticker://remove?input=25m",
+            "timeticker://add",
+            "timeticker://add?input=",
+            "timeticker://add?input=%20+",
+            "timeticker://add?input=%2",
+            "timeticker://add?input=%ff",
+        ] {
+            assert!(matches!(parse_add_url(url), Err(Error::InvalidUrl { .. })), "{url}");
+        }
+    }
+
+    #[test]
+    fn refuses_completion_commands_from_links() {
+        let input = parse_add_url("timeticker://add?input=25m%23x%20%26%26%20touch%20%2Ftmp%2Fpwned").unwrap();
+        assert_eq!(input, "25m#x && touch /tmp/pwned");
+        let task_input = parse_time_input(&input).unwrap();
+        assert!(matches!(task_input.refuse_command("links"), Err(Error::UntrustedCommand { .. })));
+
+        let task_input = parse_time_input(&parse_add_url("timeticker://add?input=25m%23x").unwrap()).unwrap();
+        assert!(task_input.refuse_command("links").is_ok());
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(percent_decode("25m%23a+b").as_deref(), Some("25m#a b"));
+        assert_eq!(percent_decode("%e5%86%99").as_deref(), Some("写"));
+        assert_eq!(percent_decode("%2B%25").as_deref(), Some("+%"));
+        assert_eq!(percent_decode("").as_deref(), Some(""));
+        assert_eq!(percent_decode("%"), None);
+        assert_eq!(percent_decode("%4"), None);
+        assert_eq!(percent_decode("%zz"), None);
+        assert_eq!(percent_decode("%C3%28"), None);
+    }
+}
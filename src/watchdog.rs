@@ -0,0 +1,158 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use tracing::debug;
+
+pub const STALL_THRESHOLD: Duration = Duration::from_secs(5); // 需要刷新时超过这个时间没有刷新视为计时线程已停止
+
+// 记录事件循环最近一次收到计时刷新的时刻，发现计时线程意外停止；
+// 使用 Instant 计时，系统休眠期间不会误判
+#[derive(Debug)]
+pub struct TickWatchdog {
+    started: Instant,
+    last_tick_ms: AtomicU64, // 最近一次刷新距 started 的毫秒数
+    generation: AtomicU64,   // 当前计时线程的代数，旧线程发现代数变化后退出
+}
+
+impl Default for TickWatchdog {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl TickWatchdog {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            last_tick_ms: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    // 收到一次刷新，或计时刚从暂停中恢复
+    pub fn record(&self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.started).as_millis() as u64;
+        self.last_tick_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+    // 距离上一次刷新是否超过了阈值
+    pub fn is_stalled(&self, now: Instant) -> bool {
+        let last = self.started + Duration::from_millis(self.last_tick_ms.load(Ordering::Relaxed));
+        now.saturating_duration_since(last) > STALL_THRESHOLD
+    }
+
+    // 开始新一代计时线程，返回新线程使用的代数
+    pub fn next_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    // 计时线程每次刷新前检查自己是否仍是当前一代
+    pub fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::Relaxed) == generation
+    }
+}
+
+// 计时线程的主循环：wait 等到下一次刷新，send 发出刷新；
+// 已被新一代线程替换或发送失败（事件循环已关闭）时退出
+pub fn run_timer(watchdog: &TickWatchdog, generation: u64, mut wait: impl FnMut(), mut send: impl FnMut() -> bool) {
+    loop {
+        wait();
+        if !watchdog.is_current(generation) {
+            debug!("计时线程已被替换，退出");
+            return;
+        }
+        if !send() {
+            debug!("事件循环已关闭，计时线程退出");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, mpsc},
+        thread,
+    };
+
+    use super::*;
+
+    const MILLI: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn stalls_only_after_the_threshold() {
+        let start = Instant::now();
+        let watchdog = TickWatchdog::new(start);
+        assert!(!watchdog.is_stalled(start + STALL_THRESHOLD));
+        assert!(watchdog.is_stalled(start + STALL_THRESHOLD + MILLI));
+
+        let later = start + 10 * STALL_THRESHOLD;
+        watchdog.record(later);
+        assert!(!watchdog.is_stalled(later + STALL_THRESHOLD));
+        assert!(watchdog.is_stalled(later + STALL_THRESHOLD + MILLI));
+        // 记录早于开始时刻的刷新不会回绕
+        watchdog.record(start - MILLI);
+        assert!(!watchdog.is_stalled(start));
+    }
+
+    #[test]
+    fn only_the_latest_generation_is_current() {
+        let watchdog = TickWatchdog::default();
+        assert!(watchdog.is_current(0));
+        let first = watchdog.next_generation();
+        let second = watchdog.next_generation();
+        assert_eq!((first, second), (1, 2));
+        assert!(!watchdog.is_current(first));
+        assert!(watchdog.is_current(second));
+    }
+
+    #[test]
+    fn superseded_timers_exit_before_sending() {
+        let watchdog = TickWatchdog::default();
+        let generation = watchdog.next_generation();
+        let mut sent = 0;
+        run_timer(&watchdog, generation, || {}, || {
+            sent += 1;
+            if sent == 3 {
+                watchdog.next_generation();
+            }
+            true
+        });
+        assert_eq!(sent, 3);
+    }
+
+    #[test]
+    fn a_killed_timer_is_detected_and_replaced() {
+        let watchdog = Arc::new(TickWatchdog::default());
+        let (ticks_tx, ticks_rx) = mpsc::channel();
+        let spawn = |ticks: mpsc::Sender<u64>, limit: Option<usize>| {
+            let watchdog = Arc::clone(&watchdog);
+            let generation = watchdog.next_generation();
+            thread::spawn(move || {
+                let mut sent = 0;
+                run_timer(&watchdog, generation, || thread::sleep(MILLI), || {
+                    sent += 1;
+                    watchdog.record(Instant::now());
+                    // 模拟计时线程意外停止
+                    limit.is_none_or(|limit| sent <= limit) && ticks.send(generation).is_ok()
+                });
+            })
+        };
+
+        spawn(ticks_tx.clone(), Some(3)).join().unwrap();
+        assert_eq!(ticks_rx.try_iter().collect::<Vec<_>>(), [1, 1, 1]);
+        let stalled_at = Instant::now() + STALL_THRESHOLD + MILLI;
+        assert!(watchdog.is_stalled(stalled_at));
+
+        // 重新启动后刷新恢复
+        let replacement = spawn(ticks_tx, None);
+        for _ in 0..3 {
+            assert_eq!(ticks_rx.recv_timeout(Duration::from_secs(5)).unwrap(), 2);
+        }
+        assert!(!watchdog.is_stalled(Instant::now()));
+        drop(ticks_rx);
+        replacement.join().unwrap();
+    }
+}
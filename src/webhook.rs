@@ -0,0 +1,170 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use snafu::ResultExt;
+use tracing::{debug, info, warn};
+
+use crate::{
+    error::{Error, Result, WebhookPayloadSnafu, WebhookSnafu},
+    task::{Task, TaskId, TaskStats},
+};
+
+pub const ENABLED: bool = cfg!(feature = "webhook"); // 编译时是否启用了 webhook 功能
+
+const TIMEOUT: Duration = Duration::from_secs(5); // 单次请求的超时时间
+const MAX_RETRIES: u32 = 2; // 第一次失败后最多重试的次数
+const RETRY_DELAY: Duration = Duration::from_secs(1); // 第一次重试前的等待时间，之后每次加倍
+
+// 任务完成时 POST 到 webhook 的 JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Payload {
+    pub task_id: TaskId,
+    pub name: String,
+    pub planned_secs: u64,
+    pub actual_secs: u64,
+    pub finished_at: String, // 本地时间，RFC 3339 格式
+}
+
+impl Payload {
+    pub fn new(task: &Task, stats: &TaskStats) -> Self {
+        let finished_at = DateTime::<Local>::from(task.finished_at.unwrap_or_else(SystemTime::now));
+        Self {
+            task_id: task.id,
+            name: task.name.clone(),
+            planned_secs: stats.planned.as_secs(),
+            actual_secs: stats.actual.as_secs(),
+            finished_at: finished_at.to_rfc3339(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).context(WebhookPayloadSnafu)
+    }
+}
+
+// 任务自己的地址优先，其次是全局地址；空字符串表示不发送
+pub fn resolve_url<'a>(task: &'a Task, global: Option<&'a str>) -> Option<&'a str> {
+    task.webhook.as_deref().or(global).filter(|url| !url.trim().is_empty())
+}
+
+// 第 attempt 次重试前的等待时间（从 0 开始）
+pub fn retry_delay(attempt: u32) -> Duration {
+    RETRY_DELAY * 2u32.saturating_pow(attempt)
+}
+
+// 发送失败时按 retry_delay 等待后重试，用完重试次数后返回最后一次的错误；
+// 会阻塞当前线程，只在后台线程中调用
+pub fn deliver(url: &str, body: &str) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        match post(url, body) {
+            Ok(()) => {
+                debug!("Webhook delivered to {}", url);
+                return Ok(());
+            }
+            Err(e) if attempt < MAX_RETRIES => {
+                let delay = retry_delay(attempt);
+                warn!("Webhook to {} failed, retrying in {:?}: {}", url, delay, e);
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// 在后台线程发送，最终失败时调用 on_failure，不阻塞事件循环
+pub fn spawn(url: String, payload: Payload, on_failure: impl FnOnce(Error) + Send + 'static) {
+    std::thread::spawn(move || {
+        let result = payload.to_json().and_then(|body| deliver(&url, &body));
+        match result {
+            Ok(()) => info!("🔗 已发送任务 '{}' 的 webhook", payload.name),
+            Err(e) => on_failure(e),
+        }
+    });
+}
+
+#[cfg(feature = "webhook")]
+fn post(url: &str, body: &str) -> Result<()> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+        .map_err(|e| {
+            WebhookSnafu {
+                url,
+                msg: e.to_string(),
+            }
+            .build()
+        })?;
+    Ok(())
+}
+
+// 未启用 webhook 功能时不发送，调用方应先检查 ENABLED
+#[cfg(not(feature = "webhook"))]
+fn post(url: &str, _body: &str) -> Result<()> {
+    WebhookSnafu {
+        url,
+        msg: "webhook support is not enabled in this build",
+    }
+    .fail()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::TaskType;
+
+    fn task(webhook: Option<&str>) -> Task {
+        let mut task = Task::new("写作".into(), TaskType::Duration(Duration::from_secs(1500))).unwrap();
+        task.webhook = webhook.map(str::to_string);
+        task
+    }
+
+    #[test]
+    fn task_url_takes_precedence_over_the_global_url() {
+        let global = Some("https://example.com/global");
+        assert_eq!(resolve_url(&task(None), global), global);
+        assert_eq!(resolve_url(&task(Some("https://example.com/task")), global), Some("https://example.com/task"));
+        assert_eq!(resolve_url(&task(Some("https://example.com/task")), None), Some("https://example.com/task"));
+        assert_eq!(resolve_url(&task(None), None), None);
+        // 任务设置空地址时不发送，即使有全局地址
+        assert_eq!(resolve_url(&task(Some("")), global), None);
+        assert_eq!(resolve_url(&task(None), Some("  ")), None);
+    }
+
+    #[test]
+    fn payload_describes_the_finished_task() {
+        let mut task = task(None);
+        let finished_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        task.finished_at = Some(finished_at);
+        let stats = TaskStats {
+            planned: Duration::from_secs(1500),
+            actual: Duration::from_secs(1620),
+            paused: Duration::from_secs(60),
+            overrun: Duration::from_secs(120),
+        };
+
+        let payload = Payload::new(&task, &stats);
+        assert_eq!(payload.finished_at, DateTime::<Local>::from(finished_at).to_rfc3339());
+        let json: serde_json::Value = serde_json::from_str(&payload.to_json().unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "task_id": task.id,
+                "name": "写作",
+                "planned_secs": 1500,
+                "actual_secs": 1620,
+                "finished_at": payload.finished_at,
+            })
+        );
+    }
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        let delays: Vec<_> = (0..MAX_RETRIES + 1).map(retry_delay).collect();
+        assert_eq!(delays, [1, 2, 4].map(Duration::from_secs));
+    }
+}